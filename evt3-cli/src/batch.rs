@@ -0,0 +1,355 @@
+//! `batch` subcommand: converts many recordings with the same options.
+
+use crate::{convert, Converted, DecodeOptions};
+use anyhow::{Context, Result};
+use clap::Args;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Arguments of `evt3-decode batch`.
+#[derive(Args, Debug)]
+pub struct BatchArgs {
+    /// EVT3 .raw files or glob patterns; quote patterns (e.g. "data/*.raw")
+    /// to have them expanded by evt3 rather than the shell
+    #[arg(value_name = "INPUT", required = true)]
+    pub inputs: Vec<String>,
+
+    /// Directory for the converted files
+    #[arg(long, value_name = "DIR")]
+    pub output_dir: PathBuf,
+
+    /// Output file name template; {stem} is the input file name without
+    /// extension and {index} its position in the input list (0000, 0001, ...)
+    ///
+    /// The extension selects the format unless --output-format is given.
+    /// --triggers, --heatmap and --summary-json paths are templates too and
+    /// must contain {stem} or {index} when converting several files. All
+    /// relative paths are placed in --output-dir; absolute paths are used
+    /// as given. Missing directories in the paths are created.
+    #[arg(long, value_name = "TEMPLATE", default_value = "{stem}.csv")]
+    pub name: String,
+
+    /// Number of files converted in parallel; 0 uses one per CPU core
+    #[arg(short, long, value_name = "N", default_value_t = 1)]
+    pub jobs: usize,
+
+    #[command(flatten)]
+    pub options: DecodeOptions,
+}
+
+/// One converted (or failed) input.
+struct Outcome {
+    input: PathBuf,
+    output: PathBuf,
+    result: Result<Converted>,
+    elapsed: Duration,
+}
+
+/// Expands glob patterns; arguments naming existing files are kept as-is.
+pub fn expand_inputs(patterns: &[String]) -> Result<Vec<PathBuf>> {
+    let mut inputs = Vec::new();
+    for pattern in patterns {
+        if Path::new(pattern).exists() || !pattern.contains(['*', '?', '[']) {
+            inputs.push(PathBuf::from(pattern));
+            continue;
+        }
+        let matches = glob::glob(pattern)
+            .with_context(|| format!("Invalid glob pattern {:?}", pattern))?
+            .collect::<Result<Vec<_>, _>>()?;
+        if matches.is_empty() {
+            anyhow::bail!("No files match {:?}", pattern);
+        }
+        inputs.extend(matches);
+    }
+    Ok(inputs)
+}
+
+/// Fills in the {stem} and {index} placeholders of a name template.
+pub fn expand_name(template: &str, input: &Path, index: usize) -> String {
+    let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+    template
+        .replace("{stem}", &stem)
+        .replace("{index}", &format!("{:04}", index))
+}
+
+/// Places an expanded output name in `dir`, unless it is an absolute path.
+pub fn output_path(dir: &Path, name: &str) -> PathBuf {
+    let name = Path::new(name);
+    if name.is_absolute() {
+        name.to_path_buf()
+    } else {
+        dir.join(name)
+    }
+}
+
+/// Creates the missing parent directories of output paths, for names such
+/// as `{stem}/events.csv`.
+pub fn create_parent_dirs<'a>(paths: impl IntoIterator<Item = &'a PathBuf>) -> Result<()> {
+    for parent in paths.into_iter().filter_map(|path| path.parent()) {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {:?}", parent))?;
+        }
+    }
+    Ok(())
+}
+
+pub fn is_template(path: &str) -> bool {
+    path.contains("{stem}") || path.contains("{index}")
+}
+
+/// Converts every input into the output directory and prints a summary table.
+///
+/// A failing file does not stop the others; the command fails (non-zero
+/// exit status) at the end if any file failed.
+pub fn run(args: &BatchArgs) -> Result<()> {
+    let inputs = expand_inputs(&args.inputs)?;
+    if args.name == "-" {
+        anyhow::bail!("--name must be a file name template, not stdout");
+    }
+    let extra_outputs = [
+        ("--triggers", &args.options.triggers),
+        ("--heatmap", &args.options.heatmap),
+        ("--summary-json", &args.options.summary_json),
+    ];
+    for (flag, path) in extra_outputs {
+        if let Some(path) = path {
+            if inputs.len() > 1 && !is_template(&path.to_string_lossy()) {
+                anyhow::bail!(
+                    "{} must contain {{stem}} or {{index}} when converting several files",
+                    flag
+                );
+            }
+        }
+    }
+
+    let mut outputs = HashMap::new();
+    for (index, input) in inputs.iter().enumerate() {
+        let output = output_path(&args.output_dir, &expand_name(&args.name, input, index));
+        if let Some(other) = outputs.insert(output.clone(), input) {
+            anyhow::bail!(
+                "{:?} and {:?} would both be written to {:?}; add {{index}} to --name",
+                other,
+                input,
+                output
+            );
+        }
+    }
+    std::fs::create_dir_all(&args.output_dir)
+        .with_context(|| format!("Failed to create {:?}", args.output_dir))?;
+
+    let jobs = match args.jobs {
+        0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        jobs => jobs,
+    }
+    .min(inputs.len());
+
+    let progress = if args.options.quiet {
+        ProgressBar::hidden()
+    } else {
+        let pb = ProgressBar::new(inputs.len() as u64);
+        pb.set_style(
+            ProgressStyle::default_bar()
+                .template("[{elapsed_precise}] {bar:30} {pos}/{len} {msg}")
+                .unwrap(),
+        );
+        pb
+    };
+
+    let next = AtomicUsize::new(0);
+    let outcomes = Mutex::new(Vec::with_capacity(inputs.len()));
+    std::thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(input) = inputs.get(index) else {
+                    break;
+                };
+                let outcome = convert_one(args, input, index);
+                progress.inc(1);
+                progress.set_message(format!("{:?}", input.file_name().unwrap_or_default()));
+                outcomes.lock().unwrap().push((index, outcome));
+            });
+        }
+    });
+    progress.finish_and_clear();
+
+    let mut outcomes = outcomes.into_inner().unwrap();
+    outcomes.sort_by_key(|(index, _)| *index);
+    let outcomes: Vec<Outcome> = outcomes.into_iter().map(|(_, outcome)| outcome).collect();
+
+    let failed = outcomes.iter().filter(|o| o.result.is_err()).count();
+    if !args.options.quiet || failed > 0 {
+        print_table(&outcomes);
+    }
+    if failed > 0 {
+        anyhow::bail!("{} of {} file(s) failed", failed, outcomes.len());
+    }
+    Ok(())
+}
+
+/// Converts input number `index` quietly, with its templated output paths.
+fn convert_one(args: &BatchArgs, input: &Path, index: usize) -> Outcome {
+    let output = output_path(&args.output_dir, &expand_name(&args.name, input, index));
+    let template = |path: &Option<PathBuf>| {
+        path.as_ref().map(|path| {
+            let name = expand_name(&path.to_string_lossy(), input, index);
+            output_path(&args.output_dir, &name)
+        })
+    };
+    let options = DecodeOptions {
+        triggers: template(&args.options.triggers),
+        heatmap: template(&args.options.heatmap),
+        summary_json: template(&args.options.summary_json),
+        quiet: true,
+        ..args.options.clone()
+    };
+
+    let start = Instant::now();
+    let extra = [&options.triggers, &options.heatmap, &options.summary_json];
+    let result = create_parent_dirs(std::iter::once(&output).chain(extra.into_iter().flatten()))
+        .and_then(|()| convert(input, &output, &options));
+    Outcome {
+        input: input.to_path_buf(),
+        output,
+        result,
+        elapsed: start.elapsed(),
+    }
+}
+
+fn print_table(outcomes: &[Outcome]) {
+    let width = outcomes
+        .iter()
+        .map(|o| o.input.display().to_string().len())
+        .chain([4])
+        .max()
+        .unwrap_or(4);
+    println!(
+        "{:<width$}  {:<6}  {:>12}  {:>9}  {:>8}  OUTPUT",
+        "FILE",
+        "STATUS",
+        "CD EVENTS",
+        "TRIGGERS",
+        "TIME",
+        width = width
+    );
+    for outcome in outcomes {
+        let input = outcome.input.display().to_string();
+        let time = format!("{:.2}s", outcome.elapsed.as_secs_f64());
+        match &outcome.result {
+            Ok(converted) => println!(
+                "{:<width$}  {:<6}  {:>12}  {:>9}  {:>8}  {}",
+                input,
+                "ok",
+                converted.cd_events,
+                converted.trigger_events,
+                time,
+                outcome.output.display(),
+                width = width
+            ),
+            Err(e) => println!(
+                "{:<width$}  {:<6}  {:>12}  {:>9}  {:>8}  {:#}",
+                input,
+                "FAIL",
+                "-",
+                "-",
+                time,
+                e,
+                width = width
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+
+    #[test]
+    fn test_expand_name() {
+        let input = Path::new("data/run.01.raw");
+        assert_eq!(expand_name("{stem}.csv", input, 0), "run.01.csv");
+        assert_eq!(expand_name("{index}.bin", input, 7), "0007.bin");
+        assert_eq!(
+            expand_name("{index}_{stem}/{stem}_t.csv", input, 12345),
+            "12345_run.01/run.01_t.csv"
+        );
+        assert_eq!(expand_name("out.csv", input, 3), "out.csv");
+        assert!(is_template("{stem}.csv"));
+        assert!(is_template("x_{index}"));
+        assert!(!is_template("out.csv"));
+    }
+
+    #[test]
+    fn test_expand_inputs() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["b.raw", "a.raw", "c.txt", "[x].raw"] {
+            File::create(dir.path().join(name)).unwrap();
+        }
+        let path = |name: &str| dir.path().join(name);
+        let arg = |name: &str| path(name).to_string_lossy().into_owned();
+
+        // Matches are sorted; arguments keep their order
+        let inputs = expand_inputs(&[arg("*.raw")]).unwrap();
+        assert_eq!(inputs, [path("[x].raw"), path("a.raw"), path("b.raw")]);
+        let inputs = expand_inputs(&[arg("?.txt"), arg("a.raw")]).unwrap();
+        assert_eq!(inputs, [path("c.txt"), path("a.raw")]);
+
+        // Existing files and plain names are not treated as patterns
+        assert_eq!(expand_inputs(&[arg("[x].raw")]).unwrap(), [path("[x].raw")]);
+        assert_eq!(
+            expand_inputs(&[arg("missing.raw")]).unwrap(),
+            [path("missing.raw")]
+        );
+
+        assert!(expand_inputs(&[arg("*.dat")]).is_err());
+        assert!(expand_inputs(&[arg("[*.raw")]).is_err());
+    }
+
+    #[test]
+    fn test_batch_creates_output_subdirectories() {
+        #[derive(clap::Parser)]
+        struct Cli {
+            #[command(flatten)]
+            batch: BatchArgs,
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("run.raw");
+        let events = [evt3_core::CdEvent::new(1, 2, 1, 100)];
+        evt3_core::output::write_raw(&input, &events, &[], &Default::default()).unwrap();
+        let output_dir = dir.path().join("out");
+
+        let cli = <Cli as clap::Parser>::parse_from([
+            "batch".as_ref(),
+            input.as_os_str(),
+            "--output-dir".as_ref(),
+            output_dir.as_os_str(),
+            "--name".as_ref(),
+            "{stem}/events.csv".as_ref(),
+            "--summary-json".as_ref(),
+            "{stem}/stats/summary.json".as_ref(),
+            "--quiet".as_ref(),
+        ]);
+        run(&cli.batch).unwrap();
+        assert!(output_dir.join("run/events.csv").is_file());
+        assert!(output_dir.join("run/stats/summary.json").is_file());
+    }
+
+    #[test]
+    fn test_output_path() {
+        let dir = Path::new("out");
+        assert_eq!(output_path(dir, "a.csv"), Path::new("out/a.csv"));
+        assert_eq!(output_path(dir, "sub/a.csv"), Path::new("out/sub/a.csv"));
+        let absolute = std::env::temp_dir().join("a.csv");
+        assert_eq!(
+            output_path(dir, &absolute.to_string_lossy()),
+            absolute.as_path()
+        );
+    }
+}
@@ -0,0 +1,385 @@
+//! `--config`: conversion pipelines described in a YAML file.
+//!
+//! ```yaml
+//! inputs: ["data/*.raw"]
+//! output_dir: out
+//! outputs:
+//!   - "{stem}.csv"
+//!   - path: "{stem}.events"
+//!     format: bin
+//! filters:
+//!   roi: [320, 180, 960, 540]
+//!   crop: true
+//!   denoise: baf:dt=2000
+//!   downsample: 2
+//! options:
+//!   time-unit: ms
+//!   triggers: "{stem}_triggers.csv"
+//! ```
+//!
+//! Keys under `filters` and `options` are the long names of the decode
+//! options (`time-unit` or `time_unit`); both sections are equivalent and
+//! only split for readability. Options given on the command line next to
+//! `--config` override the file. Relative output paths, including those of
+//! `triggers`, `heatmap` and `summary-json`, are placed in `output_dir`;
+//! absolute paths are used as given.
+
+use crate::batch::{create_parent_dirs, expand_inputs, expand_name, is_template, output_path};
+use crate::{convert, convert_to, output_format, DecodeOptions, OutputFormat};
+use anyhow::{Context, Result};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Args, Parser, ValueEnum};
+use serde::Deserialize;
+use serde_yaml::Value;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// A pipeline file.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct PipelineConfig {
+    /// Input files or glob patterns
+    #[serde(alias = "input")]
+    inputs: OneOrMany<String>,
+    /// Directory the outputs are written to, default the current directory
+    #[serde(default)]
+    output_dir: Option<PathBuf>,
+    /// Output files; templates with {stem} and {index} as in `batch`
+    #[serde(alias = "output")]
+    outputs: OneOrMany<OutputSpec>,
+    #[serde(default)]
+    filters: serde_yaml::Mapping,
+    #[serde(default)]
+    options: serde_yaml::Mapping,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+impl<T> OneOrMany<T> {
+    fn into_vec(self) -> Vec<T> {
+        match self {
+            Self::One(value) => vec![value],
+            Self::Many(values) => values,
+        }
+    }
+}
+
+/// An output path, optionally with a format overriding its extension.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum OutputSpec {
+    Path(String),
+    WithFormat { path: String, format: String },
+}
+
+/// Parser for the decode options collected from the file and command line.
+#[derive(Parser, Debug)]
+#[command(name = "evt3-decode --config", no_binary_name = true)]
+#[command(args_override_self = true)]
+struct ConfigOptions {
+    #[command(flatten)]
+    options: DecodeOptions,
+}
+
+/// Turns a `filters`/`options` mapping into command-line arguments.
+fn mapping_args(mapping: &serde_yaml::Mapping, args: &mut Vec<OsString>) -> Result<()> {
+    for (key, value) in mapping {
+        let key = key.as_str().context("Option names must be strings")?;
+        let flag = format!("--{}", key.replace('_', "-"));
+        let scalar = |value: &Value| match value {
+            Value::String(s) => Ok(s.clone()),
+            Value::Number(n) => Ok(n.to_string()),
+            Value::Bool(b) => Ok(b.to_string()),
+            _ => anyhow::bail!("Invalid value for {:?}", key),
+        };
+        match value {
+            Value::Null | Value::Bool(false) => {}
+            Value::Bool(true) => args.push(flag.into()),
+            Value::Sequence(values) => {
+                let values = values.iter().map(scalar).collect::<Result<Vec<_>>>()?;
+                args.extend([flag, values.join(",")].map(OsString::from));
+            }
+            value => args.extend([flag, scalar(value)?].map(OsString::from)),
+        }
+    }
+    Ok(())
+}
+
+/// Returns the decode options that were given explicitly on the command
+/// line, as arguments that can be parsed again.
+fn command_line_args(matches: &ArgMatches) -> Vec<OsString> {
+    let command = DecodeOptions::augment_args(clap::Command::new("evt3-decode"));
+    let mut args = Vec::new();
+    for arg in command.get_arguments() {
+        let id = arg.get_id().as_str();
+        let (Some(long), Some(ValueSource::CommandLine)) =
+            (arg.get_long(), matches.value_source(id))
+        else {
+            continue;
+        };
+        let values = matches.get_raw(id).into_iter().flatten();
+        if arg.get_action().takes_values() {
+            for value in values {
+                args.push(format!("--{}", long).into());
+                args.push(value.to_os_string());
+            }
+        } else {
+            args.push(format!("--{}", long).into());
+        }
+    }
+    args
+}
+
+/// Runs the pipeline in `path`; `matches` holds the command line, whose
+/// decode options take precedence over the file.
+pub fn run(path: &Path, matches: &ArgMatches) -> Result<()> {
+    let text =
+        std::fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+    let config: PipelineConfig =
+        serde_yaml::from_str(&text).with_context(|| format!("Invalid config file {:?}", path))?;
+
+    let mut args = Vec::new();
+    mapping_args(&config.filters, &mut args)?;
+    mapping_args(&config.options, &mut args)?;
+    args.extend(command_line_args(matches));
+    let options = ConfigOptions::try_parse_from(args)
+        .with_context(|| format!("Invalid options in {:?}", path))?
+        .options;
+
+    let inputs = expand_inputs(&config.inputs.into_vec())?;
+    let output_dir = config.output_dir.unwrap_or_default();
+    let outputs = config.outputs.into_vec();
+    if options.follow && outputs.len() > 1 {
+        anyhow::bail!(
+            "--follow writes a single output; list only one in {:?}",
+            path
+        );
+    }
+    if inputs.len() > 1 {
+        let templated = outputs.iter().map(|output| match output {
+            OutputSpec::Path(path) | OutputSpec::WithFormat { path, .. } => path.as_str(),
+        });
+        let extra = [&options.triggers, &options.heatmap, &options.summary_json];
+        let extra = extra
+            .into_iter()
+            .flatten()
+            .map(|path| path.to_str().unwrap_or(""));
+        if let Some(path) = templated.chain(extra).find(|path| !is_template(path)) {
+            anyhow::bail!(
+                "{:?} must contain {{stem}} or {{index}} when converting several files",
+                path
+            );
+        }
+    }
+
+    if !output_dir.as_os_str().is_empty() {
+        std::fs::create_dir_all(&output_dir)
+            .with_context(|| format!("Failed to create {:?}", output_dir))?;
+    }
+    for (index, input) in inputs.iter().enumerate() {
+        let template = |path: &str| output_path(&output_dir, &expand_name(path, input, index));
+        let template_option =
+            |path: &Option<PathBuf>| path.as_ref().map(|path| template(&path.to_string_lossy()));
+        let input_options = DecodeOptions {
+            triggers: template_option(&options.triggers),
+            heatmap: template_option(&options.heatmap),
+            summary_json: template_option(&options.summary_json),
+            ..options.clone()
+        };
+        let targets = outputs
+            .iter()
+            .map(|output| {
+                let (path, format) = match output {
+                    OutputSpec::Path(path) => (template(path), None),
+                    OutputSpec::WithFormat { path, format } => (template(path), Some(format)),
+                };
+                let format = match format {
+                    Some(format) => OutputFormat::from_str(format, true)
+                        .map_err(|_| anyhow::anyhow!("Unknown output format {:?}", format))?,
+                    None => output_format(&path, &input_options)?,
+                };
+                Ok((path, format))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let extra = [
+            &input_options.triggers,
+            &input_options.heatmap,
+            &input_options.summary_json,
+        ];
+        create_parent_dirs(
+            targets
+                .iter()
+                .map(|(path, _)| path)
+                .chain(extra.into_iter().flatten()),
+        )?;
+
+        let result = if input_options.follow {
+            convert(input, &targets[0].0, &input_options)
+        } else {
+            convert_to(input, &targets, &input_options)
+        };
+        result.with_context(|| format!("Failed to convert {:?}", input))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Cli;
+    use clap::CommandFactory;
+
+    fn argv(args: &[OsString]) -> Vec<&str> {
+        args.iter().map(|arg| arg.to_str().unwrap()).collect()
+    }
+
+    fn config(yaml: &str) -> PipelineConfig {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    /// The arguments of the `filters` and `options` sections, in that order.
+    fn file_args(config: &PipelineConfig) -> Result<Vec<OsString>> {
+        let mut args = Vec::new();
+        mapping_args(&config.filters, &mut args)?;
+        mapping_args(&config.options, &mut args)?;
+        Ok(args)
+    }
+
+    #[test]
+    fn test_mapping_args() {
+        let config = config(
+            r#"
+inputs: rec.raw
+outputs: "{stem}.csv"
+filters:
+  roi: [320, 180, 960, 540]
+  crop: true
+  denoise: baf:dt=2000
+  downsample: 2
+options:
+  time_unit: ms
+  merge-triggers: false
+  triggers: ~
+"#,
+        );
+        let args = file_args(&config).unwrap();
+        assert_eq!(
+            argv(&args),
+            [
+                "--roi",
+                "320,180,960,540",
+                "--crop",
+                "--denoise",
+                "baf:dt=2000",
+                "--downsample",
+                "2",
+                "--time-unit",
+                "ms",
+            ]
+        );
+
+        let options = ConfigOptions::try_parse_from(args).unwrap().options;
+        let roi = options.roi.unwrap();
+        assert_eq!((roi.x0, roi.y0, roi.x1, roi.y1), (320, 180, 960, 540));
+        assert!(options.crop);
+        assert_eq!(options.downsample, Some(2));
+        assert_eq!(options.time_unit, "ms");
+        assert!(!options.merge_triggers);
+        assert_eq!(options.triggers, None);
+    }
+
+    #[test]
+    fn test_mapping_args_scalar_lists() {
+        let config = config(
+            r#"
+inputs: rec.raw
+outputs: out.csv
+options:
+  format: [t, x, y, p]
+  column_names: true
+"#,
+        );
+        let args = file_args(&config).unwrap();
+        assert_eq!(argv(&args), ["--format", "t,x,y,p", "--column-names"]);
+    }
+
+    #[test]
+    fn test_mapping_args_rejects_nested_values() {
+        let nested = config(
+            r#"
+inputs: rec.raw
+outputs: out.csv
+filters:
+  roi: {x0: 0, y0: 0}
+"#,
+        );
+        let err = file_args(&nested).unwrap_err();
+        assert!(err.to_string().contains("\"roi\""), "{}", err);
+
+        let nested_list = config(
+            r#"
+inputs: rec.raw
+outputs: out.csv
+filters:
+  roi: [[0, 0], [10, 10]]
+"#,
+        );
+        assert!(file_args(&nested_list).is_err());
+
+        let numeric_key = config(
+            r#"
+inputs: rec.raw
+outputs: out.csv
+options:
+  1: true
+"#,
+        );
+        assert!(file_args(&numeric_key).is_err());
+    }
+
+    #[test]
+    fn test_command_line_overrides_config() {
+        let matches = Cli::command().get_matches_from([
+            "evt3-decode",
+            "--config",
+            "pipeline.yaml",
+            "--time-unit",
+            "us",
+            "--downsample",
+            "4",
+            "--column-names",
+        ]);
+        let command_line = command_line_args(&matches);
+        // In declaration order; defaults such as --format are not passed on
+        assert_eq!(
+            argv(&command_line),
+            ["--time-unit", "us", "--column-names", "--downsample", "4"]
+        );
+
+        let config = config(
+            r#"
+inputs: rec.raw
+outputs: out.csv
+filters:
+  roi: [0, 0, 640, 480]
+  downsample: 2
+options:
+  time-unit: ms
+  delimiter: ";"
+"#,
+        );
+        let mut args = file_args(&config).unwrap();
+        args.extend(command_line);
+        let options = ConfigOptions::try_parse_from(args).unwrap().options;
+        assert_eq!(options.time_unit, "us");
+        assert_eq!(options.downsample, Some(4));
+        assert!(options.column_names);
+        assert!(options.roi.is_some());
+        assert_eq!(options.delimiter.as_deref(), Some(";"));
+    }
+}
@@ -0,0 +1,100 @@
+//! `cut` subcommand: extracts a time window of a recording.
+
+use crate::time::parse_duration;
+use anyhow::{Context, Result};
+use clap::Args;
+use evt3_core::output::{self, CsvOptions};
+use evt3_core::{CdEvent, Evt3Decoder, SensorMetadata, TriggerEvent};
+use std::path::{Path, PathBuf};
+
+/// Arguments of `evt3-decode cut`.
+#[derive(Args, Debug)]
+pub struct CutArgs {
+    /// Input EVT3 .raw file path
+    #[arg(value_name = "INPUT")]
+    pub input: PathBuf,
+
+    /// Output file path (.raw, .csv, .tsv, .txt or .bin)
+    ///
+    /// .raw output is re-encoded EVT 3.0 including trigger events, readable
+    /// by this tool and the Metavision SDK.
+    #[arg(value_name = "OUTPUT")]
+    pub output: PathBuf,
+
+    /// Start of the window (inclusive), e.g. 2.5s, 300ms or 1500 (us)
+    #[arg(long, value_name = "TIME", value_parser = parse_duration, default_value = "0")]
+    pub start: u64,
+
+    /// End of the window (exclusive); defaults to the end of the recording
+    #[arg(long, value_name = "TIME", value_parser = parse_duration)]
+    pub end: Option<u64>,
+
+    /// Suppress the summary line
+    #[arg(short, long)]
+    pub quiet: bool,
+}
+
+/// Writes the events with `start <= timestamp < end` to the output file.
+///
+/// Timestamps are kept, so the cut lines up with the original recording.
+/// Decoding stops at the end of the window.
+pub fn run(args: &CutArgs) -> Result<()> {
+    if args.end.is_some_and(|end| end <= args.start) {
+        anyhow::bail!("--end must be after --start");
+    }
+
+    let result = Evt3Decoder::new()
+        .decode_file_range(&args.input, args.start, args.end)
+        .context("Failed to decode EVT3 file")?;
+
+    write_by_extension(
+        &args.output,
+        &result.cd_events,
+        &result.trigger_events,
+        &result.metadata,
+    )?;
+
+    if !args.quiet {
+        eprintln!(
+            "Wrote {} CD events and {} trigger events to {:?}",
+            result.cd_events.len(),
+            result.trigger_events.len(),
+            args.output
+        );
+    }
+    Ok(())
+}
+
+/// Writes events in the format given by the extension of `path`: .raw (with
+/// trigger events), .csv/.tsv/.txt or .bin. Missing extensions mean .raw.
+pub fn write_by_extension(
+    path: &Path,
+    cd_events: &[CdEvent],
+    trigger_events: &[TriggerEvent],
+    metadata: &SensorMetadata,
+) -> Result<()> {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("raw")
+        .to_lowercase();
+    match ext.as_str() {
+        "raw" => output::write_raw(path, cd_events, trigger_events, metadata)
+            .context("Failed to write raw output")?,
+        "csv" | "tsv" | "txt" => {
+            let options = CsvOptions {
+                delimiter: if ext == "tsv" { b'\t' } else { b',' },
+                ..Default::default()
+            };
+            output::write_csv(path, cd_events, Some(metadata), &options)
+                .context("Failed to write CSV output")?
+        }
+        "bin" => output::write_binary(path, cd_events, metadata)
+            .context("Failed to write binary output")?,
+        _ => anyhow::bail!(
+            "Unsupported output format: .{}. Use .raw, .csv, .tsv, .txt or .bin",
+            ext
+        ),
+    }
+    Ok(())
+}
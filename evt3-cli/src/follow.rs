@@ -0,0 +1,266 @@
+//! `--follow`: decodes a raw file while the capture process is still writing it.
+
+use crate::{
+    build_decoder, csv_options, open_output, output_format, time_range, Converted, DecodeOptions,
+    OutputFormat,
+};
+use anyhow::{Context, Result};
+use evt3_core::output::{CsvWriter, Evt3Writer, JsonlWriter, OutputError};
+use evt3_core::{CdEvent, SensorMetadata, TriggerEvent};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+use std::time::{Duration, Instant};
+
+/// How often a file at its end is checked for new data.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Set by the Ctrl-C handler; ends every [`FollowReader`].
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Makes Ctrl-C end [`FollowReader`] input as if the idle timeout had
+/// passed, so the events read so far are decoded and written out. A second
+/// Ctrl-C exits right away.
+fn stop_on_ctrl_c() -> Result<()> {
+    static INSTALL: Once = Once::new();
+    let mut result = Ok(());
+    INSTALL.call_once(|| {
+        result = ctrlc::set_handler(|| {
+            if INTERRUPTED.swap(true, Ordering::Relaxed) {
+                std::process::exit(130);
+            }
+        });
+    });
+    result.context("Failed to install the Ctrl-C handler")
+}
+
+/// Reader that waits at the end of a file for more data instead of
+/// reporting end-of-file, until the file has not grown for `idle_timeout`
+/// or Ctrl-C is pressed.
+pub struct FollowReader {
+    file: File,
+    idle_timeout: Option<Duration>,
+    last_data: Instant,
+}
+
+impl FollowReader {
+    /// Opens `path`; `None` waits for new data forever.
+    pub fn open(path: &Path, idle_timeout: Option<Duration>) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
+        Ok(Self {
+            file,
+            idle_timeout,
+            last_data: Instant::now(),
+        })
+    }
+}
+
+impl Read for FollowReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if INTERRUPTED.load(Ordering::Relaxed) {
+                return Ok(0);
+            }
+            let n = self.file.read(buf)?;
+            if n > 0 || buf.is_empty() {
+                self.last_data = Instant::now();
+                return Ok(n);
+            }
+            if self
+                .idle_timeout
+                .is_some_and(|timeout| self.last_data.elapsed() >= timeout)
+            {
+                return Ok(0);
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+/// Output writers that can be fed one chunk of events at a time.
+enum StreamWriter {
+    Text(CsvWriter<Box<dyn Write>>),
+    Jsonl(JsonlWriter<Box<dyn Write>>, bool),
+    Raw(Evt3Writer<Box<dyn Write>>),
+}
+
+impl StreamWriter {
+    fn write_header(&mut self, metadata: &SensorMetadata) -> Result<(), OutputError> {
+        match self {
+            Self::Text(writer) => writer.write_header(Some(metadata)),
+            Self::Jsonl(writer, true) => writer.write_header(metadata),
+            Self::Jsonl(_, false) => Ok(()),
+            Self::Raw(writer) => writer.write_header(metadata),
+        }
+    }
+
+    fn write(
+        &mut self,
+        cd_events: &[CdEvent],
+        trigger_events: &[TriggerEvent],
+        merge_triggers: bool,
+    ) -> Result<(), OutputError> {
+        match (&mut *self, merge_triggers) {
+            (Self::Text(writer), true) => writer.write_merged(cd_events, trigger_events)?,
+            (Self::Text(writer), false) => writer.write_events(cd_events)?,
+            (Self::Jsonl(writer, _), true) => writer.write_merged(cd_events, trigger_events)?,
+            (Self::Jsonl(writer, _), false) => writer.write_events(cd_events)?,
+            (Self::Raw(writer), _) => writer.write_merged(cd_events, trigger_events)?,
+        }
+        self.flush()
+    }
+
+    fn flush(&mut self) -> Result<(), OutputError> {
+        match self {
+            Self::Text(writer) => writer.flush(),
+            Self::Jsonl(writer, _) => writer.flush(),
+            Self::Raw(writer) => writer.flush(),
+        }
+    }
+}
+
+/// Decodes INPUT as it grows, writing and flushing the events of every read
+/// so that readers of OUTPUT see them right away.
+///
+/// Options that need the whole recording (trigger synchronization, heatmaps,
+/// separate trigger files) are rejected.
+pub fn convert(input_path: &Path, output_path: &Path, args: &DecodeOptions) -> Result<Converted> {
+    let unsupported = [
+        ("--sync-to", args.sync_to.is_some()),
+        ("--rebase-trigger", args.rebase_trigger.is_some()),
+        ("--rebase-time", args.rebase_time),
+        ("--trigger-debounce", args.trigger_debounce.is_some()),
+        ("--triggers", args.triggers.is_some()),
+        ("--heatmap", args.heatmap.is_some()),
+        ("--summary-json", args.summary_json.is_some()),
+        (
+            "--threads",
+            args.threads.is_some_and(|threads| threads != 1),
+        ),
+    ];
+    if let Some((flag, _)) = unsupported.iter().find(|(_, set)| *set) {
+        anyhow::bail!("{} cannot be combined with --follow", flag);
+    }
+
+    let format = output_format(output_path, args)?;
+    let csv_options = csv_options(args, format)?;
+    let (start, end) = time_range(args)?;
+    let end = end.unwrap_or(u64::MAX);
+    if args.merge_triggers && format == OutputFormat::Raw {
+        anyhow::bail!("--merge-triggers is only supported for csv, tsv, txt and jsonl output");
+    }
+    let sink = open_output(output_path)?;
+    let mut writer = match format {
+        OutputFormat::Csv | OutputFormat::Tsv | OutputFormat::Txt => {
+            StreamWriter::Text(CsvWriter::with_options(sink, csv_options))
+        }
+        OutputFormat::Jsonl => StreamWriter::Jsonl(
+            JsonlWriter::with_timestamp_unit(sink, csv_options.timestamp_unit),
+            !args.no_header,
+        ),
+        OutputFormat::Raw => StreamWriter::Raw(Evt3Writer::new(sink)),
+        other => anyhow::bail!(
+            "{} output cannot be written with --follow; use csv, tsv, txt, jsonl or raw",
+            format!("{:?}", other).to_lowercase()
+        ),
+    };
+
+    let progress = if args.quiet {
+        ProgressBar::hidden()
+    } else {
+        let pb = ProgressBar::new_spinner();
+        pb.set_style(
+            ProgressStyle::default_spinner()
+                .template("{spinner:.green} [{elapsed_precise}] {msg}")
+                .unwrap(),
+        );
+        pb.enable_steady_tick(Duration::from_millis(200));
+        pb
+    };
+    progress.set_message(format!(
+        "Following {:?}...",
+        input_path.file_name().unwrap_or_default()
+    ));
+
+    // Ctrl-C keeps its default for stdin, whose writer stops along with us
+    let reader: Box<dyn Read> = if input_path.as_os_str() == "-" {
+        Box::new(io::stdin().lock())
+    } else {
+        let idle_timeout = args.idle_timeout.map(Duration::from_micros);
+        let reader = FollowReader::open(input_path, idle_timeout)?;
+        stop_on_ctrl_c()?;
+        Box::new(reader)
+    };
+
+    let mut decoder = build_decoder(args)?;
+    let mut header_written = false;
+    let mut reached_end = false;
+    let mut converted = Converted {
+        cd_events: 0,
+        trigger_events: 0,
+    };
+    let result = decoder.decode_reader_chunked(reader, |metadata, cd, triggers| {
+        if !header_written {
+            writer.write_header(metadata)?;
+            header_written = true;
+        }
+        // Each chunk is in time order, so the --start/--end range is a slice
+        let past_end = cd.last().is_some_and(|e| e.timestamp >= end);
+        let cd = &cd[cd.partition_point(|e| e.timestamp < start)..];
+        let cd = &cd[..cd.partition_point(|e| e.timestamp < end)];
+        let triggers = &triggers[triggers.partition_point(|e| e.timestamp < start)..];
+        let triggers = &triggers[..triggers.partition_point(|e| e.timestamp < end)];
+        writer.write(cd, triggers, args.merge_triggers)?;
+        converted.cd_events += cd.len();
+        converted.trigger_events += triggers.len();
+        progress.set_message(format!(
+            "Following {:?}: {} CD events, {} trigger events",
+            input_path.file_name().unwrap_or_default(),
+            converted.cd_events,
+            converted.trigger_events
+        ));
+        if past_end {
+            // Stop reading instead of waiting for data that would be dropped
+            reached_end = true;
+            anyhow::bail!("reached --end");
+        }
+        Ok::<_, anyhow::Error>(())
+    });
+    match result {
+        Err(_) if reached_end => {}
+        result => result?,
+    }
+
+    progress.finish_with_message(format!(
+        "Done! Decoded {} CD events, {} trigger events",
+        converted.cd_events, converted.trigger_events
+    ));
+    Ok(converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_follow_reader_stops_when_interrupted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("live.raw");
+        std::fs::write(&path, [1, 2, 3]).unwrap();
+
+        // Without an idle timeout, only the interrupt ends the input
+        let mut reader = FollowReader::open(&path, None).unwrap();
+        let mut data = [0; 8];
+        assert_eq!(reader.read(&mut data).unwrap(), 3);
+        let interrupt = std::thread::spawn(|| {
+            std::thread::sleep(POLL_INTERVAL * 2);
+            INTERRUPTED.store(true, Ordering::Relaxed);
+        });
+        assert_eq!(reader.read(&mut data).unwrap(), 0);
+        interrupt.join().unwrap();
+        INTERRUPTED.store(false, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,71 @@
+//! `info` subcommand: a quick look at a raw file without decoding it.
+
+use anyhow::{Context, Result};
+use clap::Args;
+use evt3_core::Evt3Decoder;
+use std::path::PathBuf;
+
+/// Arguments of `evt3-decode info`.
+#[derive(Args, Debug)]
+pub struct InfoArgs {
+    /// Input EVT3 .raw file path
+    #[arg(value_name = "INPUT")]
+    pub input: PathBuf,
+}
+
+/// Prints the header, geometry and size estimates of a raw file.
+///
+/// Large files are only sampled (see `Evt3Decoder::probe`), so the duration
+/// and event count are marked as estimates unless the whole file was read.
+pub fn run(args: &InfoArgs) -> Result<()> {
+    let probe = Evt3Decoder::probe(&args.input)
+        .with_context(|| format!("Failed to read {:?}", args.input))?;
+    let estimate = if probe.exact { "" } else { "~" };
+
+    println!("File:         {}", args.input.display());
+    println!(
+        "File size:    {} bytes ({:.1} MB)",
+        probe.file_size,
+        probe.file_size as f64 / 1e6
+    );
+    println!("Header size:  {} bytes", probe.header_size);
+    println!(
+        "Format:       {}",
+        probe
+            .format_version()
+            .unwrap_or_else(|| "not specified (assuming EVT 3.0)".to_string())
+    );
+    println!(
+        "Sensor:       {}x{}",
+        probe.metadata.width, probe.metadata.height
+    );
+    if let (Some(first), Some(last)) = (probe.first_timestamp, probe.last_timestamp) {
+        println!(
+            "Time range:   {}..={} us ({}{:.3}s)",
+            first,
+            last,
+            estimate,
+            probe.duration() as f64 / 1e6
+        );
+    }
+    println!("CD events:    {}{}", estimate, probe.estimated_event_count);
+    if probe.duration() > 0 {
+        println!(
+            "Mean rate:    {}{:.0} events/s",
+            estimate,
+            probe.estimated_event_count as f64 * 1e6 / probe.duration() as f64
+        );
+    }
+    if !probe.exact {
+        println!("              (estimated from sampled data)");
+    }
+
+    if !probe.header.is_empty() {
+        println!("Header:");
+        for line in &probe.header {
+            println!("  % {}", line);
+        }
+    }
+
+    Ok(())
+}
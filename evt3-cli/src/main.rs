@@ -3,31 +3,139 @@
 //! Decodes Prophesee EVT 3.0 raw files to various output formats.
 
 use anyhow::{Context, Result};
-use clap::Parser;
-use evt3_core::{output, Evt3Decoder, FieldOrder};
+use clap::{ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use evt3_core::filters::{
+    BackgroundActivityFilter, DuplicateFilter, PixelMaskFilter, PolarityFilter, RefractoryFilter,
+    RoiFilter,
+};
+use evt3_core::output::{
+    self, BinaryWriter, CsvOptions, CsvWriter, EsWriter, Evt3Writer, JaerWriter, JsonlWriter,
+};
+use evt3_core::transforms::{Crop, Downsample, Orientation, PolarityMap, Reorient};
+use evt3_core::triggers::Edge;
+use evt3_core::{frames, input, triggers};
+use evt3_core::{
+    BufferPool, DecodeResult, Evt3Decoder, FieldOrder, Roi, SensorMetadata, TimestampUnit,
+};
 use indicatif::{ProgressBar, ProgressStyle};
-use std::path::PathBuf;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::time::Instant;
 
+mod batch;
+mod config;
+mod cut;
+mod follow;
+mod info;
+mod preview;
+mod render;
+mod replay;
+mod split;
+mod summary;
+mod time;
+mod validate;
+
+/// Event vectors reused by the conversions of `batch` and `--config`
+/// pipelines, instead of allocating them again for every file.
+static EVENT_BUFFERS: BufferPool = BufferPool::new();
+
 /// EVT 3.0 raw file decoder for Prophesee event cameras.
 ///
 /// Decodes .raw files in EVT 3.0 format to human-readable CSV or efficient binary formats.
 #[derive(Parser, Debug)]
 #[command(name = "evt3-decode")]
 #[command(author, version, about, long_about = None)]
-struct Args {
-    /// Input EVT3 .raw file path
-    #[arg(value_name = "INPUT")]
-    input: PathBuf,
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    decode: DecodeArgs,
+}
+
+/// Subcommands besides the default decode (`evt3-decode INPUT OUTPUT`).
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print the header, geometry and a quick duration/event-count
+    /// estimate of a raw file without decoding it
+    Info(info::InfoArgs),
+    /// Extract a time window of a recording, e.g. `cut in.raw out.raw
+    /// --start 2.5s --end 7.5s`
+    Cut(cut::CutArgs),
+    /// Split a recording into numbered files, e.g. `split in.raw --every 10s`
+    /// or `--every 10M-events`
+    Split(split::SplitArgs),
+    /// Convert INPUT to OUTPUT, like the default command; use
+    /// --output-format to write to stdout (-) or extension-less paths
+    Convert(DecodeArgs),
+    /// Convert many recordings with the same options, e.g. `batch
+    /// "data/*.raw" --output-dir csv/ --jobs 4`
+    Batch(batch::BatchArgs),
+    /// Check raw files for header problems, unknown words, truncation,
+    /// out-of-bounds events and timestamp regressions
+    Validate(validate::ValidateArgs),
+    /// Render a recording as a PNG sequence, e.g. `render in.raw frames/
+    /// --window 10ms`
+    Render(render::RenderArgs),
+    /// Show a live heatmap of event activity in the terminal, e.g. to check
+    /// a recording over SSH; press q to quit
+    Preview(preview::PreviewArgs),
+    /// Re-emit events at their recorded (or scaled) rate to stdout, UDP or
+    /// a TCP client, for testing live consumers
+    Replay(replay::ReplayArgs),
+}
+
+/// Arguments of the default decode command.
+#[derive(Args, Debug)]
+struct DecodeArgs {
+    /// Input EVT3 .raw file path, or - for stdin (see --geometry)
+    #[arg(value_name = "INPUT", required_unless_present = "config")]
+    input: Option<PathBuf>,
 
-    /// Output file path (.csv, .bin)
+    /// Output file path (.csv, .bin), or - for stdout
     ///
-    /// The output format is determined by the file extension:
+    /// The output format is determined by the file extension unless
+    /// --output-format is given:
     /// - .csv: Comma-separated values (human-readable)
+    /// - .tsv: Tab-separated values
     /// - .bin: Binary format (efficient, for programmatic access)
-    #[arg(value_name = "OUTPUT")]
-    output: PathBuf,
+    /// - .aedat: jAER AEDAT 2.0 (DAVIS address layout)
+    /// - .es: Event Stream format (loris, command_line_tools)
+    /// - .mat: MATLAB v7.3 (HDF5-based) MAT-file with x, y, p, t, width, height
+    /// - .mcap: rosbag2 (MCAP) with dvs_msgs/msg/EventArray messages
+    /// - .cols: Directory of raw column files plus manifest.json (memory-mappable)
+    /// - .raw: Re-encoded EVT 3.0 including trigger events
+    /// - .jsonl: One JSON object per event (JSON Lines)
+    #[arg(value_name = "OUTPUT", required_unless_present = "config")]
+    output: Option<PathBuf>,
+
+    /// Run the conversion pipeline described in a YAML file (inputs,
+    /// filters, options and one or more outputs) instead of INPUT and
+    /// OUTPUT; options given on the command line override the file
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["input", "output"])]
+    config: Option<PathBuf>,
+
+    #[command(flatten)]
+    options: DecodeOptions,
+}
+
+/// Conversion options shared by the decode and `batch` commands.
+#[derive(Args, Debug, Clone)]
+struct DecodeOptions {
+    /// Output format, overriding the file extension
+    ///
+    /// Required for stdout (-) and paths without a known extension. mat, mcap
+    /// and cols can only be written to files.
+    #[arg(long, value_name = "FORMAT")]
+    output_format: Option<OutputFormat>,
+
+    /// Sensor geometry as WIDTHxHEIGHT, for input without a header (e.g.
+    /// raw bytes on stdin); a geometry in the header takes precedence
+    #[arg(long, value_name = "WxH", value_parser = parse_geometry)]
+    geometry: Option<SensorMetadata>,
 
     /// Field order for CSV output.
     ///
@@ -41,36 +149,688 @@ struct Args {
     #[arg(short, long, default_value = "x,y,p,t")]
     format: String,
 
+    /// Timestamp unit for CSV output.
+    ///
+    /// One of: us (default), ns, ms, s. Milliseconds and seconds are
+    /// written as exact decimal fractions.
+    #[arg(long, value_name = "UNIT", default_value = "us")]
+    time_unit: String,
+
+    /// Column delimiter for text output.
+    ///
+    /// One of: comma, tab, semicolon, space, or any single character.
+    /// Defaults to tab for .tsv files and comma otherwise.
+    #[arg(long, value_name = "DELIM")]
+    delimiter: Option<String>,
+
+    /// Omit the geometry header line from text and JSON Lines output
+    #[arg(long)]
+    no_header: bool,
+
+    /// Write a row of column names before the data in text output
+    #[arg(long)]
+    column_names: bool,
+
+    /// Duration of one dvs_msgs/EventArray packet for .mcap output, in microseconds
+    #[arg(long, value_name = "US", default_value_t = 10_000)]
+    packet_duration: u64,
+
+    /// Append a time index to .bin output with one entry every N events
+    ///
+    /// Enables fast seeking by timestamp (see `BinaryReader::seek_to_time`).
+    #[arg(long, value_name = "N")]
+    index_interval: Option<u64>,
+
+    /// Drop noise events while decoding.
+    ///
+    /// Format: baf[:dt=US][,radius=R]. The background activity filter keeps
+    /// an event only if a neighboring pixel fired within dt microseconds
+    /// (default 1000) in a neighborhood of the given radius (default 1).
+    ///
+    /// Examples: "baf", "baf:dt=2000", "baf:dt=5000,radius=2"
+    #[arg(long, value_name = "SPEC")]
+    denoise: Option<String>,
+
+    /// Drop events at a pixel within this many microseconds of the last
+    /// kept event at the same pixel (refractory period)
+    #[arg(long, value_name = "US")]
+    refractory: Option<u64>,
+
+    /// Drop all events from the pixels listed in a mask file
+    ///
+    /// Either a text file with one x,y pair per line or a PNG image in
+    /// which every non-black pixel is masked.
+    #[arg(long, value_name = "PATH")]
+    pixel_mask: Option<PathBuf>,
+
+    /// Start of the time range to decode (inclusive, recording time), e.g.
+    /// 1.5s, 300ms or 1500000us; a bare number is in microseconds
+    #[arg(long, value_name = "TIME", value_parser = time::parse_duration)]
+    start: Option<u64>,
+
+    /// End of the time range to decode (exclusive); reading stops there
+    #[arg(long, value_name = "TIME", value_parser = time::parse_duration)]
+    end: Option<u64>,
+
+    /// Keep only the events inside the rectangle x0,y0,x1,y1 (sensor
+    /// coordinates; x1 and y1 are exclusive)
+    #[arg(long, value_name = "X0,Y0,X1,Y1", value_parser = parse_roi)]
+    roi: Option<Roi>,
+
+    /// With --roi, shift coordinates so the rectangle starts at 0,0 and
+    /// use its size as the output geometry (e.g. in the %geometry header)
+    #[arg(long, requires = "roi")]
+    crop: bool,
+
+    /// Divide coordinates by N (N x N pixel blocks become one pixel); the
+    /// output geometry shrinks accordingly
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    downsample: Option<u16>,
+
+    /// Drop exact duplicate events (same x, y, polarity and timestamp)
+    #[arg(long)]
+    dedup: bool,
+
+    /// Keep only ON or OFF events (as written, i.e. after --invert-polarity)
+    #[arg(long, value_enum, value_name = "POLARITY", default_value_t = PolarityArg::Both)]
+    polarity: PolarityArg,
+
+    /// Flip, rotate or transpose the events (e.g. rotate-180 for an upside-down camera)
+    ///
+    /// Output geometry has width and height swapped for rotate-90,
+    /// rotate-270 and transpose.
+    #[arg(long, value_enum, value_name = "OP")]
+    orient: Option<OrientArg>,
+
+    /// Swap ON and OFF polarities, for sensors with the reversed convention
+    #[arg(long)]
+    invert_polarity: bool,
+
+    /// Decode the input file on N threads [default: 0, all cores]
+    ///
+    /// The whole raw file is read into memory and decoded in chunks, with
+    /// the same output as a serial decode, so this needs the file size in
+    /// RAM on top of the events. With 1 (or a single core) the file is
+    /// decoded while it is read, with bounded read memory, which is also
+    /// how stdin input and --start/--end ranges are always decoded.
+    #[arg(long, value_name = "N")]
+    threads: Option<usize>,
+
+    /// Size of each read from the input, e.g. 64M for fast NVMe storage
+    /// (suffixes K, M and G are powers of 1024; default 2 MB)
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    read_buffer: Option<usize>,
+
+    /// Also write a log-scaled PNG of the per-pixel event counts
+    ///
+    /// Useful for spotting hot pixels and checking the field of view.
+    #[arg(long, value_name = "PATH")]
+    heatmap: Option<PathBuf>,
+
+    /// Also write the decode summary (event counts, geometry, duration,
+    /// throughput, warnings) to this file as JSON
+    #[arg(long, value_name = "PATH")]
+    summary_json: Option<PathBuf>,
+
     /// Output file for trigger events (optional)
     ///
     /// If provided, external trigger events will be written to this file.
     #[arg(short, long, value_name = "PATH")]
     triggers: Option<PathBuf>,
 
+    /// Write one trigger file per channel (e.g. triggers_ch0.csv) instead of
+    /// a single --triggers file
+    #[arg(long, requires = "triggers")]
+    trigger_split_channels: bool,
+
+    /// Interleave trigger events with the CD events in the main output
+    ///
+    /// Only for text and .bin output. Rows get a leading event kind column
+    /// (0 = CD, 1 = trigger); trigger rows hold the channel ID in x, 0 in y
+    /// and the edge value in p. Binary output is written as EVT3BIN version 2,
+    /// with the kind in the padding byte.
+    #[arg(long)]
+    merge_triggers: bool,
+
+    /// Drop trigger edges within this many microseconds of the previous
+    /// kept edge on the same channel (contact bounce)
+    #[arg(long, value_name = "US")]
+    trigger_debounce: Option<u64>,
+
+    /// Shift timestamps so that t=0 is the first rising edge on this
+    /// trigger channel (e.g. stimulus onset); earlier events are dropped
+    #[arg(long, value_name = "CHANNEL")]
+    rebase_trigger: Option<u8>,
+
+    /// Shift timestamps so that the first event (CD or trigger) is at t=0
+    #[arg(long, conflicts_with_all = ["rebase_trigger", "sync_to"])]
+    rebase_time: bool,
+
+    /// Move timestamps into the time base of a reference recording that
+    /// shares a trigger signal (e.g. the other camera of a stereo pair)
+    #[arg(long, value_name = "RAW")]
+    sync_to: Option<PathBuf>,
+
+    /// Trigger channel shared with the --sync-to recording
+    #[arg(long, value_name = "CHANNEL", default_value_t = 0)]
+    sync_channel: u8,
+
+    /// Keep reading INPUT as it grows and write new events as they arrive,
+    /// like `tail -f` (csv, tsv, txt, jsonl and raw output)
+    ///
+    /// Stops after --idle-timeout without new data, or on Ctrl-C after
+    /// writing out the events read so far. With - as INPUT, stdin is
+    /// streamed until it is closed.
+    #[arg(long)]
+    follow: bool,
+
+    /// Stop following once the input has not grown for this long, e.g. 30s
+    #[arg(long, value_name = "TIME", value_parser = time::parse_duration, requires = "follow")]
+    idle_timeout: Option<u64>,
+
     /// Suppress progress output
     #[arg(short, long)]
     quiet: bool,
 }
 
+/// Values accepted by `--output-format`, named after the file extensions.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    Csv,
+    Tsv,
+    Txt,
+    Bin,
+    Aedat,
+    Es,
+    Mat,
+    Mcap,
+    Cols,
+    Raw,
+    Jsonl,
+}
+
+impl OutputFormat {
+    /// Delimited text formats, configured by `CsvOptions`.
+    fn is_text(self) -> bool {
+        matches!(self, Self::Csv | Self::Tsv | Self::Txt)
+    }
+
+    /// Formats written front to back that can go to stdout.
+    fn is_streamable(self) -> bool {
+        !matches!(self, Self::Mat | Self::Mcap | Self::Cols)
+    }
+}
+
+/// Opens the output file, or stdout for `-`.
+fn open_output(path: &Path) -> Result<Box<dyn Write>> {
+    if path.as_os_str() == "-" {
+        return Ok(Box::new(io::stdout().lock()));
+    }
+    let file = File::create(path).with_context(|| format!("Failed to create {:?}", path))?;
+    Ok(Box::new(file))
+}
+
+/// Parses `--geometry`, e.g. `1280x720`.
+fn parse_geometry(s: &str) -> Result<SensorMetadata> {
+    let (width, height) = s
+        .split_once(['x', 'X'])
+        .with_context(|| format!("Expected WIDTHxHEIGHT, got {:?}", s))?;
+    Ok(SensorMetadata {
+        width: width.trim().parse().context("Invalid width")?,
+        height: height.trim().parse().context("Invalid height")?,
+    })
+}
+
+/// Parses `--read-buffer`, e.g. `64M`, `512K` or `1048576`.
+fn parse_size(s: &str) -> Result<usize> {
+    let s = s.trim();
+    let (number, shift) = match s.chars().last() {
+        Some('k' | 'K') => (&s[..s.len() - 1], 10),
+        Some('m' | 'M') => (&s[..s.len() - 1], 20),
+        Some('g' | 'G') => (&s[..s.len() - 1], 30),
+        _ => (s, 0),
+    };
+    let value: usize = number
+        .trim()
+        .parse()
+        .with_context(|| format!("Invalid size {:?}", s))?;
+    match value.checked_mul(1 << shift) {
+        Some(0) => anyhow::bail!("The size must be positive"),
+        Some(size) => Ok(size),
+        None => anyhow::bail!("The size {:?} is too large", s),
+    }
+}
+
+/// Parses `--roi`, e.g. `100,50,740,530`.
+fn parse_roi(s: &str) -> Result<Roi> {
+    let values = s
+        .split(',')
+        .map(|value| value.trim().parse::<u16>())
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("Invalid coordinates in {:?}", s))?;
+    match values[..] {
+        [x0, y0, x1, y1] if x0 != x1 && y0 != y1 => Ok(Roi::new(x0, y0, x1, y1)),
+        [_, _, _, _] => anyhow::bail!("The region {:?} is empty", s),
+        _ => anyhow::bail!("Expected x0,y0,x1,y1, got {:?}", s),
+    }
+}
+
+/// Values accepted by `--polarity`.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum PolarityArg {
+    On,
+    Off,
+    Both,
+}
+
+/// Values accepted by `--orient`.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum OrientArg {
+    FlipX,
+    FlipY,
+    #[value(name = "rotate-90")]
+    Rotate90,
+    #[value(name = "rotate-180")]
+    Rotate180,
+    #[value(name = "rotate-270")]
+    Rotate270,
+    Transpose,
+}
+
+impl From<OrientArg> for Orientation {
+    fn from(arg: OrientArg) -> Self {
+        match arg {
+            OrientArg::FlipX => Orientation::FlipX,
+            OrientArg::FlipY => Orientation::FlipY,
+            OrientArg::Rotate90 => Orientation::Rotate90,
+            OrientArg::Rotate180 => Orientation::Rotate180,
+            OrientArg::Rotate270 => Orientation::Rotate270,
+            OrientArg::Transpose => Orientation::Transpose,
+        }
+    }
+}
+
+/// Parses a `--denoise` spec such as `baf:dt=2000,radius=2`.
+fn parse_denoise(spec: &str, metadata: &SensorMetadata) -> Result<BackgroundActivityFilter> {
+    let (name, params) = spec.split_once(':').unwrap_or((spec, ""));
+    if name.trim() != "baf" {
+        anyhow::bail!("Unknown filter {:?}. Supported: baf", name.trim());
+    }
+
+    let mut dt = BackgroundActivityFilter::DEFAULT_DT;
+    let mut radius = 1;
+    for param in params.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (key, value) = param
+            .split_once('=')
+            .with_context(|| format!("Expected key=value, got {:?}", param))?;
+        match key.trim() {
+            "dt" => dt = value.trim().parse().context("Invalid dt")?,
+            "radius" => radius = value.trim().parse().context("Invalid radius")?,
+            other => anyhow::bail!("Unknown parameter {:?}. Use dt or radius", other),
+        }
+    }
+    Ok(BackgroundActivityFilter::new(metadata, dt).with_radius(radius))
+}
+
 fn main() -> Result<()> {
-    let args = Args::parse();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let result = match cli.command {
+        Some(Command::Info(args)) => info::run(&args),
+        Some(Command::Cut(args)) => cut::run(&args),
+        Some(Command::Split(args)) => split::run(&args),
+        Some(Command::Convert(args)) => {
+            let matches = matches.subcommand_matches("convert").unwrap_or(&matches);
+            decode(args, matches)
+        }
+        Some(Command::Batch(args)) => batch::run(&args),
+        Some(Command::Validate(args)) => validate::run(&args),
+        Some(Command::Render(args)) => render::run(&args),
+        Some(Command::Preview(args)) => preview::run(&args),
+        Some(Command::Replay(args)) => replay::run(&args),
+        None => decode(cli.decode, &matches),
+    };
+    match result {
+        // The reader of a pipe went away (e.g. `| head`): stop quietly
+        Err(e) if is_broken_pipe(&e) => Ok(()),
+        result => result,
+    }
+}
+
+fn is_broken_pipe(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause
+            .downcast_ref::<io::Error>()
+            .is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe)
+    })
+}
+
+/// Decodes INPUT into OUTPUT, or runs a --config pipeline.
+fn decode(args: DecodeArgs, matches: &ArgMatches) -> Result<()> {
+    if let Some(config) = &args.config {
+        return config::run(config, matches);
+    }
+    // Both are required unless a subcommand is given
+    let input_path = args.input.context("Missing INPUT")?;
+    let output_path = args.output.context("Missing OUTPUT")?;
+    convert(&input_path, &output_path, &args.options)?;
+    Ok(())
+}
+
+/// Creates a decoder with the geometry, filters and transforms of `args`.
+fn build_decoder(args: &DecodeOptions) -> Result<Evt3Decoder> {
+    let mut decoder = Evt3Decoder::new();
+    if let Some(geometry) = &args.geometry {
+        decoder.metadata = geometry.clone();
+    }
+    if let Some(size) = args.read_buffer {
+        decoder.set_read_buffer_size(size);
+    }
+    if let Some(path) = &args.pixel_mask {
+        let pixels = input::read_pixel_mask(path)
+            .with_context(|| format!("Failed to read pixel mask {:?}", path))?;
+        decoder.add_filter(PixelMaskFilter::new(pixels, &decoder.metadata));
+    }
+    if args.dedup {
+        decoder.add_filter(DuplicateFilter::new());
+    }
+    if let Some(spec) = &args.denoise {
+        let filter = parse_denoise(spec, &decoder.metadata)
+            .with_context(|| format!("Invalid --denoise spec: {:?}", spec))?;
+        decoder.add_filter(filter);
+    }
+    if let Some(period) = args.refractory {
+        decoder.add_filter(RefractoryFilter::new(&decoder.metadata, period));
+    }
+    let polarity = match args.polarity {
+        PolarityArg::On => Some(1),
+        PolarityArg::Off => Some(0),
+        PolarityArg::Both => None,
+    };
+    if let Some(polarity) = polarity {
+        // Filters see the polarity before --invert-polarity is applied
+        let polarity = if args.invert_polarity {
+            1 - polarity
+        } else {
+            polarity
+        };
+        decoder.add_filter(PolarityFilter::new(polarity));
+    }
+    match (args.roi, args.crop) {
+        (Some(roi), true) => decoder.add_transform(Crop::new(roi)),
+        (Some(roi), false) => decoder.add_filter(RoiFilter::new(roi)),
+        (None, _) => {}
+    }
+    if let Some(factor) = args.downsample {
+        decoder.add_transform(Downsample::new(factor));
+    }
+    if let Some(orient) = args.orient {
+        let transform = Reorient::new(orient.into(), &decoder.metadata);
+        decoder.add_transform(transform);
+    }
+    if args.invert_polarity {
+        decoder.add_transform(PolarityMap::invert());
+    }
+    Ok(decoder)
+}
+
+/// Collects the problems worth reporting about a finished decode.
+fn run_warnings(args: &DecodeOptions, result: &DecodeResult) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if result.cd_events.is_empty() {
+        warnings.push("No CD events were decoded".to_string());
+    }
+    let out_of_order = result
+        .cd_events
+        .windows(2)
+        .filter(|pair| pair[1].timestamp < pair[0].timestamp)
+        .count();
+    if out_of_order > 0 {
+        warnings.push(format!(
+            "{} CD events have a timestamp earlier than the previous event",
+            out_of_order
+        ));
+    }
+    let (width, height) = (result.metadata.width, result.metadata.height);
+    let outside = result
+        .cd_events
+        .iter()
+        .filter(|e| e.x as u32 >= width || e.y as u32 >= height)
+        .count();
+    if outside > 0 {
+        warnings.push(format!(
+            "{} CD events lie outside the {}x{} sensor",
+            outside, width, height
+        ));
+    }
+    if args.triggers.is_some() && !args.trigger_split_channels && result.trigger_events.is_empty() {
+        warnings.push("No trigger events; the --triggers file was not written".to_string());
+    }
+    warnings
+}
 
-    // Parse field order
+/// Returns the --start/--end range, checking that it is not empty.
+fn time_range(args: &DecodeOptions) -> Result<(u64, Option<u64>)> {
+    let start = args.start.unwrap_or(0);
+    if args.end.is_some_and(|end| end <= start) {
+        anyhow::bail!("--end must be after --start");
+    }
+    Ok((start, args.end))
+}
+
+/// Determines the output format from --output-format or the extension.
+fn output_format(output_path: &Path, args: &DecodeOptions) -> Result<OutputFormat> {
+    Ok(match args.output_format {
+        Some(format) => format,
+        None => {
+            let ext = output_path
+                .extension()
+                .and_then(|e| e.to_str())
+                .unwrap_or("csv");
+            OutputFormat::from_str(ext, true).map_err(|_| {
+                anyhow::anyhow!(
+                    "Unsupported output format: .{}. Use .csv, .tsv, .txt, .bin, .aedat, .es, .mat, .mcap, .cols, .raw or .jsonl, or set --output-format",
+                    ext
+                )
+            })?
+        }
+    })
+}
+
+/// Builds the text formatting options of `args` for the given output format.
+fn csv_options(args: &DecodeOptions, format: OutputFormat) -> Result<CsvOptions> {
     let field_order = FieldOrder::from_str(&args.format)
         .context("Invalid field format. Use comma-separated: x,y,p,t")?;
+    let timestamp_unit = TimestampUnit::from_str(&args.time_unit)
+        .context("Invalid timestamp unit. Use us, ns, ms or s")?;
+    let delimiter = match (&args.delimiter, format) {
+        (Some(delimiter), _) => output::parse_delimiter(delimiter)?,
+        (None, OutputFormat::Tsv) => b'\t',
+        (None, _) => b',',
+    };
+    Ok(CsvOptions {
+        field_order,
+        timestamp_unit,
+        delimiter,
+        geometry_header: !args.no_header,
+        column_names: args.column_names,
+        event_kind: args.merge_triggers,
+    })
+}
+
+/// Event counts of a finished [`convert`].
+struct Converted {
+    cd_events: usize,
+    trigger_events: usize,
+}
+
+/// Decodes one input file into one output file.
+fn convert(input_path: &Path, output_path: &Path, args: &DecodeOptions) -> Result<Converted> {
+    if args.follow {
+        return follow::convert(input_path, output_path, args);
+    }
+    let format = output_format(output_path, args)?;
+    convert_to(input_path, &[(output_path.to_path_buf(), format)], args)
+}
+
+/// Writes the decoded events of `result` to one output file.
+fn write_output(
+    result: &DecodeResult,
+    output_path: &Path,
+    format: OutputFormat,
+    args: &DecodeOptions,
+) -> Result<()> {
+    let csv_options = csv_options(args, format)?;
+    match format {
+        OutputFormat::Csv | OutputFormat::Tsv | OutputFormat::Txt => {
+            let mut writer = CsvWriter::with_options(open_output(output_path)?, csv_options);
+            writer.write_header(Some(&result.metadata))?;
+            if args.merge_triggers {
+                writer.write_merged(&result.cd_events, &result.trigger_events)?;
+            } else {
+                writer.write_events(&result.cd_events)?;
+            }
+            writer.flush().context("Failed to write CSV output")?;
+        }
+        OutputFormat::Bin => {
+            let sink = open_output(output_path)?;
+            let mut writer = match args.index_interval {
+                Some(interval) => BinaryWriter::with_index(sink, interval)?,
+                None => BinaryWriter::new(sink),
+            };
+            if args.merge_triggers {
+                writer = writer.with_event_kinds();
+                let count = result.cd_events.len() + result.trigger_events.len();
+                writer.write_header(&result.metadata, count as u64)?;
+                writer.write_merged(&result.cd_events, &result.trigger_events)?;
+            } else {
+                writer.write_header(&result.metadata, result.cd_events.len() as u64)?;
+                writer.write_events(&result.cd_events)?;
+            }
+            writer.write_index()?;
+            writer.flush().context("Failed to write binary output")?;
+        }
+        OutputFormat::Aedat => {
+            let mut writer = JaerWriter::new(
+                open_output(output_path)?,
+                &result.metadata,
+                output::JaerLayout::DAVIS,
+            )?;
+            writer.write_header()?;
+            writer.write_events(&result.cd_events)?;
+            writer
+                .flush()
+                .context("Failed to write jAER AEDAT output")?;
+        }
+        OutputFormat::Es => {
+            let mut writer = EsWriter::new(open_output(output_path)?);
+            writer.write_header(&result.metadata)?;
+            writer.write_events(&result.cd_events)?;
+            writer
+                .flush()
+                .context("Failed to write Event Stream output")?;
+        }
+        OutputFormat::Raw => {
+            let mut writer = Evt3Writer::new(open_output(output_path)?);
+            writer.write_header(&result.metadata)?;
+            writer.write_merged(&result.cd_events, &result.trigger_events)?;
+            writer.flush().context("Failed to write raw output")?;
+        }
+        OutputFormat::Jsonl => {
+            let mut writer = JsonlWriter::with_timestamp_unit(
+                open_output(output_path)?,
+                csv_options.timestamp_unit,
+            );
+            if !args.no_header {
+                writer.write_header(&result.metadata)?;
+            }
+            if args.merge_triggers {
+                writer.write_merged(&result.cd_events, &result.trigger_events)?;
+            } else {
+                writer.write_events(&result.cd_events)?;
+            }
+            writer
+                .flush()
+                .context("Failed to write JSON Lines output")?;
+        }
+        OutputFormat::Mat => {
+            output::write_mat(output_path, &result.cd_events, &result.metadata)
+                .context("Failed to write MAT-file output")?;
+        }
+        OutputFormat::Mcap => {
+            let options = output::RosbagOptions {
+                packet_duration_us: args.packet_duration,
+                ..Default::default()
+            };
+            output::write_rosbag(output_path, &result.cd_events, &result.metadata, options)
+                .context("Failed to write rosbag2 output")?;
+        }
+        OutputFormat::Cols => {
+            output::write_columns(output_path, &result.cd_events, &result.metadata)
+                .context("Failed to write column output")?;
+        }
+    }
+    Ok(())
+}
 
-    // Setup progress bar
+/// Checks that `format` can be written to `path` with the options in `args`.
+fn check_output(path: &Path, format: OutputFormat, args: &DecodeOptions) -> Result<()> {
+    let merge_formats = [OutputFormat::Bin, OutputFormat::Jsonl];
+    if args.merge_triggers && !(format.is_text() || merge_formats.contains(&format)) {
+        anyhow::bail!("--merge-triggers is only supported for csv, tsv, txt, bin and jsonl output");
+    }
+    if path.as_os_str() == "-" && !format.is_streamable() {
+        anyhow::bail!(
+            "{} output cannot be written to stdout, use a file path",
+            format!("{:?}", format).to_lowercase()
+        );
+    }
+    Ok(())
+}
+
+/// Decodes one input file once and writes it to every output.
+///
+/// The --triggers, --heatmap and --summary-json files are written once,
+/// with the text options of the first output.
+fn convert_to(
+    input_path: &Path,
+    outputs: &[(PathBuf, OutputFormat)],
+    args: &DecodeOptions,
+) -> Result<Converted> {
+    for (path, format) in outputs {
+        csv_options(args, *format)?;
+        check_output(path, *format, args)?;
+    }
+    let (_, first_format) = outputs.first().context("No output given")?;
+    let csv_options = csv_options(args, *first_format)?;
+
+    // Setup progress bar: bytes read against the file size, or a spinner
+    // for stdin
+    let from_stdin = input_path.as_os_str() == "-";
     let progress = if args.quiet {
         ProgressBar::hidden()
-    } else {
+    } else if from_stdin {
         let pb = ProgressBar::new_spinner();
         pb.set_style(
             ProgressStyle::default_spinner()
-                .template("{spinner:.green} [{elapsed_precise}] {msg}")
+                .template("{spinner:.green} [{elapsed_precise}] {bytes} {msg}")
                 .unwrap(),
         );
         pb.set_message("Decoding...");
         pb
+    } else {
+        let file_size = std::fs::metadata(input_path)
+            .with_context(|| format!("Failed to open {:?}", input_path))?
+            .len();
+        let pb = ProgressBar::new(file_size);
+        pb.set_style(
+            ProgressStyle::default_bar()
+                .template(
+                    "[{elapsed_precise}] {bar:30} {bytes}/{total_bytes} ({bytes_per_sec}, ETA {eta}) {msg}",
+                )
+                .unwrap(),
+        );
+        pb
     };
 
     let start_time = Instant::now();
@@ -78,13 +838,84 @@ fn main() -> Result<()> {
     // Decode the file
     progress.set_message(format!(
         "Decoding {:?}...",
-        args.input.file_name().unwrap_or_default()
+        input_path.file_name().unwrap_or_default()
     ));
 
-    let mut decoder = Evt3Decoder::new();
-    let result = decoder
-        .decode_file(&args.input)
-        .context("Failed to decode EVT3 file")?;
+    let mut decoder = build_decoder(args)?;
+    let bytes_read = progress.clone();
+    decoder.set_progress_callback(move |bytes| bytes_read.set_position(bytes));
+    let (start, end) = time_range(args)?;
+    let threads = args.threads.unwrap_or(0);
+    let all_cores = threads == 0;
+    let mut buffers = EVENT_BUFFERS.take();
+    let mut result = if from_stdin {
+        decoder.decode_reader(io::stdin().lock()).map(|mut result| {
+            let in_range = |t: u64| t >= start && t < end.unwrap_or(u64::MAX);
+            result.cd_events.retain(|e| in_range(e.timestamp));
+            result.trigger_events.retain(|e| in_range(e.timestamp));
+            result
+        })
+    } else if args.start.is_some() || end.is_some() {
+        decoder.decode_file_range(input_path, start, end)
+    } else if threads == 1 || (all_cores && rayon::current_num_threads() == 1) {
+        decoder
+            .decode_file_into(input_path, &mut buffers)
+            .map(|metadata| buffers.take_result(metadata))
+    } else if all_cores {
+        // All cores: the global pool, shared by the files of a batch
+        decoder
+            .decode_file_parallel_into(input_path, &mut buffers)
+            .map(|metadata| buffers.take_result(metadata))
+    } else {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .context("Failed to start the decoding threads")?
+            .install(|| decoder.decode_file_parallel_into(input_path, &mut buffers))
+            .map(|metadata| buffers.take_result(metadata))
+    }
+    .context("Failed to decode EVT3 file")?;
+    if let Some(gap) = args.trigger_debounce {
+        triggers::debounce(&mut result.trigger_events, gap);
+    }
+    if let Some(reference_path) = &args.sync_to {
+        let reference = Evt3Decoder::new()
+            .decode_file(reference_path)
+            .with_context(|| format!("Failed to decode reference {:?}", reference_path))?;
+        let sync = triggers::estimate_offset(
+            &reference.trigger_events,
+            &result.trigger_events,
+            args.sync_channel,
+            Edge::Rising,
+        )
+        .with_context(|| {
+            format!(
+                "Both recordings need rising edges on trigger channel {}",
+                args.sync_channel
+            )
+        })?;
+        triggers::apply_offset(
+            &mut result.cd_events,
+            &mut result.trigger_events,
+            sync.offset,
+        );
+        if !args.quiet {
+            progress.set_message(format!(
+                "Synchronized to {:?}: offset {} us ({} edges matched, residual {:.1} us)",
+                reference_path.file_name().unwrap_or_default(),
+                sync.offset,
+                sync.matched_edges,
+                sync.residual
+            ));
+        }
+    }
+    if let Some(channel) = args.rebase_trigger {
+        triggers::rebase_to_trigger(&mut result.cd_events, &mut result.trigger_events, channel)
+            .with_context(|| format!("No rising edge on trigger channel {}", channel))?;
+    }
+    if args.rebase_time {
+        triggers::rebase_to_first_event(&mut result.cd_events, &mut result.trigger_events);
+    }
 
     let decode_duration = start_time.elapsed();
 
@@ -97,44 +928,44 @@ fn main() -> Result<()> {
         ));
     }
 
-    // Determine output format from extension
-    let output_ext = args
-        .output
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("csv");
-
-    progress.set_message(format!(
-        "Writing to {:?}...",
-        args.output.file_name().unwrap_or_default()
-    ));
+    for (output_path, format) in outputs {
+        progress.set_message(format!(
+            "Writing to {}...",
+            if output_path.as_os_str() == "-" {
+                "stdout".to_string()
+            } else {
+                format!("{:?}", output_path.file_name().unwrap_or_default())
+            }
+        ));
+        write_output(&result, output_path, *format, args)?;
+    }
 
-    match output_ext.to_lowercase().as_str() {
-        "csv" => {
-            output::write_csv(
-                &args.output,
-                &result.cd_events,
-                Some(&result.metadata),
-                field_order,
-            )
-            .context("Failed to write CSV output")?;
-        }
-        "bin" => {
-            output::write_binary(&args.output, &result.cd_events, &result.metadata)
-                .context("Failed to write binary output")?;
-        }
-        _ => {
-            anyhow::bail!(
-                "Unsupported output format: .{}. Use .csv or .bin",
-                output_ext
-            );
-        }
+    if let Some(heatmap_path) = &args.heatmap {
+        let (width, height) = (result.metadata.width, result.metadata.height);
+        let counts = frames::heatmap(&result.cd_events, width, height, None);
+        output::write_heatmap_png(heatmap_path, &counts, width, height)
+            .context("Failed to write heatmap")?;
     }
 
     // Write trigger events if requested
     if let Some(trigger_path) = &args.triggers {
-        if !result.trigger_events.is_empty() {
-            output::write_trigger_csv(trigger_path, &result.trigger_events)
+        if args.trigger_split_channels {
+            let written = output::write_trigger_csv_per_channel(
+                trigger_path,
+                &result.trigger_events,
+                &csv_options,
+            )
+            .context("Failed to write trigger CSV")?;
+
+            if !args.quiet {
+                progress.set_message(format!(
+                    "Wrote {} trigger events to {} per-channel files",
+                    result.trigger_events.len(),
+                    written.len()
+                ));
+            }
+        } else if !result.trigger_events.is_empty() {
+            output::write_trigger_csv(trigger_path, &result.trigger_events, &csv_options)
                 .context("Failed to write trigger CSV")?;
 
             if !args.quiet {
@@ -148,6 +979,7 @@ fn main() -> Result<()> {
     }
 
     let total_duration = start_time.elapsed();
+    let warnings = run_warnings(args, &result);
 
     progress.finish_with_message(format!(
         "Done! Decoded {} events in {:.2}s (sensor: {}x{})",
@@ -157,22 +989,99 @@ fn main() -> Result<()> {
         result.metadata.height
     ));
 
-    if !args.quiet {
+    let output_paths: Vec<&Path> = outputs.iter().map(|(path, _)| path.as_path()).collect();
+    let summary = (!args.quiet || args.summary_json.is_some()).then(|| result.summary());
+    if let (Some(path), Some(summary)) = (&args.summary_json, &summary) {
+        let run = summary::RunSummary {
+            input: input_path,
+            outputs: &output_paths,
+            summary,
+            elapsed: total_duration,
+            warnings: &warnings,
+        };
+        run.write(path)?;
+    }
+
+    if let (false, Some(summary)) = (args.quiet, summary) {
         // Print summary
         let events_per_sec = result.cd_events.len() as f64 / total_duration.as_secs_f64();
         eprintln!();
         eprintln!("Summary:");
-        eprintln!("  Input:        {:?}", args.input);
-        eprintln!("  Output:       {:?}", args.output);
-        eprintln!("  CD Events:    {}", result.cd_events.len());
-        eprintln!("  Triggers:     {}", result.trigger_events.len());
+        eprintln!("  Input:        {:?}", input_path);
+        for path in &output_paths {
+            eprintln!("  Output:       {:?}", path);
+        }
+        eprintln!("  CD Events:    {}", summary.event_count);
+        match summary.on_off_ratio() {
+            Some(ratio) => eprintln!(
+                "  Polarity:     {} ON / {} OFF (ON/OFF ratio {:.2})",
+                summary.on.count, summary.off.count, ratio
+            ),
+            None => eprintln!(
+                "  Polarity:     {} ON / {} OFF",
+                summary.on.count, summary.off.count
+            ),
+        }
+        eprintln!("  Triggers:     {}", summary.trigger_count);
+        for timing in &summary.trigger_timing {
+            eprintln!(
+                "  Trigger ch{}:  {:.3} Hz, jitter {:.1} us (period {}..={} us, {} rising edges)",
+                timing.id,
+                timing.frequency(),
+                timing.jitter,
+                timing.min_period,
+                timing.max_period,
+                timing.edge_count
+            );
+        }
         eprintln!(
             "  Sensor:       {}x{}",
-            result.metadata.width, result.metadata.height
+            summary.metadata.width, summary.metadata.height
+        );
+        if let (Some((x_min, x_max)), Some((y_min, y_max))) = (summary.x_range, summary.y_range) {
+            eprintln!(
+                "  Extent:       x {}..={}, y {}..={}",
+                x_min, x_max, y_min, y_max
+            );
+        }
+        if let (Some(first), Some(last)) = (summary.first_timestamp, summary.last_timestamp) {
+            eprintln!(
+                "  Time range:   {}..={} us ({:.3}s)",
+                first,
+                last,
+                summary.duration() as f64 / 1e6
+            );
+        }
+        eprintln!(
+            "  Mean rate:    {:.0} events/s ({:.0} ON, {:.0} OFF)",
+            summary.mean_rate(),
+            summary.on.mean_rate,
+            summary.off.mean_rate
+        );
+        for (name, stats) in [("ON", &summary.on), ("OFF", &summary.off)] {
+            if let (Some((cx, cy)), Some((sx, sy))) = (stats.centroid, stats.spread) {
+                eprintln!(
+                    "  {:<3} centroid: ({:.1}, {:.1}) +/- ({:.1}, {:.1}) px",
+                    name, cx, cy, sx, sy
+                );
+            }
+        }
+        eprintln!(
+            "  Peak rate:    {:.0} events/s (1 ms bins)",
+            summary.peak_rate
         );
         eprintln!("  Duration:     {:.3}s", total_duration.as_secs_f64());
         eprintln!("  Throughput:   {:.0} events/s", events_per_sec);
+        for warning in &warnings {
+            eprintln!("  Warning:      {}", warning);
+        }
     }
 
-    Ok(())
+    let converted = Converted {
+        cd_events: result.cd_events.len(),
+        trigger_events: result.trigger_events.len(),
+    };
+    buffers.recycle(result);
+    EVENT_BUFFERS.put(buffers);
+    Ok(converted)
 }
@@ -0,0 +1,262 @@
+//! `preview` subcommand: live heatmap of event activity in the terminal.
+
+use crate::follow::FollowReader;
+use crate::time::parse_duration;
+use anyhow::{Context, Result};
+use clap::Args;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use evt3_core::{frames, Evt3Decoder, SensorMetadata};
+use ratatui::backend::CrosstermBackend;
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::widgets::{Paragraph, Widget};
+use ratatui::Terminal;
+use std::fs::File;
+use std::io::{self, Read, Stdout};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Arguments of `evt3-decode preview`.
+#[derive(Args, Debug)]
+pub struct PreviewArgs {
+    /// Input EVT3 .raw file path
+    #[arg(value_name = "INPUT")]
+    pub input: PathBuf,
+
+    /// Recording time accumulated into each displayed frame
+    #[arg(long, value_name = "TIME", value_parser = parse_duration, default_value = "33ms")]
+    pub window: u64,
+
+    /// Playback speed relative to the recording (2 = twice as fast);
+    /// 0 shows frames as fast as they are decoded
+    #[arg(long, value_name = "FACTOR", default_value_t = 1.0)]
+    pub speed: f64,
+
+    /// Keep reading INPUT as the capture process appends to it
+    #[arg(long)]
+    pub follow: bool,
+}
+
+/// Event counts of one window, downsampled to the terminal when drawn.
+///
+/// Each terminal cell shows two pixel blocks using the upper half block
+/// character, with brightness on a log scale relative to the busiest block.
+struct Heatmap<'a> {
+    counts: &'a [u32],
+    width: u32,
+    height: u32,
+}
+
+impl Widget for Heatmap<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.width == 0 || area.height == 0 || self.width == 0 || self.height == 0 {
+            return;
+        }
+        // Square pixel blocks, two per cell vertically
+        let div_ceil = |a: u32, b: u32| (a as f64 / b as f64).ceil() as u32;
+        let scale = div_ceil(self.width, area.width as u32)
+            .max(div_ceil(self.height, area.height as u32 * 2))
+            .max(1);
+        let grid_width = div_ceil(self.width, scale) as usize;
+        let grid_height = div_ceil(self.height, scale) as usize;
+        let mut grid = vec![0u32; grid_width * grid_height];
+        for (y, row) in self.counts.chunks_exact(self.width as usize).enumerate() {
+            let grid_row = &mut grid[(y / scale as usize) * grid_width..][..grid_width];
+            for (x, &count) in row.iter().enumerate() {
+                grid_row[x / scale as usize] += count;
+            }
+        }
+
+        let max = grid.iter().copied().max().unwrap_or(0);
+        let color = |count: u32| {
+            let level = match max {
+                0 => 0.0,
+                _ => (count as f64).ln_1p() / (max as f64).ln_1p(),
+            };
+            let [r, g, b] = [0, 1, 2].map(|i| {
+                let (from, to) = (frames::BACKGROUND_RGB[i], frames::ON_RGB[i]);
+                (from as f64 + (to as f64 - from as f64) * level).round() as u8
+            });
+            Color::Rgb(r, g, b)
+        };
+        let cell = |x: usize, y: usize| match (x < grid_width, y < grid_height) {
+            (true, true) => color(grid[y * grid_width + x]),
+            _ => Color::Reset,
+        };
+
+        for row in 0..area.height {
+            for column in 0..area.width {
+                let (x, y) = (column as usize, row as usize * 2);
+                buf.get_mut(area.x + column, area.y + row)
+                    .set_symbol("▀")
+                    .set_style(Style::default().fg(cell(x, y)).bg(cell(x, y + 1)));
+            }
+        }
+    }
+}
+
+/// Terminal in raw mode on the alternate screen, restored when dropped.
+struct Screen {
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+}
+
+impl Screen {
+    fn enter() -> Result<Self> {
+        terminal::enable_raw_mode().context("Failed to set up the terminal")?;
+        crossterm::execute!(io::stdout(), EnterAlternateScreen)?;
+        let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+        terminal.hide_cursor()?;
+        Ok(Self { terminal })
+    }
+
+    fn draw(&mut self, heatmap: Heatmap, status: &str) -> Result<()> {
+        self.terminal.draw(|frame| {
+            let areas = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Length(1)])
+                .split(frame.size());
+            frame.render_widget(heatmap, areas[0]);
+            frame.render_widget(Paragraph::new(status), areas[1]);
+        })?;
+        Ok(())
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = self.terminal.show_cursor();
+        let _ = crossterm::execute!(io::stdout(), LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Waits until `deadline` (or only checks for input with `None`) and
+/// returns true if the user asked to quit.
+fn wait_for_quit(deadline: Option<Instant>) -> Result<bool> {
+    loop {
+        let timeout = deadline.map_or(Duration::ZERO, |deadline| {
+            deadline.saturating_duration_since(Instant::now())
+        });
+        if !event::poll(timeout)? {
+            return Ok(false);
+        }
+        if let Event::Key(key) = event::read()? {
+            let ctrl_c =
+                key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+            if key.kind == KeyEventKind::Press
+                && (matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) || ctrl_c)
+            {
+                return Ok(true);
+            }
+        }
+    }
+}
+
+/// Shows one heatmap per window of recording time, paced by `--speed`.
+pub fn run(args: &PreviewArgs) -> Result<()> {
+    if args.speed.is_nan() || args.speed < 0.0 {
+        anyhow::bail!("--speed must be positive, or 0 for no pacing");
+    }
+    if args.window == 0 {
+        anyhow::bail!("--window must be positive");
+    }
+    let reader: Box<dyn Read> = if args.follow {
+        Box::new(FollowReader::open(&args.input, None)?)
+    } else {
+        Box::new(
+            File::open(&args.input).with_context(|| format!("Failed to open {:?}", args.input))?,
+        )
+    };
+    let name = args.input.file_name().unwrap_or_default().to_string_lossy();
+
+    let mut screen = Screen::enter()?;
+    let start = Instant::now();
+    let mut metadata = SensorMetadata::default();
+    let mut counts = Vec::new();
+    let mut window_start = None;
+    let mut frame_index = 0u64;
+    let mut window_events = 0u64;
+    let mut quit = false;
+
+    let show = |screen: &mut Screen,
+                metadata: &SensorMetadata,
+                counts: &mut Vec<u32>,
+                time: u64,
+                events: u64,
+                label: &str|
+     -> Result<()> {
+        let rate = events as f64 / (args.window as f64 / 1e6);
+        let status = format!(
+            " {}  t = {:.3} s  {:.2} Mev/s  {}x{}{}  [q] quit",
+            name,
+            time as f64 / 1e6,
+            rate / 1e6,
+            metadata.width,
+            metadata.height,
+            label
+        );
+        let heatmap = Heatmap {
+            counts,
+            width: metadata.width,
+            height: metadata.height,
+        };
+        screen.draw(heatmap, &status)?;
+        counts.iter_mut().for_each(|count| *count = 0);
+        Ok(())
+    };
+
+    let result = Evt3Decoder::new().decode_reader_chunked(reader, |chunk_metadata, cd, _| {
+        if counts.is_empty() {
+            metadata = chunk_metadata.clone();
+            counts = vec![0u32; metadata.width as usize * metadata.height as usize];
+        }
+        for event in cd {
+            let origin = *window_start.get_or_insert(event.timestamp);
+            while event.timestamp >= origin + (frame_index + 1) * args.window {
+                let time = origin + frame_index * args.window;
+                show(&mut screen, &metadata, &mut counts, time, window_events, "")?;
+                window_events = 0;
+                frame_index += 1;
+                let deadline = (args.speed > 0.0).then(|| {
+                    let elapsed = (frame_index * args.window) as f64 / args.speed;
+                    start + Duration::from_micros(elapsed as u64)
+                });
+                if wait_for_quit(deadline)? {
+                    quit = true;
+                    anyhow::bail!("quit");
+                }
+            }
+            if (event.x as u32) < metadata.width && (event.y as u32) < metadata.height {
+                counts[event.y as usize * metadata.width as usize + event.x as usize] += 1;
+            }
+            window_events += 1;
+        }
+        // Keep the key handling responsive while waiting for data
+        if wait_for_quit(None)? {
+            quit = true;
+            anyhow::bail!("quit");
+        }
+        Ok::<_, anyhow::Error>(())
+    });
+    match result {
+        Err(_) if quit => return Ok(()),
+        result => result?,
+    }
+
+    // Keep the last window on screen until the user quits
+    if let Some(origin) = window_start {
+        let time = origin + frame_index * args.window;
+        show(
+            &mut screen,
+            &metadata,
+            &mut counts,
+            time,
+            window_events,
+            "  (end)",
+        )?;
+    }
+    while !wait_for_quit(Some(Instant::now() + Duration::from_secs(3600)))? {}
+    Ok(())
+}
@@ -0,0 +1,218 @@
+//! `render` subcommand: turns a recording into image frames or a video.
+
+use crate::time::parse_duration;
+use anyhow::{Context, Result};
+use clap::Args;
+use evt3_core::output::image::write_rgb_png;
+use evt3_core::{frames, CdEvent, Evt3Decoder, SensorMetadata};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+/// Arguments of `evt3-decode render`.
+#[derive(Args, Debug)]
+#[command(group(clap::ArgGroup::new("target").required(true).args(["output_dir", "video"])))]
+pub struct RenderArgs {
+    /// Input EVT3 .raw file path
+    #[arg(value_name = "INPUT")]
+    pub input: PathBuf,
+
+    /// Directory for the PNG frames (frame_000000.png, ...)
+    #[arg(value_name = "OUTPUT_DIR")]
+    pub output_dir: Option<PathBuf>,
+
+    /// Encode the frames into a video file (e.g. .mp4) with ffmpeg instead
+    #[arg(long, value_name = "FILE")]
+    pub video: Option<PathBuf>,
+
+    /// Frame rate of the video
+    #[arg(long, value_name = "FPS", default_value_t = 60.0)]
+    pub fps: f64,
+
+    /// Time window accumulated into each frame, e.g. 10ms or 33.3ms
+    ///
+    /// Defaults to 10ms for PNG frames and to 1/FPS for videos, which plays
+    /// back in real time.
+    #[arg(long, value_name = "TIME", value_parser = parse_duration)]
+    pub window: Option<u64>,
+
+    /// ffmpeg executable used for --video
+    #[arg(long, value_name = "PATH", default_value = "ffmpeg")]
+    pub ffmpeg: PathBuf,
+
+    /// Suppress the summary line
+    #[arg(short, long)]
+    pub quiet: bool,
+}
+
+/// Renders windows of events and writes them as numbered frames.
+struct FrameWriter {
+    sink: FrameSink,
+    frames: u64,
+}
+
+impl FrameWriter {
+    /// Renders the events of one window and writes the next frame.
+    fn write(&mut self, metadata: &SensorMetadata, events: &[CdEvent]) -> Result<()> {
+        let image = frames::render_rgb(events, metadata.width, metadata.height, 0, u64::MAX);
+        self.sink.write_frame(self.frames, &image, metadata)?;
+        self.frames += 1;
+        Ok(())
+    }
+}
+
+/// Destination of the rendered frames.
+enum FrameSink {
+    /// Numbered PNG files in a directory
+    Png { dir: PathBuf },
+    /// Raw RGB frames piped into an ffmpeg process, started on the first
+    /// frame once the geometry is known
+    Video {
+        path: PathBuf,
+        ffmpeg: PathBuf,
+        fps: f64,
+        process: Option<(Child, ChildStdin)>,
+    },
+}
+
+impl FrameSink {
+    /// Writes frame number `index`.
+    fn write_frame(&mut self, index: u64, image: &[u8], metadata: &SensorMetadata) -> Result<()> {
+        match self {
+            Self::Png { dir } => {
+                let path = dir.join(format!("frame_{:06}.png", index));
+                write_rgb_png(&path, image, metadata.width, metadata.height)
+                    .with_context(|| format!("Failed to write {:?}", path))?;
+            }
+            Self::Video {
+                path,
+                ffmpeg,
+                fps,
+                process,
+            } => {
+                if process.is_none() {
+                    let mut child = spawn_ffmpeg(ffmpeg, path, *fps, metadata)?;
+                    let stdin = child.stdin.take().context("ffmpeg has no stdin")?;
+                    *process = Some((child, stdin));
+                }
+                if let Some((_, stdin)) = process {
+                    stdin
+                        .write_all(image)
+                        .context("Failed to pipe frame to ffmpeg")?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Waits for the video encoder to finish.
+    fn finish(self) -> Result<()> {
+        if let Self::Video {
+            process: Some((mut child, stdin)),
+            ..
+        } = self
+        {
+            drop(stdin);
+            let status = child.wait().context("Failed to wait for ffmpeg")?;
+            if !status.success() {
+                anyhow::bail!("ffmpeg exited with {}", status);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Starts ffmpeg reading raw RGB frames from stdin and writing H.264.
+fn spawn_ffmpeg(ffmpeg: &Path, path: &Path, fps: f64, metadata: &SensorMetadata) -> Result<Child> {
+    Command::new(ffmpeg)
+        .args([
+            "-y",
+            "-loglevel",
+            "error",
+            "-f",
+            "rawvideo",
+            "-pix_fmt",
+            "rgb24",
+        ])
+        .args(["-s", &format!("{}x{}", metadata.width, metadata.height)])
+        .args(["-r", &fps.to_string(), "-i", "-"])
+        // yuv420p, which most players need, requires even dimensions
+        .args(["-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2"])
+        .args(["-c:v", "libx264", "-pix_fmt", "yuv420p"])
+        .arg(path)
+        .stdin(Stdio::piped())
+        .spawn()
+        .with_context(|| {
+            format!(
+                "Failed to run {:?}; install ffmpeg, pass --ffmpeg or render PNG frames instead",
+                ffmpeg
+            )
+        })
+}
+
+/// Renders one frame per window, counting windows from the first event.
+///
+/// Pixels show the polarity of their last event in the window (light = ON,
+/// blue = OFF). Windows without events still produce a frame, so the frame
+/// number always maps to the same point in time.
+pub fn run(args: &RenderArgs) -> Result<()> {
+    if args.fps.is_nan() || args.fps <= 0.0 {
+        anyhow::bail!("--fps must be positive");
+    }
+    let window = match (args.window, &args.video) {
+        (Some(window), _) => window,
+        (None, Some(_)) => (1e6 / args.fps).round() as u64,
+        (None, None) => 10_000,
+    };
+    if window == 0 {
+        anyhow::bail!("--window must be positive");
+    }
+
+    let (sink, target) = match (&args.video, &args.output_dir) {
+        (Some(path), _) => (
+            FrameSink::Video {
+                path: path.clone(),
+                ffmpeg: args.ffmpeg.clone(),
+                fps: args.fps,
+                process: None,
+            },
+            path,
+        ),
+        (None, Some(dir)) => {
+            std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+            (FrameSink::Png { dir: dir.clone() }, dir)
+        }
+        (None, None) => unreachable!("clap requires OUTPUT_DIR or --video"),
+    };
+
+    let mut writer = FrameWriter { sink, frames: 0 };
+    let mut window_start = None;
+    let mut window_events: Vec<CdEvent> = Vec::new();
+    let mut metadata = SensorMetadata::default();
+
+    Evt3Decoder::new().decode_file_chunked(&args.input, |chunk_metadata, cd, _| {
+        metadata = chunk_metadata.clone();
+        for event in cd {
+            let start = *window_start.get_or_insert(event.timestamp);
+            // Slightly out-of-order events stay in the current window
+            while event.timestamp >= start + (writer.frames + 1) * window {
+                writer.write(&metadata, &window_events)?;
+                window_events.clear();
+            }
+            window_events.push(*event);
+        }
+        Ok::<_, anyhow::Error>(())
+    })?;
+    if window_start.is_some() {
+        writer.write(&metadata, &window_events)?;
+    }
+    writer.sink.finish()?;
+
+    if !args.quiet {
+        eprintln!(
+            "Wrote {} frames of {} us to {:?}",
+            writer.frames, window, target
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,226 @@
+//! `replay` subcommand: re-emits a recording in (scaled) real time.
+
+use crate::time::parse_duration;
+use anyhow::{Context, Result};
+use clap::{Args, ValueEnum};
+use evt3_core::output::{BinaryWriter, CsvOptions, CsvWriter, BINARY_EVENT_LEN};
+use evt3_core::{CdEvent, Evt3Decoder, TriggerEvent};
+use std::io::{self, Write};
+use std::net::{TcpListener, TcpStream, UdpSocket};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Largest UDP payload sent, small enough to avoid IP fragmentation on Ethernet.
+const MAX_DATAGRAM: usize = 1472;
+
+/// Arguments of `evt3-decode replay`.
+#[derive(Args, Debug)]
+pub struct ReplayArgs {
+    /// Input EVT3 .raw file path
+    #[arg(value_name = "INPUT")]
+    pub input: PathBuf,
+
+    /// Destination: "-" for stdout, udp://HOST:PORT to send datagrams, or
+    /// tcp://HOST:PORT to listen and stream to the first client that connects
+    #[arg(long, value_name = "TARGET", default_value = "-")]
+    pub to: String,
+
+    /// Playback speed relative to the recording (2 = twice as fast);
+    /// 0 sends as fast as possible
+    #[arg(long, value_name = "FACTOR", default_value_t = 1.0)]
+    pub speed: f64,
+
+    /// Events are sent in batches covering this much recording time
+    #[arg(long, value_name = "TIME", value_parser = parse_duration, default_value = "1ms")]
+    pub batch: u64,
+
+    /// Encoding of the events
+    #[arg(long, value_enum, default_value_t = ReplayFormat::Csv)]
+    pub format: ReplayFormat,
+
+    /// Also send trigger events, marked by a leading event kind
+    /// (0 = CD, 1 = trigger) as in --merge-triggers output
+    #[arg(long)]
+    pub triggers: bool,
+}
+
+/// Encodings accepted by `--format`.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReplayFormat {
+    /// One x,y,p,t text line per event
+    Csv,
+    /// 14-byte EVT3BIN event records without the file header
+    Bin,
+}
+
+/// Where replayed events go.
+enum Sink {
+    Stdout(io::Stdout),
+    Udp(UdpSocket),
+    Tcp(TcpStream),
+}
+
+impl Sink {
+    fn open(target: &str) -> Result<Self> {
+        if target == "-" {
+            return Ok(Self::Stdout(io::stdout()));
+        }
+        if let Some(address) = target.strip_prefix("udp://") {
+            let socket = UdpSocket::bind("0.0.0.0:0").context("Failed to open UDP socket")?;
+            socket
+                .connect(address)
+                .with_context(|| format!("Invalid UDP address {:?}", address))?;
+            return Ok(Self::Udp(socket));
+        }
+        if let Some(address) = target.strip_prefix("tcp://") {
+            let listener = TcpListener::bind(address)
+                .with_context(|| format!("Failed to listen on {:?}", address))?;
+            eprintln!("Waiting for a client on tcp://{}...", address);
+            let (stream, peer) = listener.accept().context("Failed to accept client")?;
+            eprintln!("Replaying to {}", peer);
+            stream.set_nodelay(true)?;
+            return Ok(Self::Tcp(stream));
+        }
+        anyhow::bail!(
+            "Unknown replay target {:?}. Use -, udp://HOST:PORT or tcp://HOST:PORT",
+            target
+        )
+    }
+
+    /// Sends one batch. UDP batches are split into datagrams at record
+    /// boundaries so every datagram can be parsed on its own.
+    fn send(&mut self, data: &[u8], format: ReplayFormat) -> Result<()> {
+        match self {
+            Self::Stdout(stdout) => {
+                let mut stdout = stdout.lock();
+                stdout.write_all(data)?;
+                stdout.flush()?;
+            }
+            Self::Tcp(stream) => stream.write_all(data)?,
+            Self::Udp(socket) => {
+                let mut rest = data;
+                while !rest.is_empty() {
+                    let len = datagram_len(rest, format);
+                    socket.send(&rest[..len])?;
+                    rest = &rest[len..];
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Length of the next datagram: as many whole records as fit.
+fn datagram_len(data: &[u8], format: ReplayFormat) -> usize {
+    if data.len() <= MAX_DATAGRAM {
+        return data.len();
+    }
+    match format {
+        ReplayFormat::Csv => data[..MAX_DATAGRAM]
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(MAX_DATAGRAM, |i| i + 1),
+        ReplayFormat::Bin => {
+            let record = BINARY_EVENT_LEN as usize;
+            MAX_DATAGRAM / record * record
+        }
+    }
+}
+
+/// Encodes one batch of events.
+fn encode(
+    format: ReplayFormat,
+    cd_events: &[CdEvent],
+    trigger_events: &[TriggerEvent],
+    with_triggers: bool,
+) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    match format {
+        ReplayFormat::Csv => {
+            let options = CsvOptions {
+                event_kind: with_triggers,
+                ..Default::default()
+            };
+            let mut writer = CsvWriter::with_options(&mut data, options);
+            if with_triggers {
+                writer.write_merged(cd_events, trigger_events)?;
+            } else {
+                writer.write_events(cd_events)?;
+            }
+            writer.flush()?;
+        }
+        ReplayFormat::Bin => {
+            let mut writer = BinaryWriter::new(&mut data);
+            if with_triggers {
+                writer = writer.with_event_kinds();
+                writer.write_merged(cd_events, trigger_events)?;
+            } else {
+                writer.write_events(cd_events)?;
+            }
+            writer.flush()?;
+        }
+    }
+    Ok(data)
+}
+
+/// Replays the input, sending each batch once its recording time has
+/// elapsed (divided by the speed) since the first event.
+pub fn run(args: &ReplayArgs) -> Result<()> {
+    if args.speed.is_nan() || args.speed < 0.0 {
+        anyhow::bail!("--speed must be positive, or 0 for no pacing");
+    }
+    if args.batch == 0 {
+        anyhow::bail!("--batch must be positive");
+    }
+    let mut sink = Sink::open(&args.to)?;
+
+    let start = Instant::now();
+    let mut origin = None;
+    let mut batch_index = 0u64;
+    let mut cd_batch = Vec::new();
+    let mut trigger_batch = Vec::new();
+    let send_batch = |sink: &mut Sink,
+                      index: u64,
+                      cd: &mut Vec<CdEvent>,
+                      triggers: &mut Vec<TriggerEvent>|
+     -> Result<()> {
+        if args.speed > 0.0 {
+            // Events go out no earlier than the end of their batch
+            let elapsed = ((index + 1) * args.batch) as f64 / args.speed;
+            let deadline = start + Duration::from_micros(elapsed as u64);
+            if let Some(wait) = deadline.checked_duration_since(Instant::now()) {
+                std::thread::sleep(wait);
+            }
+        }
+        if !cd.is_empty() || (args.triggers && !triggers.is_empty()) {
+            let data = encode(args.format, cd, triggers, args.triggers)?;
+            sink.send(&data, args.format)?;
+        }
+        cd.clear();
+        triggers.clear();
+        Ok(())
+    };
+
+    Evt3Decoder::new().decode_file_chunked(&args.input, |_, cd, triggers| {
+        let mut triggers = triggers.iter().peekable();
+        for event in cd {
+            let origin = *origin.get_or_insert(event.timestamp);
+            let index = event.timestamp.saturating_sub(origin) / args.batch;
+            if index > batch_index {
+                let batch_end = origin + (batch_index + 1) * args.batch;
+                while let Some(trigger) = triggers.next_if(|t| t.timestamp < batch_end) {
+                    trigger_batch.push(*trigger);
+                }
+                send_batch(&mut sink, batch_index, &mut cd_batch, &mut trigger_batch)?;
+                batch_index = index;
+            }
+            while let Some(trigger) = triggers.next_if(|t| t.timestamp <= event.timestamp) {
+                trigger_batch.push(*trigger);
+            }
+            cd_batch.push(*event);
+        }
+        trigger_batch.extend(triggers);
+        Ok::<_, anyhow::Error>(())
+    })?;
+    send_batch(&mut sink, batch_index, &mut cd_batch, &mut trigger_batch)
+}
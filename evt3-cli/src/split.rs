@@ -0,0 +1,218 @@
+//! `split` subcommand: cuts a recording into numbered segments.
+
+use crate::cut::write_by_extension;
+use crate::time::parse_duration;
+use anyhow::{Context, Result};
+use clap::Args;
+use evt3_core::types::{self, Event};
+use evt3_core::{CdEvent, Evt3Decoder, SensorMetadata, TriggerEvent};
+use std::path::{Path, PathBuf};
+
+/// Arguments of `evt3-decode split`.
+#[derive(Args, Debug)]
+pub struct SplitArgs {
+    /// Input EVT3 .raw file path
+    #[arg(value_name = "INPUT")]
+    pub input: PathBuf,
+
+    /// Segment length: a duration (10s, 500ms) or an event count (10M-events,
+    /// 500k-events)
+    #[arg(long, value_name = "SPEC", value_parser = parse_every)]
+    pub every: Every,
+
+    /// Directory for the segment files; defaults to the input's directory
+    #[arg(long, value_name = "DIR")]
+    pub output_dir: Option<PathBuf>,
+
+    /// Format of the segment files (raw, csv, tsv, txt or bin)
+    #[arg(long, value_name = "EXT", default_value = "raw")]
+    pub extension: String,
+
+    /// Suppress the summary line
+    #[arg(short, long)]
+    pub quiet: bool,
+}
+
+/// Segment length given by `--every`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Every {
+    /// Microseconds per segment, counted from the first event
+    Duration(u64),
+    /// CD events per segment; trigger events go with the following CD event
+    Events(u64),
+}
+
+/// Parses `--every`: `10s`, `500ms`, `10M-events`, `500k-events`, `1000-events`.
+fn parse_every(s: &str) -> Result<Every> {
+    let every = match s.trim().strip_suffix("-events") {
+        Some(count) => {
+            let (number, scale) = match count.chars().last() {
+                Some('k' | 'K') => (&count[..count.len() - 1], 1e3),
+                Some('M') => (&count[..count.len() - 1], 1e6),
+                Some('G') => (&count[..count.len() - 1], 1e9),
+                _ => (count, 1.0),
+            };
+            let value: f64 = number
+                .parse()
+                .with_context(|| format!("Invalid event count {:?}", count))?;
+            let events = (value * scale).round();
+            if events >= u64::MAX as f64 {
+                anyhow::bail!("Event count {:?} is too large", count);
+            }
+            Every::Events(events as u64)
+        }
+        None => Every::Duration(parse_duration(s)?),
+    };
+    if matches!(every, Every::Duration(0) | Every::Events(0)) {
+        anyhow::bail!("Segment length must be positive");
+    }
+    Ok(every)
+}
+
+/// Buffers the events of the current segment and writes them out when the
+/// next segment starts.
+struct SegmentWriter {
+    dir: PathBuf,
+    stem: String,
+    extension: String,
+    metadata: SensorMetadata,
+    cd_events: Vec<CdEvent>,
+    trigger_events: Vec<TriggerEvent>,
+    written: Vec<PathBuf>,
+}
+
+impl SegmentWriter {
+    fn path(&self, index: u64) -> PathBuf {
+        self.dir
+            .join(format!("{}_{:04}.{}", self.stem, index, self.extension))
+    }
+
+    fn finish_segment(&mut self, index: u64) -> Result<()> {
+        let path = self.path(index);
+        write_by_extension(&path, &self.cd_events, &self.trigger_events, &self.metadata)
+            .with_context(|| format!("Failed to write segment {:?}", path))?;
+        self.cd_events.clear();
+        self.trigger_events.clear();
+        self.written.push(path);
+        Ok(())
+    }
+}
+
+/// Streams the input and writes one file per segment, named
+/// `<stem>_0000.<ext>`, `<stem>_0001.<ext>`, ...
+///
+/// With a duration, the number is the segment's position in time, so empty
+/// segments leave gaps in the numbering. Timestamps are not rebased.
+pub fn run(args: &SplitArgs) -> Result<()> {
+    let dir = match &args.output_dir {
+        Some(dir) => {
+            std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+            dir.clone()
+        }
+        None => args
+            .input
+            .parent()
+            .map_or_else(PathBuf::new, Path::to_path_buf),
+    };
+    let mut writer = SegmentWriter {
+        dir,
+        stem: args
+            .input
+            .file_stem()
+            .map_or_else(|| "segment".into(), |s| s.to_string_lossy().into_owned()),
+        extension: args.extension.trim_start_matches('.').to_lowercase(),
+        metadata: SensorMetadata::default(),
+        cd_events: Vec::new(),
+        trigger_events: Vec::new(),
+        written: Vec::new(),
+    };
+
+    let mut current = None;
+    let mut origin = None;
+    let mut cd_seen = 0u64;
+    Evt3Decoder::new().decode_file_chunked(&args.input, |metadata, cd, triggers| {
+        writer.metadata = metadata.clone();
+        for event in types::merge_by_time(cd, triggers) {
+            let index = match args.every {
+                Every::Duration(length) => {
+                    let origin = *origin.get_or_insert(event.timestamp());
+                    event.timestamp().saturating_sub(origin) / length
+                }
+                Every::Events(count) => cd_seen / count,
+            };
+            // Slightly out-of-order events stay in the current segment
+            match current {
+                Some(c) if index > c => {
+                    writer.finish_segment(c)?;
+                    current = Some(index);
+                }
+                None => current = Some(index),
+                _ => {}
+            }
+            match event {
+                Event::Cd(event) => {
+                    writer.cd_events.push(event);
+                    cd_seen += 1;
+                }
+                Event::Trigger(event) => writer.trigger_events.push(event),
+            }
+        }
+        Ok::<_, anyhow::Error>(())
+    })?;
+    if let Some(index) = current {
+        writer.finish_segment(index)?;
+    }
+
+    if !args.quiet {
+        eprintln!(
+            "Wrote {} CD events to {} segments in {:?}",
+            cd_seen,
+            writer.written.len(),
+            writer.dir
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_every_event_counts() {
+        let events = |s| parse_every(s).unwrap();
+        assert_eq!(events("1000-events"), Every::Events(1000));
+        assert_eq!(events("500k-events"), Every::Events(500_000));
+        assert_eq!(events("500K-events"), Every::Events(500_000));
+        assert_eq!(events("10M-events"), Every::Events(10_000_000));
+        assert_eq!(events("2G-events"), Every::Events(2_000_000_000));
+        assert_eq!(events("2.5k-events"), Every::Events(2500));
+        assert_eq!(events(" 1M-events "), Every::Events(1_000_000));
+    }
+
+    #[test]
+    fn test_parse_every_durations() {
+        assert_eq!(parse_every("10s").unwrap(), Every::Duration(10_000_000));
+        assert_eq!(parse_every("500ms").unwrap(), Every::Duration(500_000));
+        assert_eq!(parse_every("250").unwrap(), Every::Duration(250));
+    }
+
+    #[test]
+    fn test_parse_every_rejects_invalid() {
+        // Zero
+        assert!(parse_every("0-events").is_err());
+        assert!(parse_every("0k-events").is_err());
+        assert!(parse_every("0s").is_err());
+        // Missing number
+        assert!(parse_every("-events").is_err());
+        assert!(parse_every("k-events").is_err());
+        assert!(parse_every("").is_err());
+        // Unknown suffix
+        assert!(parse_every("10m-events").is_err());
+        assert!(parse_every("10T-events").is_err());
+        assert!(parse_every("10events").is_err());
+        // Overflow
+        assert!(parse_every("100000000000G-events").is_err());
+        assert!(parse_every("18446744073709551616-events").is_err());
+    }
+}
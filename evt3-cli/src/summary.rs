@@ -0,0 +1,96 @@
+//! `--summary-json`: the decode summary as a JSON document for pipelines.
+
+use anyhow::{Context, Result};
+use evt3_core::DecodeSummary;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::Duration;
+
+/// Everything reported about one decode run.
+pub struct RunSummary<'a> {
+    pub input: &'a Path,
+    pub outputs: &'a [&'a Path],
+    pub summary: &'a DecodeSummary,
+    /// Wall-clock time of the whole run (decoding and writing)
+    pub elapsed: Duration,
+    pub warnings: &'a [String],
+}
+
+/// Quotes and escapes a string for JSON.
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Formats a number, using null for values JSON cannot represent.
+fn json_f64(value: f64) -> String {
+    if value.is_finite() {
+        format!("{}", value)
+    } else {
+        "null".to_string()
+    }
+}
+
+fn json_option<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| "null".to_string(), |v| v.to_string())
+}
+
+impl RunSummary<'_> {
+    /// Renders the summary as a pretty-printed JSON object.
+    pub fn to_json(&self) -> String {
+        let summary = self.summary;
+        let elapsed = self.elapsed.as_secs_f64();
+        let outputs: Vec<String> = self
+            .outputs
+            .iter()
+            .map(|path| json_string(&path.to_string_lossy()))
+            .collect();
+        let warnings: Vec<String> = self.warnings.iter().map(|w| json_string(w)).collect();
+        let fields = [
+            ("input", json_string(&self.input.to_string_lossy())),
+            ("outputs", format!("[{}]", outputs.join(", "))),
+            ("width", summary.metadata.width.to_string()),
+            ("height", summary.metadata.height.to_string()),
+            ("cd_events", summary.event_count.to_string()),
+            ("on_events", summary.on.count.to_string()),
+            ("off_events", summary.off.count.to_string()),
+            ("trigger_events", summary.trigger_count.to_string()),
+            ("first_timestamp_us", json_option(summary.first_timestamp)),
+            ("last_timestamp_us", json_option(summary.last_timestamp)),
+            (
+                "recording_duration_s",
+                json_f64(summary.duration() as f64 / 1e6),
+            ),
+            ("mean_rate", json_f64(summary.mean_rate())),
+            ("peak_rate", json_f64(summary.peak_rate)),
+            ("elapsed_s", json_f64(elapsed)),
+            ("throughput", json_f64(summary.event_count as f64 / elapsed)),
+            ("warnings", format!("[{}]", warnings.join(", "))),
+        ];
+        let body: Vec<String> = fields
+            .iter()
+            .map(|(key, value)| format!("  {}: {}", json_string(key), value))
+            .collect();
+        format!("{{\n{}\n}}\n", body.join(",\n"))
+    }
+
+    /// Writes the JSON summary to `path`.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.to_json()).with_context(|| format!("Failed to write {:?}", path))
+    }
+}
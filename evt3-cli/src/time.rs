@@ -0,0 +1,72 @@
+//! Parsing of durations and time points given on the command line.
+
+use anyhow::{Context, Result};
+
+/// Parses a duration such as `2.5s`, `10ms` or `500us` into microseconds.
+///
+/// Numbers without a unit are microseconds, like all other time arguments.
+pub fn parse_duration(s: &str) -> Result<u64> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let scale = match unit.trim() {
+        "" | "us" => 1.0,
+        "ms" => 1e3,
+        "s" => 1e6,
+        "min" => 60e6,
+        other => anyhow::bail!("Unknown time unit {:?}. Use us, ms, s or min", other),
+    };
+    let value: f64 = number
+        .parse()
+        .with_context(|| format!("Invalid duration {:?}", s))?;
+    let micros = (value * scale).round();
+    if micros >= u64::MAX as f64 {
+        anyhow::bail!("Duration {:?} is too long", s);
+    }
+    Ok(micros as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration_units() {
+        assert_eq!(parse_duration("500us").unwrap(), 500);
+        assert_eq!(parse_duration("10ms").unwrap(), 10_000);
+        assert_eq!(parse_duration("3s").unwrap(), 3_000_000);
+        assert_eq!(parse_duration("2min").unwrap(), 120_000_000);
+        assert_eq!(parse_duration(" 5 ms ").unwrap(), 5000);
+    }
+
+    #[test]
+    fn test_parse_duration_fractions() {
+        assert_eq!(parse_duration("2.5s").unwrap(), 2_500_000);
+        assert_eq!(parse_duration("0.5min").unwrap(), 30_000_000);
+        assert_eq!(parse_duration("1.5ms").unwrap(), 1500);
+        // Rounded to the nearest microsecond
+        assert_eq!(parse_duration("0.4us").unwrap(), 0);
+        assert_eq!(parse_duration("1.0006ms").unwrap(), 1001);
+    }
+
+    #[test]
+    fn test_parse_duration_bare_numbers() {
+        assert_eq!(parse_duration("0").unwrap(), 0);
+        assert_eq!(parse_duration("1234").unwrap(), 1234);
+        assert_eq!(parse_duration("7.6").unwrap(), 8);
+    }
+
+    #[test]
+    fn test_parse_duration_rejects_invalid() {
+        for input in [
+            "", "s", "ms", "-5s", "1e3us", "5h", "5 sec", "1.2.3s", "abc",
+        ] {
+            assert!(parse_duration(input).is_err(), "{:?}", input);
+        }
+        assert!(parse_duration("18446744073709551616").is_err());
+        assert!(parse_duration("99999999999999999999s").is_err());
+        assert!(parse_duration("400000000000000min").is_err());
+    }
+}
@@ -0,0 +1,93 @@
+//! `validate` subcommand: screens raw files for damage before archiving.
+
+use anyhow::{Context, Result};
+use clap::Args;
+use evt3_core::validate::{self, ValidationReport};
+use std::path::{Path, PathBuf};
+
+/// Arguments of `evt3-decode validate`.
+#[derive(Args, Debug)]
+pub struct ValidateArgs {
+    /// EVT3 .raw files to check
+    #[arg(value_name = "INPUT", required = true)]
+    pub inputs: Vec<PathBuf>,
+
+    /// Only print files that fail
+    #[arg(short, long)]
+    pub quiet: bool,
+}
+
+/// Validates every input and prints a pass/fail report with counts.
+///
+/// Fails (non-zero exit status) if any file fails a check.
+pub fn run(args: &ValidateArgs) -> Result<()> {
+    let mut failed = 0;
+    for input in &args.inputs {
+        let report = validate::validate_file(input)
+            .with_context(|| format!("Failed to read {:?}", input))?;
+        if !report.is_valid() {
+            failed += 1;
+        } else if args.quiet {
+            continue;
+        }
+        print_report(input, &report);
+    }
+
+    if failed > 0 {
+        anyhow::bail!(
+            "{} of {} file(s) failed validation",
+            failed,
+            args.inputs.len()
+        );
+    }
+    Ok(())
+}
+
+fn print_report(input: &Path, report: &ValidationReport) {
+    let status = |ok: bool| if ok { "ok  " } else { "FAIL" };
+    println!(
+        "{}: {}",
+        input.display(),
+        if report.is_valid() { "PASS" } else { "FAIL" }
+    );
+    println!(
+        "  Header:      {} {}x{}{}",
+        status(report.header_issues.is_empty()),
+        report.metadata.width,
+        report.metadata.height,
+        report
+            .header_issues
+            .iter()
+            .map(|issue| format!("; {}", issue))
+            .collect::<String>()
+    );
+    println!(
+        "  Word types:  {} {} words, {} unknown",
+        status(report.unknown_words == 0),
+        report.word_count,
+        report.unknown_words
+    );
+    println!(
+        "  Start:       {} {} words before the first TIME_HIGH",
+        status(report.leading_words == 0),
+        report.leading_words
+    );
+    println!(
+        "  Truncation:  {} {} trailing bytes",
+        status(report.trailing_bytes == 0),
+        report.trailing_bytes
+    );
+    println!(
+        "  Bounds:      {} {} CD events, {} outside the sensor",
+        status(report.out_of_bounds == 0),
+        report.cd_events,
+        report.out_of_bounds
+    );
+    println!(
+        "  Timestamps:  {} {} regressions (max {} us), {} trigger events",
+        status(report.timestamp_regressions == 0),
+        report.timestamp_regressions,
+        report.max_regression,
+        report.trigger_events
+    );
+}
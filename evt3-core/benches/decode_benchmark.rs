@@ -1,9 +1,15 @@
 //! Benchmarks for EVT3 decoder performance.
 //!
 //! Run with: cargo bench
+//!
+//! Run a single group with e.g. `cargo bench -- decode_scenario`; the
+//! synthetic streams come from the `*_words` generators below.
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
-use evt3_core::Evt3Decoder;
+use evt3_core::types::RawEventType;
+use evt3_core::{
+    parser, CdEvent, ColumnarEvents, DecodeError, EventBuffers, Evt3Decoder, TriggerEvent,
+};
 use std::path::Path;
 
 const TEST_FILE: &str = "test_data/laser.raw";
@@ -66,8 +72,386 @@ fn decode_buffer_benchmark(c: &mut Criterion) {
         })
     });
 
+    group.bench_function("synthetic_100k_events_bytes", |b| {
+        b.iter(|| {
+            let mut decoder = Evt3Decoder::new();
+            let mut cd_events = Vec::new();
+            let mut trigger_events = Vec::new();
+            decoder.decode_bytes(black_box(&data), &mut cd_events, &mut trigger_events);
+            black_box(cd_events.len())
+        })
+    });
+
+    group.finish();
+}
+
+/// Advances a xorshift32 generator and returns its new state.
+fn xorshift32(state: &mut u32) -> u32 {
+    *state ^= *state << 13;
+    *state ^= *state >> 17;
+    *state ^= *state << 5;
+    *state
+}
+
+/// Generates a stream mixing all word types in an irregular order, so the
+/// event type of the next word cannot be predicted.
+fn mixed_words(count: usize) -> Vec<u16> {
+    let mut words = vec![0x8000u16];
+    let mut state = 0x2545_F491u32;
+    let mut time_high = 0u16;
+    while words.len() < count {
+        xorshift32(&mut state);
+        let payload = (state >> 8) as u16 & 0xFFF;
+        let word = match state % 16 {
+            0..=2 => payload & 0x7FF,            // ADDR_Y
+            3..=6 => 0x2000 | (payload & 0xFFF), // ADDR_X
+            7 => 0x3000 | (payload & 0xFFF),     // VECT_BASE_X
+            8 | 9 => 0x4000 | payload,           // VECT_12
+            10 => 0x5000 | (payload & 0xFF),     // VECT_8
+            11..=13 => 0x6000 | payload,         // TIME_LOW
+            14 => {
+                time_high = (time_high + 1) & 0xFFF;
+                0x8000 | time_high // TIME_HIGH
+            }
+            _ => 0xA000 | (payload & 0xF01), // EXT_TRIGGER
+        };
+        words.push(word);
+    }
+    words
+}
+
+/// Generates a busy scene: each row is a VECT_BASE_X followed by a run of
+/// dense VECT_12 and VECT_8 masks, as sensors emit for moving edges.
+fn vector_heavy_words(count: usize) -> Vec<u16> {
+    let mut words = vec![0x8000u16];
+    let mut state = 0x9E37_79B9u32;
+    let mut time = 0u32;
+    while words.len() < count {
+        time += 1;
+        if (time & 0xFFF) == 0 {
+            words.push(0x8000 | ((time >> 12) & 0xFFF) as u16); // TIME_HIGH
+        }
+        words.push(0x6000 | (time & 0xFFF) as u16); // TIME_LOW
+        let rnd = xorshift32(&mut state);
+        words.push((rnd & 0x1FF) as u16); // ADDR_Y
+        words.push(0x3000 | ((rnd >> 9) & 0x800) as u16 | ((rnd >> 10) & 0x1FF) as u16); // VECT_BASE_X
+        for _ in 0..8 {
+            // Mostly set bits: OR of two random masks
+            let mask = xorshift32(&mut state) | xorshift32(&mut state);
+            words.push(0x4000 | (mask & 0xFFF) as u16); // VECT_12
+        }
+        words.push(0x5000 | ((state >> 12) & 0xFF) as u16); // VECT_8
+    }
+    words.truncate(count);
+    words
+}
+
+/// Generates a stream dominated by external triggers, with a few CD events
+/// in between, as when a fast encoder or strobe is wired to the camera.
+fn trigger_heavy_words(count: usize) -> Vec<u16> {
+    let mut words = vec![0x8000u16];
+    let mut time = 0u32;
+    while words.len() < count {
+        time += 1;
+        if (time & 0xFFF) == 0 {
+            words.push(0x8000 | ((time >> 12) & 0xFFF) as u16); // TIME_HIGH
+        }
+        words.push(0x6000 | (time & 0xFFF) as u16); // TIME_LOW
+        let edge = (time & 1) as u16;
+        let channel = ((time >> 1) & 0x3) as u16;
+        words.push(0xA000 | (channel << 8) | edge); // EXT_TRIGGER
+        if (time & 7) == 0 {
+            words.push((time % 480) as u16); // ADDR_Y
+            words.push(0x2800 | (time % 640) as u16); // ADDR_X
+        }
+    }
+    words.truncate(count);
+    words
+}
+
+/// Generates background-activity-like noise: isolated events at random
+/// pixels, each with its own ADDR_Y and ADDR_X, and no vectors.
+fn noise_words(count: usize) -> Vec<u16> {
+    let mut words = vec![0x8000u16];
+    let mut state = 0x7F4A_7C15u32;
+    let mut time = 0u32;
+    while words.len() < count {
+        let rnd = xorshift32(&mut state);
+        let previous = time;
+        time += rnd >> 29; // 0..8 us between events
+        if time >> 12 != previous >> 12 {
+            words.push(0x8000 | ((time >> 12) & 0xFFF) as u16); // TIME_HIGH
+        }
+        words.push(0x6000 | (time & 0xFFF) as u16); // TIME_LOW
+        words.push((rnd % 720) as u16); // ADDR_Y
+        words.push(0x2000 | ((rnd >> 10) & 0x800) as u16 | ((rnd >> 12) % 1280) as u16);
+        // ADDR_X
+    }
+    words.truncate(count);
+    words
+}
+
+fn decode_scenario_benchmark(c: &mut Criterion) {
+    let scenarios = [
+        ("vector_heavy", vector_heavy_words(1_000_000)),
+        ("trigger_heavy", trigger_heavy_words(1_000_000)),
+        ("noise", noise_words(1_000_000)),
+    ];
+
+    let mut group = c.benchmark_group("decode_scenario");
+    group.throughput(Throughput::Elements(1_000_000));
+
+    for (name, words) in &scenarios {
+        group.bench_function(*name, |b| {
+            let mut cd_events = Vec::new();
+            let mut trigger_events = Vec::new();
+            b.iter(|| {
+                let mut decoder = Evt3Decoder::new();
+                cd_events.clear();
+                trigger_events.clear();
+                decoder.decode_buffer(black_box(words), &mut cd_events, &mut trigger_events);
+                black_box(cd_events.len() + trigger_events.len())
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn decode_sink_benchmark(c: &mut Criterion) {
+    let bytes: Vec<u8> = vector_heavy_words(1_000_000)
+        .iter()
+        .flat_map(|w| w.to_le_bytes())
+        .collect();
+
+    let mut group = c.benchmark_group("decode_sink");
+    group.throughput(Throughput::Elements(bytes.len() as u64 / 2));
+
+    // A new Vec per decode, grown as events arrive
+    group.bench_function("vec", |b| {
+        b.iter(|| {
+            let mut cd_events = Vec::new();
+            let mut trigger_events = Vec::new();
+            Evt3Decoder::new().decode_bytes(black_box(&bytes), &mut cd_events, &mut trigger_events);
+            black_box(cd_events.len())
+        })
+    });
+
+    // Buffers recycled across decodes
+    group.bench_function("vec_reused", |b| {
+        let mut buffers = EventBuffers::new();
+        b.iter(|| {
+            buffers.clear();
+            Evt3Decoder::new().decode_bytes(
+                black_box(&bytes),
+                &mut buffers.cd_events,
+                &mut buffers.trigger_events,
+            );
+            black_box(buffers.cd_events.len())
+        })
+    });
+
+    group.bench_function("columnar", |b| {
+        b.iter(|| {
+            let mut cd_events = ColumnarEvents::new();
+            let mut trigger_events = Vec::new();
+            Evt3Decoder::new().decode_bytes_columnar(
+                black_box(&bytes),
+                &mut cd_events,
+                &mut trigger_events,
+            );
+            black_box(cd_events.len())
+        })
+    });
+
+    // One read buffer at a time, events discarded after each
+    group.bench_function("streaming", |b| {
+        b.iter(|| {
+            let mut count = 0;
+            Evt3Decoder::new()
+                .decode_reader_chunked(black_box(&bytes[..]), |_, cd, _| {
+                    count += cd.len();
+                    Ok::<_, DecodeError>(())
+                })
+                .unwrap();
+            black_box(count)
+        })
+    });
+
+    group.finish();
+}
+
+/// The decoding loop as it was before words were dispatched through a table:
+/// each type nibble is mapped to a `RawEventType` with `from_u8` and matched
+/// on. Kept as the baseline of `decode_dispatch`.
+#[derive(Default)]
+struct FromU8Decoder {
+    time_base: u64,
+    time_low: u64,
+    current_time: u64,
+    n_time_high_loops: u64,
+    time_base_set: bool,
+    y: u16,
+    base_x: u16,
+    polarity: u8,
+}
+
+impl FromU8Decoder {
+    const MAX_TIMESTAMP_BASE: u64 = ((1 << 12) - 1) << 12;
+    const TIME_LOOP: u64 = Self::MAX_TIMESTAMP_BASE + (1 << 12);
+    const LOOP_THRESHOLD: u64 = 10 << 12;
+
+    fn decode(&mut self, words: &[u16], cd: &mut Vec<CdEvent>, triggers: &mut Vec<TriggerEvent>) {
+        for &word in words {
+            let event_type = parser::get_event_type(word);
+            if !self.time_base_set {
+                if event_type == RawEventType::TimeHigh as u8 {
+                    self.time_base = (parser::time_get_value(word) as u64) << 12;
+                    self.current_time = self.time_base;
+                    self.time_base_set = true;
+                }
+                continue;
+            }
+            match RawEventType::from_u8(event_type) {
+                Some(RawEventType::AddrX) => cd.push(CdEvent::new(
+                    parser::addr_x_get_x(word),
+                    self.y,
+                    parser::addr_x_get_polarity(word),
+                    self.current_time,
+                )),
+                Some(RawEventType::Vect12) => {
+                    self.vector(parser::vect_12_get_valid(word) as u32, 12, cd)
+                }
+                Some(RawEventType::Vect8) => {
+                    self.vector(parser::vect_8_get_valid(word) as u32, 8, cd)
+                }
+                Some(RawEventType::AddrY) => self.y = parser::addr_y_get_y(word),
+                Some(RawEventType::VectBaseX) => {
+                    self.base_x = parser::vect_base_x_get_x(word);
+                    self.polarity = parser::vect_base_x_get_polarity(word);
+                }
+                Some(RawEventType::TimeHigh) => {
+                    let time_val = parser::time_get_value(word) as u64;
+                    let mut time_base = (time_val << 12) + self.n_time_high_loops * Self::TIME_LOOP;
+                    if self.time_base > time_base
+                        && self.time_base - time_base
+                            >= Self::MAX_TIMESTAMP_BASE - Self::LOOP_THRESHOLD
+                    {
+                        time_base += Self::TIME_LOOP;
+                        self.n_time_high_loops += 1;
+                    }
+                    self.time_base = time_base;
+                    self.current_time = time_base;
+                }
+                Some(RawEventType::TimeLow) => {
+                    self.time_low = parser::time_get_value(word) as u64;
+                    self.current_time = self.time_base + self.time_low;
+                }
+                Some(RawEventType::ExtTrigger) => triggers.push(TriggerEvent::new(
+                    parser::ext_trigger_get_value(word),
+                    parser::ext_trigger_get_id(word),
+                    self.current_time,
+                )),
+                Some(_) | None => {}
+            }
+        }
+    }
+
+    fn vector(&mut self, mut valid: u32, count: u16, cd: &mut Vec<CdEvent>) {
+        let end_x = self.base_x + count;
+        for x in self.base_x..end_x {
+            if valid & 1 != 0 {
+                cd.push(CdEvent::new(x, self.y, self.polarity, self.current_time));
+            }
+            valid >>= 1;
+        }
+        self.base_x = end_x;
+    }
+}
+
+fn decode_dispatch_benchmark(c: &mut Criterion) {
+    let words = mixed_words(1_000_000);
+
+    let mut group = c.benchmark_group("decode_dispatch");
+    group.throughput(Throughput::Elements(words.len() as u64));
+
+    // Same output, checked so the baseline cannot drift from the decoder
+    let (mut expected, mut expected_triggers) = (Vec::new(), Vec::new());
+    Evt3Decoder::new().decode_buffer(&words, &mut expected, &mut expected_triggers);
+    let (mut baseline, mut baseline_triggers) = (Vec::new(), Vec::new());
+    FromU8Decoder::default().decode(&words, &mut baseline, &mut baseline_triggers);
+    assert_eq!(baseline, expected);
+    assert_eq!(baseline_triggers, expected_triggers);
+
+    group.bench_function("from_u8_match", |b| {
+        let mut cd_events = Vec::new();
+        let mut trigger_events = Vec::new();
+        b.iter(|| {
+            cd_events.clear();
+            trigger_events.clear();
+            FromU8Decoder::default().decode(black_box(&words), &mut cd_events, &mut trigger_events);
+            black_box(cd_events.len())
+        })
+    });
+
+    group.bench_function("mixed_word_types", |b| {
+        let mut cd_events = Vec::new();
+        let mut trigger_events = Vec::new();
+        b.iter(|| {
+            let mut decoder = Evt3Decoder::new();
+            cd_events.clear();
+            trigger_events.clear();
+            decoder.decode_buffer(black_box(&words), &mut cd_events, &mut trigger_events);
+            black_box(cd_events.len())
+        })
+    });
+
+    group.finish();
+}
+
+fn decode_parallel_benchmark(c: &mut Criterion) {
+    // Several chunks of the parallel decoder
+    let bytes: Vec<u8> = mixed_words(8_000_000)
+        .iter()
+        .flat_map(|w| w.to_le_bytes())
+        .collect();
+
+    let mut group = c.benchmark_group("decode_parallel");
+    group.throughput(Throughput::Elements(bytes.len() as u64 / 2));
+    group.sample_size(10);
+
+    group.bench_function("serial", |b| {
+        b.iter(|| {
+            let mut cd_events = Vec::new();
+            let mut trigger_events = Vec::new();
+            Evt3Decoder::new().decode_bytes(black_box(&bytes), &mut cd_events, &mut trigger_events);
+            black_box(cd_events.len())
+        })
+    });
+
+    group.bench_function("parallel", |b| {
+        b.iter(|| {
+            let mut cd_events = Vec::new();
+            let mut trigger_events = Vec::new();
+            Evt3Decoder::new().decode_bytes_parallel(
+                black_box(&bytes),
+                &mut cd_events,
+                &mut trigger_events,
+            );
+            black_box(cd_events.len())
+        })
+    });
+
     group.finish();
 }
 
-criterion_group!(benches, decode_file_benchmark, decode_buffer_benchmark);
+criterion_group!(
+    benches,
+    decode_file_benchmark,
+    decode_buffer_benchmark,
+    decode_dispatch_benchmark,
+    decode_scenario_benchmark,
+    decode_sink_benchmark,
+    decode_parallel_benchmark
+);
 criterion_main!(benches);
@@ -0,0 +1,140 @@
+//! Reusable event buffers.
+//!
+//! Decoding a long recording grows event vectors to several gigabytes.
+//! When converting many recordings, [`EventBuffers`] passed to the `*_into`
+//! decode methods keep those allocations from one file to the next, and a
+//! [`BufferPool`] shares them between threads.
+
+use crate::types::{CdEvent, DecodeResult, SensorMetadata, TriggerEvent};
+use std::sync::Mutex;
+
+/// Event vectors that keep their allocations across decodes.
+///
+/// Filled by [`Evt3Decoder::decode_buffer_into`](crate::decoder::Evt3Decoder::decode_buffer_into),
+/// [`decode_file_into`](crate::decoder::Evt3Decoder::decode_file_into) and
+/// [`decode_file_parallel_into`](crate::decoder::Evt3Decoder::decode_file_parallel_into).
+#[derive(Debug, Default)]
+pub struct EventBuffers {
+    /// Decoded CD events
+    pub cd_events: Vec<CdEvent>,
+    /// Decoded trigger events
+    pub trigger_events: Vec<TriggerEvent>,
+    // Raw data of a file read into memory for parallel decoding
+    pub(crate) bytes: Vec<u8>,
+}
+
+impl EventBuffers {
+    /// Creates empty buffers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Removes all events, keeping the allocations.
+    pub fn clear(&mut self) {
+        self.cd_events.clear();
+        self.trigger_events.clear();
+        self.bytes.clear();
+    }
+
+    /// Moves the events out into a [`DecodeResult`], leaving the event
+    /// vectors empty.
+    ///
+    /// Hand the result back with [`recycle`](Self::recycle) once done with
+    /// it to reuse its vectors.
+    pub fn take_result(&mut self, metadata: SensorMetadata) -> DecodeResult {
+        DecodeResult {
+            cd_events: std::mem::take(&mut self.cd_events),
+            trigger_events: std::mem::take(&mut self.trigger_events),
+            metadata,
+        }
+    }
+
+    /// Takes over the vectors of `result` for the next decode, cleared.
+    pub fn recycle(&mut self, result: DecodeResult) {
+        self.cd_events = result.cd_events;
+        self.trigger_events = result.trigger_events;
+        self.cd_events.clear();
+        self.trigger_events.clear();
+    }
+}
+
+/// A shared stock of [`EventBuffers`] for decoding on several threads.
+///
+/// [`take`](Self::take) hands out buffers returned earlier with
+/// [`put`](Self::put), or new ones if there are none, so the pool grows to
+/// as many buffers as are used at the same time.
+#[derive(Debug, Default)]
+pub struct BufferPool {
+    buffers: Mutex<Vec<EventBuffers>>,
+}
+
+impl BufferPool {
+    /// Creates an empty pool.
+    pub const fn new() -> Self {
+        Self {
+            buffers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Takes buffers from the pool, or new ones if it is empty.
+    pub fn take(&self) -> EventBuffers {
+        self.buffers.lock().unwrap().pop().unwrap_or_default()
+    }
+
+    /// Returns buffers to the pool, cleared.
+    pub fn put(&self, mut buffers: EventBuffers) {
+        buffers.clear();
+        self.buffers.lock().unwrap().push(buffers);
+    }
+
+    /// Returns the number of buffers in the pool.
+    pub fn len(&self) -> usize {
+        self.buffers.lock().unwrap().len()
+    }
+
+    /// Returns `true` if the pool holds no buffers.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_result_and_recycle() {
+        let mut buffers = EventBuffers::new();
+        buffers
+            .cd_events
+            .extend((0..100).map(|t| CdEvent::new(0, 0, 1, t)));
+        buffers.trigger_events.push(TriggerEvent::new(1, 0, 5));
+        let result = buffers.take_result(SensorMetadata::default());
+        assert_eq!(result.cd_events.len(), 100);
+        assert_eq!(result.trigger_events.len(), 1);
+        assert!(buffers.cd_events.is_empty());
+
+        let allocation = result.cd_events.as_ptr();
+        buffers.recycle(result);
+        assert!(buffers.cd_events.is_empty());
+        assert!(buffers.cd_events.capacity() >= 100);
+        assert_eq!(buffers.cd_events.as_ptr(), allocation);
+    }
+
+    #[test]
+    fn test_buffer_pool() {
+        static POOL: BufferPool = BufferPool::new();
+        assert!(POOL.is_empty());
+
+        let mut buffers = POOL.take();
+        buffers.cd_events.resize(1000, CdEvent::new(0, 0, 0, 0));
+        let allocation = buffers.cd_events.as_ptr();
+        POOL.put(buffers);
+        assert_eq!(POOL.len(), 1);
+
+        let buffers = POOL.take();
+        assert!(buffers.cd_events.is_empty());
+        assert_eq!(buffers.cd_events.as_ptr(), allocation);
+        assert!(POOL.is_empty());
+    }
+}
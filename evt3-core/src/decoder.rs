@@ -3,11 +3,24 @@
 //! This module implements the EVT 3.0 decoding state machine that tracks
 //! timestamp, coordinates, and polarity across events.
 
+use crate::buffers::EventBuffers;
+use crate::filters::{self, EventFilter};
 use crate::parser;
-use crate::types::{CdEvent, DecodeResult, RawEventType, SensorMetadata, TriggerEvent};
+use crate::spill::{SpilledDecodeResult, SpilledEvents, BYTES_PER_EVENT};
+use crate::stats::{DecodeSummary, SummaryBuilder};
+use crate::transforms::{self, EventTransform};
+use crate::types::{
+    CdEvent, ColumnarDecodeResult, ColumnarEvents, DecodeResult, EventChunk, ProbeResult,
+    RawEventType, SensorMetadata, TriggerEvent,
+};
+use crossbeam_channel::Receiver;
+use std::fmt;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Read};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::marker::PhantomData;
+use std::ops::ControlFlow;
 use std::path::Path;
+use std::sync::mpsc::{self, SyncSender};
 use thiserror::Error;
 
 /// Errors that can occur during EVT 3.0 decoding.
@@ -28,14 +41,211 @@ const MAX_TIMESTAMP_BASE: u64 = ((1u64 << 12) - 1) << 12; // 16773120us
 const TIME_LOOP: u64 = MAX_TIMESTAMP_BASE + (1 << 12); // 16777216us
 const LOOP_THRESHOLD: u64 = 10 << 12; // Threshold for loop detection
 
-/// Buffer size for reading raw data (number of 16-bit words).
-const READ_BUFFER_SIZE: usize = 1_000_000;
+/// Default size of the buffer raw data is read into, in bytes.
+///
+/// Change it per decoder with [`Evt3Decoder::set_read_buffer_size`].
+pub const DEFAULT_READ_BUFFER_SIZE: usize = 2_000_000;
+
+/// Read buffers queued between the reader thread and the decoder when
+/// decoding files.
+const PIPELINE_DEPTH: usize = 2;
+
+/// Bytes decoded at each sample position by [`Evt3Decoder::probe`].
+const PROBE_WINDOW: u64 = 64 * 1024;
+
+/// Distance in bytes between sample positions in [`Evt3Decoder::probe`].
+const PROBE_STRIDE: u64 = 4 * 1024 * 1024;
+
+/// Number of CD events in each chunk sent by [`Evt3Decoder::spawn_decode`].
+pub const SPAWN_CHUNK_EVENTS: usize = 65_536;
+
+/// Chunks [`Evt3Decoder::spawn_decode`] decodes ahead of its receiver.
+const SPAWN_QUEUE_CHUNKS: usize = 16;
+
+/// Files smaller than this are decoded without first estimating their
+/// event count, as growing the event vectors costs little at that size.
+const PREALLOCATE_MIN_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Destination of decoded CD events: a `Vec<CdEvent>` or [`ColumnarEvents`].
+pub(crate) trait EventSink {
+    fn push(&mut self, event: CdEvent);
+
+    fn len(&self) -> usize;
+
+    fn reserve(&mut self, additional: usize);
+
+    /// Runs filters and then transforms over the events from `start` on.
+    fn process_tail(
+        &mut self,
+        filters: &mut [Box<dyn EventFilter>],
+        transforms: &mut [Box<dyn EventTransform>],
+        start: usize,
+    );
+}
+
+impl EventSink for Vec<CdEvent> {
+    #[inline]
+    fn push(&mut self, event: CdEvent) {
+        Vec::push(self, event);
+    }
+
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        Vec::reserve(self, additional);
+    }
+
+    fn process_tail(
+        &mut self,
+        filters: &mut [Box<dyn EventFilter>],
+        transforms: &mut [Box<dyn EventTransform>],
+        start: usize,
+    ) {
+        filters::retain_tail(filters, self, start);
+        transforms::apply_tail(transforms, self, start);
+    }
+}
+
+impl EventSink for ColumnarEvents {
+    #[inline]
+    fn push(&mut self, event: CdEvent) {
+        ColumnarEvents::push(self, event);
+    }
+
+    fn len(&self) -> usize {
+        ColumnarEvents::len(self)
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        ColumnarEvents::reserve(self, additional);
+    }
+
+    fn process_tail(
+        &mut self,
+        filters: &mut [Box<dyn EventFilter>],
+        transforms: &mut [Box<dyn EventTransform>],
+        start: usize,
+    ) {
+        if filters.is_empty() && transforms.is_empty() {
+            return;
+        }
+        self.retain_tail(start, |event| {
+            filters.iter_mut().all(|filter| filter.keep(event))
+                && transforms.iter_mut().all(|t| t.apply(event))
+        });
+    }
+}
+
+/// Type nibbles of the words handled by [`Evt3Decoder::decode_buffer`].
+const ADDR_Y: u8 = RawEventType::AddrY as u8;
+const ADDR_X: u8 = RawEventType::AddrX as u8;
+const VECT_BASE_X: u8 = RawEventType::VectBaseX as u8;
+const VECT_12: u8 = RawEventType::Vect12 as u8;
+const VECT_8: u8 = RawEventType::Vect8 as u8;
+const TIME_LOW: u8 = RawEventType::TimeLow as u8;
+const TIME_HIGH: u8 = RawEventType::TimeHigh as u8;
+const EXT_TRIGGER: u8 = RawEventType::ExtTrigger as u8;
+
+/// Handler of one word type in [`Evt3Decoder::decode_words`].
+type Handler<S> = fn(&mut Evt3Decoder, u16, &mut S, &mut Vec<TriggerEvent>);
+
+/// Word handlers for an event sink, indexed by the 4-bit word type.
+struct Dispatch<S>(PhantomData<S>);
+
+impl<S: EventSink> Dispatch<S> {
+    /// Types without CD or trigger data (CONTINUED_4, OTHERS, CONTINUED_12
+    /// and reserved ones) are skipped.
+    const TABLE: [Handler<S>; 16] = {
+        let mut table = [Evt3Decoder::on_skip as Handler<S>; 16];
+        table[ADDR_Y as usize] = Evt3Decoder::on_addr_y;
+        table[ADDR_X as usize] = Evt3Decoder::on_addr_x;
+        table[VECT_BASE_X as usize] = Evt3Decoder::on_vect_base_x;
+        table[VECT_12 as usize] = Evt3Decoder::on_vect_12;
+        table[VECT_8 as usize] = Evt3Decoder::on_vect_8;
+        table[TIME_LOW as usize] = Evt3Decoder::on_time_low;
+        table[TIME_HIGH as usize] = Evt3Decoder::on_time_high;
+        table[EXT_TRIGGER as usize] = Evt3Decoder::on_ext_trigger;
+        table
+    };
+}
+
+/// Reader that counts the bytes read through it, for progress reporting.
+struct CountingReader<R> {
+    inner: R,
+    count: u64,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+}
+
+/// A buffer of whole words read by [`read_chunks`], with the number of
+/// bytes read from the file so far.
+type Chunk = std::io::Result<(Vec<u8>, u64)>;
+
+/// Reads `reader` into buffers of whole words and sends them to `chunks`
+/// until the end of the data, an error or the receiver hanging up.
+///
+/// Buffers sent back through `free` are reused. A read ending mid-word
+/// keeps its odd byte for the next buffer.
+fn read_chunks<R: Read>(
+    mut reader: BufReader<CountingReader<R>>,
+    chunks: SyncSender<Chunk>,
+    free: mpsc::Receiver<Vec<u8>>,
+    buffer_size: usize,
+) {
+    let mut carry = None;
+    loop {
+        let mut buffer = free.try_recv().unwrap_or_default();
+        buffer.resize(buffer_size, 0);
+        let mut filled = 0;
+        if let Some(byte) = carry.take() {
+            buffer[0] = byte;
+            filled = 1;
+        }
+        let bytes_read = loop {
+            match reader.read(&mut buffer[filled..]) {
+                Ok(n) => break n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    let _ = chunks.send(Err(e));
+                    return;
+                }
+            }
+        };
+        if bytes_read == 0 {
+            return;
+        }
+        filled += bytes_read;
+        if filled % 2 == 1 {
+            filled -= 1;
+            carry = Some(buffer[filled]);
+        }
+        buffer.truncate(filled);
+        if chunks.send(Ok((buffer, reader.get_ref().count))).is_err() {
+            return;
+        }
+    }
+}
+
+/// Reads little-endian words from `bytes`, ignoring a trailing odd byte.
+#[inline]
+pub(crate) fn le_words(bytes: &[u8]) -> impl Iterator<Item = u16> + '_ {
+    bytes
+        .chunks_exact(2)
+        .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+}
 
 /// Stateful EVT 3.0 decoder.
 ///
 /// Maintains internal state to properly reconstruct the event stream according
 /// to the EVT 3.0 specification.
-#[derive(Debug)]
 pub struct Evt3Decoder {
     // Timestamp state
     time_base: u64,
@@ -49,10 +259,66 @@ pub struct Evt3Decoder {
     current_base_x: u16,
     current_polarity: u8,
 
+    // Streaming filters and transforms applied to decoded CD events
+    filters: Vec<Box<dyn EventFilter>>,
+    transforms: Vec<Box<dyn EventTransform>>,
+
+    // Called with the number of bytes read so far after every read
+    progress: Option<Box<dyn FnMut(u64) + Send>>,
+
+    // Size of the buffer each read fills, in bytes
+    read_buffer_size: usize,
+
     // Metadata
     pub metadata: SensorMetadata,
 }
 
+/// Position of the decoding state machine between two words.
+///
+/// A state captured with [`Evt3Decoder::state`] after decoding the data up
+/// to some byte offset lets another decoder resume at that offset with
+/// [`Evt3Decoder::set_state`] instead of decoding from the start, e.g. to
+/// seek through a recording with a time index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DecoderState {
+    /// Timestamp base of the last TIME_HIGH, including wrap-arounds
+    pub time_base: u64,
+    /// Value of the last TIME_LOW
+    pub time_low: u64,
+    /// Timestamp of the next event
+    pub current_time: u64,
+    /// Number of TIME_HIGH wrap-arounds so far
+    pub n_time_high_loops: u64,
+    /// Whether a TIME_HIGH has been seen; data before it is skipped
+    pub time_base_set: bool,
+    /// Row of the next event
+    pub y: u16,
+    /// Column of the next vector event
+    pub base_x: u16,
+    /// Polarity of the next vector event
+    pub polarity: u8,
+}
+
+impl fmt::Debug for Evt3Decoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Evt3Decoder")
+            .field("time_base", &self.time_base)
+            .field("time_low", &self.time_low)
+            .field("current_time", &self.current_time)
+            .field("n_time_high_loops", &self.n_time_high_loops)
+            .field("first_time_base_set", &self.first_time_base_set)
+            .field("current_y", &self.current_y)
+            .field("current_base_x", &self.current_base_x)
+            .field("current_polarity", &self.current_polarity)
+            .field("filters", &self.filters.len())
+            .field("transforms", &self.transforms.len())
+            .field("progress", &self.progress.is_some())
+            .field("read_buffer_size", &self.read_buffer_size)
+            .field("metadata", &self.metadata)
+            .finish()
+    }
+}
+
 impl Default for Evt3Decoder {
     fn default() -> Self {
         Self::new()
@@ -71,11 +337,105 @@ impl Evt3Decoder {
             current_y: 0,
             current_base_x: 0,
             current_polarity: 0,
+            filters: Vec::new(),
+            transforms: Vec::new(),
+            progress: None,
+            read_buffer_size: DEFAULT_READ_BUFFER_SIZE,
             metadata: SensorMetadata::default(),
         }
     }
 
-    /// Resets the decoder state.
+    /// Sets a callback reporting how far decoding has progressed.
+    ///
+    /// It is called after every read with the total number of bytes read
+    /// from the file or stream so far, header included, so comparing it to
+    /// the file size gives the fraction decoded. Applies to all `decode_*`
+    /// methods that read from a file or reader.
+    pub fn set_progress_callback<F: FnMut(u64) + Send + 'static>(&mut self, callback: F) {
+        self.progress = Some(Box::new(callback));
+    }
+
+    /// Removes the progress callback.
+    pub fn clear_progress_callback(&mut self) {
+        self.progress = None;
+    }
+
+    /// Calls the progress callback, if any, with the bytes read so far.
+    pub(crate) fn report_progress(&mut self, bytes_read: u64) {
+        if let Some(progress) = &mut self.progress {
+            progress(bytes_read);
+        }
+    }
+
+    /// Sets the size of the buffer raw data is read into, in bytes.
+    ///
+    /// The default of [`DEFAULT_READ_BUFFER_SIZE`] suits most disks; fast
+    /// NVMe arrays and network filesystems can reach higher throughput
+    /// with reads of tens of megabytes. Sizes below 2 bytes are raised to
+    /// 2. When decoding files, a few buffers of this size are in flight
+    /// between the reader thread and the decoder.
+    pub fn set_read_buffer_size(&mut self, bytes: usize) {
+        self.read_buffer_size = bytes.max(2);
+    }
+
+    /// Returns the size of the buffer raw data is read into, in bytes.
+    pub fn read_buffer_size(&self) -> usize {
+        self.read_buffer_size
+    }
+
+    /// Adds a streaming filter applied to CD events as they are decoded.
+    ///
+    /// Filters run in the order they were added; an event is kept only if
+    /// every filter keeps it. Trigger events are not filtered. The filter is
+    /// given the current sensor geometry, and the geometry from the file
+    /// header once [`decode_file`](Self::decode_file) has parsed it.
+    pub fn add_filter<F: EventFilter + 'static>(&mut self, mut filter: F) {
+        filter.set_geometry(&self.metadata);
+        self.filters.push(Box::new(filter));
+    }
+
+    /// Removes all streaming filters.
+    pub fn clear_filters(&mut self) {
+        self.filters.clear();
+    }
+
+    /// Adds a transform applied to CD events as they are decoded.
+    ///
+    /// Transforms run in the order they were added, after all filters, so
+    /// filters always see sensor coordinates. The geometry reported in
+    /// [`DecodeResult::metadata`] is the transformed one.
+    pub fn add_transform<T: EventTransform + 'static>(&mut self, mut transform: T) {
+        transform.set_geometry(&self.output_metadata());
+        self.transforms.push(Box::new(transform));
+    }
+
+    /// Removes all transforms.
+    pub fn clear_transforms(&mut self) {
+        self.transforms.clear();
+    }
+
+    /// Passes the current sensor geometry down the filter and transform chain.
+    fn update_geometry(&mut self) {
+        for filter in &mut self.filters {
+            filter.set_geometry(&self.metadata);
+        }
+        let mut metadata = self.metadata.clone();
+        for transform in &mut self.transforms {
+            transform.set_geometry(&metadata);
+            metadata = transform.transform_metadata(&metadata);
+        }
+    }
+
+    /// Returns the sensor geometry after applying all transforms.
+    pub fn output_metadata(&self) -> SensorMetadata {
+        self.transforms
+            .iter()
+            .fold(self.metadata.clone(), |metadata, transform| {
+                transform.transform_metadata(&metadata)
+            })
+    }
+
+    /// Resets the decoder state, including the state of all filters.
     pub fn reset(&mut self) {
         self.time_base = 0;
         self.time_low = 0;
@@ -85,22 +445,125 @@ impl Evt3Decoder {
         self.current_y = 0;
         self.current_base_x = 0;
         self.current_polarity = 0;
+        for filter in &mut self.filters {
+            filter.reset();
+        }
+    }
+
+    /// Returns the state of the decoding state machine, see [`DecoderState`].
+    pub fn state(&self) -> DecoderState {
+        DecoderState {
+            time_base: self.time_base,
+            time_low: self.time_low,
+            current_time: self.current_time,
+            n_time_high_loops: self.n_time_high_loops,
+            time_base_set: self.first_time_base_set,
+            y: self.current_y,
+            base_x: self.current_base_x,
+            polarity: self.current_polarity,
+        }
+    }
+
+    /// Restores a state returned by [`state`](Self::state), so the next
+    /// buffer is decoded as if the data before it had been decoded.
+    ///
+    /// The state of filters is not part of it.
+    pub fn set_state(&mut self, state: DecoderState) {
+        self.time_base = state.time_base;
+        self.time_low = state.time_low;
+        self.current_time = state.current_time;
+        self.n_time_high_loops = state.n_time_high_loops;
+        self.first_time_base_set = state.time_base_set;
+        self.current_y = state.y;
+        self.current_base_x = state.base_x;
+        self.current_polarity = state.polarity;
     }
 
     /// Decodes a buffer of 16-bit words into CD and trigger events.
     ///
     /// This is the core decoding function that processes raw EVT 3.0 data.
+    /// Decoded events are appended; registered filters and transforms only
+    /// see the events decoded by this call.
     pub fn decode_buffer(
         &mut self,
         words: &[u16],
         cd_events: &mut Vec<CdEvent>,
         trigger_events: &mut Vec<TriggerEvent>,
     ) {
-        let mut iter = words.iter();
+        self.decode_into(words.iter().copied(), cd_events, trigger_events);
+    }
+
+    /// Decodes a buffer of 16-bit words into reusable [`EventBuffers`].
+    ///
+    /// Like [`decode_buffer`](Self::decode_buffer); events are appended.
+    pub fn decode_buffer_into(&mut self, words: &[u16], buffers: &mut EventBuffers) {
+        self.decode_buffer(words, &mut buffers.cd_events, &mut buffers.trigger_events);
+    }
+
+    /// Decodes little-endian EVT 3.0 data straight from bytes.
+    ///
+    /// Same as [`decode_buffer`](Self::decode_buffer) without converting the
+    /// bytes to a `Vec<u16>` first. A trailing odd byte is ignored: callers
+    /// feeding data in pieces must carry it over to the next call.
+    pub fn decode_bytes(
+        &mut self,
+        bytes: &[u8],
+        cd_events: &mut Vec<CdEvent>,
+        trigger_events: &mut Vec<TriggerEvent>,
+    ) {
+        self.decode_into(le_words(bytes), cd_events, trigger_events);
+    }
+
+    /// Decodes little-endian EVT 3.0 data straight into columns.
+    ///
+    /// Like [`decode_bytes`](Self::decode_bytes), but fills one vector per
+    /// field instead of a `Vec<CdEvent>`.
+    pub fn decode_bytes_columnar(
+        &mut self,
+        bytes: &[u8],
+        cd_events: &mut ColumnarEvents,
+        trigger_events: &mut Vec<TriggerEvent>,
+    ) {
+        self.decode_into(le_words(bytes), cd_events, trigger_events);
+    }
+
+    /// Decodes words into `cd_events` and runs the filters and transforms
+    /// over the new events.
+    fn decode_into<I, S>(
+        &mut self,
+        words: I,
+        cd_events: &mut S,
+        trigger_events: &mut Vec<TriggerEvent>,
+    ) where
+        I: IntoIterator<Item = u16>,
+        S: EventSink,
+    {
+        let start = cd_events.len();
+        self.decode_words(words, cd_events, trigger_events);
+        self.process_tail(cd_events, start);
+    }
+
+    /// Runs the registered filters and then transforms over the events of
+    /// `cd_events` from `start` on.
+    pub(crate) fn process_tail<S: EventSink>(&mut self, cd_events: &mut S, start: usize) {
+        cd_events.process_tail(&mut self.filters, &mut self.transforms, start);
+    }
+
+    /// Runs the decoding state machine over a sequence of words.
+    pub(crate) fn decode_words<I, S>(
+        &mut self,
+        words: I,
+        cd_events: &mut S,
+        trigger_events: &mut Vec<TriggerEvent>,
+    ) where
+        I: IntoIterator<Item = u16>,
+        S: EventSink,
+    {
+        let mut iter = words.into_iter();
 
         // Skip until first TIME_HIGH if not yet set
         if !self.first_time_base_set {
-            for &word in iter.by_ref() {
+            for word in iter.by_ref() {
                 let event_type = parser::get_event_type(word);
                 if event_type == RawEventType::TimeHigh as u8 {
                     let time_val = parser::time_get_value(word);
@@ -112,65 +575,55 @@ impl Evt3Decoder {
             }
         }
 
-        // Process remaining events
-        for &word in iter {
-            let event_type = parser::get_event_type(word);
-
-            match RawEventType::from_u8(event_type) {
-                Some(RawEventType::AddrX) => {
-                    let x = parser::addr_x_get_x(word);
-                    let pol = parser::addr_x_get_polarity(word);
-                    cd_events.push(CdEvent::new(x, self.current_y, pol, self.current_time));
-                }
+        // Process remaining events through the 16-entry handler table
+        let table = Dispatch::<S>::TABLE;
+        for word in iter {
+            table[parser::get_event_type(word) as usize](self, word, cd_events, trigger_events);
+        }
+    }
 
-                Some(RawEventType::Vect12) => {
-                    let valid = parser::vect_12_get_valid(word);
-                    self.process_vector_events(valid as u32, 12, cd_events);
-                }
+    // Handlers of `Dispatch::TABLE`, one per word type
 
-                Some(RawEventType::Vect8) => {
-                    let valid = parser::vect_8_get_valid(word);
-                    self.process_vector_events(valid as u32, 8, cd_events);
-                }
+    fn on_addr_x<S: EventSink>(&mut self, word: u16, cd: &mut S, _: &mut Vec<TriggerEvent>) {
+        let x = parser::addr_x_get_x(word);
+        let pol = parser::addr_x_get_polarity(word);
+        cd.push(CdEvent::new(x, self.current_y, pol, self.current_time));
+    }
 
-                Some(RawEventType::AddrY) => {
-                    self.current_y = parser::addr_y_get_y(word);
-                }
+    fn on_vect_12<S: EventSink>(&mut self, word: u16, cd: &mut S, _: &mut Vec<TriggerEvent>) {
+        self.process_vector_events(parser::vect_12_get_valid(word) as u32, 12, cd);
+    }
 
-                Some(RawEventType::VectBaseX) => {
-                    self.current_base_x = parser::vect_base_x_get_x(word);
-                    self.current_polarity = parser::vect_base_x_get_polarity(word);
-                }
+    fn on_vect_8<S: EventSink>(&mut self, word: u16, cd: &mut S, _: &mut Vec<TriggerEvent>) {
+        self.process_vector_events(parser::vect_8_get_valid(word) as u32, 8, cd);
+    }
 
-                Some(RawEventType::TimeHigh) => {
-                    self.process_time_high(word);
-                }
+    fn on_addr_y<S: EventSink>(&mut self, word: u16, _: &mut S, _: &mut Vec<TriggerEvent>) {
+        self.current_y = parser::addr_y_get_y(word);
+    }
 
-                Some(RawEventType::TimeLow) => {
-                    self.time_low = parser::time_get_value(word) as u64;
-                    self.current_time = self.time_base + self.time_low;
-                }
+    fn on_vect_base_x<S: EventSink>(&mut self, word: u16, _: &mut S, _: &mut Vec<TriggerEvent>) {
+        self.current_base_x = parser::vect_base_x_get_x(word);
+        self.current_polarity = parser::vect_base_x_get_polarity(word);
+    }
 
-                Some(RawEventType::ExtTrigger) => {
-                    let value = parser::ext_trigger_get_value(word);
-                    let id = parser::ext_trigger_get_id(word);
-                    trigger_events.push(TriggerEvent::new(value, id, self.current_time));
-                }
+    fn on_time_high<S: EventSink>(&mut self, word: u16, _: &mut S, _: &mut Vec<TriggerEvent>) {
+        self.process_time_high(word);
+    }
 
-                Some(RawEventType::Continued4)
-                | Some(RawEventType::Others)
-                | Some(RawEventType::Continued12) => {
-                    // These event types are not commonly used for CD events
-                    // and are skipped in this implementation
-                }
+    fn on_time_low<S: EventSink>(&mut self, word: u16, _: &mut S, _: &mut Vec<TriggerEvent>) {
+        self.time_low = parser::time_get_value(word) as u64;
+        self.current_time = self.time_base + self.time_low;
+    }
 
-                None => {
-                    // Reserved/unknown event type, skip
-                }
-            }
-        }
+    fn on_ext_trigger<S: EventSink>(&mut self, word: u16, _: &mut S, t: &mut Vec<TriggerEvent>) {
+        let value = parser::ext_trigger_get_value(word);
+        let id = parser::ext_trigger_get_id(word);
+        t.push(TriggerEvent::new(value, id, self.current_time));
     }
 
+    fn on_skip<S: EventSink>(&mut self, _: u16, _: &mut S, _: &mut Vec<TriggerEvent>) {}
+
     /// Processes TIME_HIGH events with loop detection.
     #[inline]
     fn process_time_high(&mut self, word: u16) {
@@ -191,65 +644,583 @@ impl Evt3Decoder {
 
     /// Processes vector events (VECT_12 or VECT_8) and emits CD events.
     #[inline]
-    fn process_vector_events(&mut self, mut valid: u32, count: u16, cd_events: &mut Vec<CdEvent>) {
-        let end_x = self.current_base_x + count;
-
-        for x in self.current_base_x..end_x {
-            if valid & 0x1 != 0 {
-                cd_events.push(CdEvent::new(
-                    x,
-                    self.current_y,
-                    self.current_polarity,
-                    self.current_time,
-                ));
-            }
-            valid >>= 1;
+    fn process_vector_events<S: EventSink>(
+        &mut self,
+        mut valid: u32,
+        count: u16,
+        cd_events: &mut S,
+    ) {
+        // Visit only the set bits, so the loop branches once per event
+        // rather than once per bit of a random-looking mask
+        while valid != 0 {
+            let x = self.current_base_x + valid.trailing_zeros() as u16;
+            cd_events.push(CdEvent::new(
+                x,
+                self.current_y,
+                self.current_polarity,
+                self.current_time,
+            ));
+            valid &= valid - 1;
         }
 
-        self.current_base_x = end_x;
+        self.current_base_x += count;
     }
 
     /// Decodes an EVT 3.0 file from disk.
     ///
-    /// Parses the file header (if present) and decodes all events.
+    /// Parses the file header (if present) and decodes all events. For large
+    /// files the event vector is sized up front from a [`probe`](Self::probe)
+    /// of the file, instead of being reallocated and copied as it grows.
     pub fn decode_file<P: AsRef<Path>>(&mut self, path: P) -> Result<DecodeResult, DecodeError> {
-        let file = File::open(path.as_ref())?;
-        let mut reader = BufReader::new(file);
+        let mut buffers = EventBuffers::new();
+        let metadata = self.decode_file_into(path, &mut buffers)?;
+        Ok(buffers.take_result(metadata))
+    }
+
+    /// Decodes an EVT 3.0 file from disk into reusable [`EventBuffers`],
+    /// returning the output sensor geometry.
+    ///
+    /// Like [`decode_file`](Self::decode_file), but the buffers are cleared
+    /// and refilled instead of allocating new vectors, so converting many
+    /// recordings in turn reuses one allocation.
+    pub fn decode_file_into<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        buffers: &mut EventBuffers,
+    ) -> Result<SensorMetadata, DecodeError> {
+        buffers.clear();
+        self.reserve_for_file(path.as_ref(), &mut buffers.cd_events);
+        self.decode_file_chunks(
+            path.as_ref(),
+            &mut buffers.cd_events,
+            &mut buffers.trigger_events,
+            |_, _, _| ControlFlow::Continue(()),
+        )?;
+        Ok(self.output_metadata())
+    }
+
+    /// Decodes an EVT 3.0 file from disk into [`ColumnarEvents`].
+    ///
+    /// Like [`decode_file`](Self::decode_file), but the CD events are
+    /// written straight into one vector per field, ready to be handed to
+    /// columnar consumers without a conversion pass.
+    pub fn decode_file_columnar<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<ColumnarDecodeResult, DecodeError> {
+        let mut cd_events = ColumnarEvents::new();
+        let mut trigger_events = Vec::new();
+        self.reserve_for_file(path.as_ref(), &mut cd_events);
+        self.decode_file_chunks(
+            path.as_ref(),
+            &mut cd_events,
+            &mut trigger_events,
+            |_, _, _| ControlFlow::Continue(()),
+        )?;
+
+        Ok(ColumnarDecodeResult {
+            cd_events,
+            trigger_events,
+            metadata: self.output_metadata(),
+        })
+    }
+
+    /// Decodes an EVT 3.0 file keeping at most about `memory_budget` bytes
+    /// of CD events in memory.
+    ///
+    /// Like [`decode_file_columnar`](Self::decode_file_columnar), but once
+    /// the decoded events exceed the budget they are written to a temporary
+    /// file in `spill_dir` and memory is reused for the next events. The
+    /// budget can be overshot by the events of one read and by the spare
+    /// capacity of the growing vectors. Use a directory on
+    /// disk: a tmpfs such as `/tmp` on many systems is backed by memory
+    /// itself. Trigger events always stay in memory.
+    ///
+    /// ```no_run
+    /// use evt3_core::Evt3Decoder;
+    ///
+    /// let result = Evt3Decoder::new()
+    ///     .decode_file_spilled("recording.raw", 4 << 30, "/data/scratch")
+    ///     .unwrap();
+    /// for event in result.cd_events.iter() {
+    ///     let event = event.unwrap();
+    ///     // ...
+    /// }
+    /// ```
+    pub fn decode_file_spilled<P: AsRef<Path>, Q: AsRef<Path>>(
+        &mut self,
+        path: P,
+        memory_budget: usize,
+        spill_dir: Q,
+    ) -> Result<SpilledDecodeResult, DecodeError> {
+        let budget_events = (memory_budget / BYTES_PER_EVENT).max(1);
+        let mut spilled = SpilledEvents::default();
+        let mut error = None;
+        // Grown as needed; spilling keeps the allocation for the next events
+        let mut cd_events = ColumnarEvents::new();
+        let mut trigger_events = Vec::new();
+        self.decode_file_chunks(
+            path.as_ref(),
+            &mut cd_events,
+            &mut trigger_events,
+            |_, cd, _| {
+                if cd.len() < budget_events {
+                    return ControlFlow::Continue(());
+                }
+                match spilled.spill(cd, spill_dir.as_ref()) {
+                    Ok(()) => ControlFlow::Continue(()),
+                    Err(e) => {
+                        error = Some(e);
+                        ControlFlow::Break(())
+                    }
+                }
+            },
+        )?;
+        if let Some(e) = error {
+            return Err(e.into());
+        }
+        spilled.finish(cd_events);
+
+        Ok(SpilledDecodeResult {
+            cd_events: spilled,
+            trigger_events,
+            metadata: self.output_metadata(),
+        })
+    }
+
+    /// Decodes an EVT 3.0 stream from any reader, such as stdin or a pipe.
+    ///
+    /// The stream may start with a text header like a file. Streams without
+    /// one are decoded with the geometry already set in [`metadata`](Self::metadata).
+    pub fn decode_reader<R: Read>(&mut self, reader: R) -> Result<DecodeResult, DecodeError> {
+        let mut cd_events = Vec::new();
+        let mut trigger_events = Vec::new();
+        self.decode_reader_chunks(reader, &mut cd_events, &mut trigger_events, |_, _, _| {
+            ControlFlow::Continue(())
+        })?;
+
+        Ok(DecodeResult {
+            cd_events,
+            trigger_events,
+            metadata: self.output_metadata(),
+        })
+    }
+
+    /// Decodes the events of an EVT 3.0 file with `start <= timestamp < end`.
+    ///
+    /// Events outside the range are dropped after each read buffer and reading
+    /// stops at the first buffer that reaches `end`, so cutting a short window
+    /// from a long recording only keeps the window in memory.
+    pub fn decode_file_range<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        start: u64,
+        end: Option<u64>,
+    ) -> Result<DecodeResult, DecodeError> {
+        let end = end.unwrap_or(u64::MAX);
+        let mut cd_events = Vec::new();
+        let mut trigger_events = Vec::new();
+        let mut cd_chunk = Vec::new();
+        let mut trigger_chunk = Vec::new();
+        self.decode_file_chunks(
+            path.as_ref(),
+            &mut cd_chunk,
+            &mut trigger_chunk,
+            |_, cd, triggers| {
+                let reached_end = cd.last().is_some_and(|e| e.timestamp >= end)
+                    || triggers.last().is_some_and(|e| e.timestamp >= end);
+                let in_range = |t: u64| t >= start && t < end;
+                cd_events.extend(cd.drain(..).filter(|e| in_range(e.timestamp)));
+                trigger_events.extend(triggers.drain(..).filter(|e| in_range(e.timestamp)));
+                if reached_end {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            },
+        )?;
+
+        Ok(DecodeResult {
+            cd_events,
+            trigger_events,
+            metadata: self.output_metadata(),
+        })
+    }
+
+    /// Decodes an EVT 3.0 file and returns only its summary statistics.
+    ///
+    /// Events are discarded after each read buffer, so memory use does not
+    /// grow with the file size. Registered filters and transforms apply.
+    pub fn summarize_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<DecodeSummary, DecodeError> {
+        let mut builder = SummaryBuilder::new();
+        let mut cd_events = Vec::new();
+        let mut trigger_events = Vec::new();
+        self.decode_file_chunks(
+            path.as_ref(),
+            &mut cd_events,
+            &mut trigger_events,
+            |_, cd, triggers| {
+                builder.add_events(cd);
+                builder.add_triggers(triggers);
+                cd.clear();
+                triggers.clear();
+                ControlFlow::Continue(())
+            },
+        )?;
+
+        Ok(builder.finish(&self.output_metadata()))
+    }
+
+    /// Decodes an EVT 3.0 file one read buffer at a time.
+    ///
+    /// `on_chunk` receives the output geometry and the events decoded from
+    /// each buffer, which are discarded afterwards, so whole recordings can be
+    /// streamed to a writer. An error returned by `on_chunk` stops decoding
+    /// and is passed through.
+    pub fn decode_file_chunked<P, E, F>(&mut self, path: P, on_chunk: F) -> Result<(), E>
+    where
+        P: AsRef<Path>,
+        E: From<DecodeError>,
+        F: FnMut(&SensorMetadata, &[CdEvent], &[TriggerEvent]) -> Result<(), E>,
+    {
+        let file = File::open(path).map_err(DecodeError::from)?;
+        self.decode_reader_chunked(file, on_chunk)
+    }
+
+    /// Decodes an EVT 3.0 stream one read at a time, like
+    /// [`decode_file_chunked`](Self::decode_file_chunked).
+    ///
+    /// `on_chunk` is called after every read that returned data, so events
+    /// from a pipe or a growing file are passed on as soon as they arrive.
+    pub fn decode_reader_chunked<R, E, F>(&mut self, reader: R, mut on_chunk: F) -> Result<(), E>
+    where
+        R: Read,
+        E: From<DecodeError>,
+        F: FnMut(&SensorMetadata, &[CdEvent], &[TriggerEvent]) -> Result<(), E>,
+    {
+        let mut error = None;
+        let mut cd_events = Vec::new();
+        let mut trigger_events = Vec::new();
+        self.decode_reader_chunks(
+            reader,
+            &mut cd_events,
+            &mut trigger_events,
+            |metadata, cd, triggers| {
+                let result = on_chunk(metadata, cd, triggers);
+                cd.clear();
+                triggers.clear();
+                match result {
+                    Ok(()) => ControlFlow::Continue(()),
+                    Err(e) => {
+                        error = Some(e);
+                        ControlFlow::Break(())
+                    }
+                }
+            },
+        )?;
+        error.map_or(Ok(()), Err)
+    }
+
+    /// Decodes an EVT 3.0 file on a background thread, sending the events
+    /// in chunks of [`SPAWN_CHUNK_EVENTS`] CD events.
+    ///
+    /// The channel holds a few chunks, so decoding runs ahead of the
+    /// receiver while it writes or sends the previous chunks, and pauses
+    /// when the receiver falls behind. A decode error is sent as the last
+    /// message; dropping the receiver stops decoding. The decoder, with its
+    /// filters and transforms, moves to the thread.
+    ///
+    /// ```no_run
+    /// use evt3_core::Evt3Decoder;
+    ///
+    /// for chunk in Evt3Decoder::new().spawn_decode("recording.raw") {
+    ///     let chunk = chunk?;
+    ///     println!("{} events", chunk.cd_events.len());
+    /// }
+    /// # Ok::<(), evt3_core::DecodeError>(())
+    /// ```
+    pub fn spawn_decode<P: AsRef<Path>>(
+        mut self,
+        path: P,
+    ) -> Receiver<Result<EventChunk, DecodeError>> {
+        let path = path.as_ref().to_path_buf();
+        let (sender, receiver) = crossbeam_channel::bounded(SPAWN_QUEUE_CHUNKS);
+        std::thread::spawn(move || {
+            let mut cd_events = Vec::new();
+            let mut trigger_events = Vec::new();
+            let mut disconnected = false;
+            let result = self.decode_file_chunks(
+                &path,
+                &mut cd_events,
+                &mut trigger_events,
+                |metadata, cd, triggers| {
+                    let full = cd.len() - cd.len() % SPAWN_CHUNK_EVENTS;
+                    for events in cd[..full].chunks_exact(SPAWN_CHUNK_EVENTS) {
+                        let chunk = EventChunk {
+                            cd_events: events.to_vec(),
+                            trigger_events: std::mem::take(triggers),
+                            metadata: metadata.clone(),
+                        };
+                        if sender.send(Ok(chunk)).is_err() {
+                            disconnected = true;
+                            return ControlFlow::Break(());
+                        }
+                    }
+                    cd.drain(..full);
+                    ControlFlow::Continue(())
+                },
+            );
+            let last = match result {
+                Ok(()) if disconnected => return,
+                Ok(()) if cd_events.is_empty() && trigger_events.is_empty() => return,
+                Ok(()) => Ok(EventChunk {
+                    cd_events,
+                    trigger_events,
+                    metadata: self.output_metadata(),
+                }),
+                Err(e) => Err(e),
+            };
+            // The receiver may be gone already
+            let _ = sender.send(last);
+        });
+        receiver
+    }
+
+    /// Reserves room in `cd_events` for the estimated number of events in
+    /// the file at `path`, plus a margin for the estimate's error.
+    ///
+    /// Skipped for small files and when filters or transforms are
+    /// registered, since they may drop most events. Errors are left for
+    /// the decode itself to report.
+    fn reserve_for_file<S: EventSink>(&self, path: &Path, cd_events: &mut S) {
+        if !self.filters.is_empty() || !self.transforms.is_empty() {
+            return;
+        }
+        match std::fs::metadata(path) {
+            Ok(metadata) if metadata.len() >= PREALLOCATE_MIN_BYTES => {}
+            _ => return,
+        }
+        if let Ok(probe) = Self::probe(path) {
+            let estimate = probe.estimated_event_count;
+            cd_events.reserve((estimate + estimate / 16) as usize);
+        }
+    }
+
+    /// Opens `path` and decodes it like [`decode_reader_chunks`](Self::decode_reader_chunks),
+    /// with the file read on a separate thread.
+    ///
+    /// The reader thread fills buffers ahead of the decoder through a
+    /// bounded channel, so reads from slow disks or network filesystems
+    /// overlap with decoding instead of alternating with it.
+    fn decode_file_chunks<S, F>(
+        &mut self,
+        path: &Path,
+        cd_events: &mut S,
+        trigger_events: &mut Vec<TriggerEvent>,
+        mut on_chunk: F,
+    ) -> Result<(), DecodeError>
+    where
+        S: EventSink,
+        F: FnMut(&SensorMetadata, &mut S, &mut Vec<TriggerEvent>) -> ControlFlow<()>,
+    {
+        let mut reader = BufReader::new(CountingReader {
+            inner: File::open(path)?,
+            count: 0,
+        });
+        self.parse_header(&mut reader)?;
+        let metadata = self.output_metadata();
+
+        std::thread::scope(|scope| {
+            let (chunk_sender, chunks) = mpsc::sync_channel(PIPELINE_DEPTH);
+            let (free_sender, free) = mpsc::channel();
+            let buffer_size = self.read_buffer_size;
+            scope.spawn(move || read_chunks(reader, chunk_sender, free, buffer_size));
+
+            for chunk in chunks {
+                let (bytes, count) = chunk?;
+                self.decode_into(le_words(&bytes), cd_events, trigger_events);
+                self.report_progress(count);
+                // The reader may have stopped already; the buffer is then dropped
+                let _ = free_sender.send(bytes);
+                if on_chunk(&metadata, cd_events, trigger_events).is_break() {
+                    break;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Parses the header of a stream and decodes its data one read at a
+    /// time, calling `on_chunk` with the output geometry and vectors after
+    /// each read until it returns [`ControlFlow::Break`].
+    fn decode_reader_chunks<R, S, F>(
+        &mut self,
+        reader: R,
+        cd_events: &mut S,
+        trigger_events: &mut Vec<TriggerEvent>,
+        mut on_chunk: F,
+    ) -> Result<(), DecodeError>
+    where
+        R: Read,
+        S: EventSink,
+        F: FnMut(&SensorMetadata, &mut S, &mut Vec<TriggerEvent>) -> ControlFlow<()>,
+    {
+        let mut reader = BufReader::new(CountingReader {
+            inner: reader,
+            count: 0,
+        });
 
         // Parse header
         self.parse_header(&mut reader)?;
+        let metadata = self.output_metadata();
 
         // Read and decode raw data
-        let mut cd_events = Vec::new();
-        let mut trigger_events = Vec::new();
-        let mut buffer = vec![0u8; READ_BUFFER_SIZE * 2]; // 2 bytes per word
+        let mut buffer = vec![0u8; self.read_buffer_size];
+        let mut filled = 0;
 
         loop {
-            let bytes_read = reader.read(&mut buffer)?;
-            if bytes_read == 0 {
+            let bytes_read = match reader.read(&mut buffer[filled..]) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            };
+            filled += bytes_read;
+            self.decode_into(le_words(&buffer[..filled]), cd_events, trigger_events);
+
+            // Pipes can return an odd number of bytes; keep the last one for
+            // the next read
+            let odd = filled % 2;
+            buffer.copy_within(filled - odd..filled, 0);
+            filled = odd;
+
+            self.report_progress(reader.get_ref().count);
+            if on_chunk(&metadata, cd_events, trigger_events).is_break() {
                 break;
             }
+        }
+
+        Ok(())
+    }
+
+    /// Probes an EVT 3.0 file without decoding all of it.
+    ///
+    /// Parses the header and decodes small windows at the start, at the end
+    /// and every few megabytes in between. The first and last timestamps
+    /// come from the first and last window; the windows in between are only
+    /// used to count TIME_HIGH wrap-arounds (every ~16.7 s) and to
+    /// extrapolate the event count from the sampled event density.
+    ///
+    /// The last timestamp is exact as long as every 16.7 s of recording
+    /// contains a sample position, i.e. the data rate stays above roughly
+    /// 250 KB/s. Small files are decoded completely, see [`ProbeResult::exact`].
+    pub fn probe<P: AsRef<Path>>(path: P) -> Result<ProbeResult, DecodeError> {
+        Self::probe_with(path.as_ref(), PROBE_WINDOW, PROBE_STRIDE)
+    }
+
+    fn probe_with(path: &Path, window: u64, stride: u64) -> Result<ProbeResult, DecodeError> {
+        let file = File::open(path)?;
+        let file_size = file.metadata()?.len();
+        let mut reader = BufReader::new(file);
 
-            // Convert bytes to u16 words (little-endian)
-            let words: Vec<u16> = buffer[..bytes_read]
-                .chunks_exact(2)
-                .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
-                .collect();
+        let mut decoder = Self::new();
+        let header = decoder.parse_header(&mut reader)?;
+        let header_size = reader.stream_position()?;
+        let mut file = reader.into_inner();
+        let data_size = file_size - header_size;
 
-            self.decode_buffer(&words, &mut cd_events, &mut trigger_events);
+        // Sample positions, always including the start and the last window
+        let mut positions = Vec::new();
+        if data_size <= window.max(stride) {
+            positions.push((0, data_size));
+        } else {
+            let last = (data_size - window) & !1;
+            let mut pos = 0;
+            while pos < last {
+                positions.push((pos, window));
+                pos += stride.max(window);
+            }
+            positions.push((last, data_size - last));
         }
+        let exact = positions.len() == 1;
 
-        Ok(DecodeResult {
-            cd_events,
-            trigger_events,
-            metadata: self.metadata.clone(),
+        let mut loops = 0u64;
+        let mut previous_high = None;
+        let mut sampled_words = 0u64;
+        let mut sampled_events = 0u64;
+        let mut first_timestamp = None;
+        let mut last_timestamp = None;
+        let mut bytes = Vec::new();
+        let mut cd_events = Vec::new();
+        let mut trigger_events = Vec::new();
+
+        for (i, &(pos, len)) in positions.iter().enumerate() {
+            bytes.resize(len as usize, 0);
+            file.seek(SeekFrom::Start(header_size + pos))?;
+            file.read_exact(&mut bytes)?;
+
+            // Count wrap-arounds across the whole sequence of sampled TIME_HIGHs
+            let mut loops_at_start = None;
+            for chunk in bytes.chunks_exact(2) {
+                let word = u16::from_le_bytes([chunk[0], chunk[1]]);
+                if parser::get_event_type(word) == RawEventType::TimeHigh as u8 {
+                    let high = parser::time_get_value(word);
+                    if previous_high.is_some_and(|previous| high < previous) {
+                        loops += 1;
+                    }
+                    previous_high = Some(high);
+                    loops_at_start.get_or_insert(loops);
+                }
+            }
+            let offset = loops_at_start.unwrap_or(loops) * TIME_LOOP;
+
+            let mut window_decoder = Self::new();
+            cd_events.clear();
+            trigger_events.clear();
+            window_decoder.decode_bytes(&bytes, &mut cd_events, &mut trigger_events);
+            sampled_words += bytes.len() as u64 / 2;
+            sampled_events += cd_events.len() as u64;
+
+            if i == 0 {
+                first_timestamp = cd_events.first().map(|e| e.timestamp + offset);
+            }
+            if i == positions.len() - 1 {
+                last_timestamp = cd_events.iter().map(|e| e.timestamp + offset).max();
+            }
+        }
+
+        let total_words = data_size / 2;
+        let estimated_event_count = if exact || sampled_words == 0 {
+            sampled_events
+        } else {
+            (sampled_events as f64 / sampled_words as f64 * total_words as f64).round() as u64
+        };
+
+        Ok(ProbeResult {
+            metadata: decoder.metadata,
+            file_size,
+            header_size,
+            header,
+            first_timestamp,
+            last_timestamp,
+            estimated_event_count,
+            exact,
         })
     }
 
     /// Parses the file header to extract metadata.
-    fn parse_header<R: BufRead>(&mut self, reader: &mut R) -> Result<(), DecodeError> {
+    ///
+    /// Reads the `%` lines at the start of `reader` and leaves it at the
+    /// first data byte, so the rest can be fed to [`decode_buffer`](Self::decode_buffer).
+    /// Filters and transforms are given the geometry from the header.
+    /// Returns the header lines without the leading `%`.
+    pub fn parse_header<R: BufRead>(&mut self, reader: &mut R) -> Result<Vec<String>, DecodeError> {
         // EVT3 files may have a text header starting with '%'
         // We need to carefully peek and read line by line
+        let mut lines = Vec::new();
 
         loop {
             let bytes_peeked = reader.fill_buf()?;
@@ -272,9 +1243,11 @@ impl Evt3Decoder {
             }
 
             self.parse_header_line(&line);
+            lines.push(line[1..].trim().to_string());
         }
 
-        Ok(())
+        self.update_geometry();
+        Ok(lines)
     }
 
     /// Parses a single header line.
@@ -396,6 +1369,54 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_resume_from_state() {
+        let words: Vec<u16> = vec![
+            0x8000, // TIME_HIGH
+            0x60C8, // TIME_LOW: 200
+            0x0064, // ADDR_Y: y=100
+            0x3000, // VECT_BASE_X: x=0, pol=0
+            0x4E38, // VECT_12: x=3,4,5,9,10,11
+            0x8001, // TIME_HIGH: 4096
+            0x4E38, // VECT_12: x=15,16,17,21,22,23
+        ];
+        let mut full = Vec::new();
+        Evt3Decoder::new().decode_buffer(&words, &mut full, &mut Vec::new());
+
+        let mut first = Evt3Decoder::new();
+        let mut events = Vec::new();
+        first.decode_buffer(&words[..5], &mut events, &mut Vec::new());
+        let state = first.state();
+        assert_eq!(state.current_time, 200);
+        assert_eq!(state.base_x, 12);
+
+        // A fresh decoder picks up where the first one stopped
+        let mut second = Evt3Decoder::new();
+        second.set_state(state);
+        second.decode_buffer(&words[5..], &mut events, &mut Vec::new());
+        assert_eq!(events, full);
+        assert_eq!(events.last().unwrap().timestamp, 4096);
+    }
+
+    #[test]
+    fn test_decode_bytes_matches_words() {
+        let words: Vec<u16> = vec![0x8000, 0x60C8, 0x0064, 0x3000, 0x4E38, 0x8001, 0x4E38];
+        let bytes: Vec<u8> = words.iter().flat_map(|w| w.to_le_bytes()).collect();
+        let mut expected = Vec::new();
+        Evt3Decoder::new().decode_buffer(&words, &mut expected, &mut Vec::new());
+
+        let mut events = Vec::new();
+        Evt3Decoder::new().decode_bytes(&bytes, &mut events, &mut Vec::new());
+        assert_eq!(events, expected);
+
+        // A trailing odd byte is left to the caller
+        let mut decoder = Evt3Decoder::new();
+        let mut events = Vec::new();
+        decoder.decode_bytes(&bytes[..9], &mut events, &mut Vec::new());
+        decoder.decode_bytes(&bytes[8..], &mut events, &mut Vec::new());
+        assert_eq!(events, expected);
+    }
+
     #[test]
     fn test_parse_header_line_format() {
         let mut decoder = Evt3Decoder::new();
@@ -411,4 +1432,427 @@ mod tests {
         assert_eq!(decoder.metadata.width, 320);
         assert_eq!(decoder.metadata.height, 240);
     }
+
+    /// Encodes events as TIME_HIGH/TIME_LOW/ADDR_Y/ADDR_X words.
+    fn encode(events: &[CdEvent]) -> Vec<u8> {
+        let mut words = Vec::new();
+        let mut high = None;
+        for event in events {
+            let time_high = ((event.timestamp >> 12) & 0xFFF) as u16;
+            if high != Some(time_high) {
+                words.push(0x8000 | time_high);
+                high = Some(time_high);
+            }
+            words.push(0x6000 | (event.timestamp & 0xFFF) as u16);
+            words.push(event.y & 0x7FF);
+            words.push(0x2000 | ((event.polarity as u16) << 11) | (event.x & 0x7FF));
+        }
+        words.iter().flat_map(|w| w.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn test_probe() {
+        // 40 s at one event per millisecond, wrapping the 24-bit time twice
+        let events: Vec<CdEvent> = (0..40_000u64)
+            .map(|i| CdEvent::new((i % 640) as u16, (i % 480) as u16, 1, 5_000 + i * 1_000))
+            .collect();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, b"% format EVT3;width=640;height=480\n% end\n")
+            .unwrap();
+        std::io::Write::write_all(&mut file, &encode(&events)).unwrap();
+
+        let exact = Evt3Decoder::probe(file.path()).unwrap();
+        assert!(exact.exact);
+        assert_eq!((exact.metadata.width, exact.metadata.height), (640, 480));
+        assert_eq!(exact.header_size, 41);
+        assert_eq!(exact.header, ["format EVT3;width=640;height=480"]);
+        assert_eq!(exact.format_version().as_deref(), Some("EVT3"));
+        assert_eq!(exact.first_timestamp, Some(5_000));
+        assert_eq!(exact.last_timestamp, Some(5_000 + 39_999 * 1_000));
+        assert_eq!(exact.estimated_event_count, 40_000);
+
+        // Sampling 2 KB every 16 KB (~1.9 s of data) still tracks the wrap-arounds
+        let sampled = Evt3Decoder::probe_with(file.path(), 2 * 1024, 16 * 1024).unwrap();
+        assert!(!sampled.exact);
+        assert_eq!(sampled.first_timestamp, exact.first_timestamp);
+        assert_eq!(sampled.last_timestamp, exact.last_timestamp);
+        assert_eq!(sampled.duration(), 39_999 * 1_000);
+        let error = sampled.estimated_event_count.abs_diff(40_000);
+        assert!(error < 400, "estimate off by {}", error);
+    }
+
+    #[test]
+    fn test_summarize_file() {
+        let events: Vec<CdEvent> = (0..1_000u64)
+            .map(|i| CdEvent::new((i % 64) as u16, (i % 48) as u16, (i % 2) as u8, i * 10))
+            .collect();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, b"% format EVT3;width=640;height=480\n% end\n")
+            .unwrap();
+        std::io::Write::write_all(&mut file, &encode(&events)).unwrap();
+
+        let summary = Evt3Decoder::new().summarize_file(file.path()).unwrap();
+        let result = Evt3Decoder::new().decode_file(file.path()).unwrap();
+        assert_eq!(summary, result.summary());
+        assert_eq!(summary.event_count, 1_000);
+        assert_eq!((summary.on.count, summary.off.count), (500, 500));
+        assert_eq!(summary.x_range, Some((0, 63)));
+        assert_eq!(summary.last_timestamp, Some(9_990));
+        assert_eq!(summary.metadata.width, 640);
+    }
+
+    #[test]
+    fn test_decode_file_range() {
+        let events: Vec<CdEvent> = (0..1_000u64)
+            .map(|i| CdEvent::new((i % 64) as u16, 0, 1, i * 10))
+            .collect();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, b"% format EVT3;width=640;height=480\n% end\n")
+            .unwrap();
+        std::io::Write::write_all(&mut file, &encode(&events)).unwrap();
+
+        let result = Evt3Decoder::new()
+            .decode_file_range(file.path(), 2_000, Some(3_000))
+            .unwrap();
+        assert_eq!(result.cd_events, &events[200..300]);
+        assert_eq!(result.metadata.width, 640);
+
+        let tail = Evt3Decoder::new()
+            .decode_file_range(file.path(), 9_000, None)
+            .unwrap();
+        assert_eq!(tail.cd_events, &events[900..]);
+    }
+
+    #[test]
+    fn test_decode_file_columnar() {
+        let events: Vec<CdEvent> = (0..1_000u64)
+            .map(|i| CdEvent::new((i % 64) as u16, (i % 48) as u16, (i % 2) as u8, i * 10))
+            .collect();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, b"% format EVT3;width=640;height=480\n% end\n")
+            .unwrap();
+        std::io::Write::write_all(&mut file, &encode(&events)).unwrap();
+
+        let result = Evt3Decoder::new()
+            .decode_file_columnar(file.path())
+            .unwrap();
+        assert_eq!(result.cd_events.to_events(), events);
+        assert_eq!(result.metadata.width, 640);
+
+        // Filters and transforms apply as for Vec<CdEvent>
+        let decoder = || {
+            let mut decoder = Evt3Decoder::new();
+            let metadata = SensorMetadata::default();
+            decoder.add_filter(crate::filters::PixelMaskFilter::new([(1, 1)], &metadata));
+            decoder.add_transform(crate::transforms::Crop::new(crate::types::Roi::new(
+                0, 0, 32, 24,
+            )));
+            decoder
+        };
+        let expected = decoder().decode_file(file.path()).unwrap();
+        let columnar = decoder().decode_file_columnar(file.path()).unwrap();
+        assert!(!expected.cd_events.is_empty());
+        assert_eq!(columnar.cd_events.to_events(), expected.cd_events);
+        assert_eq!(columnar.metadata.width, 32);
+    }
+
+    #[test]
+    fn test_progress_callback() {
+        let events: Vec<CdEvent> = (0..2_000_000u64)
+            .map(|i| CdEvent::new((i % 64) as u16, 0, 1, i))
+            .collect();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, b"% format EVT3;width=640;height=480\n% end\n")
+            .unwrap();
+        std::io::Write::write_all(&mut file, &encode(&events)).unwrap();
+        let file_size = file.as_file().metadata().unwrap().len();
+
+        let reports = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut decoder = Evt3Decoder::new();
+        let sink = reports.clone();
+        decoder.set_progress_callback(move |bytes| sink.lock().unwrap().push(bytes));
+        let result = decoder.decode_file(file.path()).unwrap();
+        assert_eq!(result.cd_events.len(), events.len());
+
+        let reports = reports.lock().unwrap();
+        assert!(reports.len() > 1, "{:?}", reports);
+        assert!(reports.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(*reports.last().unwrap(), file_size);
+    }
+
+    #[test]
+    fn test_decode_file_pipelined() {
+        // Several read buffers, so the reader thread runs ahead of the decoder
+        let events: Vec<CdEvent> = (0..1_500_000u64)
+            .map(|i| CdEvent::new((i % 640) as u16, (i % 480) as u16, (i % 2) as u8, i * 20))
+            .collect();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, b"% format EVT3;width=640;height=480\n% end\n")
+            .unwrap();
+        std::io::Write::write_all(&mut file, &encode(&events)).unwrap();
+
+        let result = Evt3Decoder::new().decode_file(file.path()).unwrap();
+        let sequential = Evt3Decoder::new()
+            .decode_reader(File::open(file.path()).unwrap())
+            .unwrap();
+        assert_eq!(result.cd_events, sequential.cd_events);
+        assert_eq!(result.cd_events.len(), events.len());
+
+        // Stopping early hangs up on the reader thread
+        let mut chunks = 0;
+        Evt3Decoder::new()
+            .decode_file_chunked(file.path(), |_, _, _| {
+                chunks += 1;
+                Err(DecodeError::UnexpectedEof)
+            })
+            .unwrap_err();
+        assert_eq!(chunks, 1);
+    }
+
+    #[test]
+    fn test_read_buffer_size() {
+        let events: Vec<CdEvent> = (0..1000u64)
+            .map(|i| CdEvent::new((i % 640) as u16, (i % 480) as u16, (i % 2) as u8, i * 20))
+            .collect();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, b"% format EVT3;width=640;height=480\n% end\n")
+            .unwrap();
+        std::io::Write::write_all(&mut file, &encode(&events)).unwrap();
+        let expected = Evt3Decoder::new().decode_file(file.path()).unwrap();
+        assert_eq!(expected.cd_events.len(), events.len());
+
+        // Odd sizes split words across reads
+        for size in [0, 3, 1001, 1 << 26] {
+            let mut decoder = Evt3Decoder::new();
+            decoder.set_read_buffer_size(size);
+            assert_eq!(decoder.read_buffer_size(), size.max(2));
+            let result = decoder.decode_file(file.path()).unwrap();
+            assert_eq!(result.cd_events, expected.cd_events);
+
+            let mut decoder = Evt3Decoder::new();
+            decoder.set_read_buffer_size(size);
+            let result = decoder
+                .decode_reader(File::open(file.path()).unwrap())
+                .unwrap();
+            assert_eq!(result.cd_events, expected.cd_events);
+        }
+    }
+
+    #[test]
+    fn test_decode_file_into() {
+        let events: Vec<CdEvent> = (0..5000u64)
+            .map(|i| CdEvent::new((i % 640) as u16, (i % 480) as u16, (i % 2) as u8, i * 20))
+            .collect();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, b"% format EVT3;width=640;height=480\n% end\n")
+            .unwrap();
+        std::io::Write::write_all(&mut file, &encode(&events)).unwrap();
+        let expected = Evt3Decoder::new().decode_file(file.path()).unwrap();
+
+        let mut buffers = EventBuffers::new();
+        let metadata = Evt3Decoder::new()
+            .decode_file_into(file.path(), &mut buffers)
+            .unwrap();
+        assert_eq!(metadata.width, 640);
+        assert_eq!(buffers.cd_events, expected.cd_events);
+
+        // The second file reuses the allocation of the first
+        let allocation = buffers.cd_events.as_ptr();
+        Evt3Decoder::new()
+            .decode_file_into(file.path(), &mut buffers)
+            .unwrap();
+        assert_eq!(buffers.cd_events, expected.cd_events);
+        assert_eq!(buffers.cd_events.as_ptr(), allocation);
+
+        let mut buffers = EventBuffers::new();
+        Evt3Decoder::new().decode_buffer_into(&[0x8000, 0x0001, 0x2005], &mut buffers);
+        assert_eq!(buffers.cd_events, [CdEvent::new(5, 1, 0, 0)]);
+    }
+
+    #[test]
+    fn test_decode_file_spilled() {
+        let events: Vec<CdEvent> = (0..10_000u64)
+            .map(|i| CdEvent::new((i % 640) as u16, (i % 480) as u16, (i % 2) as u8, i * 20))
+            .collect();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, b"% format EVT3;width=640;height=480\n% end\n")
+            .unwrap();
+        std::io::Write::write_all(&mut file, &encode(&events)).unwrap();
+        let expected = Evt3Decoder::new().decode_file(file.path()).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        // Small reads and a budget of 1000 events spill most of the file
+        let mut decoder = Evt3Decoder::new();
+        decoder.set_read_buffer_size(4096);
+        let result = decoder
+            .decode_file_spilled(file.path(), 1000 * BYTES_PER_EVENT, dir.path())
+            .unwrap();
+        assert_eq!(result.metadata.width, 640);
+        assert_eq!(result.cd_events.len(), events.len());
+        assert!(result.cd_events.spilled_len() >= 9000);
+        let decoded: Vec<CdEvent> = result.cd_events.iter().map(Result::unwrap).collect();
+        assert_eq!(decoded, expected.cd_events);
+
+        // Within the budget nothing is spilled
+        let result = Evt3Decoder::new()
+            .decode_file_spilled(file.path(), usize::MAX, dir.path())
+            .unwrap();
+        assert_eq!(result.cd_events.spilled_len(), 0);
+        assert_eq!(result.cd_events.chunks().count(), 1);
+    }
+
+    #[test]
+    fn test_decode_file_preallocates() {
+        // ~18 MB of event data, above PREALLOCATE_MIN_BYTES
+        let events: Vec<CdEvent> = (0..3_000_000u64)
+            .map(|i| CdEvent::new((i % 640) as u16, (i % 480) as u16, 1, i * 5))
+            .collect();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, b"% format EVT3;width=640;height=480\n% end\n")
+            .unwrap();
+        std::io::Write::write_all(&mut file, &encode(&events)).unwrap();
+
+        // Growing by doubling would end at 4_194_304
+        let result = Evt3Decoder::new().decode_file(file.path()).unwrap();
+        assert_eq!(result.cd_events.len(), events.len());
+        assert!(result.cd_events.capacity() < events.len() * 9 / 8);
+
+        let columnar = Evt3Decoder::new()
+            .decode_file_columnar(file.path())
+            .unwrap();
+        assert!(columnar.cd_events.timestamp.capacity() < events.len() * 9 / 8);
+    }
+
+    #[test]
+    fn test_spawn_decode() {
+        let events: Vec<CdEvent> = (0..150_000u64)
+            .map(|i| CdEvent::new((i % 640) as u16, (i % 480) as u16, 1, i * 10))
+            .collect();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, b"% format EVT3;width=640;height=480\n% end\n")
+            .unwrap();
+        std::io::Write::write_all(&mut file, &encode(&events)).unwrap();
+
+        let chunks: Vec<EventChunk> = Evt3Decoder::new()
+            .spawn_decode(file.path())
+            .iter()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let sizes: Vec<usize> = chunks.iter().map(|c| c.cd_events.len()).collect();
+        assert_eq!(
+            sizes,
+            [SPAWN_CHUNK_EVENTS, SPAWN_CHUNK_EVENTS, 150_000 - 2 * 65_536]
+        );
+        assert_eq!(chunks[0].metadata.width, 640);
+        let decoded: Vec<CdEvent> = chunks.into_iter().flat_map(|c| c.cd_events).collect();
+        assert_eq!(decoded, events);
+
+        let errors = Evt3Decoder::new().spawn_decode("/nonexistent/recording.raw");
+        assert!(matches!(errors.recv(), Ok(Err(DecodeError::Io(_)))));
+        assert!(errors.recv().is_err());
+    }
+
+    #[test]
+    fn test_decode_file_chunked() {
+        let events: Vec<CdEvent> = (0..100u64)
+            .map(|i| CdEvent::new(i as u16, 0, 1, i * 10))
+            .collect();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, b"% format EVT3;width=640;height=480\n% end\n")
+            .unwrap();
+        std::io::Write::write_all(&mut file, &encode(&events)).unwrap();
+
+        let mut decoded = Vec::new();
+        Evt3Decoder::new()
+            .decode_file_chunked(file.path(), |metadata, cd, _| {
+                assert_eq!(metadata.width, 640);
+                decoded.extend_from_slice(cd);
+                Ok::<_, DecodeError>(())
+            })
+            .unwrap();
+        assert_eq!(decoded, events);
+
+        let result = Evt3Decoder::new().decode_file_chunked(file.path(), |_, _, _| {
+            Err(DecodeError::InvalidFormat("stop".to_string()))
+        });
+        assert!(matches!(result, Err(DecodeError::InvalidFormat(_))));
+    }
+
+    #[test]
+    fn test_decode_reader_odd_reads() {
+        /// Returns at most three bytes per read, like a slow pipe.
+        struct Trickle<'a>(&'a [u8]);
+        impl Read for Trickle<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let n = self.0.len().min(buf.len()).min(3);
+                buf[..n].copy_from_slice(&self.0[..n]);
+                self.0 = &self.0[n..];
+                Ok(n)
+            }
+        }
+
+        let events: Vec<CdEvent> = (0..20u64)
+            .map(|i| CdEvent::new(i as u16, 7, 0, i * 5))
+            .collect();
+        let bytes = encode(&events);
+
+        let mut decoder = Evt3Decoder::new();
+        decoder.metadata = SensorMetadata {
+            width: 320,
+            height: 240,
+        };
+        let result = decoder.decode_reader(Trickle(&bytes)).unwrap();
+        assert_eq!(result.cd_events, events);
+        // No header: the preset geometry is kept
+        assert_eq!(result.metadata.width, 320);
+    }
+
+    #[test]
+    fn test_decode_with_filter() {
+        let mut decoder = Evt3Decoder::new();
+        let metadata = SensorMetadata::default();
+        decoder.add_filter(crate::filters::PixelMaskFilter::new([(4, 100)], &metadata));
+        let mut cd_events = vec![CdEvent::new(4, 100, 0, 0)];
+        let mut trigger_events = Vec::new();
+
+        let words: Vec<u16> = vec![0x8000, 0x60C8, 0x0064, 0x3000, 0x4E38];
+        decoder.decode_buffer(&words, &mut cd_events, &mut trigger_events);
+
+        // Previously collected events are left alone
+        let x_coords: Vec<u16> = cd_events.iter().map(|e| e.x).collect();
+        assert_eq!(x_coords, vec![4, 3, 5, 9, 10, 11]);
+    }
+
+    #[test]
+    fn test_decode_with_crop() {
+        let mut decoder = Evt3Decoder::new();
+        decoder.add_transform(crate::transforms::Crop::new(crate::types::Roi::new(
+            4, 90, 10, 110,
+        )));
+        let mut cd_events = Vec::new();
+        let mut trigger_events = Vec::new();
+
+        let words: Vec<u16> = vec![0x8000, 0x60C8, 0x0064, 0x3000, 0x4E38];
+        decoder.decode_buffer(&words, &mut cd_events, &mut trigger_events);
+
+        let coords: Vec<(u16, u16)> = cd_events.iter().map(|e| (e.x, e.y)).collect();
+        assert_eq!(coords, vec![(0, 10), (1, 10), (5, 10)]);
+        let metadata = decoder.output_metadata();
+        assert_eq!((metadata.width, metadata.height), (6, 20));
+    }
+
+    #[test]
+    fn test_decode_dedup_redundant_vector_words() {
+        let mut decoder = Evt3Decoder::new();
+        decoder.add_filter(crate::filters::DuplicateFilter::new());
+        let mut cd_events = Vec::new();
+        let mut trigger_events = Vec::new();
+
+        // The same VECT_BASE_X + VECT_12 pair emitted twice
+        let words: Vec<u16> = vec![0x8000, 0x60C8, 0x0064, 0x3000, 0x4007, 0x3000, 0x4007];
+        decoder.decode_buffer(&words, &mut cd_events, &mut trigger_events);
+
+        let x_coords: Vec<u16> = cd_events.iter().map(|e| e.x).collect();
+        assert_eq!(x_coords, vec![0, 1, 2]);
+    }
 }
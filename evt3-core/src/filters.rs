@@ -0,0 +1,824 @@
+//! Event filters.
+//!
+//! Filters decide per event whether it is kept. They can be run as a
+//! post-processing pass over decoded events with [`filter_events`], or
+//! registered on the decoder with [`Evt3Decoder::add_filter`] to drop events
+//! while decoding, before they are ever collected.
+//!
+//! [`Evt3Decoder::add_filter`]: crate::decoder::Evt3Decoder::add_filter
+
+use crate::types::{CdEvent, Roi, SensorMetadata};
+use std::collections::{HashMap, HashSet};
+
+/// A stateful per-event filter.
+///
+/// Events are presented in stream order. Implementations may keep state
+/// between calls (e.g. the last timestamp per pixel).
+pub trait EventFilter: Send {
+    /// Returns `true` if the event should be kept.
+    fn keep(&mut self, event: &CdEvent) -> bool;
+
+    /// Clears any state accumulated from previous events.
+    fn reset(&mut self) {}
+
+    /// Adapts the filter to the sensor geometry.
+    ///
+    /// Called by the decoder when a filter is added and again once the file
+    /// header has been parsed, so per-pixel state can be sized correctly.
+    fn set_geometry(&mut self, _metadata: &SensorMetadata) {}
+}
+
+/// Applies a filter to a list of events in place, preserving order.
+pub fn filter_events<F: EventFilter + ?Sized>(filter: &mut F, events: &mut Vec<CdEvent>) {
+    events.retain(|event| filter.keep(event));
+}
+
+/// Runs a filter chain over `events[start..]`, compacting the kept events.
+///
+/// An event is kept only if every filter keeps it. Later filters do not
+/// see events dropped by earlier ones.
+pub(crate) fn retain_tail(
+    filters: &mut [Box<dyn EventFilter>],
+    events: &mut Vec<CdEvent>,
+    start: usize,
+) {
+    if filters.is_empty() {
+        return;
+    }
+    let mut write = start;
+    for read in start..events.len() {
+        let event = events[read];
+        if filters.iter_mut().all(|filter| filter.keep(&event)) {
+            events[write] = event;
+            write += 1;
+        }
+    }
+    events.truncate(write);
+}
+
+/// Detects hot pixels from per-pixel event counts.
+///
+/// A pixel is hot if its event count exceeds `median + sigma * spread`,
+/// computed over all pixels that fired at least once. The spread is the
+/// median absolute deviation scaled to a standard deviation, but never less
+/// than the Poisson noise `sqrt(median)`, so a quiet recording where most
+/// pixels fire equally often does not flag every pixel above the median.
+/// Using the median rather than the mean keeps a few stuck pixels from
+/// inflating the threshold.
+#[derive(Debug, Clone)]
+pub struct HotPixelDetector {
+    sigma: f64,
+    counts: HashMap<(u16, u16), u64>,
+}
+
+impl HotPixelDetector {
+    /// Creates a detector flagging pixels more than `sigma` deviations above the median rate.
+    pub fn new(sigma: f64) -> Self {
+        Self {
+            sigma,
+            counts: HashMap::new(),
+        }
+    }
+
+    /// Accumulates event counts from a batch of events.
+    pub fn observe(&mut self, events: &[CdEvent]) {
+        for event in events {
+            *self.counts.entry((event.x, event.y)).or_insert(0) += 1;
+        }
+    }
+
+    /// Returns the event count threshold above which a pixel is hot.
+    ///
+    /// Returns `None` if no events have been observed.
+    pub fn threshold(&self) -> Option<f64> {
+        if self.counts.is_empty() {
+            return None;
+        }
+        let mut counts: Vec<u64> = self.counts.values().copied().collect();
+        let center = median(&mut counts);
+        let mut deviations: Vec<u64> = counts
+            .iter()
+            .map(|&c| (c as f64 - center).abs().round() as u64)
+            .collect();
+        let mad = median(&mut deviations);
+        let spread = (1.4826 * mad).max(center.sqrt());
+        Some(center + self.sigma * spread)
+    }
+
+    /// Returns the hot pixels as `(x, y)` pairs, sorted by coordinates.
+    pub fn hot_pixels(&self) -> Vec<(u16, u16)> {
+        let Some(threshold) = self.threshold() else {
+            return Vec::new();
+        };
+        let mut pixels: Vec<(u16, u16)> = self
+            .counts
+            .iter()
+            .filter(|(_, &count)| count as f64 > threshold)
+            .map(|(&pixel, _)| pixel)
+            .collect();
+        pixels.sort_unstable_by_key(|&(x, y)| (y, x));
+        pixels
+    }
+}
+
+fn median(values: &mut [u64]) -> f64 {
+    values.sort_unstable();
+    let mid = values.len() / 2;
+    if values.len() % 2 == 1 {
+        values[mid] as f64
+    } else {
+        (values[mid - 1] + values[mid]) as f64 / 2.0
+    }
+}
+
+/// Detects hot pixels in a list of events.
+///
+/// See [`HotPixelDetector`] for the threshold used.
+pub fn detect_hot_pixels(events: &[CdEvent], sigma: f64) -> Vec<(u16, u16)> {
+    let mut detector = HotPixelDetector::new(sigma);
+    detector.observe(events);
+    detector.hot_pixels()
+}
+
+/// Filter that drops all events from a fixed set of pixels.
+///
+/// Used for hot pixels found by [`HotPixelDetector`] as well as for
+/// dead or masked pixel lists, e.g. read with
+/// [`read_pixel_mask`](crate::input::read_pixel_mask). Events outside the
+/// sensor geometry are kept.
+#[derive(Debug, Clone)]
+pub struct PixelMaskFilter {
+    pixels: Vec<(u16, u16)>,
+    width: u32,
+    height: u32,
+    mask: Vec<bool>,
+}
+
+impl PixelMaskFilter {
+    /// Creates a filter removing the given `(x, y)` pixels.
+    pub fn new<I>(pixels: I, metadata: &SensorMetadata) -> Self
+    where
+        I: IntoIterator<Item = (u16, u16)>,
+    {
+        let mut pixels: Vec<(u16, u16)> = pixels.into_iter().collect();
+        pixels.sort_unstable_by_key(|&(x, y)| (y, x));
+        pixels.dedup();
+
+        let mut mask = vec![false; metadata.width as usize * metadata.height as usize];
+        for &(x, y) in &pixels {
+            if (x as u32) < metadata.width && (y as u32) < metadata.height {
+                mask[y as usize * metadata.width as usize + x as usize] = true;
+            }
+        }
+        Self {
+            pixels,
+            width: metadata.width,
+            height: metadata.height,
+            mask,
+        }
+    }
+
+    /// Detects hot pixels in `events` and creates a filter removing them.
+    pub fn detect(events: &[CdEvent], metadata: &SensorMetadata, sigma: f64) -> Self {
+        Self::new(detect_hot_pixels(events, sigma), metadata)
+    }
+
+    /// Returns the number of masked pixels.
+    pub fn len(&self) -> usize {
+        self.pixels.len()
+    }
+
+    /// Returns `true` if no pixels are masked.
+    pub fn is_empty(&self) -> bool {
+        self.pixels.is_empty()
+    }
+
+    /// Returns the masked pixels as `(x, y)` pairs, sorted by coordinates.
+    pub fn pixels(&self) -> &[(u16, u16)] {
+        &self.pixels
+    }
+}
+
+impl EventFilter for PixelMaskFilter {
+    #[inline]
+    fn keep(&mut self, event: &CdEvent) -> bool {
+        if event.x as u32 >= self.width || event.y as u32 >= self.height {
+            return true;
+        }
+        !self.mask[event.y as usize * self.width as usize + event.x as usize]
+    }
+
+    fn set_geometry(&mut self, metadata: &SensorMetadata) {
+        if metadata.width != self.width || metadata.height != self.height {
+            let pixels = std::mem::take(&mut self.pixels);
+            *self = Self::new(pixels, metadata);
+        }
+    }
+}
+
+/// Background activity filter.
+///
+/// Keeps an event only if a pixel in its neighborhood (excluding the pixel
+/// itself) fired within the last `dt` microseconds. Isolated events, which
+/// are mostly sensor noise, are dropped. Every event, kept or not, updates
+/// the last timestamp of its pixel.
+#[derive(Debug, Clone)]
+pub struct BackgroundActivityFilter {
+    dt: u64,
+    radius: u16,
+    width: u32,
+    height: u32,
+    /// Last timestamp + 1 per pixel; 0 means the pixel never fired
+    last: Vec<u64>,
+}
+
+impl BackgroundActivityFilter {
+    /// Default correlation window in microseconds.
+    pub const DEFAULT_DT: u64 = 1_000;
+
+    /// Creates a filter with a correlation window of `dt` microseconds and
+    /// the 8-connected neighborhood (radius 1).
+    pub fn new(metadata: &SensorMetadata, dt: u64) -> Self {
+        Self {
+            dt,
+            radius: 1,
+            width: metadata.width,
+            height: metadata.height,
+            last: vec![0; metadata.width as usize * metadata.height as usize],
+        }
+    }
+
+    /// Sets the neighborhood radius (1 = 3x3, 2 = 5x5, ...).
+    pub fn with_radius(mut self, radius: u16) -> Self {
+        self.radius = radius.max(1);
+        self
+    }
+
+    /// Returns the correlation window in microseconds.
+    pub fn dt(&self) -> u64 {
+        self.dt
+    }
+
+    /// Returns the neighborhood radius.
+    pub fn radius(&self) -> u16 {
+        self.radius
+    }
+}
+
+impl EventFilter for BackgroundActivityFilter {
+    fn keep(&mut self, event: &CdEvent) -> bool {
+        let (x, y) = (event.x as u32, event.y as u32);
+        if x >= self.width || y >= self.height {
+            return true;
+        }
+        let radius = self.radius as u32;
+        let width = self.width as usize;
+
+        let mut supported = false;
+        'search: for ny in y.saturating_sub(radius)..=(y + radius).min(self.height - 1) {
+            let row = ny as usize * width;
+            for nx in x.saturating_sub(radius)..=(x + radius).min(self.width - 1) {
+                if nx == x && ny == y {
+                    continue;
+                }
+                let last = self.last[row + nx as usize];
+                if last != 0 && event.timestamp.saturating_sub(last - 1) <= self.dt {
+                    supported = true;
+                    break 'search;
+                }
+            }
+        }
+
+        self.last[y as usize * width + x as usize] = event.timestamp + 1;
+        supported
+    }
+
+    fn reset(&mut self) {
+        self.last.fill(0);
+    }
+
+    fn set_geometry(&mut self, metadata: &SensorMetadata) {
+        if metadata.width != self.width || metadata.height != self.height {
+            *self = Self::new(metadata, self.dt).with_radius(self.radius);
+        }
+    }
+}
+
+/// Refractory period filter.
+///
+/// Drops events arriving at a pixel less than `period` microseconds after
+/// the last kept event of that pixel (of either polarity). This thins out
+/// the bursts a pixel emits for a single strong edge and limits hot pixels
+/// to one event per period. Dropped events do not extend the period.
+#[derive(Debug, Clone)]
+pub struct RefractoryFilter {
+    period: u64,
+    width: u32,
+    height: u32,
+    /// Last kept timestamp + 1 per pixel; 0 means no event was kept yet
+    last: Vec<u64>,
+}
+
+impl RefractoryFilter {
+    /// Creates a filter with a refractory period of `period` microseconds.
+    pub fn new(metadata: &SensorMetadata, period: u64) -> Self {
+        Self {
+            period,
+            width: metadata.width,
+            height: metadata.height,
+            last: vec![0; metadata.width as usize * metadata.height as usize],
+        }
+    }
+
+    /// Returns the refractory period in microseconds.
+    pub fn period(&self) -> u64 {
+        self.period
+    }
+}
+
+impl EventFilter for RefractoryFilter {
+    fn keep(&mut self, event: &CdEvent) -> bool {
+        let (x, y) = (event.x as u32, event.y as u32);
+        if x >= self.width || y >= self.height {
+            return true;
+        }
+        let last = &mut self.last[y as usize * self.width as usize + x as usize];
+        if *last != 0 && event.timestamp.saturating_sub(*last - 1) < self.period {
+            return false;
+        }
+        *last = event.timestamp + 1;
+        true
+    }
+
+    fn reset(&mut self) {
+        self.last.fill(0);
+    }
+
+    fn set_geometry(&mut self, metadata: &SensorMetadata) {
+        if metadata.width != self.width || metadata.height != self.height {
+            *self = Self::new(metadata, self.period);
+        }
+    }
+}
+
+/// Rate limit applied by [`RateLimitFilter`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RateLimit {
+    /// Keep at most this many events per millisecond of sensor time
+    PerMillisecond(u32),
+    /// Keep each event with this probability (0.0 to 1.0)
+    Fraction(f64),
+}
+
+/// SplitMix64 generator, so filtering is reproducible without extra dependencies.
+#[derive(Debug, Clone)]
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a uniform value in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Filter that randomly drops events to limit the event rate.
+///
+/// With [`RateLimit::PerMillisecond`], events are kept with probability
+/// `N / rate` where `rate` is the event count of the previous millisecond,
+/// so survivors are spread over the whole window instead of favoring its
+/// start, and a hard cap guarantees no more than `N` events per millisecond.
+/// The output is deterministic for a given seed.
+#[derive(Debug, Clone)]
+pub struct RateLimitFilter {
+    limit: RateLimit,
+    seed: u64,
+    rng: SplitMix64,
+    window: u64,
+    seen: u64,
+    kept: u64,
+    previous_seen: u64,
+}
+
+impl RateLimitFilter {
+    /// Creates a rate limiting filter with the given PRNG seed.
+    pub fn new(limit: RateLimit, seed: u64) -> Self {
+        Self {
+            limit,
+            seed,
+            rng: SplitMix64(seed),
+            window: 0,
+            seen: 0,
+            kept: 0,
+            previous_seen: 0,
+        }
+    }
+
+    /// Keeps at most `max_events` events per millisecond.
+    pub fn per_millisecond(max_events: u32, seed: u64) -> Self {
+        Self::new(RateLimit::PerMillisecond(max_events), seed)
+    }
+
+    /// Keeps each event with probability `fraction`.
+    pub fn fraction(fraction: f64, seed: u64) -> Self {
+        Self::new(RateLimit::Fraction(fraction), seed)
+    }
+
+    /// Returns the configured limit.
+    pub fn limit(&self) -> RateLimit {
+        self.limit
+    }
+}
+
+impl EventFilter for RateLimitFilter {
+    fn keep(&mut self, event: &CdEvent) -> bool {
+        match self.limit {
+            RateLimit::Fraction(fraction) => self.rng.next_f64() < fraction,
+            RateLimit::PerMillisecond(max_events) => {
+                let window = event.timestamp / 1_000;
+                if window != self.window {
+                    // Only an immediately preceding window predicts the current rate
+                    self.previous_seen = if window == self.window + 1 {
+                        self.seen
+                    } else {
+                        0
+                    };
+                    self.window = window;
+                    self.seen = 0;
+                    self.kept = 0;
+                }
+                self.seen += 1;
+
+                let max_events = max_events as u64;
+                if self.kept >= max_events {
+                    return false;
+                }
+                let rate = self.previous_seen.max(self.seen);
+                let keep =
+                    rate <= max_events || self.rng.next_f64() < max_events as f64 / rate as f64;
+                if keep {
+                    self.kept += 1;
+                }
+                keep
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.limit, self.seed);
+    }
+}
+
+/// Filter that drops exact duplicates (same x, y, polarity and timestamp).
+///
+/// Some firmware emits redundant vector words, producing the same event
+/// twice. Duplicates always share a timestamp, so only the events of the
+/// current timestamp are remembered; memory use is bounded by the number of
+/// events per microsecond.
+#[derive(Debug, Clone, Default)]
+pub struct DuplicateFilter {
+    timestamp: u64,
+    seen: HashSet<(u16, u16, u8)>,
+}
+
+impl DuplicateFilter {
+    /// Creates a duplicate filter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl EventFilter for DuplicateFilter {
+    #[inline]
+    fn keep(&mut self, event: &CdEvent) -> bool {
+        if event.timestamp != self.timestamp {
+            self.timestamp = event.timestamp;
+            self.seen.clear();
+        }
+        self.seen.insert((event.x, event.y, event.polarity))
+    }
+
+    fn reset(&mut self) {
+        self.seen.clear();
+    }
+}
+
+/// Removes exact duplicate events in place, keeping the first occurrence.
+///
+/// Events are expected in time order, as produced by the decoder;
+/// duplicates separated by an event with a different timestamp are not
+/// detected.
+pub fn remove_duplicates(events: &mut Vec<CdEvent>) {
+    filter_events(&mut DuplicateFilter::new(), events);
+}
+
+/// Filter that keeps only the events inside a region of interest.
+///
+/// Coordinates are left unchanged; use [`Crop`](crate::transforms::Crop) to
+/// also move the region to the origin and shrink the output geometry.
+#[derive(Debug, Clone, Copy)]
+pub struct RoiFilter {
+    roi: Roi,
+}
+
+impl RoiFilter {
+    /// Creates a filter for the given region.
+    pub fn new(roi: Roi) -> Self {
+        Self { roi }
+    }
+
+    /// Returns the region of interest.
+    pub fn roi(&self) -> Roi {
+        self.roi
+    }
+}
+
+impl EventFilter for RoiFilter {
+    #[inline]
+    fn keep(&mut self, event: &CdEvent) -> bool {
+        self.roi.contains(event.x, event.y)
+    }
+}
+
+/// Filter that keeps only the events of one polarity (1 = ON, 0 = OFF).
+#[derive(Debug, Clone, Copy)]
+pub struct PolarityFilter {
+    polarity: u8,
+}
+
+impl PolarityFilter {
+    /// Creates a filter keeping events with the given polarity.
+    pub fn new(polarity: u8) -> Self {
+        Self { polarity }
+    }
+
+    /// Returns the kept polarity.
+    pub fn polarity(&self) -> u8 {
+        self.polarity
+    }
+}
+
+impl EventFilter for PolarityFilter {
+    #[inline]
+    fn keep(&mut self, event: &CdEvent) -> bool {
+        event.polarity == self.polarity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noisy_events() -> Vec<CdEvent> {
+        let mut events = Vec::new();
+        let mut t = 0;
+        for round in 0..20u16 {
+            // Background: 100 pixels, 30 of them twice as active as the rest
+            for i in 0..100u16 {
+                if round < 10 || i < 30 {
+                    events.push(CdEvent::new(i % 10, i / 10, 1, t));
+                    t += 1;
+                }
+            }
+            // Stuck pixel firing constantly
+            for _ in 0..50 {
+                events.push(CdEvent::new(42, 17, 0, t));
+                t += 1;
+            }
+        }
+        events
+    }
+
+    #[test]
+    fn test_detect_hot_pixels() {
+        let events = noisy_events();
+        assert_eq!(detect_hot_pixels(&events, 5.0), vec![(42, 17)]);
+        assert!(detect_hot_pixels(&[], 5.0).is_empty());
+    }
+
+    #[test]
+    fn test_uniform_activity_has_no_hot_pixels() {
+        let events: Vec<CdEvent> = (0..1000u64)
+            .map(|t| CdEvent::new((t % 50) as u16, 0, 1, t))
+            .collect();
+        assert!(detect_hot_pixels(&events, 3.0).is_empty());
+    }
+
+    #[test]
+    fn test_hot_pixel_filter() {
+        let metadata = SensorMetadata {
+            width: 64,
+            height: 32,
+        };
+        let mut events = noisy_events();
+        let total = events.len();
+        let mut filter = PixelMaskFilter::detect(&events, &metadata, 5.0);
+        assert_eq!(filter.len(), 1);
+
+        filter_events(&mut filter, &mut events);
+        assert_eq!(events.len(), total - 20 * 50);
+        assert!(events.iter().all(|e| (e.x, e.y) != (42, 17)));
+    }
+
+    #[test]
+    fn test_pixel_mask_filter_geometry() {
+        let small = SensorMetadata {
+            width: 8,
+            height: 8,
+        };
+        // (20, 3) is outside the initial geometry but kept in the list
+        let mut filter = PixelMaskFilter::new([(2, 3), (20, 3), (2, 3)], &small);
+        assert_eq!(filter.pixels(), &[(2, 3), (20, 3)]);
+        assert!(!filter.keep(&CdEvent::new(2, 3, 1, 0)));
+        assert!(filter.keep(&CdEvent::new(20, 3, 1, 0)));
+
+        filter.set_geometry(&SensorMetadata::default());
+        assert!(!filter.keep(&CdEvent::new(2, 3, 1, 0)));
+        assert!(!filter.keep(&CdEvent::new(20, 3, 1, 0)));
+        assert!(filter.keep(&CdEvent::new(21, 3, 1, 0)));
+    }
+
+    #[test]
+    fn test_background_activity_filter() {
+        let metadata = SensorMetadata {
+            width: 16,
+            height: 16,
+        };
+        let mut filter = BackgroundActivityFilter::new(&metadata, 1_000);
+        let mut events = vec![
+            CdEvent::new(5, 5, 1, 0),       // no support yet
+            CdEvent::new(6, 5, 1, 500),     // neighbor fired 500us ago
+            CdEvent::new(12, 12, 0, 600),   // isolated
+            CdEvent::new(5, 5, 1, 700),     // own pixel does not count, (6,5) does
+            CdEvent::new(13, 12, 0, 5_000), // (12,12) fired too long ago
+            CdEvent::new(15, 15, 1, 5_001), // corner pixel, isolated
+        ];
+        filter_events(&mut filter, &mut events);
+        let timestamps: Vec<u64> = events.iter().map(|e| e.timestamp).collect();
+        assert_eq!(timestamps, vec![500, 700]);
+    }
+
+    #[test]
+    fn test_background_activity_filter_radius() {
+        let metadata = SensorMetadata {
+            width: 16,
+            height: 16,
+        };
+        let events = [CdEvent::new(2, 2, 1, 0), CdEvent::new(4, 4, 1, 10)];
+
+        let mut narrow = BackgroundActivityFilter::new(&metadata, 100);
+        assert!(!events.iter().any(|e| narrow.keep(e)));
+
+        let mut wide = BackgroundActivityFilter::new(&metadata, 100).with_radius(2);
+        assert!(!wide.keep(&events[0]));
+        assert!(wide.keep(&events[1]));
+
+        wide.reset();
+        assert!(!wide.keep(&events[1]));
+    }
+
+    #[test]
+    fn test_rate_limit_per_millisecond() {
+        // 5000 events per millisecond for 10ms
+        let events: Vec<CdEvent> = (0..50_000u64)
+            .map(|i| CdEvent::new((i % 640) as u16, 0, 1, i / 5))
+            .collect();
+
+        let mut limited = events.clone();
+        filter_events(&mut RateLimitFilter::per_millisecond(100, 7), &mut limited);
+        for ms in 0..10 {
+            let count = limited.iter().filter(|e| e.timestamp / 1_000 == ms).count();
+            assert!(count <= 100, "{} events in ms {}", count, ms);
+        }
+        assert!(limited.len() > 500);
+        // Survivors are spread over each window, not just its first events
+        assert!(limited.iter().any(|e| e.timestamp % 1_000 > 900));
+
+        // Same seed, same output
+        let mut again = events.clone();
+        filter_events(&mut RateLimitFilter::per_millisecond(100, 7), &mut again);
+        assert_eq!(limited, again);
+    }
+
+    #[test]
+    fn test_rate_limit_fraction() {
+        let events: Vec<CdEvent> = (0..10_000u64).map(|t| CdEvent::new(0, 0, 1, t)).collect();
+        let mut filter = RateLimitFilter::fraction(0.25, 42);
+
+        let mut first = events.clone();
+        filter_events(&mut filter, &mut first);
+        assert!((2_000..3_000).contains(&first.len()));
+
+        filter.reset();
+        let mut second = events;
+        filter_events(&mut filter, &mut second);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_remove_duplicates() {
+        let mut events = vec![
+            CdEvent::new(1, 1, 1, 10),
+            CdEvent::new(2, 1, 1, 10),
+            CdEvent::new(1, 1, 1, 10), // duplicate
+            CdEvent::new(1, 1, 0, 10), // other polarity
+            CdEvent::new(1, 1, 1, 11),
+            CdEvent::new(1, 1, 1, 11), // duplicate
+        ];
+        remove_duplicates(&mut events);
+        assert_eq!(
+            events,
+            vec![
+                CdEvent::new(1, 1, 1, 10),
+                CdEvent::new(2, 1, 1, 10),
+                CdEvent::new(1, 1, 0, 10),
+                CdEvent::new(1, 1, 1, 11),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_roi_filter() {
+        let mut events = vec![
+            CdEvent::new(10, 20, 1, 0),
+            CdEvent::new(9, 20, 1, 1),
+            CdEvent::new(109, 69, 0, 2),
+            CdEvent::new(110, 30, 0, 3),
+            CdEvent::new(50, 70, 1, 4),
+        ];
+        filter_events(&mut RoiFilter::new(Roi::new(10, 20, 110, 70)), &mut events);
+        assert_eq!(
+            events,
+            vec![CdEvent::new(10, 20, 1, 0), CdEvent::new(109, 69, 0, 2)]
+        );
+    }
+
+    #[test]
+    fn test_refractory_filter() {
+        let metadata = SensorMetadata {
+            width: 4,
+            height: 4,
+        };
+        let mut events = vec![
+            CdEvent::new(1, 1, 1, 0),
+            CdEvent::new(1, 1, 0, 300),
+            CdEvent::new(2, 1, 1, 400),
+            CdEvent::new(1, 1, 1, 600),
+            CdEvent::new(1, 1, 1, 1_000),
+            CdEvent::new(1, 1, 1, 1_200),
+        ];
+        filter_events(&mut RefractoryFilter::new(&metadata, 500), &mut events);
+        // 300 is within the period of 0; 600 is not, although 300 was
+        // only 300 us before it, because dropped events do not count
+        assert_eq!(
+            events,
+            vec![
+                CdEvent::new(1, 1, 1, 0),
+                CdEvent::new(2, 1, 1, 400),
+                CdEvent::new(1, 1, 1, 600),
+                CdEvent::new(1, 1, 1, 1_200),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_polarity_filter() {
+        let events = vec![
+            CdEvent::new(1, 1, 1, 0),
+            CdEvent::new(2, 2, 0, 1),
+            CdEvent::new(3, 3, 1, 2),
+        ];
+        let mut on = events.clone();
+        filter_events(&mut PolarityFilter::new(1), &mut on);
+        assert_eq!(on, vec![events[0], events[2]]);
+        let mut off = events.clone();
+        filter_events(&mut PolarityFilter::new(0), &mut off);
+        assert_eq!(off, vec![events[1]]);
+    }
+
+    #[test]
+    fn test_retain_tail_only_touches_new_events() {
+        let metadata = SensorMetadata {
+            width: 4,
+            height: 4,
+        };
+        let mut filters: Vec<Box<dyn EventFilter>> =
+            vec![Box::new(PixelMaskFilter::new([(1, 1)], &metadata))];
+        let mut events = vec![
+            CdEvent::new(1, 1, 1, 0),
+            CdEvent::new(1, 1, 1, 1),
+            CdEvent::new(2, 1, 1, 2),
+            CdEvent::new(1, 1, 0, 3),
+        ];
+        retain_tail(&mut filters, &mut events, 1);
+        let timestamps: Vec<u64> = events.iter().map(|e| e.timestamp).collect();
+        assert_eq!(timestamps, vec![0, 2]);
+    }
+}
@@ -0,0 +1,200 @@
+//! Event frame accumulation.
+//!
+//! Frames are flat, row-major `width * height` arrays indexed by
+//! `y * width + x`, ready to be reshaped into images or tensors.
+
+use crate::types::CdEvent;
+
+/// Counts the events per pixel with timestamps in `t0..t1`.
+///
+/// Events outside the `width` x `height` geometry are ignored. Events do
+/// not need to be sorted by time.
+pub fn accumulate(events: &[CdEvent], width: u32, height: u32, t0: u64, t1: u64) -> Vec<u32> {
+    let mut frame = vec![0u32; width as usize * height as usize];
+    for (index, _) in pixels_in_range(events, width, height, t0, t1) {
+        frame[index] += 1;
+    }
+    frame
+}
+
+/// Sums event polarities per pixel with timestamps in `t0..t1`.
+///
+/// ON events count as +1 and OFF events as -1, so a pixel that saw as many
+/// ON as OFF events ends up at zero.
+pub fn accumulate_signed(
+    events: &[CdEvent],
+    width: u32,
+    height: u32,
+    t0: u64,
+    t1: u64,
+) -> Vec<i32> {
+    let mut frame = vec![0i32; width as usize * height as usize];
+    for (index, event) in pixels_in_range(events, width, height, t0, t1) {
+        frame[index] += if event.polarity != 0 { 1 } else { -1 };
+    }
+    frame
+}
+
+/// Counts the events per pixel over the whole recording or a time range.
+///
+/// With `time_range = Some((t0, t1))` only events with timestamps in
+/// `t0..t1` are counted. Useful for spotting hot pixels and checking the
+/// field of view.
+pub fn heatmap(
+    events: &[CdEvent],
+    width: u32,
+    height: u32,
+    time_range: Option<(u64, u64)>,
+) -> Vec<u32> {
+    match time_range {
+        Some((t0, t1)) => accumulate(events, width, height, t0, t1),
+        None => {
+            let mut frame = vec![0u32; width as usize * height as usize];
+            for event in events {
+                if (event.x as u32) < width && (event.y as u32) < height {
+                    frame[event.y as usize * width as usize + event.x as usize] += 1;
+                }
+            }
+            frame
+        }
+    }
+}
+
+/// Counts the events in a `bins_x` x `bins_y` grid over the sensor.
+///
+/// Pixel column `x` falls into bin `x * bins_x / width` (likewise for rows),
+/// so bins are equal up to one pixel when they do not divide the sensor.
+/// With `polarity`, only events of that polarity are counted. Returns a
+/// flat row-major `bins_x * bins_y` array; events outside the geometry are
+/// ignored.
+pub fn histogram2d(
+    events: &[CdEvent],
+    width: u32,
+    height: u32,
+    (bins_x, bins_y): (u32, u32),
+    polarity: Option<u8>,
+) -> Vec<u32> {
+    let mut counts = vec![0u32; bins_x as usize * bins_y as usize];
+    if counts.is_empty() {
+        return counts;
+    }
+    let bin =
+        |coordinate: u16, bins: u32, size: u32| coordinate as usize * bins as usize / size as usize;
+    for event in events {
+        if (event.x as u32) >= width
+            || (event.y as u32) >= height
+            || polarity.is_some_and(|p| event.polarity != p)
+        {
+            continue;
+        }
+        let (bx, by) = (bin(event.x, bins_x, width), bin(event.y, bins_y, height));
+        counts[by * bins_x as usize + bx] += 1;
+    }
+    counts
+}
+
+/// Background color of [`render_rgb`] frames (Metavision's dark palette).
+pub const BACKGROUND_RGB: [u8; 3] = [30, 37, 52];
+
+/// Color of pixels whose last event in the window was ON.
+pub const ON_RGB: [u8; 3] = [216, 223, 236];
+
+/// Color of pixels whose last event in the window was OFF.
+pub const OFF_RGB: [u8; 3] = [64, 126, 201];
+
+/// Renders the events with timestamps in `t0..t1` as an RGB image.
+///
+/// Returns `width * height * 3` bytes. Each pixel takes the color of its
+/// last event ([`ON_RGB`] or [`OFF_RGB`]), so events should be sorted by
+/// time; pixels without events keep [`BACKGROUND_RGB`].
+pub fn render_rgb(events: &[CdEvent], width: u32, height: u32, t0: u64, t1: u64) -> Vec<u8> {
+    let mut image = BACKGROUND_RGB.repeat(width as usize * height as usize);
+    for (index, event) in pixels_in_range(events, width, height, t0, t1) {
+        let color = if event.polarity != 0 { ON_RGB } else { OFF_RGB };
+        image[index * 3..index * 3 + 3].copy_from_slice(&color);
+    }
+    image
+}
+
+/// Iterates over the events in the time range and geometry with their pixel index.
+fn pixels_in_range(
+    events: &[CdEvent],
+    width: u32,
+    height: u32,
+    t0: u64,
+    t1: u64,
+) -> impl Iterator<Item = (usize, &CdEvent)> {
+    events
+        .iter()
+        .filter(move |e| {
+            e.timestamp >= t0 && e.timestamp < t1 && (e.x as u32) < width && (e.y as u32) < height
+        })
+        .map(move |e| (e.y as usize * width as usize + e.x as usize, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events() -> Vec<CdEvent> {
+        vec![
+            CdEvent::new(0, 0, 1, 10),
+            CdEvent::new(0, 0, 1, 20),
+            CdEvent::new(2, 1, 0, 30),
+            CdEvent::new(2, 1, 1, 40),
+            CdEvent::new(1, 1, 0, 50),
+            CdEvent::new(3, 0, 1, 60), // outside the 3x2 geometry
+        ]
+    }
+
+    #[test]
+    fn test_accumulate() {
+        let frame = accumulate(&events(), 3, 2, 0, 100);
+        assert_eq!(frame, vec![2, 0, 0, 0, 1, 2]);
+
+        // Time range is half-open
+        let frame = accumulate(&events(), 3, 2, 20, 50);
+        assert_eq!(frame, vec![1, 0, 0, 0, 0, 2]);
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let mut all = events();
+        all.push(CdEvent::new(0, 0, 0, u64::MAX));
+        assert_eq!(heatmap(&all, 3, 2, None), vec![3, 0, 0, 0, 1, 2]);
+        assert_eq!(heatmap(&all, 3, 2, Some((30, 50))), vec![0, 0, 0, 0, 0, 2]);
+    }
+
+    #[test]
+    fn test_histogram2d() {
+        // One bin per pixel matches the heatmap
+        assert_eq!(
+            histogram2d(&events(), 3, 2, (3, 2), None),
+            heatmap(&events(), 3, 2, None)
+        );
+
+        // Columns 0 and 1 share the first of two bins
+        assert_eq!(histogram2d(&events(), 3, 2, (2, 1), None), vec![3, 2]);
+        assert_eq!(histogram2d(&events(), 3, 2, (2, 1), Some(0)), vec![1, 1]);
+        assert!(histogram2d(&events(), 3, 2, (0, 1), None).is_empty());
+    }
+
+    #[test]
+    fn test_accumulate_signed() {
+        let frame = accumulate_signed(&events(), 3, 2, 0, u64::MAX);
+        assert_eq!(frame, vec![2, 0, 0, 0, -1, 0]);
+    }
+
+    #[test]
+    fn test_render_rgb() {
+        let image = render_rgb(&events(), 3, 2, 0, 45);
+        assert_eq!(image.len(), 3 * 2 * 3);
+        let pixel = |i: usize| &image[i * 3..i * 3 + 3];
+        assert_eq!(pixel(0), ON_RGB);
+        assert_eq!(pixel(1), BACKGROUND_RGB);
+        // (2, 1) saw OFF then ON; the last event wins
+        assert_eq!(pixel(5), ON_RGB);
+        // (1, 1) fired at 50, after the window
+        assert_eq!(pixel(4), BACKGROUND_RGB);
+    }
+}
@@ -0,0 +1,703 @@
+//! Readers for files produced by the output writers.
+//!
+//! Supports reading back the CSV format written by
+//! [`CsvWriter`](crate::output::CsvWriter) and the EVT3BIN format written by
+//! [`BinaryWriter`](crate::output::BinaryWriter), including time-based
+//! seeking via the optional index footer. Also reads pixel mask files
+//! (coordinate lists or PNG images) for
+//! [`PixelMaskFilter`](crate::filters::PixelMaskFilter).
+
+use crate::output::{
+    FieldOrder, TimestampUnit, BINARY_EVENT_LEN, BINARY_HEADER_LEN, BINARY_INDEX_MAGIC,
+    BINARY_INDEX_TRAILER_LEN, BINARY_KIND_CD, BINARY_VERSION, BINARY_VERSION_EVENT_KINDS,
+};
+use crate::types::{CdEvent, Event, SensorMetadata, TriggerEvent};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Errors that can occur while reading decoded event files.
+#[derive(Error, Debug)]
+pub enum InputError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Parse error on line {line}: {message}")]
+    Parse { line: usize, message: String },
+
+    #[error("Invalid file format: {0}")]
+    InvalidFormat(String),
+}
+
+/// Events and metadata read back from a CSV file.
+#[derive(Debug)]
+pub struct CsvContents {
+    /// Parsed CD events
+    pub events: Vec<CdEvent>,
+    /// Sensor geometry from the `%geometry` header, if present
+    pub metadata: Option<SensorMetadata>,
+}
+
+/// CSV reader for CD events.
+///
+/// Understands the `%geometry:W,H` header line and an optional column name
+/// row (e.g. `timestamp,x,y,polarity`), which overrides the configured field
+/// order. Other lines starting with `%` or `#` are treated as comments.
+pub struct CsvReader<R: BufRead> {
+    reader: R,
+    field_order: FieldOrder,
+    timestamp_unit: TimestampUnit,
+}
+
+impl<R: BufRead> CsvReader<R> {
+    /// Creates a new CSV reader expecting microsecond timestamps.
+    pub fn new(reader: R, field_order: FieldOrder) -> Self {
+        Self::with_timestamp_unit(reader, field_order, TimestampUnit::default())
+    }
+
+    /// Creates a new CSV reader expecting timestamps in the given unit.
+    pub fn with_timestamp_unit(
+        reader: R,
+        field_order: FieldOrder,
+        timestamp_unit: TimestampUnit,
+    ) -> Self {
+        Self {
+            reader,
+            field_order,
+            timestamp_unit,
+        }
+    }
+
+    /// Reads all remaining lines into events.
+    pub fn read_all(mut self) -> Result<CsvContents, InputError> {
+        let mut events = Vec::new();
+        let mut metadata = None;
+        let mut line = String::new();
+        let mut line_no = 0;
+
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                break;
+            }
+            line_no += 1;
+
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+
+            if let Some(geometry) = trimmed.strip_prefix("%geometry:") {
+                metadata = Some(parse_geometry(geometry).ok_or_else(|| InputError::Parse {
+                    line: line_no,
+                    message: format!("Invalid geometry header: {}", trimmed),
+                })?);
+                continue;
+            }
+
+            if trimmed.starts_with('%') || trimmed.starts_with('#') {
+                continue;
+            }
+
+            // A leading column name row overrides the configured order
+            if events.is_empty() && trimmed.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                self.field_order =
+                    FieldOrder::from_str(trimmed).map_err(|e| InputError::Parse {
+                        line: line_no,
+                        message: e.to_string(),
+                    })?;
+                continue;
+            }
+
+            events.push(self.parse_event(trimmed, line_no)?);
+        }
+
+        Ok(CsvContents { events, metadata })
+    }
+
+    /// Parses a single data row according to the field order.
+    fn parse_event(&self, row: &str, line_no: usize) -> Result<CdEvent, InputError> {
+        let indices = self.field_order.indices();
+
+        let parse_error = |message: String| InputError::Parse {
+            line: line_no,
+            message,
+        };
+
+        let mut event = CdEvent::new(0, 0, 0, 0);
+        let mut fields = row.split(',').map(str::trim);
+        for &field in &indices {
+            let value = fields
+                .next()
+                .ok_or_else(|| parse_error(format!("Expected 4 fields: {}", row)))?;
+            let invalid = || parse_error(format!("Invalid value: {}", value));
+            match field {
+                0 => event.x = value.parse().map_err(|_| invalid())?,
+                1 => event.y = value.parse().map_err(|_| invalid())?,
+                2 => event.polarity = value.parse().map_err(|_| invalid())?,
+                _ => {
+                    event.timestamp =
+                        parse_timestamp(value, self.timestamp_unit).ok_or_else(invalid)?
+                }
+            }
+        }
+
+        if fields.next().is_some() {
+            return Err(parse_error(format!("Expected 4 fields: {}", row)));
+        }
+
+        Ok(event)
+    }
+}
+
+/// Parses the `W,H` part of a `%geometry:W,H` header.
+fn parse_geometry(s: &str) -> Option<SensorMetadata> {
+    let (w, h) = s.split_once(',')?;
+    Some(SensorMetadata {
+        width: w.trim().parse().ok()?,
+        height: h.trim().parse().ok()?,
+    })
+}
+
+/// Parses a timestamp written in `unit` back into microseconds.
+///
+/// Fractional units are parsed with integer arithmetic so values written by
+/// [`TimestampUnit::format`] round-trip exactly.
+pub fn parse_timestamp(s: &str, unit: TimestampUnit) -> Option<u64> {
+    let (digits, scale) = match unit {
+        TimestampUnit::Microseconds => return s.parse().ok(),
+        TimestampUnit::Nanoseconds => return s.parse::<u64>().ok().map(|ns| ns / 1_000),
+        TimestampUnit::Milliseconds => (3, 1_000),
+        TimestampUnit::Seconds => (6, 1_000_000),
+    };
+
+    let (whole, frac) = s.split_once('.').unwrap_or((s, ""));
+    if frac.len() > digits || !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let whole: u64 = whole.parse().ok()?;
+    let frac_value: u64 = if frac.is_empty() {
+        0
+    } else {
+        frac.parse().ok()?
+    };
+    let frac_us = frac_value * 10u64.pow((digits - frac.len()) as u32);
+    whole.checked_mul(scale)?.checked_add(frac_us)
+}
+
+/// Reads CD events from a CSV file written by [`write_csv`](crate::output::write_csv).
+pub fn read_csv<P: AsRef<Path>>(
+    path: P,
+    field_order: FieldOrder,
+) -> Result<CsvContents, InputError> {
+    let file = File::open(path)?;
+    CsvReader::new(BufReader::new(file), field_order).read_all()
+}
+
+/// Reader for EVT3BIN files with support for seeking by timestamp.
+///
+/// Events are assumed to be stored in non-decreasing timestamp order, as
+/// produced by the decoder. Version 2 files may interleave trigger records;
+/// event counts and positions then refer to records of both kinds.
+pub struct BinaryReader<R: Read + Seek> {
+    reader: R,
+    metadata: SensorMetadata,
+    event_kinds: bool,
+    event_count: u64,
+    /// Index of the next event to be read
+    position: u64,
+    /// (timestamp, byte offset) entries from the index footer, if present
+    index: Vec<(u64, u64)>,
+}
+
+impl BinaryReader<BufReader<File>> {
+    /// Opens an EVT3BIN file.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, InputError> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read + Seek> BinaryReader<R> {
+    /// Creates a reader, parsing the header and the index footer if present.
+    pub fn new(mut reader: R) -> Result<Self, InputError> {
+        let mut header = [0u8; BINARY_HEADER_LEN as usize];
+        reader.seek(SeekFrom::Start(0))?;
+        reader.read_exact(&mut header)?;
+
+        if &header[0..8] != b"EVT3BIN\0" {
+            return Err(InputError::InvalidFormat(
+                "Missing EVT3BIN magic number".to_string(),
+            ));
+        }
+        let version = u32::from_le_bytes(header[8..12].try_into().unwrap());
+        if version != BINARY_VERSION && version != BINARY_VERSION_EVENT_KINDS {
+            return Err(InputError::InvalidFormat(format!(
+                "Unsupported EVT3BIN version: {}",
+                version
+            )));
+        }
+        let metadata = SensorMetadata {
+            width: u32::from_le_bytes(header[12..16].try_into().unwrap()),
+            height: u32::from_le_bytes(header[16..20].try_into().unwrap()),
+        };
+        let event_count = u64::from_le_bytes(header[20..28].try_into().unwrap());
+
+        let mut this = Self {
+            reader,
+            metadata,
+            event_kinds: version == BINARY_VERSION_EVENT_KINDS,
+            event_count,
+            position: 0,
+            index: Vec::new(),
+        };
+        this.index = this.read_index()?;
+        this.reader.seek(SeekFrom::Start(BINARY_HEADER_LEN))?;
+        Ok(this)
+    }
+
+    /// Reads the index footer, returning an empty index if there is none.
+    fn read_index(&mut self) -> Result<Vec<(u64, u64)>, InputError> {
+        let file_len = self.reader.seek(SeekFrom::End(0))?;
+        let events_end = BINARY_HEADER_LEN + self.event_count * BINARY_EVENT_LEN;
+        if file_len < events_end + BINARY_INDEX_TRAILER_LEN {
+            return Ok(Vec::new());
+        }
+
+        let mut trailer = [0u8; BINARY_INDEX_TRAILER_LEN as usize];
+        self.reader
+            .seek(SeekFrom::Start(file_len - BINARY_INDEX_TRAILER_LEN))?;
+        self.reader.read_exact(&mut trailer)?;
+        if &trailer[24..32] != BINARY_INDEX_MAGIC {
+            return Ok(Vec::new());
+        }
+
+        let entry_count = u64::from_le_bytes(trailer[8..16].try_into().unwrap());
+        let index_offset = u64::from_le_bytes(trailer[16..24].try_into().unwrap());
+        if index_offset + entry_count * 16 + BINARY_INDEX_TRAILER_LEN != file_len {
+            return Err(InputError::InvalidFormat(
+                "Inconsistent index footer".to_string(),
+            ));
+        }
+
+        self.reader.seek(SeekFrom::Start(index_offset))?;
+        let mut entries = Vec::with_capacity(entry_count as usize);
+        let mut entry = [0u8; 16];
+        for _ in 0..entry_count {
+            self.reader.read_exact(&mut entry)?;
+            entries.push((
+                u64::from_le_bytes(entry[0..8].try_into().unwrap()),
+                u64::from_le_bytes(entry[8..16].try_into().unwrap()),
+            ));
+        }
+        Ok(entries)
+    }
+
+    /// Returns the sensor metadata from the header.
+    pub fn metadata(&self) -> &SensorMetadata {
+        &self.metadata
+    }
+
+    /// Returns true if the file is a version 2 file that may contain trigger records.
+    pub fn has_event_kinds(&self) -> bool {
+        self.event_kinds
+    }
+
+    /// Returns the total number of events in the file.
+    pub fn event_count(&self) -> u64 {
+        self.event_count
+    }
+
+    /// Returns the index of the next event to be read.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns true if the file has an index footer.
+    pub fn has_index(&self) -> bool {
+        !self.index.is_empty()
+    }
+
+    /// Positions the reader at the given event index.
+    pub fn seek_to_event(&mut self, event_index: u64) -> Result<(), InputError> {
+        let event_index = event_index.min(self.event_count);
+        self.reader.seek(SeekFrom::Start(
+            BINARY_HEADER_LEN + event_index * BINARY_EVENT_LEN,
+        ))?;
+        self.position = event_index;
+        Ok(())
+    }
+
+    /// Positions the reader at the first event with `timestamp >= t`.
+    ///
+    /// Uses the index footer to jump close to the target and scans forward
+    /// from there; without an index, falls back to a binary search over the
+    /// fixed-size event records. Returns the index of that event (equal to
+    /// [`event_count`](Self::event_count) if all events are earlier).
+    pub fn seek_to_time(&mut self, t: u64) -> Result<u64, InputError> {
+        let (mut lo, mut hi) = (0, self.event_count);
+
+        if !self.index.is_empty() {
+            // Last index entry strictly before t; the answer lies after it,
+            // and no later than the first entry at or after t.
+            let after = self.index.partition_point(|&(ts, _)| ts < t);
+            let to_event = |offset: u64| (offset - BINARY_HEADER_LEN) / BINARY_EVENT_LEN;
+            if after > 0 {
+                lo = to_event(self.index[after - 1].1);
+            }
+            if after < self.index.len() {
+                hi = to_event(self.index[after].1);
+            }
+        }
+
+        // Binary search within [lo, hi) for the first timestamp >= t
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if self.read_timestamp_at(mid)? < t {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+
+        self.seek_to_event(lo)?;
+        Ok(lo)
+    }
+
+    /// Reads the timestamp of the event at the given index.
+    fn read_timestamp_at(&mut self, event_index: u64) -> Result<u64, InputError> {
+        let mut buf = [0u8; 8];
+        self.reader.seek(SeekFrom::Start(
+            BINARY_HEADER_LEN + event_index * BINARY_EVENT_LEN + 6,
+        ))?;
+        self.reader.read_exact(&mut buf)?;
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    /// Reads up to `max_events` events from the current position.
+    ///
+    /// Trigger records of version 2 files count towards `max_events` but are
+    /// skipped; use [`read_mixed`](Self::read_mixed) to get them too.
+    pub fn read_events(&mut self, max_events: usize) -> Result<Vec<CdEvent>, InputError> {
+        Ok(self
+            .read_mixed(max_events)?
+            .into_iter()
+            .filter_map(|event| match event {
+                Event::Cd(cd) => Some(cd),
+                Event::Trigger(_) => None,
+            })
+            .collect())
+    }
+
+    /// Reads up to `max_events` CD and trigger events from the current position.
+    pub fn read_mixed(&mut self, max_events: usize) -> Result<Vec<Event>, InputError> {
+        let count = (max_events as u64).min(self.event_count - self.position) as usize;
+        let mut events = Vec::with_capacity(count);
+        let mut record = [0u8; BINARY_EVENT_LEN as usize];
+        for _ in 0..count {
+            self.reader.read_exact(&mut record)?;
+            let x = u16::from_le_bytes([record[0], record[1]]);
+            let y = u16::from_le_bytes([record[2], record[3]]);
+            let timestamp = u64::from_le_bytes(record[6..14].try_into().unwrap());
+            events.push(if !self.event_kinds || record[5] == BINARY_KIND_CD {
+                Event::Cd(CdEvent::new(x, y, record[4], timestamp))
+            } else {
+                Event::Trigger(TriggerEvent::new(record[4], x as u8, timestamp))
+            });
+        }
+        self.position += count as u64;
+        Ok(events)
+    }
+
+    /// Reads all events with `t0 <= timestamp < t1`.
+    pub fn read_time_range(&mut self, t0: u64, t1: u64) -> Result<Vec<CdEvent>, InputError> {
+        let start = self.seek_to_time(t0)?;
+        let end = self.seek_to_time(t1)?;
+        self.seek_to_event(start)?;
+        self.read_events((end - start) as usize)
+    }
+}
+
+/// Parses a pixel list with one `x,y` pair per line.
+///
+/// Coordinates may be separated by commas, semicolons or whitespace. Blank
+/// lines, lines starting with `%` or `#`, and a leading column name row
+/// (e.g. `x,y`) are skipped.
+pub fn parse_pixel_list<R: BufRead>(reader: R) -> Result<Vec<(u16, u16)>, InputError> {
+    let mut pixels = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('%') || trimmed.starts_with('#') {
+            continue;
+        }
+        if pixels.is_empty() && trimmed.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            continue;
+        }
+
+        let parse_error = || InputError::Parse {
+            line: i + 1,
+            message: format!("Expected x,y: {}", trimmed),
+        };
+        let mut fields = trimmed
+            .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+            .filter(|f| !f.is_empty());
+        let (Some(x), Some(y), None) = (fields.next(), fields.next(), fields.next()) else {
+            return Err(parse_error());
+        };
+        pixels.push((
+            x.parse().map_err(|_| parse_error())?,
+            y.parse().map_err(|_| parse_error())?,
+        ));
+    }
+    Ok(pixels)
+}
+
+/// Reads the masked pixels from a PNG image.
+///
+/// Every non-black pixel is masked; the alpha channel is ignored. Any bit
+/// depth and color type is accepted.
+#[cfg(feature = "png")]
+pub fn read_png_mask<R: Read>(reader: R) -> Result<Vec<(u16, u16)>, InputError> {
+    let invalid = |e: png::DecodingError| InputError::InvalidFormat(format!("PNG: {}", e));
+
+    let mut decoder = png::Decoder::new(reader);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(invalid)?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(invalid)?;
+
+    let (channels, color_channels) = match info.color_type {
+        png::ColorType::Grayscale => (1, 1),
+        png::ColorType::GrayscaleAlpha => (2, 1),
+        png::ColorType::Rgb => (3, 3),
+        png::ColorType::Rgba => (4, 3),
+        png::ColorType::Indexed => {
+            return Err(InputError::InvalidFormat(
+                "PNG: unexpanded palette image".to_string(),
+            ))
+        }
+    };
+    if info.width > u16::MAX as u32 + 1 || info.height > u16::MAX as u32 + 1 {
+        return Err(InputError::InvalidFormat(format!(
+            "PNG mask too large: {}x{}",
+            info.width, info.height
+        )));
+    }
+
+    let mut pixels = Vec::new();
+    for (y, row) in buffer
+        .chunks_exact(info.line_size)
+        .take(info.height as usize)
+        .enumerate()
+    {
+        for (x, pixel) in row.chunks_exact(channels).enumerate() {
+            if pixel[..color_channels].iter().any(|&v| v != 0) {
+                pixels.push((x as u16, y as u16));
+            }
+        }
+    }
+    Ok(pixels)
+}
+
+/// Reads a pixel mask file.
+///
+/// `.png` files are read with [`read_png_mask`] (requires the `png`
+/// feature); anything else is parsed as a text list of `x,y` coordinates
+/// with [`parse_pixel_list`].
+pub fn read_pixel_mask<P: AsRef<Path>>(path: P) -> Result<Vec<(u16, u16)>, InputError> {
+    let path = path.as_ref();
+    let is_png = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("png"));
+    let file = File::open(path)?;
+
+    if is_png {
+        #[cfg(feature = "png")]
+        return read_png_mask(BufReader::new(file));
+        #[cfg(not(feature = "png"))]
+        return Err(InputError::InvalidFormat(
+            "PNG masks require the `png` feature".to_string(),
+        ));
+    }
+    parse_pixel_list(BufReader::new(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::CsvWriter;
+
+    #[test]
+    fn test_csv_roundtrip() {
+        let events = vec![
+            CdEvent::new(100, 200, 1, 12345),
+            CdEvent::new(101, 201, 0, 12346),
+        ];
+        let mut output = Vec::new();
+        {
+            let mut writer = CsvWriter::new(&mut output, FieldOrder::TXYP);
+            writer
+                .write_header(Some(&SensorMetadata {
+                    width: 640,
+                    height: 480,
+                }))
+                .unwrap();
+            writer.write_events(&events).unwrap();
+            writer.flush().unwrap();
+        }
+
+        let contents = CsvReader::new(output.as_slice(), FieldOrder::TXYP)
+            .read_all()
+            .unwrap();
+        assert_eq!(contents.events, events);
+        let metadata = contents.metadata.unwrap();
+        assert_eq!((metadata.width, metadata.height), (640, 480));
+    }
+
+    #[test]
+    fn test_csv_header_row_overrides_order() {
+        let data = "timestamp,x,y,polarity\n500,1,2,1\n";
+        let contents = CsvReader::new(data.as_bytes(), FieldOrder::XYPT)
+            .read_all()
+            .unwrap();
+        assert_eq!(contents.events, vec![CdEvent::new(1, 2, 1, 500)]);
+        assert!(contents.metadata.is_none());
+    }
+
+    #[test]
+    fn test_csv_parse_errors() {
+        let err = CsvReader::new("1,2,3\n".as_bytes(), FieldOrder::XYPT)
+            .read_all()
+            .unwrap_err();
+        assert!(matches!(err, InputError::Parse { line: 1, .. }));
+
+        let err = CsvReader::new("1,2,1,5\n1,b,1,6\n".as_bytes(), FieldOrder::XYPT)
+            .read_all()
+            .unwrap_err();
+        assert!(matches!(err, InputError::Parse { line: 2, .. }));
+    }
+
+    #[test]
+    fn test_parse_pixel_list() {
+        let data = "# dead pixels\nx,y\n1,2\n\n3 4\n5;6\n";
+        let pixels = parse_pixel_list(data.as_bytes()).unwrap();
+        assert_eq!(pixels, vec![(1, 2), (3, 4), (5, 6)]);
+
+        let err = parse_pixel_list("1,2\n1,2,3\n".as_bytes()).unwrap_err();
+        assert!(matches!(err, InputError::Parse { line: 2, .. }));
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_read_png_mask() {
+        // 3x2 grayscale image with two white pixels
+        let mut data = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut data, 3, 2);
+            encoder.set_color(png::ColorType::Grayscale);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header().unwrap();
+            writer.write_image_data(&[0, 255, 0, 0, 0, 1]).unwrap();
+        }
+        let pixels = read_png_mask(data.as_slice()).unwrap();
+        assert_eq!(pixels, vec![(1, 0), (2, 1)]);
+    }
+
+    #[test]
+    fn test_parse_timestamp_units() {
+        assert_eq!(
+            parse_timestamp("1.234567", TimestampUnit::Seconds),
+            Some(1_234_567)
+        );
+        assert_eq!(
+            parse_timestamp("2.5", TimestampUnit::Seconds),
+            Some(2_500_000)
+        );
+        assert_eq!(
+            parse_timestamp("1234.567", TimestampUnit::Milliseconds),
+            Some(1_234_567)
+        );
+        assert_eq!(
+            parse_timestamp("1234567000", TimestampUnit::Nanoseconds),
+            Some(1_234_567)
+        );
+        assert_eq!(parse_timestamp("1.2345678", TimestampUnit::Seconds), None);
+    }
+
+    fn sample_events() -> Vec<CdEvent> {
+        (0..1000u64)
+            .map(|i| CdEvent::new(i as u16, (i * 2) as u16, (i % 2) as u8, i / 3 * 10))
+            .collect()
+    }
+
+    #[test]
+    fn test_binary_reader_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.bin");
+        let events = sample_events();
+        crate::output::write_binary(&path, &events, &SensorMetadata::default()).unwrap();
+
+        let mut reader = BinaryReader::open(&path).unwrap();
+        assert!(!reader.has_index());
+        assert_eq!(reader.event_count(), 1000);
+        assert_eq!(reader.metadata().width, 1280);
+        assert_eq!(reader.read_events(usize::MAX).unwrap(), events);
+    }
+
+    #[test]
+    fn test_binary_reader_seek_to_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let events = sample_events();
+        let indexed = dir.path().join("indexed.bin");
+        let plain = dir.path().join("plain.bin");
+        let metadata = SensorMetadata::default();
+        crate::output::write_binary_indexed(&indexed, &events, &metadata, 64).unwrap();
+        crate::output::write_binary(&plain, &events, &metadata).unwrap();
+
+        let mut with_index = BinaryReader::open(&indexed).unwrap();
+        let mut without_index = BinaryReader::open(&plain).unwrap();
+        assert!(with_index.has_index());
+        assert_eq!(with_index.event_count(), 1000);
+
+        for t in [0, 5, 10, 1234, 1235, 3330, 5000] {
+            let expected = events.iter().position(|e| e.timestamp >= t).unwrap_or(1000) as u64;
+            assert_eq!(with_index.seek_to_time(t).unwrap(), expected, "t={}", t);
+            assert_eq!(without_index.seek_to_time(t).unwrap(), expected, "t={}", t);
+        }
+
+        let window = with_index.read_time_range(100, 200).unwrap();
+        let expected: Vec<CdEvent> = events
+            .iter()
+            .filter(|e| (100..200).contains(&e.timestamp))
+            .copied()
+            .collect();
+        assert_eq!(window, expected);
+    }
+
+    #[test]
+    fn test_binary_reader_merged_triggers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("merged.bin");
+        let events = sample_events();
+        let triggers = [TriggerEvent::new(1, 3, 15), TriggerEvent::new(0, 3, 1_000)];
+        let metadata = SensorMetadata::default();
+        crate::output::write_binary_merged(&path, &events, &triggers, &metadata, Some(100))
+            .unwrap();
+
+        let mut reader = BinaryReader::open(&path).unwrap();
+        assert!(reader.has_event_kinds());
+        assert!(reader.has_index());
+        assert_eq!(reader.event_count(), 1002);
+
+        let mixed = reader.read_mixed(usize::MAX).unwrap();
+        let expected: Vec<Event> = crate::types::merge_by_time(&events, &triggers).collect();
+        assert_eq!(mixed, expected);
+        assert_eq!(mixed[6], Event::Trigger(triggers[0]));
+
+        reader.seek_to_event(0).unwrap();
+        assert_eq!(reader.read_events(usize::MAX).unwrap(), events);
+    }
+}
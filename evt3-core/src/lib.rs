@@ -22,14 +22,52 @@
 //! - File header parsing for sensor metadata
 //! - Multiple output formats (CSV, binary, Arrow IPC)
 //! - Customizable field ordering for output
+//! - Reading CSV output back into events
+//! - Streaming event filters (hot pixel removal, background activity denoising)
+//! - Event transforms such as cropping, applied while decoding or afterwards
+//! - Event frame accumulation for visualization and learning pipelines
+//! - Dense representations such as time surfaces
+//! - Summary statistics (ranges, polarity counts, event rates)
+//! - Trigger analysis such as slicing events by trigger edges
+//! - Integrity checks of raw files (header, word types, truncation, timestamps)
 //! - Zero-copy buffer decoding for streaming use cases
+//! - Reusable event buffers for converting many recordings
+//! - Columnar (struct of arrays) decoding for numpy and Arrow consumers
+//! - Decoding under a memory budget, spilling events to disk
+//! - Chunk-parallel decoding with rayon, identical to serial decoding
+//! - Asynchronous decoding as a tokio `Stream` (feature `async`)
+//! - Time indices for random access into long recordings
 
+pub mod buffers;
 pub mod decoder;
+pub mod filters;
+pub mod frames;
+pub mod input;
 pub mod output;
+pub mod parallel;
 pub mod parser;
+pub mod representations;
+pub mod spill;
+pub mod stats;
+#[cfg(feature = "async")]
+pub mod stream;
+pub mod time_index;
+pub mod transforms;
+pub mod triggers;
 pub mod types;
+pub mod validate;
 
 // Re-export commonly used types
-pub use decoder::{DecodeError, Evt3Decoder};
-pub use output::{FieldOrder, OutputError};
-pub use types::{CdEvent, DecodeResult, SensorMetadata, TriggerEvent};
+pub use buffers::{BufferPool, EventBuffers};
+pub use decoder::{DecodeError, DecoderState, Evt3Decoder};
+pub use filters::EventFilter;
+pub use input::InputError;
+pub use output::{FieldOrder, OutputError, TimestampUnit};
+pub use spill::{SpilledDecodeResult, SpilledEvents};
+pub use stats::DecodeSummary;
+pub use time_index::TimeIndex;
+pub use transforms::EventTransform;
+pub use types::{
+    CdEvent, ColumnarDecodeResult, ColumnarEvents, DecodeResult, Event, EventChunk, ProbeResult,
+    Roi, SensorMetadata, TriggerEvent,
+};
@@ -2,12 +2,26 @@
 //!
 //! Supports multiple output formats including CSV, binary, and Apache Arrow IPC.
 
-use crate::types::{CdEvent, SensorMetadata, TriggerEvent};
+#[cfg(feature = "png")]
+pub mod image;
+pub mod jaer;
+pub mod mat;
+pub mod raw;
+pub mod rosbag;
+
+use crate::types::{self, CdEvent, ColumnarEvents, Event, SensorMetadata, TriggerEvent};
 use std::fs::File;
 use std::io::{BufWriter, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
+#[cfg(feature = "png")]
+pub use image::write_heatmap_png;
+pub use jaer::{write_aedat, JaerLayout, JaerWriter};
+pub use mat::write_mat;
+pub use raw::{write_raw, Evt3Writer};
+pub use rosbag::{write_rosbag, RosbagOptions, RosbagWriter};
+
 /// Errors that can occur during output writing.
 #[derive(Error, Debug)]
 pub enum OutputError {
@@ -87,6 +101,73 @@ impl std::str::FromStr for FieldOrder {
     }
 }
 
+/// Unit used for timestamps in text outputs.
+///
+/// EVT 3.0 timestamps are microseconds since the start of the recording;
+/// the other units are exact rescalings of that value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimestampUnit {
+    /// Integer microseconds (default, native EVT 3.0 resolution)
+    #[default]
+    Microseconds,
+    /// Integer nanoseconds
+    Nanoseconds,
+    /// Fractional milliseconds, e.g. `1234.567`
+    Milliseconds,
+    /// Fractional seconds, e.g. `1.234567`
+    Seconds,
+}
+
+impl std::str::FromStr for TimestampUnit {
+    type Err = OutputError;
+
+    /// Parses a unit name such as "us", "ns", "ms" or "s".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "us" | "µs" | "micros" | "microseconds" => Ok(Self::Microseconds),
+            "ns" | "nanos" | "nanoseconds" => Ok(Self::Nanoseconds),
+            "ms" | "millis" | "milliseconds" => Ok(Self::Milliseconds),
+            "s" | "sec" | "seconds" => Ok(Self::Seconds),
+            other => Err(OutputError::InvalidFormat(format!(
+                "Unknown timestamp unit: {}. Use us, ns, ms or s",
+                other
+            ))),
+        }
+    }
+}
+
+impl TimestampUnit {
+    /// Returns a displayable representation of a microsecond timestamp in this unit.
+    ///
+    /// Fractional units are printed with exact integer arithmetic, so no
+    /// precision is lost to floating-point rounding.
+    #[inline]
+    pub fn format(self, timestamp: u64) -> impl std::fmt::Display {
+        FormattedTimestamp {
+            timestamp,
+            unit: self,
+        }
+    }
+}
+
+/// Display adapter returned by [`TimestampUnit::format`].
+struct FormattedTimestamp {
+    timestamp: u64,
+    unit: TimestampUnit,
+}
+
+impl std::fmt::Display for FormattedTimestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let t = self.timestamp;
+        match self.unit {
+            TimestampUnit::Microseconds => write!(f, "{}", t),
+            TimestampUnit::Nanoseconds => write!(f, "{}", t as u128 * 1000),
+            TimestampUnit::Milliseconds => write!(f, "{}.{:03}", t / 1_000, t % 1_000),
+            TimestampUnit::Seconds => write!(f, "{}.{:06}", t / 1_000_000, t % 1_000_000),
+        }
+    }
+}
+
 impl FieldOrder {
     /// Returns the header string for this field order.
     pub fn header(&self) -> &'static str {
@@ -97,79 +178,202 @@ impl FieldOrder {
             Self::Custom(_) => "x,y,polarity,timestamp", // Will be reordered when writing
         }
     }
+
+    /// Returns the field indices (x=0, y=1, p=2, t=3) in output order.
+    pub fn indices(&self) -> [usize; 4] {
+        match self {
+            Self::XYPT => [0, 1, 2, 3],
+            Self::TXYP => [3, 0, 1, 2],
+            Self::XYTP => [0, 1, 3, 2],
+            Self::Custom(indices) => *indices,
+        }
+    }
+}
+
+/// Field names used for the optional column name row, indexed like [`FieldOrder::indices`].
+const FIELD_NAMES: [&str; 4] = ["x", "y", "polarity", "timestamp"];
+
+/// Parses a delimiter name ("comma", "tab", "semicolon", "space") or a single ASCII character.
+pub fn parse_delimiter(s: &str) -> Result<u8, OutputError> {
+    match s {
+        "comma" | "," => Ok(b','),
+        "tab" | "\\t" | "\t" => Ok(b'\t'),
+        "semicolon" | ";" => Ok(b';'),
+        "space" | " " => Ok(b' '),
+        _ if s.len() == 1 && s.is_ascii() => Ok(s.as_bytes()[0]),
+        _ => Err(OutputError::InvalidFormat(format!(
+            "Invalid delimiter: {:?}. Use comma, tab, semicolon, space or a single character",
+            s
+        ))),
+    }
+}
+
+/// Formatting options for delimited text output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// Order of the x, y, p, t columns
+    pub field_order: FieldOrder,
+    /// Unit used for timestamps
+    pub timestamp_unit: TimestampUnit,
+    /// Column delimiter (`,` by default)
+    pub delimiter: u8,
+    /// Write the `%geometry:W,H` line when metadata is available
+    pub geometry_header: bool,
+    /// Write a row of column names before the data
+    pub column_names: bool,
+    /// Prefix every row with an event kind column (0 = CD, 1 = trigger),
+    /// for streams that interleave CD and trigger events
+    pub event_kind: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            field_order: FieldOrder::default(),
+            timestamp_unit: TimestampUnit::default(),
+            delimiter: b',',
+            geometry_header: true,
+            column_names: false,
+            event_kind: false,
+        }
+    }
 }
 
 /// CSV output writer for CD events.
+///
+/// Despite the name, any single-byte delimiter can be used (see [`CsvOptions`]).
 pub struct CsvWriter<W: Write> {
     writer: BufWriter<W>,
-    field_order: FieldOrder,
+    options: CsvOptions,
 }
 
 impl<W: Write> CsvWriter<W> {
-    /// Creates a new CSV writer.
+    /// Creates a new CSV writer with microsecond timestamps.
     pub fn new(writer: W, field_order: FieldOrder) -> Self {
+        Self::with_timestamp_unit(writer, field_order, TimestampUnit::default())
+    }
+
+    /// Creates a new CSV writer that emits timestamps in the given unit.
+    pub fn with_timestamp_unit(
+        writer: W,
+        field_order: FieldOrder,
+        timestamp_unit: TimestampUnit,
+    ) -> Self {
+        Self::with_options(
+            writer,
+            CsvOptions {
+                field_order,
+                timestamp_unit,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Creates a new writer with full control over the text formatting.
+    pub fn with_options(writer: W, options: CsvOptions) -> Self {
         Self {
             writer: BufWriter::new(writer),
-            field_order,
+            options,
         }
     }
 
     /// Writes the CSV header with optional geometry metadata.
+    ///
+    /// Depending on the options this writes the `%geometry` line and/or a
+    /// row of column names; with both disabled nothing is written.
     pub fn write_header(&mut self, metadata: Option<&SensorMetadata>) -> Result<(), OutputError> {
         // Write geometry header if available
-        if let Some(meta) = metadata {
+        if let Some(meta) = metadata.filter(|_| self.options.geometry_header) {
             writeln!(self.writer, "%geometry:{},{}", meta.width, meta.height)?;
         }
+        if self.options.column_names {
+            if self.options.event_kind {
+                self.writer.write_all(b"kind")?;
+                self.writer.write_all(&[self.options.delimiter])?;
+            }
+            for (i, &field) in self.options.field_order.indices().iter().enumerate() {
+                if i > 0 {
+                    self.writer.write_all(&[self.options.delimiter])?;
+                }
+                self.writer.write_all(FIELD_NAMES[field].as_bytes())?;
+            }
+            self.writer.write_all(b"\n")?;
+        }
         Ok(())
     }
 
     /// Writes a batch of CD events.
     pub fn write_events(&mut self, events: &[CdEvent]) -> Result<(), OutputError> {
+        let indices = self.options.field_order.indices();
         for event in events {
-            self.write_event(event)?;
+            self.write_event(event, &indices)?;
+        }
+        Ok(())
+    }
+
+    /// Writes CD and trigger events interleaved in timestamp order.
+    ///
+    /// Trigger rows use the CD columns: x holds the channel ID, y is 0 and
+    /// p holds the edge value. Enable [`CsvOptions::event_kind`] to tell the
+    /// two kinds apart. Both inputs must be sorted by timestamp.
+    pub fn write_merged(
+        &mut self,
+        cd_events: &[CdEvent],
+        trigger_events: &[TriggerEvent],
+    ) -> Result<(), OutputError> {
+        let indices = self.options.field_order.indices();
+        for event in types::merge_by_time(cd_events, trigger_events) {
+            match event {
+                Event::Cd(cd) => self.write_event(&cd, &indices)?,
+                Event::Trigger(trigger) => self.write_row(
+                    1,
+                    [trigger.id as u16, 0, trigger.value as u16],
+                    trigger.timestamp,
+                    &indices,
+                )?,
+            }
         }
         Ok(())
     }
 
     /// Writes a single CD event.
     #[inline]
-    fn write_event(&mut self, event: &CdEvent) -> Result<(), OutputError> {
-        match self.field_order {
-            FieldOrder::XYPT => {
-                writeln!(
-                    self.writer,
-                    "{},{},{},{}",
-                    event.x, event.y, event.polarity, event.timestamp
-                )?;
-            }
-            FieldOrder::TXYP => {
-                writeln!(
-                    self.writer,
-                    "{},{},{},{}",
-                    event.timestamp, event.x, event.y, event.polarity
-                )?;
-            }
-            FieldOrder::XYTP => {
-                writeln!(
-                    self.writer,
-                    "{},{},{},{}",
-                    event.x, event.y, event.timestamp, event.polarity
-                )?;
+    fn write_event(&mut self, event: &CdEvent, indices: &[usize; 4]) -> Result<(), OutputError> {
+        self.write_row(
+            0,
+            [event.x, event.y, event.polarity as u16],
+            event.timestamp,
+            indices,
+        )
+    }
+
+    /// Writes one row of x, y, p and timestamp values of the given event kind.
+    #[inline]
+    fn write_row(
+        &mut self,
+        kind: u8,
+        values: [u16; 3],
+        timestamp: u64,
+        indices: &[usize; 4],
+    ) -> Result<(), OutputError> {
+        if self.options.event_kind {
+            write!(self.writer, "{}", kind)?;
+            self.writer.write_all(&[self.options.delimiter])?;
+        }
+        for (i, &field) in indices.iter().enumerate() {
+            if i > 0 {
+                self.writer.write_all(&[self.options.delimiter])?;
             }
-            FieldOrder::Custom(indices) => {
-                let values = [
-                    event.x as u64,
-                    event.y as u64,
-                    event.polarity as u64,
-                    event.timestamp,
-                ];
-                writeln!(
+            match field {
+                0..=2 => write!(self.writer, "{}", values[field])?,
+                _ => write!(
                     self.writer,
-                    "{},{},{},{}",
-                    values[indices[0]], values[indices[1]], values[indices[2]], values[indices[3]]
-                )?;
+                    "{}",
+                    self.options.timestamp_unit.format(timestamp)
+                )?,
             }
         }
+        self.writer.write_all(b"\n")?;
         Ok(())
     }
 
@@ -181,25 +385,51 @@ impl<W: Write> CsvWriter<W> {
 }
 
 /// CSV writer for trigger events.
+///
+/// Rows are `value,id,timestamp`; only the timestamp unit and delimiter of
+/// [`CsvOptions`] apply.
 pub struct TriggerCsvWriter<W: Write> {
     writer: BufWriter<W>,
+    timestamp_unit: TimestampUnit,
+    delimiter: char,
 }
 
 impl<W: Write> TriggerCsvWriter<W> {
-    /// Creates a new trigger CSV writer.
+    /// Creates a new trigger CSV writer with microsecond timestamps.
     pub fn new(writer: W) -> Self {
+        Self::with_timestamp_unit(writer, TimestampUnit::default())
+    }
+
+    /// Creates a new trigger CSV writer that emits timestamps in the given unit.
+    pub fn with_timestamp_unit(writer: W, timestamp_unit: TimestampUnit) -> Self {
+        Self::with_options(
+            writer,
+            &CsvOptions {
+                timestamp_unit,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Creates a new trigger writer using the unit and delimiter from `options`.
+    pub fn with_options(writer: W, options: &CsvOptions) -> Self {
         Self {
             writer: BufWriter::new(writer),
+            timestamp_unit: options.timestamp_unit,
+            delimiter: options.delimiter as char,
         }
     }
 
     /// Writes a batch of trigger events.
     pub fn write_events(&mut self, events: &[TriggerEvent]) -> Result<(), OutputError> {
+        let d = self.delimiter;
         for event in events {
             writeln!(
                 self.writer,
-                "{},{},{}",
-                event.value, event.id, event.timestamp
+                "{}{d}{}{d}{}",
+                event.value,
+                event.id,
+                self.timestamp_unit.format(event.timestamp)
             )?;
         }
         Ok(())
@@ -221,16 +451,74 @@ impl<W: Write> TriggerCsvWriter<W> {
 /// - padding: u8 (1 byte, for alignment)
 /// - timestamp: u64 (8 bytes)
 ///   Total: 14 bytes per event (padded to 16 for alignment)
+///
+/// When created with [`with_index`](Self::with_index), an index footer is
+/// appended after the events by [`write_index`](Self::write_index):
+/// - entries: (timestamp: u64, byte offset: u64) for every N-th event
+/// - trailer: interval: u64, entry count: u64, index offset: u64, magic `EVT3IDX\0`
+///
+/// Readers that only look at the header and the event count ignore the footer.
+///
+/// With [`with_event_kinds`](Self::with_event_kinds) the file is written as
+/// version 2, where the padding byte holds the event kind (0 = CD,
+/// 1 = trigger) so that trigger events can be interleaved with CD events.
+/// Trigger records store the channel ID in x, 0 in y and the edge value in
+/// the polarity byte.
 pub struct BinaryWriter<W: Write> {
     writer: BufWriter<W>,
+    index_interval: Option<u64>,
+    index: Vec<(u64, u64)>,
+    events_written: u64,
+    event_kinds: bool,
 }
 
+/// Size of the EVT3BIN header in bytes.
+pub const BINARY_HEADER_LEN: u64 = 28;
+/// Size of one EVT3BIN event record in bytes.
+pub const BINARY_EVENT_LEN: u64 = 14;
+/// Size of the EVT3BIN index trailer in bytes.
+pub const BINARY_INDEX_TRAILER_LEN: u64 = 32;
+/// Magic number terminating an EVT3BIN index footer.
+pub const BINARY_INDEX_MAGIC: &[u8; 8] = b"EVT3IDX\0";
+/// EVT3BIN version of files with CD events only.
+pub const BINARY_VERSION: u32 = 1;
+/// EVT3BIN version of files whose records carry an event kind byte.
+pub const BINARY_VERSION_EVENT_KINDS: u32 = 2;
+/// Event kind byte of a CD record in version 2 EVT3BIN files.
+pub const BINARY_KIND_CD: u8 = 0;
+/// Event kind byte of a trigger record in version 2 EVT3BIN files.
+pub const BINARY_KIND_TRIGGER: u8 = 1;
+
 impl<W: Write> BinaryWriter<W> {
     /// Creates a new binary writer.
     pub fn new(writer: W) -> Self {
         Self {
             writer: BufWriter::new(writer),
+            index_interval: None,
+            index: Vec::new(),
+            events_written: 0,
+            event_kinds: false,
+        }
+    }
+
+    /// Writes a version 2 file that can hold trigger events, see
+    /// [`write_merged`](Self::write_merged).
+    pub fn with_event_kinds(mut self) -> Self {
+        self.event_kinds = true;
+        self
+    }
+
+    /// Creates a binary writer that records an index entry every `interval` events.
+    pub fn with_index(writer: W, interval: u64) -> Result<Self, OutputError> {
+        if interval == 0 {
+            return Err(OutputError::InvalidFormat(
+                "Index interval must be greater than zero".to_string(),
+            ));
         }
+        Ok(Self {
+            index_interval: Some(interval),
+            ..Self::new(writer)
+        })
     }
 
     /// Writes a header with metadata.
@@ -242,7 +530,12 @@ impl<W: Write> BinaryWriter<W> {
         // Magic number "EVT3BIN\0"
         self.writer.write_all(b"EVT3BIN\0")?;
         // Version (u32)
-        self.writer.write_all(&1u32.to_le_bytes())?;
+        let version = if self.event_kinds {
+            BINARY_VERSION_EVENT_KINDS
+        } else {
+            BINARY_VERSION
+        };
+        self.writer.write_all(&version.to_le_bytes())?;
         // Sensor width (u32)
         self.writer.write_all(&metadata.width.to_le_bytes())?;
         // Sensor height (u32)
@@ -255,14 +548,265 @@ impl<W: Write> BinaryWriter<W> {
     /// Writes a batch of CD events.
     pub fn write_events(&mut self, events: &[CdEvent]) -> Result<(), OutputError> {
         for event in events {
-            self.writer.write_all(&event.x.to_le_bytes())?;
-            self.writer.write_all(&event.y.to_le_bytes())?;
-            self.writer.write_all(&[event.polarity, 0])?; // polarity + padding
-            self.writer.write_all(&event.timestamp.to_le_bytes())?;
+            self.write_record(
+                event.x,
+                event.y,
+                event.polarity,
+                BINARY_KIND_CD,
+                event.timestamp,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Writes CD and trigger events interleaved in timestamp order.
+    ///
+    /// Requires a writer created with [`with_event_kinds`](Self::with_event_kinds).
+    /// Both inputs must be sorted by timestamp; the header's event count must
+    /// include the trigger events.
+    pub fn write_merged(
+        &mut self,
+        cd_events: &[CdEvent],
+        trigger_events: &[TriggerEvent],
+    ) -> Result<(), OutputError> {
+        if !self.event_kinds {
+            return Err(OutputError::InvalidFormat(
+                "Trigger events require a writer created with with_event_kinds".to_string(),
+            ));
+        }
+        for event in types::merge_by_time(cd_events, trigger_events) {
+            match event {
+                Event::Cd(e) => {
+                    self.write_record(e.x, e.y, e.polarity, BINARY_KIND_CD, e.timestamp)?
+                }
+                Event::Trigger(e) => {
+                    self.write_record(e.id as u16, 0, e.value, BINARY_KIND_TRIGGER, e.timestamp)?
+                }
+            }
         }
         Ok(())
     }
 
+    /// Writes one record, adding an index entry when due.
+    fn write_record(
+        &mut self,
+        x: u16,
+        y: u16,
+        polarity: u8,
+        kind: u8,
+        timestamp: u64,
+    ) -> Result<(), OutputError> {
+        if let Some(interval) = self.index_interval {
+            let next_entry = self.index.len() as u64 * interval;
+            if self.events_written == next_entry {
+                let offset = BINARY_HEADER_LEN + self.events_written * BINARY_EVENT_LEN;
+                self.index.push((timestamp, offset));
+            }
+        }
+        self.events_written += 1;
+
+        self.writer.write_all(&x.to_le_bytes())?;
+        self.writer.write_all(&y.to_le_bytes())?;
+        self.writer.write_all(&[polarity, kind])?; // polarity + padding/kind
+        self.writer.write_all(&timestamp.to_le_bytes())?;
+        Ok(())
+    }
+
+    /// Writes the index footer, if this writer was created with an index.
+    ///
+    /// Must be called once, after all events have been written.
+    pub fn write_index(&mut self) -> Result<(), OutputError> {
+        let Some(interval) = self.index_interval else {
+            return Ok(());
+        };
+
+        let index_offset = BINARY_HEADER_LEN + self.events_written * BINARY_EVENT_LEN;
+        for &(timestamp, offset) in &self.index {
+            self.writer.write_all(&timestamp.to_le_bytes())?;
+            self.writer.write_all(&offset.to_le_bytes())?;
+        }
+        self.writer.write_all(&interval.to_le_bytes())?;
+        self.writer
+            .write_all(&(self.index.len() as u64).to_le_bytes())?;
+        self.writer.write_all(&index_offset.to_le_bytes())?;
+        self.writer.write_all(BINARY_INDEX_MAGIC)?;
+        Ok(())
+    }
+
+    /// Flushes the writer.
+    pub fn flush(&mut self) -> Result<(), OutputError> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Event Stream (`.es`) writer for CD events.
+///
+/// Writes the DVS stream type of the Event Stream 2.0.0 format used by
+/// loris and command_line_tools:
+/// - 12-byte signature `Event Stream`, version bytes `2, 0, 0`, type byte `1` (DVS)
+/// - sensor width and height as little-endian u16
+/// - per event: `(dt << 1) | is_increase`, x (u16 LE), y (u16 LE), where `dt`
+///   is the 7-bit delta to the previous event; larger gaps are encoded with
+///   overflow bytes `0xFF`, each adding 127 us
+///
+/// Events must be written in non-decreasing timestamp order.
+pub struct EsWriter<W: Write> {
+    writer: BufWriter<W>,
+    previous_timestamp: u64,
+}
+
+impl<W: Write> EsWriter<W> {
+    /// Maximum time delta that fits in a single event byte.
+    const MAX_DELTA: u64 = 127;
+    /// Overflow byte that advances the stream time by `MAX_DELTA`.
+    const OVERFLOW: u8 = 0xFF;
+
+    /// Creates a new Event Stream writer.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            previous_timestamp: 0,
+        }
+    }
+
+    /// Writes the Event Stream header for a DVS stream with the given geometry.
+    pub fn write_header(&mut self, metadata: &SensorMetadata) -> Result<(), OutputError> {
+        let width = u16::try_from(metadata.width).map_err(|_| {
+            OutputError::InvalidFormat(format!("Width {} exceeds u16 range", metadata.width))
+        })?;
+        let height = u16::try_from(metadata.height).map_err(|_| {
+            OutputError::InvalidFormat(format!("Height {} exceeds u16 range", metadata.height))
+        })?;
+
+        self.writer.write_all(b"Event Stream")?;
+        // Version 2.0.0
+        self.writer.write_all(&[2, 0, 0])?;
+        // Stream type: DVS
+        self.writer.write_all(&[1])?;
+        self.writer.write_all(&width.to_le_bytes())?;
+        self.writer.write_all(&height.to_le_bytes())?;
+        Ok(())
+    }
+
+    /// Writes a batch of CD events.
+    pub fn write_events(&mut self, events: &[CdEvent]) -> Result<(), OutputError> {
+        for event in events {
+            let mut delta = event
+                .timestamp
+                .checked_sub(self.previous_timestamp)
+                .ok_or_else(|| {
+                    OutputError::InvalidFormat(format!(
+                        "Timestamps must be non-decreasing: {} after {}",
+                        event.timestamp, self.previous_timestamp
+                    ))
+                })?;
+
+            while delta >= Self::MAX_DELTA {
+                self.writer.write_all(&[Self::OVERFLOW])?;
+                delta -= Self::MAX_DELTA;
+            }
+
+            let x = event.x.to_le_bytes();
+            let y = event.y.to_le_bytes();
+            self.writer.write_all(&[
+                ((delta as u8) << 1) | (event.polarity & 1),
+                x[0],
+                x[1],
+                y[0],
+                y[1],
+            ])?;
+            self.previous_timestamp = event.timestamp;
+        }
+        Ok(())
+    }
+
+    /// Flushes the writer.
+    pub fn flush(&mut self) -> Result<(), OutputError> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// JSON Lines writer for CD events.
+///
+/// Writes one object per line, `{"x":100,"y":50,"p":1,"t":1234}`, so events
+/// can be streamed into tools like `jq`. The optional header line holds the
+/// geometry as `{"width":1280,"height":720}`. In merged output, trigger
+/// events are written as `{"id":0,"value":1,"t":1234}`.
+pub struct JsonlWriter<W: Write> {
+    writer: BufWriter<W>,
+    timestamp_unit: TimestampUnit,
+}
+
+impl<W: Write> JsonlWriter<W> {
+    /// Creates a new JSON Lines writer with microsecond timestamps.
+    pub fn new(writer: W) -> Self {
+        Self::with_timestamp_unit(writer, TimestampUnit::default())
+    }
+
+    /// Creates a new JSON Lines writer that emits timestamps in the given unit.
+    pub fn with_timestamp_unit(writer: W, timestamp_unit: TimestampUnit) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            timestamp_unit,
+        }
+    }
+
+    /// Writes the geometry line.
+    pub fn write_header(&mut self, metadata: &SensorMetadata) -> Result<(), OutputError> {
+        writeln!(
+            self.writer,
+            "{{\"width\":{},\"height\":{}}}",
+            metadata.width, metadata.height
+        )?;
+        Ok(())
+    }
+
+    /// Writes a batch of CD events.
+    pub fn write_events(&mut self, events: &[CdEvent]) -> Result<(), OutputError> {
+        for event in events {
+            self.write_cd(event)?;
+        }
+        Ok(())
+    }
+
+    /// Writes CD and trigger events interleaved in timestamp order.
+    ///
+    /// Both inputs must be sorted by timestamp.
+    pub fn write_merged(
+        &mut self,
+        cd_events: &[CdEvent],
+        trigger_events: &[TriggerEvent],
+    ) -> Result<(), OutputError> {
+        for event in types::merge_by_time(cd_events, trigger_events) {
+            match event {
+                Event::Cd(event) => self.write_cd(&event)?,
+                Event::Trigger(event) => writeln!(
+                    self.writer,
+                    "{{\"id\":{},\"value\":{},\"t\":{}}}",
+                    event.id,
+                    event.value,
+                    self.timestamp_unit.format(event.timestamp)
+                )?,
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn write_cd(&mut self, event: &CdEvent) -> Result<(), OutputError> {
+        writeln!(
+            self.writer,
+            "{{\"x\":{},\"y\":{},\"p\":{},\"t\":{}}}",
+            event.x,
+            event.y,
+            event.polarity,
+            self.timestamp_unit.format(event.timestamp)
+        )?;
+        Ok(())
+    }
+
     /// Flushes the writer.
     pub fn flush(&mut self) -> Result<(), OutputError> {
         self.writer.flush()?;
@@ -270,33 +814,226 @@ impl<W: Write> BinaryWriter<W> {
     }
 }
 
+/// Column-major output for CD events.
+///
+/// Writes one flat little-endian file per field into a directory, plus a
+/// `manifest.json` describing the columns, so the data can be memory-mapped
+/// directly (e.g. with `numpy.memmap`) without any parsing:
+/// - `x.u16`: x coordinates (u16)
+/// - `y.u16`: y coordinates (u16)
+/// - `p.u8`: polarities (u8)
+/// - `t.u64`: timestamps in microseconds (u64)
+pub struct ColumnWriter {
+    dir: PathBuf,
+    x: BufWriter<File>,
+    y: BufWriter<File>,
+    polarity: BufWriter<File>,
+    timestamp: BufWriter<File>,
+    event_count: u64,
+}
+
+impl ColumnWriter {
+    /// File name of the column manifest inside the output directory.
+    pub const MANIFEST: &'static str = "manifest.json";
+
+    /// Column names, file names and numpy dtype strings, in manifest order.
+    pub const COLUMNS: [(&'static str, &'static str, &'static str); 4] = [
+        ("x", "x.u16", "<u2"),
+        ("y", "y.u16", "<u2"),
+        ("polarity", "p.u8", "u1"),
+        ("timestamp", "t.u64", "<u8"),
+    ];
+
+    /// Creates the output directory (if needed) and opens one file per column.
+    pub fn create<P: AsRef<Path>>(dir: P) -> Result<Self, OutputError> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir)?;
+        let open = |name: &str| -> Result<BufWriter<File>, OutputError> {
+            Ok(BufWriter::new(File::create(dir.join(name))?))
+        };
+        Ok(Self {
+            x: open(Self::COLUMNS[0].1)?,
+            y: open(Self::COLUMNS[1].1)?,
+            polarity: open(Self::COLUMNS[2].1)?,
+            timestamp: open(Self::COLUMNS[3].1)?,
+            dir,
+            event_count: 0,
+        })
+    }
+
+    /// Writes a batch of CD events.
+    pub fn write_events(&mut self, events: &[CdEvent]) -> Result<(), OutputError> {
+        for event in events {
+            self.x.write_all(&event.x.to_le_bytes())?;
+            self.y.write_all(&event.y.to_le_bytes())?;
+            self.polarity.write_all(&[event.polarity])?;
+            self.timestamp.write_all(&event.timestamp.to_le_bytes())?;
+        }
+        self.event_count += events.len() as u64;
+        Ok(())
+    }
+
+    /// Writes a batch of CD events that are already split into columns.
+    pub fn write_columnar(&mut self, events: &ColumnarEvents) -> Result<(), OutputError> {
+        for x in &events.x {
+            self.x.write_all(&x.to_le_bytes())?;
+        }
+        for y in &events.y {
+            self.y.write_all(&y.to_le_bytes())?;
+        }
+        self.polarity.write_all(&events.polarity)?;
+        for timestamp in &events.timestamp {
+            self.timestamp.write_all(&timestamp.to_le_bytes())?;
+        }
+        self.event_count += events.len() as u64;
+        Ok(())
+    }
+
+    /// Flushes all column files and writes the manifest.
+    pub fn finish(mut self, metadata: &SensorMetadata) -> Result<(), OutputError> {
+        self.x.flush()?;
+        self.y.flush()?;
+        self.polarity.flush()?;
+        self.timestamp.flush()?;
+
+        let columns: Vec<String> = Self::COLUMNS
+            .iter()
+            .map(|(name, file, dtype)| {
+                format!(
+                    "    {{\"name\": \"{}\", \"file\": \"{}\", \"dtype\": \"{}\"}}",
+                    name, file, dtype
+                )
+            })
+            .collect();
+
+        let mut manifest = BufWriter::new(File::create(self.dir.join(Self::MANIFEST))?);
+        writeln!(manifest, "{{")?;
+        writeln!(manifest, "  \"format\": \"evt3-columns\",")?;
+        writeln!(manifest, "  \"version\": 1,")?;
+        writeln!(manifest, "  \"width\": {},", metadata.width)?;
+        writeln!(manifest, "  \"height\": {},", metadata.height)?;
+        writeln!(manifest, "  \"event_count\": {},", self.event_count)?;
+        writeln!(manifest, "  \"timestamp_unit\": \"us\",")?;
+        writeln!(manifest, "  \"columns\": [\n{}\n  ]", columns.join(",\n"))?;
+        writeln!(manifest, "}}")?;
+        manifest.flush()?;
+        Ok(())
+    }
+}
+
 /// Writes CD events to a CSV file.
 pub fn write_csv<P: AsRef<Path>>(
     path: P,
     events: &[CdEvent],
     metadata: Option<&SensorMetadata>,
-    field_order: FieldOrder,
+    options: &CsvOptions,
 ) -> Result<(), OutputError> {
     let file = File::create(path)?;
-    let mut writer = CsvWriter::new(file, field_order);
+    let mut writer = CsvWriter::with_options(file, *options);
     writer.write_header(metadata)?;
     writer.write_events(events)?;
     writer.flush()?;
     Ok(())
 }
 
+/// Writes CD and trigger events interleaved in timestamp order to a CSV file.
+///
+/// An event kind column (0 = CD, 1 = trigger) is always written first; see
+/// [`CsvWriter::write_merged`] for the layout of trigger rows.
+pub fn write_csv_merged<P: AsRef<Path>>(
+    path: P,
+    cd_events: &[CdEvent],
+    trigger_events: &[TriggerEvent],
+    metadata: Option<&SensorMetadata>,
+    options: &CsvOptions,
+) -> Result<(), OutputError> {
+    let file = File::create(path)?;
+    let options = CsvOptions {
+        event_kind: true,
+        ..*options
+    };
+    let mut writer = CsvWriter::with_options(file, options);
+    writer.write_header(metadata)?;
+    writer.write_merged(cd_events, trigger_events)?;
+    writer.flush()?;
+    Ok(())
+}
+
 /// Writes trigger events to a CSV file.
 pub fn write_trigger_csv<P: AsRef<Path>>(
     path: P,
     events: &[TriggerEvent],
+    options: &CsvOptions,
+) -> Result<(), OutputError> {
+    let file = File::create(path)?;
+    let mut writer = TriggerCsvWriter::with_options(file, options);
+    writer.write_events(events)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Returns the per-channel variant of a trigger file path, e.g.
+/// `triggers.csv` becomes `triggers_ch0.csv` for channel 0.
+pub fn trigger_channel_path(path: &Path, channel: u8) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let mut name = format!("{}_ch{}", stem, channel);
+    if let Some(ext) = path.extension() {
+        name.push('.');
+        name.push_str(&ext.to_string_lossy());
+    }
+    path.with_file_name(name)
+}
+
+/// Writes trigger events to one CSV file per channel.
+///
+/// File names are derived from `path` with [`trigger_channel_path`]. Only
+/// channels that have events get a file. Returns the channels and paths
+/// written, ordered by channel.
+pub fn write_trigger_csv_per_channel<P: AsRef<Path>>(
+    path: P,
+    events: &[TriggerEvent],
+    options: &CsvOptions,
+) -> Result<Vec<(u8, PathBuf)>, OutputError> {
+    let mut channels: Vec<u8> = events.iter().map(|e| e.id).collect();
+    channels.sort_unstable();
+    channels.dedup();
+
+    let mut written = Vec::with_capacity(channels.len());
+    for channel in channels {
+        let channel_path = trigger_channel_path(path.as_ref(), channel);
+        let channel_events: Vec<TriggerEvent> =
+            events.iter().filter(|e| e.id == channel).copied().collect();
+        write_trigger_csv(&channel_path, &channel_events, options)?;
+        written.push((channel, channel_path));
+    }
+    Ok(written)
+}
+
+/// Writes CD events to an Event Stream (`.es`) file.
+pub fn write_es<P: AsRef<Path>>(
+    path: P,
+    events: &[CdEvent],
+    metadata: &SensorMetadata,
 ) -> Result<(), OutputError> {
     let file = File::create(path)?;
-    let mut writer = TriggerCsvWriter::new(file);
+    let mut writer = EsWriter::new(file);
+    writer.write_header(metadata)?;
     writer.write_events(events)?;
     writer.flush()?;
     Ok(())
 }
 
+/// Writes CD events as column files plus a JSON manifest into a directory.
+pub fn write_columns<P: AsRef<Path>>(
+    dir: P,
+    events: &[CdEvent],
+    metadata: &SensorMetadata,
+) -> Result<(), OutputError> {
+    let mut writer = ColumnWriter::create(dir)?;
+    writer.write_events(events)?;
+    writer.finish(metadata)
+}
+
 /// Writes CD events to a binary file.
 pub fn write_binary<P: AsRef<Path>>(
     path: P,
@@ -311,6 +1048,44 @@ pub fn write_binary<P: AsRef<Path>>(
     Ok(())
 }
 
+/// Writes CD and trigger events interleaved in timestamp order to a version 2
+/// binary file, optionally with an index entry every `index_interval` records.
+pub fn write_binary_merged<P: AsRef<Path>>(
+    path: P,
+    cd_events: &[CdEvent],
+    trigger_events: &[TriggerEvent],
+    metadata: &SensorMetadata,
+    index_interval: Option<u64>,
+) -> Result<(), OutputError> {
+    let file = File::create(path)?;
+    let writer = match index_interval {
+        Some(interval) => BinaryWriter::with_index(file, interval)?,
+        None => BinaryWriter::new(file),
+    };
+    let mut writer = writer.with_event_kinds();
+    writer.write_header(metadata, (cd_events.len() + trigger_events.len()) as u64)?;
+    writer.write_merged(cd_events, trigger_events)?;
+    writer.write_index()?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes CD events to a binary file with an index entry every `interval` events.
+pub fn write_binary_indexed<P: AsRef<Path>>(
+    path: P,
+    events: &[CdEvent],
+    metadata: &SensorMetadata,
+    interval: u64,
+) -> Result<(), OutputError> {
+    let file = File::create(path)?;
+    let mut writer = BinaryWriter::with_index(file, interval)?;
+    writer.write_header(metadata, events.len() as u64)?;
+    writer.write_events(events)?;
+    writer.write_index()?;
+    writer.flush()?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -374,4 +1149,295 @@ mod tests {
         let output_str = String::from_utf8(output).unwrap();
         assert!(output_str.contains("12345,100,200,1"));
     }
+
+    #[test]
+    fn test_csv_writer_space_delimited_without_header() {
+        let mut output = Vec::new();
+        {
+            let options = CsvOptions {
+                delimiter: parse_delimiter("space").unwrap(),
+                geometry_header: false,
+                ..Default::default()
+            };
+            let mut writer = CsvWriter::with_options(&mut output, options);
+            writer
+                .write_header(Some(&SensorMetadata::default()))
+                .unwrap();
+            writer
+                .write_events(&[CdEvent::new(100, 200, 1, 12345)])
+                .unwrap();
+            writer.flush().unwrap();
+        }
+
+        assert_eq!(String::from_utf8(output).unwrap(), "100 200 1 12345\n");
+    }
+
+    #[test]
+    fn test_csv_writer_tsv_column_names() {
+        let mut output = Vec::new();
+        {
+            let options = CsvOptions {
+                field_order: FieldOrder::TXYP,
+                delimiter: parse_delimiter("tab").unwrap(),
+                column_names: true,
+                ..Default::default()
+            };
+            let mut writer = CsvWriter::with_options(&mut output, options);
+            writer.write_header(None).unwrap();
+            writer
+                .write_events(&[CdEvent::new(100, 200, 1, 12345)])
+                .unwrap();
+            writer.flush().unwrap();
+        }
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "timestamp\tx\ty\tpolarity\n12345\t100\t200\t1\n"
+        );
+    }
+
+    #[test]
+    fn test_trigger_csv_per_channel() {
+        assert_eq!(
+            trigger_channel_path(Path::new("out/triggers.csv"), 3),
+            PathBuf::from("out/triggers_ch3.csv")
+        );
+        assert_eq!(
+            trigger_channel_path(Path::new("triggers"), 0),
+            PathBuf::from("triggers_ch0")
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let events = [
+            TriggerEvent::new(1, 2, 10),
+            TriggerEvent::new(1, 0, 20),
+            TriggerEvent::new(0, 2, 30),
+        ];
+        let written = write_trigger_csv_per_channel(
+            dir.path().join("triggers.csv"),
+            &events,
+            &CsvOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            written,
+            vec![
+                (0, dir.path().join("triggers_ch0.csv")),
+                (2, dir.path().join("triggers_ch2.csv")),
+            ]
+        );
+        let ch2 = std::fs::read_to_string(&written[1].1).unwrap();
+        assert_eq!(ch2, "1,2,10\n0,2,30\n");
+    }
+
+    #[test]
+    fn test_csv_writer_merged_triggers() {
+        let mut output = Vec::new();
+        {
+            let options = CsvOptions {
+                field_order: FieldOrder::TXYP,
+                column_names: true,
+                event_kind: true,
+                ..Default::default()
+            };
+            let mut writer = CsvWriter::with_options(&mut output, options);
+            writer.write_header(None).unwrap();
+            writer
+                .write_merged(
+                    &[CdEvent::new(1, 2, 1, 100), CdEvent::new(3, 4, 0, 300)],
+                    &[TriggerEvent::new(1, 5, 200)],
+                )
+                .unwrap();
+            writer.flush().unwrap();
+        }
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "kind,timestamp,x,y,polarity\n0,100,1,2,1\n1,200,5,0,1\n0,300,3,4,0\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_delimiter() {
+        assert_eq!(parse_delimiter("comma").unwrap(), b',');
+        assert_eq!(parse_delimiter("\\t").unwrap(), b'\t');
+        assert_eq!(parse_delimiter(";").unwrap(), b';');
+        assert_eq!(parse_delimiter("|").unwrap(), b'|');
+        assert!(parse_delimiter("ab").is_err());
+    }
+
+    #[test]
+    fn test_timestamp_unit_parsing() {
+        assert_eq!(
+            TimestampUnit::from_str("us").unwrap(),
+            TimestampUnit::Microseconds
+        );
+        assert_eq!(
+            TimestampUnit::from_str("NS").unwrap(),
+            TimestampUnit::Nanoseconds
+        );
+        assert_eq!(
+            TimestampUnit::from_str("ms").unwrap(),
+            TimestampUnit::Milliseconds
+        );
+        assert_eq!(
+            TimestampUnit::from_str("s").unwrap(),
+            TimestampUnit::Seconds
+        );
+        assert!(TimestampUnit::from_str("min").is_err());
+    }
+
+    #[test]
+    fn test_timestamp_unit_formatting() {
+        let t = 1_234_567;
+        assert_eq!(TimestampUnit::Microseconds.format(t).to_string(), "1234567");
+        assert_eq!(
+            TimestampUnit::Nanoseconds.format(t).to_string(),
+            "1234567000"
+        );
+        assert_eq!(
+            TimestampUnit::Milliseconds.format(t).to_string(),
+            "1234.567"
+        );
+        assert_eq!(TimestampUnit::Seconds.format(t).to_string(), "1.234567");
+        assert_eq!(TimestampUnit::Seconds.format(5).to_string(), "0.000005");
+    }
+
+    #[test]
+    fn test_csv_writer_custom_order_with_unit() {
+        let mut output = Vec::new();
+        {
+            let mut writer = CsvWriter::with_timestamp_unit(
+                &mut output,
+                FieldOrder::Custom([2, 3, 0, 1]),
+                TimestampUnit::Seconds,
+            );
+            writer
+                .write_events(&[CdEvent::new(100, 200, 1, 2_500_000)])
+                .unwrap();
+            writer.flush().unwrap();
+        }
+
+        let output_str = String::from_utf8(output).unwrap();
+        assert_eq!(output_str, "1,2.500000,100,200\n");
+    }
+
+    #[test]
+    fn test_jsonl_writer() {
+        let mut buffer = Vec::new();
+        {
+            let mut writer =
+                JsonlWriter::with_timestamp_unit(&mut buffer, TimestampUnit::Milliseconds);
+            writer
+                .write_header(&SensorMetadata {
+                    width: 640,
+                    height: 480,
+                })
+                .unwrap();
+            writer
+                .write_merged(
+                    &[CdEvent::new(100, 50, 1, 1500)],
+                    &[TriggerEvent::new(1, 2, 2000)],
+                )
+                .unwrap();
+            writer.flush().unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "{\"width\":640,\"height\":480}\n\
+             {\"x\":100,\"y\":50,\"p\":1,\"t\":1.500}\n\
+             {\"id\":2,\"value\":1,\"t\":2.000}\n"
+        );
+    }
+
+    #[test]
+    fn test_column_writer() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("events.cols");
+        let events = [
+            CdEvent::new(100, 200, 1, 12345),
+            CdEvent::new(101, 201, 0, 1 << 40),
+        ];
+        write_columns(
+            &out,
+            &events,
+            &SensorMetadata {
+                width: 640,
+                height: 480,
+            },
+        )
+        .unwrap();
+
+        let x = std::fs::read(out.join("x.u16")).unwrap();
+        assert_eq!(x, [100, 0, 101, 0]);
+        let p = std::fs::read(out.join("p.u8")).unwrap();
+        assert_eq!(p, [1, 0]);
+        let t = std::fs::read(out.join("t.u64")).unwrap();
+        assert_eq!(t.len(), 16);
+        assert_eq!(u64::from_le_bytes(t[8..16].try_into().unwrap()), 1 << 40);
+
+        let manifest = std::fs::read_to_string(out.join(ColumnWriter::MANIFEST)).unwrap();
+        assert!(manifest.contains("\"event_count\": 2"));
+        assert!(manifest.contains("\"width\": 640"));
+        assert!(manifest.contains("\"dtype\": \"<u8\""));
+    }
+
+    #[test]
+    fn test_column_writer_columnar() {
+        let dir = tempfile::tempdir().unwrap();
+        let events = [
+            CdEvent::new(100, 200, 1, 12345),
+            CdEvent::new(101, 201, 0, 1 << 40),
+        ];
+        let metadata = SensorMetadata::default();
+        write_columns(dir.path().join("aos"), &events, &metadata).unwrap();
+
+        let mut writer = ColumnWriter::create(dir.path().join("soa")).unwrap();
+        writer
+            .write_columnar(&events.iter().copied().collect())
+            .unwrap();
+        writer.finish(&metadata).unwrap();
+
+        for (_, file, _) in ColumnWriter::COLUMNS {
+            let read = |name: &str| std::fs::read(dir.path().join(name).join(file)).unwrap();
+            assert_eq!(read("soa"), read("aos"), "{}", file);
+        }
+    }
+
+    #[test]
+    fn test_es_writer() {
+        let mut output = Vec::new();
+        {
+            let mut writer = EsWriter::new(&mut output);
+            writer
+                .write_header(&SensorMetadata {
+                    width: 640,
+                    height: 480,
+                })
+                .unwrap();
+            writer
+                .write_events(&[
+                    CdEvent::new(0x102, 3, 1, 10),
+                    CdEvent::new(4, 5, 0, 10 + 127 * 2 + 3),
+                ])
+                .unwrap();
+            writer.flush().unwrap();
+        }
+
+        assert_eq!(&output[..12], b"Event Stream");
+        assert_eq!(&output[12..16], &[2, 0, 0, 1]);
+        assert_eq!(&output[16..20], &[0x80, 0x02, 0xE0, 0x01]);
+        assert_eq!(&output[20..25], &[(10 << 1) | 1, 0x02, 0x01, 3, 0]);
+        assert_eq!(&output[25..27], &[0xFF, 0xFF]);
+        assert_eq!(&output[27..32], &[3 << 1, 4, 0, 5, 0]);
+        assert_eq!(output.len(), 32);
+    }
+
+    #[test]
+    fn test_es_writer_rejects_unsorted() {
+        let mut writer = EsWriter::new(Vec::new());
+        let result = writer.write_events(&[CdEvent::new(0, 0, 0, 10), CdEvent::new(0, 0, 0, 5)]);
+        assert!(result.is_err());
+    }
 }
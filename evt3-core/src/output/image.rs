@@ -0,0 +1,126 @@
+//! PNG image output (requires the `png` feature).
+
+use super::OutputError;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Maps per-pixel counts to 8-bit gray levels on a logarithmic scale.
+///
+/// The busiest pixel maps to 255 and empty pixels to 0. The logarithm keeps
+/// ordinary pixels visible next to hot pixels firing orders of magnitude
+/// more often.
+pub fn log_scale(counts: &[u32]) -> Vec<u8> {
+    let max = counts.iter().copied().max().unwrap_or(0);
+    if max == 0 {
+        return vec![0; counts.len()];
+    }
+    let norm = 255.0 / (max as f64).ln_1p();
+    counts
+        .iter()
+        .map(|&c| ((c as f64).ln_1p() * norm).round() as u8)
+        .collect()
+}
+
+/// Writes an 8-bit image to any writer as PNG.
+fn write_png_to<W: Write>(
+    writer: W,
+    data: &[u8],
+    width: u32,
+    height: u32,
+    color: png::ColorType,
+) -> Result<(), OutputError> {
+    let channels = match color {
+        png::ColorType::Rgb => 3,
+        _ => 1,
+    };
+    if data.len() != width as usize * height as usize * channels {
+        return Err(OutputError::InvalidFormat(format!(
+            "Image data has {} bytes, expected {}x{}x{}",
+            data.len(),
+            width,
+            height,
+            channels
+        )));
+    }
+    let invalid = |e: png::EncodingError| OutputError::InvalidFormat(format!("PNG: {}", e));
+
+    let mut encoder = png::Encoder::new(writer, width, height);
+    encoder.set_color(color);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(invalid)?;
+    writer.write_image_data(data).map_err(invalid)?;
+    writer.finish().map_err(invalid)
+}
+
+/// Writes a row-major 8-bit grayscale image as PNG.
+pub fn write_gray_png<P: AsRef<Path>>(
+    path: P,
+    data: &[u8],
+    width: u32,
+    height: u32,
+) -> Result<(), OutputError> {
+    let file = BufWriter::new(File::create(path)?);
+    write_png_to(file, data, width, height, png::ColorType::Grayscale)
+}
+
+/// Writes a row-major 8-bit RGB image as PNG.
+pub fn write_rgb_png<P: AsRef<Path>>(
+    path: P,
+    data: &[u8],
+    width: u32,
+    height: u32,
+) -> Result<(), OutputError> {
+    let file = BufWriter::new(File::create(path)?);
+    write_png_to(file, data, width, height, png::ColorType::Rgb)
+}
+
+/// Writes per-pixel event counts (e.g. from [`frames::heatmap`]) as a
+/// log-scaled grayscale PNG.
+///
+/// [`frames::heatmap`]: crate::frames::heatmap
+pub fn write_heatmap_png<P: AsRef<Path>>(
+    path: P,
+    counts: &[u32],
+    width: u32,
+    height: u32,
+) -> Result<(), OutputError> {
+    write_gray_png(path, &log_scale(counts), width, height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_scale() {
+        assert_eq!(log_scale(&[0, 0]), vec![0, 0]);
+        let gray = log_scale(&[0, 1, 9, 99]);
+        assert_eq!(gray[0], 0);
+        assert_eq!(gray[3], 255);
+        // 10 events is half way to 100 on a log scale
+        assert!(gray[1] < gray[2] && (127..=128).contains(&gray[2]));
+    }
+
+    #[test]
+    fn test_png_roundtrip() {
+        let mut data = Vec::new();
+        write_png_to(
+            &mut data,
+            &[0, 64, 128, 255],
+            2,
+            2,
+            png::ColorType::Grayscale,
+        )
+        .unwrap();
+
+        let decoder = png::Decoder::new(data.as_slice());
+        let mut reader = decoder.read_info().unwrap();
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer).unwrap();
+        assert_eq!((info.width, info.height), (2, 2));
+        assert_eq!(&buffer[..4], &[0, 64, 128, 255]);
+
+        assert!(write_png_to(Vec::new(), &[0; 3], 2, 2, png::ColorType::Grayscale).is_err());
+    }
+}
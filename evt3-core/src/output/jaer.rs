@@ -0,0 +1,198 @@
+//! jAER AEDAT 2.0 output of CD events.
+//!
+//! AEDAT 2.0 is the native recording format of [jAER](https://github.com/SensorsINI/jaer):
+//! a few `#` comment lines followed by big-endian `(int32 address, int32 timestamp)`
+//! pairs. jAER conventions handled here:
+//! - the address packs x, y and polarity according to the chip class
+//!   (see [`JaerLayout`]); polarity bit set means ON
+//! - y is flipped, because jAER places the origin at the bottom-left
+//! - timestamps are 32-bit microseconds and wrap after ~71 minutes, which
+//!   jAER handles transparently on playback
+
+use super::OutputError;
+use crate::types::{CdEvent, SensorMetadata};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Bit layout of a jAER event address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JaerLayout {
+    /// Bit offset of the x coordinate
+    pub x_shift: u32,
+    /// Number of bits available for x
+    pub x_bits: u32,
+    /// Bit offset of the y coordinate
+    pub y_shift: u32,
+    /// Number of bits available for y
+    pub y_bits: u32,
+    /// Bit offset of the polarity flag
+    pub polarity_shift: u32,
+}
+
+impl JaerLayout {
+    /// DAVIS240/DAVIS346 layout: y at bit 22, x at bit 12, polarity at bit 11.
+    pub const DAVIS: Self = Self {
+        x_shift: 12,
+        x_bits: 10,
+        y_shift: 22,
+        y_bits: 9,
+        polarity_shift: 11,
+    };
+
+    /// DVS128 layout: y at bit 8, x at bit 1, polarity at bit 0.
+    pub const DVS128: Self = Self {
+        x_shift: 1,
+        x_bits: 7,
+        y_shift: 8,
+        y_bits: 7,
+        polarity_shift: 0,
+    };
+
+    /// Returns an error if a sensor of the given size does not fit this layout.
+    fn check_geometry(&self, metadata: &SensorMetadata) -> Result<(), OutputError> {
+        if metadata.width > 1 << self.x_bits || metadata.height > 1 << self.y_bits {
+            return Err(OutputError::InvalidFormat(format!(
+                "Sensor {}x{} does not fit the jAER address layout ({} x bits, {} y bits)",
+                metadata.width, metadata.height, self.x_bits, self.y_bits
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for JaerLayout {
+    fn default() -> Self {
+        Self::DAVIS
+    }
+}
+
+/// Writer for jAER AEDAT 2.0 files.
+pub struct JaerWriter<W: Write> {
+    writer: BufWriter<W>,
+    layout: JaerLayout,
+    width: u32,
+    height: u32,
+}
+
+impl<W: Write> JaerWriter<W> {
+    /// Creates a new writer for a sensor of the given geometry.
+    pub fn new(
+        writer: W,
+        metadata: &SensorMetadata,
+        layout: JaerLayout,
+    ) -> Result<Self, OutputError> {
+        layout.check_geometry(metadata)?;
+        Ok(Self {
+            writer: BufWriter::new(writer),
+            layout,
+            width: metadata.width,
+            height: metadata.height,
+        })
+    }
+
+    /// Writes the AEDAT 2.0 header lines.
+    pub fn write_header(&mut self) -> Result<(), OutputError> {
+        self.writer.write_all(b"#!AER-DAT2.0\r\n")?;
+        self.writer
+            .write_all(b"# This is a raw AE data file - do not edit\r\n")?;
+        self.writer.write_all(
+            b"# Data format is int32 address, int32 timestamp (8 bytes total), repeated for each event\r\n",
+        )?;
+        self.writer.write_all(b"# Timestamps tick is 1 us\r\n")?;
+        write!(
+            self.writer,
+            "# created by evt3-core {} from a {}x{} EVT 3.0 recording\r\n",
+            env!("CARGO_PKG_VERSION"),
+            self.width,
+            self.height
+        )?;
+        Ok(())
+    }
+
+    /// Writes a batch of CD events.
+    pub fn write_events(&mut self, events: &[CdEvent]) -> Result<(), OutputError> {
+        for event in events {
+            if event.x as u32 >= self.width || event.y as u32 >= self.height {
+                return Err(OutputError::InvalidFormat(format!(
+                    "Event at ({}, {}) is outside the {}x{} sensor",
+                    event.x, event.y, self.width, self.height
+                )));
+            }
+            let flipped_y = self.height - 1 - event.y as u32;
+            let address = (flipped_y << self.layout.y_shift)
+                | ((event.x as u32) << self.layout.x_shift)
+                | (((event.polarity & 1) as u32) << self.layout.polarity_shift);
+            self.writer.write_all(&address.to_be_bytes())?;
+            self.writer
+                .write_all(&(event.timestamp as u32).to_be_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Flushes the writer.
+    pub fn flush(&mut self) -> Result<(), OutputError> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes CD events to a jAER AEDAT 2.0 file.
+pub fn write_aedat<P: AsRef<Path>>(
+    path: P,
+    events: &[CdEvent],
+    metadata: &SensorMetadata,
+    layout: JaerLayout,
+) -> Result<(), OutputError> {
+    let mut writer = JaerWriter::new(File::create(path)?, metadata, layout)?;
+    writer.write_header()?;
+    writer.write_events(events)?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jaer_davis_encoding() {
+        let metadata = SensorMetadata {
+            width: 346,
+            height: 260,
+        };
+        let mut output = Vec::new();
+        {
+            let mut writer = JaerWriter::new(&mut output, &metadata, JaerLayout::DAVIS).unwrap();
+            writer.write_header().unwrap();
+            writer
+                .write_events(&[
+                    CdEvent::new(10, 0, 1, 1_000),
+                    CdEvent::new(0, 259, 0, 2_000),
+                ])
+                .unwrap();
+            writer.flush().unwrap();
+        }
+
+        assert!(output.starts_with(b"#!AER-DAT2.0\r\n"));
+        let data_start = output.len() - 16;
+        assert_eq!(output[data_start - 1], b'\n');
+        let word = |i: usize| {
+            u32::from_be_bytes(
+                output[data_start + i * 4..data_start + i * 4 + 4]
+                    .try_into()
+                    .unwrap(),
+            )
+        };
+        // y=0 is flipped to the top row (259)
+        assert_eq!(word(0), (259 << 22) | (10 << 12) | (1 << 11));
+        assert_eq!(word(1), 1_000);
+        assert_eq!(word(2), 0);
+        assert_eq!(word(3), 2_000);
+    }
+
+    #[test]
+    fn test_jaer_rejects_oversized_sensor() {
+        let result = JaerWriter::new(Vec::new(), &SensorMetadata::default(), JaerLayout::DAVIS);
+        assert!(result.is_err());
+    }
+}
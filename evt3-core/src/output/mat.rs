@@ -0,0 +1,606 @@
+//! MATLAB MAT-file output of CD events.
+//!
+//! Writes a version 7.3 MAT-file (what MATLAB's `save -v7.3` produces): an
+//! HDF5 file behind a 512-byte MATLAB header, loadable with `load` in MATLAB
+//! R2006b and later and with `h5py` in Python. The file contains the column
+//! vectors `x` (uint16), `y` (uint16), `p` (uint8) and `t` (uint64,
+//! microseconds), plus the scalars `width` and `height` (double). Sizes and
+//! addresses are 64-bit, so the event count is not limited by the format.
+//!
+//! The HDF5 structure is the simplest one the format allows, written in a
+//! single pass: a version 0 superblock, a root group indexed by one B-tree
+//! leaf and one symbol table node, and one contiguous, uncompressed dataset
+//! per variable carrying the `MATLAB_class` attribute MATLAB reads the type
+//! from. Empty vectors follow MATLAB's convention of storing the dimensions
+//! instead, marked with a `MATLAB_empty` attribute.
+
+use super::OutputError;
+use crate::types::{CdEvent, SensorMetadata};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Size of the MATLAB header in front of the HDF5 data.
+const USERBLOCK_SIZE: u64 = 512;
+
+/// The HDF5 "undefined address".
+const UNDEFINED: u64 = u64::MAX;
+
+/// Size of a version 0 superblock with 8-byte offsets and lengths.
+const SUPERBLOCK_SIZE: u64 = 96;
+
+/// Group B-tree and symbol table node capacities stored in the superblock.
+const GROUP_LEAF_K: u16 = 4;
+const GROUP_INTERNAL_K: u16 = 16;
+
+/// A group B-tree node is always allocated for 2K children and 2K+1 keys.
+const BTREE_SIZE: u64 =
+    24 + (2 * GROUP_INTERNAL_K as u64) * 8 + (2 * GROUP_INTERNAL_K as u64 + 1) * 8;
+
+/// A symbol table node is always allocated for 2K 40-byte entries.
+const SYMBOL_NODE_SIZE: u64 = 8 + 2 * GROUP_LEAF_K as u64 * SYMBOL_ENTRY_SIZE;
+const SYMBOL_ENTRY_SIZE: u64 = 40;
+
+/// Size of a local heap header.
+const HEAP_HEADER_SIZE: u64 = 32;
+
+/// Local heap free list offset meaning "no free block".
+const HEAP_FREE_NONE: u64 = 1;
+
+/// Object header message types.
+const MSG_DATASPACE: u16 = 0x0001;
+const MSG_DATATYPE: u16 = 0x0003;
+const MSG_FILL_VALUE: u16 = 0x0005;
+const MSG_LAYOUT: u16 = 0x0008;
+const MSG_ATTRIBUTE: u16 = 0x000C;
+const MSG_SYMBOL_TABLE: u16 = 0x0011;
+
+/// Rounds a byte count up to the 8-byte boundary HDF5 aligns structures to.
+fn padded(len: u64) -> u64 {
+    (len + 7) & !7
+}
+
+/// Appends zero bytes up to the next 8-byte boundary.
+fn pad(buf: &mut Vec<u8>) {
+    buf.resize(padded(buf.len() as u64) as usize, 0);
+}
+
+/// Element type of a dataset or attribute.
+#[derive(Clone, Copy)]
+enum Datatype {
+    /// Little-endian unsigned integer of the given byte size.
+    Unsigned(u32),
+    /// Little-endian IEEE 754 double.
+    Double,
+    /// Null-terminated ASCII string of the given length.
+    String(u32),
+}
+
+impl Datatype {
+    fn size(self) -> u64 {
+        match self {
+            Datatype::Unsigned(size) | Datatype::String(size) => size as u64,
+            Datatype::Double => 8,
+        }
+    }
+
+    /// Encodes the datatype message.
+    fn encode(self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        match self {
+            Datatype::Unsigned(size) => {
+                // Version 1, class 0 (fixed-point), little-endian, unsigned
+                buf.extend_from_slice(&[0x10, 0, 0, 0]);
+                buf.extend_from_slice(&size.to_le_bytes());
+                buf.extend_from_slice(&0u16.to_le_bytes());
+                buf.extend_from_slice(&(size as u16 * 8).to_le_bytes());
+            }
+            Datatype::Double => {
+                // Version 1, class 1 (floating-point), little-endian,
+                // implied mantissa MSB, sign at bit 63
+                buf.extend_from_slice(&[0x11, 0x20, 63, 0]);
+                buf.extend_from_slice(&8u32.to_le_bytes());
+                buf.extend_from_slice(&0u16.to_le_bytes());
+                buf.extend_from_slice(&64u16.to_le_bytes());
+                buf.extend_from_slice(&[52, 11, 0, 52]);
+                buf.extend_from_slice(&1023u32.to_le_bytes());
+            }
+            Datatype::String(len) => {
+                // Version 1, class 3 (string), null-terminated ASCII
+                buf.extend_from_slice(&[0x13, 0, 0, 0]);
+                buf.extend_from_slice(&len.to_le_bytes());
+            }
+        }
+        buf
+    }
+}
+
+/// Encodes a version 1 dataspace message; no dimensions means a scalar.
+fn dataspace(dims: &[u64]) -> Vec<u8> {
+    let mut buf = vec![1, dims.len() as u8, 0, 0, 0, 0, 0, 0];
+    for dim in dims {
+        buf.extend_from_slice(&dim.to_le_bytes());
+    }
+    buf
+}
+
+/// Encodes a version 1 attribute message with a scalar value.
+fn attribute(name: &str, datatype: Datatype, value: &[u8]) -> Vec<u8> {
+    let datatype = datatype.encode();
+    let space = dataspace(&[]);
+    let mut buf = vec![1, 0];
+    buf.extend_from_slice(&(name.len() as u16 + 1).to_le_bytes());
+    buf.extend_from_slice(&(datatype.len() as u16).to_le_bytes());
+    buf.extend_from_slice(&(space.len() as u16).to_le_bytes());
+    buf.extend_from_slice(name.as_bytes());
+    buf.push(0);
+    pad(&mut buf);
+    buf.extend_from_slice(&datatype);
+    pad(&mut buf);
+    buf.extend_from_slice(&space);
+    pad(&mut buf);
+    buf.extend_from_slice(value);
+    buf
+}
+
+/// Encodes a version 1 object header holding `messages` as
+/// (type, flags, data).
+fn object_header(messages: &[(u16, u8, Vec<u8>)]) -> Vec<u8> {
+    let size: u64 = messages
+        .iter()
+        .map(|(_, _, data)| 8 + padded(data.len() as u64))
+        .sum();
+    let mut buf = vec![1, 0];
+    buf.extend_from_slice(&(messages.len() as u16).to_le_bytes());
+    // Reference count, header size and alignment padding
+    buf.extend_from_slice(&1u32.to_le_bytes());
+    buf.extend_from_slice(&(size as u32).to_le_bytes());
+    buf.extend_from_slice(&[0; 4]);
+    for (kind, flags, data) in messages {
+        buf.extend_from_slice(&kind.to_le_bytes());
+        buf.extend_from_slice(&(padded(data.len() as u64) as u16).to_le_bytes());
+        buf.extend_from_slice(&[*flags, 0, 0, 0]);
+        buf.extend_from_slice(data);
+        pad(&mut buf);
+    }
+    buf
+}
+
+/// Encodes a symbol table entry pointing at an object header.
+fn symbol_entry(buf: &mut Vec<u8>, name_offset: u64, header: u64) {
+    buf.extend_from_slice(&name_offset.to_le_bytes());
+    buf.extend_from_slice(&header.to_le_bytes());
+    // Cache type 0 with an unused scratch pad
+    buf.extend_from_slice(&[0; 24]);
+}
+
+/// Writes the data of a variable.
+type WriteData<'a> = &'a dyn Fn(&mut dyn Write) -> io::Result<()>;
+
+/// A numeric column vector (or scalar) stored as a dataset in the root group.
+struct Variable<'a> {
+    name: &'static str,
+    class: &'static str,
+    datatype: Datatype,
+    rows: u64,
+    write_data: WriteData<'a>,
+}
+
+impl Variable<'_> {
+    /// Size of the stored data; an empty vector stores its two dimensions.
+    fn data_len(&self) -> u64 {
+        if self.rows == 0 {
+            16
+        } else {
+            self.rows * self.datatype.size()
+        }
+    }
+
+    /// Encodes the dataset object header for data stored at `address`.
+    fn header(&self, address: u64) -> Vec<u8> {
+        let class = attribute(
+            "MATLAB_class",
+            Datatype::String(self.class.len() as u32),
+            self.class.as_bytes(),
+        );
+        let mut messages = Vec::new();
+        if self.rows == 0 {
+            messages.push((MSG_DATASPACE, 0, dataspace(&[2])));
+            messages.push((MSG_DATATYPE, 1, Datatype::Unsigned(8).encode()));
+        } else {
+            // HDF5 lists dimensions in reverse MATLAB order: rows x 1 is [1, rows]
+            messages.push((MSG_DATASPACE, 0, dataspace(&[1, self.rows])));
+            messages.push((MSG_DATATYPE, 1, self.datatype.encode()));
+        }
+        // Version 2 fill value: allocated early, never filled, undefined
+        messages.push((MSG_FILL_VALUE, 1, vec![2, 1, 1, 0]));
+        // Version 3 contiguous layout
+        let mut layout = vec![3, 1];
+        layout.extend_from_slice(&address.to_le_bytes());
+        layout.extend_from_slice(&self.data_len().to_le_bytes());
+        messages.push((MSG_LAYOUT, 0, layout));
+        messages.push((MSG_ATTRIBUTE, 0, class));
+        if self.rows == 0 {
+            let empty = attribute("MATLAB_empty", Datatype::Unsigned(1), &[1]);
+            messages.push((MSG_ATTRIBUTE, 0, empty));
+        }
+        object_header(&messages)
+    }
+
+    fn write(&self, writer: &mut dyn Write) -> io::Result<()> {
+        if self.rows == 0 {
+            // MATLAB dimensions of the empty column vector: 0 x 1
+            writer.write_all(&0u64.to_le_bytes())?;
+            writer.write_all(&1u64.to_le_bytes())
+        } else {
+            (self.write_data)(writer)
+        }
+    }
+}
+
+/// Writes the 512-byte MATLAB header that precedes the HDF5 superblock.
+fn write_userblock(writer: &mut dyn Write) -> io::Result<()> {
+    let mut header = [0u8; USERBLOCK_SIZE as usize];
+    header[..116].fill(b' ');
+    let description = concat!(
+        "MATLAB 7.3 MAT-file, created by evt3-core ",
+        env!("CARGO_PKG_VERSION"),
+        ", HDF5 schema 1.00 ."
+    );
+    header[..description.len()].copy_from_slice(description.as_bytes());
+    // Subsystem data offset (unused), version 0x0200 and little-endian "IM"
+    header[116..124].fill(b' ');
+    header[124..126].copy_from_slice(&0x0200u16.to_le_bytes());
+    header[126..128].copy_from_slice(b"IM");
+    writer.write_all(&header)
+}
+
+/// Writes `variables` as a MAT-file.
+///
+/// All addresses are relative to the superblock at [`USERBLOCK_SIZE`]. The
+/// metadata goes first, followed by the data of each variable, so the file is
+/// written front to back without seeking.
+fn write_variables(writer: &mut dyn Write, variables: &[Variable<'_>]) -> Result<(), OutputError> {
+    // Local heap with the variable names; offset 0 is the empty root name
+    // and the symbol table lists (name offset, variable index) sorted by name
+    let mut heap = vec![0u8; 8];
+    let mut order: Vec<usize> = (0..variables.len()).collect();
+    order.sort_by_key(|&i| variables[i].name);
+    let mut sorted = Vec::new();
+    for i in order {
+        sorted.push((heap.len() as u64, i));
+        heap.extend_from_slice(variables[i].name.as_bytes());
+        heap.push(0);
+        pad(&mut heap);
+    }
+    assert!(sorted.len() <= 2 * GROUP_LEAF_K as usize);
+
+    let root_header_addr = SUPERBLOCK_SIZE;
+    let btree_addr = root_header_addr + 40;
+    let heap_addr = btree_addr + BTREE_SIZE;
+    let heap_data_addr = heap_addr + HEAP_HEADER_SIZE;
+    let symbol_node_addr = heap_data_addr + heap.len() as u64;
+
+    // Dataset headers do not change size with their data address
+    let mut header_addrs = Vec::new();
+    let mut addr = symbol_node_addr + SYMBOL_NODE_SIZE;
+    for variable in variables {
+        header_addrs.push(addr);
+        addr += variable.header(0).len() as u64;
+    }
+    let mut data_addrs = Vec::new();
+    for variable in variables {
+        data_addrs.push(addr);
+        addr += padded(variable.data_len());
+    }
+    let end_of_file = addr;
+
+    let mut meta = Vec::new();
+
+    // Superblock version 0 with 8-byte offsets and lengths
+    meta.extend_from_slice(b"\x89HDF\r\n\x1a\n");
+    meta.extend_from_slice(&[0, 0, 0, 0, 0, 8, 8, 0]);
+    meta.extend_from_slice(&GROUP_LEAF_K.to_le_bytes());
+    meta.extend_from_slice(&GROUP_INTERNAL_K.to_le_bytes());
+    meta.extend_from_slice(&0u32.to_le_bytes());
+    meta.extend_from_slice(&USERBLOCK_SIZE.to_le_bytes());
+    meta.extend_from_slice(&UNDEFINED.to_le_bytes());
+    meta.extend_from_slice(&end_of_file.to_le_bytes());
+    meta.extend_from_slice(&UNDEFINED.to_le_bytes());
+    // Root group entry, caching its B-tree and heap addresses
+    meta.extend_from_slice(&0u64.to_le_bytes());
+    meta.extend_from_slice(&root_header_addr.to_le_bytes());
+    meta.extend_from_slice(&1u32.to_le_bytes());
+    meta.extend_from_slice(&0u32.to_le_bytes());
+    meta.extend_from_slice(&btree_addr.to_le_bytes());
+    meta.extend_from_slice(&heap_addr.to_le_bytes());
+
+    // Root group object header
+    let mut symbol_table = btree_addr.to_le_bytes().to_vec();
+    symbol_table.extend_from_slice(&heap_addr.to_le_bytes());
+    meta.extend_from_slice(&object_header(&[(MSG_SYMBOL_TABLE, 0, symbol_table)]));
+
+    // B-tree leaf with the single symbol table node as its only child
+    let btree_start = meta.len();
+    meta.extend_from_slice(b"TREE");
+    meta.extend_from_slice(&[0, 0]);
+    meta.extend_from_slice(&1u16.to_le_bytes());
+    meta.extend_from_slice(&UNDEFINED.to_le_bytes());
+    meta.extend_from_slice(&UNDEFINED.to_le_bytes());
+    let last_name = sorted.last().map_or(0, |&(offset, _)| offset);
+    meta.extend_from_slice(&0u64.to_le_bytes());
+    meta.extend_from_slice(&symbol_node_addr.to_le_bytes());
+    meta.extend_from_slice(&last_name.to_le_bytes());
+    meta.resize(btree_start + BTREE_SIZE as usize, 0);
+
+    // Local heap
+    meta.extend_from_slice(b"HEAP");
+    meta.extend_from_slice(&[0, 0, 0, 0]);
+    meta.extend_from_slice(&(heap.len() as u64).to_le_bytes());
+    meta.extend_from_slice(&HEAP_FREE_NONE.to_le_bytes());
+    meta.extend_from_slice(&heap_data_addr.to_le_bytes());
+    meta.extend_from_slice(&heap);
+
+    // Symbol table node with the entries sorted by name
+    let node_start = meta.len();
+    meta.extend_from_slice(b"SNOD");
+    meta.extend_from_slice(&[1, 0]);
+    meta.extend_from_slice(&(sorted.len() as u16).to_le_bytes());
+    for &(name_offset, i) in &sorted {
+        symbol_entry(&mut meta, name_offset, header_addrs[i]);
+    }
+    meta.resize(node_start + SYMBOL_NODE_SIZE as usize, 0);
+
+    for (variable, &data_addr) in variables.iter().zip(&data_addrs) {
+        meta.extend_from_slice(&variable.header(data_addr));
+    }
+    debug_assert_eq!(
+        meta.len() as u64,
+        data_addrs.first().copied().unwrap_or(end_of_file)
+    );
+
+    write_userblock(writer)?;
+    writer.write_all(&meta)?;
+    for variable in variables {
+        variable.write(writer)?;
+        let len = variable.data_len();
+        writer.write_all(&[0u8; 8][..(padded(len) - len) as usize])?;
+    }
+    Ok(())
+}
+
+/// Writes CD events and sensor geometry to a MATLAB MAT-file.
+pub fn write_mat<P: AsRef<Path>>(
+    path: P,
+    events: &[CdEvent],
+    metadata: &SensorMetadata,
+) -> Result<(), OutputError> {
+    let file = File::create(path)?;
+    write_mat_to(file, events, metadata)
+}
+
+/// Writes a MAT-file to any writer.
+pub fn write_mat_to<W: Write>(
+    writer: W,
+    events: &[CdEvent],
+    metadata: &SensorMetadata,
+) -> Result<(), OutputError> {
+    let rows = events.len() as u64;
+    let width = metadata.width as f64;
+    let height = metadata.height as f64;
+    let x = |w: &mut dyn Write| {
+        events
+            .iter()
+            .try_for_each(|e| w.write_all(&e.x.to_le_bytes()))
+    };
+    let y = |w: &mut dyn Write| {
+        events
+            .iter()
+            .try_for_each(|e| w.write_all(&e.y.to_le_bytes()))
+    };
+    let p = |w: &mut dyn Write| events.iter().try_for_each(|e| w.write_all(&[e.polarity]));
+    let t = |w: &mut dyn Write| {
+        events
+            .iter()
+            .try_for_each(|e| w.write_all(&e.timestamp.to_le_bytes()))
+    };
+    let width_data = |w: &mut dyn Write| w.write_all(&width.to_le_bytes());
+    let height_data = |w: &mut dyn Write| w.write_all(&height.to_le_bytes());
+
+    let column = |name, class, datatype, write_data| Variable {
+        name,
+        class,
+        datatype,
+        rows,
+        write_data,
+    };
+    let scalar = |name, write_data| Variable {
+        name,
+        class: "double",
+        datatype: Datatype::Double,
+        rows: 1,
+        write_data,
+    };
+    let variables = [
+        column("x", "uint16", Datatype::Unsigned(2), &x as WriteData<'_>),
+        column("y", "uint16", Datatype::Unsigned(2), &y),
+        column("p", "uint8", Datatype::Unsigned(1), &p),
+        column("t", "uint64", Datatype::Unsigned(8), &t),
+        scalar("width", &width_data as WriteData<'_>),
+        scalar("height", &height_data),
+    ];
+
+    let mut writer = BufWriter::new(writer);
+    write_variables(&mut writer, &variables)?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u16_at(data: &[u8], pos: u64) -> u16 {
+        let pos = pos as usize;
+        u16::from_le_bytes(data[pos..pos + 2].try_into().unwrap())
+    }
+
+    fn u64_at(data: &[u8], pos: u64) -> u64 {
+        let pos = pos as usize;
+        u64::from_le_bytes(data[pos..pos + 8].try_into().unwrap())
+    }
+
+    fn c_str(data: &[u8], pos: u64) -> String {
+        let rest = &data[pos as usize..];
+        let len = rest.iter().position(|&b| b == 0).unwrap();
+        String::from_utf8(rest[..len].to_vec()).unwrap()
+    }
+
+    /// A dataset read back by following the HDF5 structures.
+    struct Dataset {
+        name: String,
+        dims: Vec<u64>,
+        element_size: u32,
+        attributes: Vec<(String, Vec<u8>)>,
+        data: Vec<u8>,
+    }
+
+    /// Reads the datasets of the root group, checking the structures on the way.
+    fn read_datasets(file: &[u8]) -> Vec<Dataset> {
+        let base = USERBLOCK_SIZE;
+        assert_eq!(
+            &file[base as usize..base as usize + 8],
+            b"\x89HDF\r\n\x1a\n"
+        );
+        assert_eq!(u64_at(file, base + 24), base);
+        assert_eq!(base + u64_at(file, base + 40), file.len() as u64);
+
+        // Root entry scratch pad -> B-tree -> symbol table node
+        let btree = base + u64_at(file, base + 80);
+        let heap = base + u64_at(file, base + 88);
+        assert_eq!(&file[btree as usize..btree as usize + 4], b"TREE");
+        assert_eq!(u16_at(file, btree + 6), 1);
+        let node = base + u64_at(file, btree + 32);
+        assert_eq!(&file[heap as usize..heap as usize + 4], b"HEAP");
+        let heap_data = base + u64_at(file, heap + 24);
+        assert_eq!(&file[node as usize..node as usize + 4], b"SNOD");
+
+        let count = u16_at(file, node + 6) as u64;
+        (0..count)
+            .map(|i| {
+                let entry = node + 8 + i * SYMBOL_ENTRY_SIZE;
+                let name = c_str(file, heap_data + u64_at(file, entry));
+                let header = base + u64_at(file, entry + 8);
+                assert_eq!(file[header as usize], 1);
+
+                let mut dataset = Dataset {
+                    name,
+                    dims: Vec::new(),
+                    element_size: 0,
+                    attributes: Vec::new(),
+                    data: Vec::new(),
+                };
+                let mut pos = header + 16;
+                for _ in 0..u16_at(file, header + 2) {
+                    let kind = u16_at(file, pos);
+                    let size = u16_at(file, pos + 2) as u64;
+                    assert_eq!(size % 8, 0);
+                    let msg = pos + 8;
+                    match kind {
+                        MSG_DATASPACE => {
+                            let rank = file[msg as usize + 1] as u64;
+                            dataset.dims =
+                                (0..rank).map(|d| u64_at(file, msg + 8 + d * 8)).collect();
+                        }
+                        MSG_DATATYPE => {
+                            dataset.element_size = u16_at(file, msg + 4) as u32;
+                        }
+                        MSG_LAYOUT => {
+                            let addr = base + u64_at(file, msg + 2);
+                            let len = u64_at(file, msg + 10);
+                            dataset.data = file[addr as usize..(addr + len) as usize].to_vec();
+                        }
+                        MSG_ATTRIBUTE => {
+                            let name_len = u16_at(file, msg + 2) as u64;
+                            let type_len = u16_at(file, msg + 4) as u64;
+                            let space_len = u16_at(file, msg + 6) as u64;
+                            let value_size = u16_at(file, msg + 8 + padded(name_len) + 4) as u64;
+                            let value =
+                                msg + 8 + padded(name_len) + padded(type_len) + padded(space_len);
+                            dataset.attributes.push((
+                                c_str(file, msg + 8),
+                                file[value as usize..(value + value_size) as usize].to_vec(),
+                            ));
+                        }
+                        _ => {}
+                    }
+                    pos = msg + size;
+                }
+                dataset
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_mat_layout() {
+        let mut output = Vec::new();
+        let events = [
+            CdEvent::new(1, 2, 1, 100),
+            CdEvent::new(3, 4, 0, 200),
+            CdEvent::new(5, 6, 1, 1 << 40),
+        ];
+        let metadata = SensorMetadata::default();
+        write_mat_to(&mut output, &events, &metadata).unwrap();
+
+        assert!(output.starts_with(b"MATLAB 7.3 MAT-file"));
+        assert_eq!(&output[124..128], &[0x00, 0x02, b'I', b'M']);
+
+        let datasets = read_datasets(&output);
+        let names: Vec<&str> = datasets.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, ["height", "p", "t", "width", "x", "y"]);
+
+        let get = |name: &str| datasets.iter().find(|d| d.name == name).unwrap();
+        let class = |name: &str| get(name).attributes[0].clone();
+
+        let x = get("x");
+        assert_eq!(x.dims, [1, 3]);
+        assert_eq!(x.element_size, 2);
+        assert_eq!(x.data, [1, 0, 3, 0, 5, 0]);
+        assert_eq!(class("x"), ("MATLAB_class".to_string(), b"uint16".to_vec()));
+
+        assert_eq!(get("p").data, [1, 0, 1]);
+        assert_eq!(class("p").1, b"uint8");
+        let t = get("t");
+        assert_eq!(t.element_size, 8);
+        assert_eq!(u64_at(&t.data, 16), 1 << 40);
+        assert_eq!(class("t").1, b"uint64");
+
+        let width = get("width");
+        assert_eq!(width.dims, [1, 1]);
+        assert_eq!(
+            f64::from_le_bytes(width.data[..].try_into().unwrap()),
+            metadata.width as f64
+        );
+        assert_eq!(class("height").1, b"double");
+    }
+
+    #[test]
+    fn test_empty_mat() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.mat");
+        write_mat(&path, &[], &SensorMetadata::default()).unwrap();
+        let output = std::fs::read(&path).unwrap();
+
+        let datasets = read_datasets(&output);
+        let x = datasets.iter().find(|d| d.name == "x").unwrap();
+        assert_eq!(x.dims, [2]);
+        assert_eq!(x.element_size, 8);
+        assert_eq!(u64_at(&x.data, 0), 0);
+        assert_eq!(u64_at(&x.data, 8), 1);
+        assert_eq!(x.attributes[1], ("MATLAB_empty".to_string(), vec![1]));
+
+        // Scalars are never empty
+        let width = datasets.iter().find(|d| d.name == "width").unwrap();
+        assert_eq!(width.attributes.len(), 1);
+    }
+}
@@ -0,0 +1,254 @@
+//! EVT 3.0 raw output.
+//!
+//! Re-encodes decoded events as EVT 3.0 words so they can be read back by
+//! [`Evt3Decoder`](crate::decoder::Evt3Decoder) and the Metavision SDK. Each
+//! CD event becomes an `EVT_ADDR_X` word; `EVT_ADDR_Y`, `EVT_TIME_HIGH` and
+//! `EVT_TIME_LOW` words are only emitted when the value changes. Vectorized
+//! words are not used, so files are somewhat larger than camera recordings.
+//!
+//! Timestamps beyond the 24-bit EVT 3.0 time range are kept by emitting a
+//! `TIME_HIGH` wrap-around for each elapsed time loop, which the decoder
+//! counts like the wrap-arounds of a long recording.
+
+use super::OutputError;
+use crate::types::{self, CdEvent, Event, SensorMetadata, TriggerEvent};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Duration of one EVT 3.0 time loop in microseconds (24-bit time).
+const TIME_LOOP: u64 = 1 << 24;
+
+/// Largest coordinate that fits the 11-bit address fields.
+const MAX_ADDRESS: u16 = (1 << 11) - 1;
+
+/// Largest trigger channel ID that fits the 4-bit ID field.
+const MAX_TRIGGER_ID: u8 = 0xF;
+
+/// Writer for EVT 3.0 raw files.
+///
+/// Events must be written in timestamp order; an event earlier than the
+/// previous one is rejected.
+pub struct Evt3Writer<W: Write> {
+    writer: BufWriter<W>,
+    last_timestamp: Option<u64>,
+    loops: Option<u64>,
+    time_high: Option<u16>,
+    time_low: Option<u16>,
+    y: Option<u16>,
+}
+
+impl<W: Write> Evt3Writer<W> {
+    /// Creates a new writer.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            last_timestamp: None,
+            loops: None,
+            time_high: None,
+            time_low: None,
+            y: None,
+        }
+    }
+
+    /// Writes the text header with the sensor geometry.
+    pub fn write_header(&mut self, metadata: &SensorMetadata) -> Result<(), OutputError> {
+        writeln!(self.writer, "% evt 3.0")?;
+        writeln!(
+            self.writer,
+            "% format EVT3;width={};height={}",
+            metadata.width, metadata.height
+        )?;
+        writeln!(self.writer, "% end")?;
+        Ok(())
+    }
+
+    /// Writes a batch of CD events.
+    pub fn write_events(&mut self, events: &[CdEvent]) -> Result<(), OutputError> {
+        for event in events {
+            self.write_cd(event)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a batch of trigger events.
+    pub fn write_triggers(&mut self, events: &[TriggerEvent]) -> Result<(), OutputError> {
+        for event in events {
+            self.write_trigger(event)?;
+        }
+        Ok(())
+    }
+
+    /// Writes CD and trigger events interleaved in timestamp order.
+    ///
+    /// Both inputs must be sorted by timestamp.
+    pub fn write_merged(
+        &mut self,
+        cd_events: &[CdEvent],
+        trigger_events: &[TriggerEvent],
+    ) -> Result<(), OutputError> {
+        for event in types::merge_by_time(cd_events, trigger_events) {
+            match event {
+                Event::Cd(event) => self.write_cd(&event)?,
+                Event::Trigger(event) => self.write_trigger(&event)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Flushes the writer.
+    pub fn flush(&mut self) -> Result<(), OutputError> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    fn write_cd(&mut self, event: &CdEvent) -> Result<(), OutputError> {
+        if event.x > MAX_ADDRESS || event.y > MAX_ADDRESS {
+            return Err(OutputError::InvalidFormat(format!(
+                "Event at ({}, {}) does not fit the 11-bit EVT 3.0 address fields",
+                event.x, event.y
+            )));
+        }
+        self.write_time(event.timestamp)?;
+        if self.y != Some(event.y) {
+            self.write_word(event.y)?; // EVT_ADDR_Y, system type 0
+            self.y = Some(event.y);
+        }
+        self.write_word(0x2000 | ((event.polarity as u16 & 1) << 11) | event.x)
+    }
+
+    fn write_trigger(&mut self, event: &TriggerEvent) -> Result<(), OutputError> {
+        if event.id > MAX_TRIGGER_ID {
+            return Err(OutputError::InvalidFormat(format!(
+                "Trigger channel {} does not fit the 4-bit EVT 3.0 trigger ID",
+                event.id
+            )));
+        }
+        self.write_time(event.timestamp)?;
+        self.write_word(0xA000 | ((event.id as u16) << 8) | (event.value as u16 & 1))
+    }
+
+    /// Emits the time words needed to move the decoder to `timestamp`.
+    fn write_time(&mut self, timestamp: u64) -> Result<(), OutputError> {
+        if let Some(last) = self.last_timestamp.filter(|&last| timestamp < last) {
+            return Err(OutputError::InvalidFormat(format!(
+                "Event at {} us is out of timestamp order (after {} us)",
+                timestamp, last
+            )));
+        }
+        self.last_timestamp = Some(timestamp);
+
+        let loops = timestamp / TIME_LOOP;
+        let high = ((timestamp % TIME_LOOP) >> 12) as u16;
+        let low = (timestamp & 0xFFF) as u16;
+
+        let current_loops = self.loops.unwrap_or(0);
+        if loops > current_loops || self.loops.is_none() {
+            // Each drop from the last to the first TIME_HIGH value is one loop
+            for _ in current_loops..loops {
+                self.write_word(0x8FFF)?;
+                self.write_word(0x8000)?;
+            }
+            self.loops = Some(loops);
+            self.time_high = None;
+        }
+        if self.time_high != Some(high) {
+            self.write_word(0x8000 | high)?;
+            self.time_high = Some(high);
+            self.time_low = None;
+        }
+        if self.time_low != Some(low) {
+            self.write_word(0x6000 | low)?;
+            self.time_low = Some(low);
+        }
+        Ok(())
+    }
+
+    fn write_word(&mut self, word: u16) -> Result<(), OutputError> {
+        self.writer.write_all(&word.to_le_bytes())?;
+        Ok(())
+    }
+}
+
+/// Writes CD and trigger events to an EVT 3.0 raw file.
+///
+/// Both inputs must be sorted by timestamp.
+pub fn write_raw<P: AsRef<Path>>(
+    path: P,
+    cd_events: &[CdEvent],
+    trigger_events: &[TriggerEvent],
+    metadata: &SensorMetadata,
+) -> Result<(), OutputError> {
+    let mut writer = Evt3Writer::new(File::create(path)?);
+    writer.write_header(metadata)?;
+    writer.write_merged(cd_events, trigger_events)?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::Evt3Decoder;
+
+    #[test]
+    fn test_raw_round_trip() {
+        // Spans two 24-bit time loops
+        let cd_events: Vec<CdEvent> = (0..100u64)
+            .map(|i| {
+                CdEvent::new(
+                    (i * 7 % 640) as u16,
+                    (i % 3) as u16,
+                    (i % 2) as u8,
+                    i * 400_000,
+                )
+            })
+            .collect();
+        let trigger_events = vec![
+            TriggerEvent::new(1, 0, 1_000),
+            TriggerEvent::new(0, 3, 20_000_000),
+        ];
+        let metadata = SensorMetadata {
+            width: 640,
+            height: 480,
+        };
+        let file = tempfile::NamedTempFile::new().unwrap();
+        write_raw(file.path(), &cd_events, &trigger_events, &metadata).unwrap();
+
+        let result = Evt3Decoder::new().decode_file(file.path()).unwrap();
+        assert_eq!(result.metadata, metadata);
+        assert_eq!(result.cd_events, cd_events);
+        assert_eq!(result.trigger_events, trigger_events);
+    }
+
+    #[test]
+    fn test_raw_rejects_out_of_range() {
+        let mut writer = Evt3Writer::new(Vec::new());
+        assert!(writer.write_events(&[CdEvent::new(2048, 0, 1, 0)]).is_err());
+        assert!(writer
+            .write_triggers(&[TriggerEvent::new(1, 16, 0)])
+            .is_err());
+        writer
+            .write_events(&[CdEvent::new(0, 0, 1, TIME_LOOP)])
+            .unwrap();
+        assert!(writer.write_events(&[CdEvent::new(0, 0, 1, 0)]).is_err());
+    }
+
+    #[test]
+    fn test_raw_rejects_unsorted() {
+        let mut writer = Evt3Writer::new(Vec::new());
+        writer
+            .write_events(&[CdEvent::new(0, 0, 1, 5000), CdEvent::new(1, 0, 1, 5000)])
+            .unwrap();
+        // Within the same time loop and TIME_HIGH value
+        let err = writer
+            .write_events(&[CdEvent::new(0, 0, 1, 4999)])
+            .unwrap_err();
+        assert!(err.to_string().contains("out of timestamp order"));
+        assert!(writer
+            .write_triggers(&[TriggerEvent::new(1, 0, 10)])
+            .is_err());
+        writer
+            .write_triggers(&[TriggerEvent::new(1, 0, 5000)])
+            .unwrap();
+    }
+}
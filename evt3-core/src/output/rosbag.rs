@@ -0,0 +1,386 @@
+//! rosbag2 export of CD events as `dvs_msgs/msg/EventArray` messages.
+//!
+//! Events are grouped into packets of a configurable duration, serialized
+//! with ROS 2 CDR encoding and stored in an [MCAP](https://mcap.dev) file,
+//! which is the default rosbag2 storage format since ROS 2 Iron and can be
+//! played back with `ros2 bag play recording.mcap`.
+
+use super::OutputError;
+use crate::types::{CdEvent, SensorMetadata};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// MCAP file magic, written at the start and end of the file.
+const MCAP_MAGIC: &[u8; 8] = b"\x89MCAP0\r\n";
+
+/// MCAP record opcodes.
+const OP_HEADER: u8 = 0x01;
+const OP_FOOTER: u8 = 0x02;
+const OP_SCHEMA: u8 = 0x03;
+const OP_CHANNEL: u8 = 0x04;
+const OP_MESSAGE: u8 = 0x05;
+const OP_DATA_END: u8 = 0x0F;
+
+const SCHEMA_ID: u16 = 1;
+const CHANNEL_ID: u16 = 1;
+
+/// Message definition of `dvs_msgs/msg/EventArray` with its dependencies.
+const EVENT_ARRAY_DEFINITION: &str = "\
+std_msgs/Header header
+uint32 height
+uint32 width
+Event[] events
+================================================================================
+MSG: dvs_msgs/Event
+uint16 x
+uint16 y
+builtin_interfaces/Time ts
+bool polarity
+================================================================================
+MSG: std_msgs/Header
+builtin_interfaces/Time stamp
+string frame_id
+================================================================================
+MSG: builtin_interfaces/Time
+int32 sec
+uint32 nanosec
+";
+
+/// Options for rosbag2 export.
+#[derive(Debug, Clone)]
+pub struct RosbagOptions {
+    /// Topic the event arrays are published on
+    pub topic: String,
+    /// `frame_id` stored in each message header
+    pub frame_id: String,
+    /// Duration of one `EventArray` packet in microseconds
+    pub packet_duration_us: u64,
+}
+
+impl Default for RosbagOptions {
+    fn default() -> Self {
+        Self {
+            topic: "/dvs/events".to_string(),
+            frame_id: "camera".to_string(),
+            packet_duration_us: 10_000,
+        }
+    }
+}
+
+/// Writer for rosbag2 (MCAP) files containing `dvs_msgs/msg/EventArray` messages.
+///
+/// Events are buffered until a packet's duration has elapsed, then emitted
+/// as one message stamped with the packet's start time. Call
+/// [`finish`](Self::finish) to emit the last packet and close the file.
+pub struct RosbagWriter<W: Write> {
+    writer: BufWriter<W>,
+    options: RosbagOptions,
+    width: u32,
+    height: u32,
+    packet: Vec<CdEvent>,
+    packet_start: u64,
+    sequence: u32,
+}
+
+impl<W: Write> RosbagWriter<W> {
+    /// Creates a new writer and writes the MCAP header, schema and channel.
+    pub fn new(
+        writer: W,
+        metadata: &SensorMetadata,
+        options: RosbagOptions,
+    ) -> Result<Self, OutputError> {
+        if options.packet_duration_us == 0 {
+            return Err(OutputError::InvalidFormat(
+                "Packet duration must be greater than zero".to_string(),
+            ));
+        }
+
+        let mut this = Self {
+            writer: BufWriter::new(writer),
+            options,
+            width: metadata.width,
+            height: metadata.height,
+            packet: Vec::new(),
+            packet_start: 0,
+            sequence: 0,
+        };
+        this.write_preamble()?;
+        Ok(this)
+    }
+
+    /// Writes the file magic, header, schema and channel records.
+    fn write_preamble(&mut self) -> Result<(), OutputError> {
+        self.writer.write_all(MCAP_MAGIC)?;
+
+        let mut header = Vec::new();
+        put_string(&mut header, "ros2");
+        put_string(
+            &mut header,
+            concat!("evt3-core ", env!("CARGO_PKG_VERSION")),
+        );
+        self.write_record(OP_HEADER, &header)?;
+
+        let mut schema = Vec::new();
+        schema.extend_from_slice(&SCHEMA_ID.to_le_bytes());
+        put_string(&mut schema, "dvs_msgs/msg/EventArray");
+        put_string(&mut schema, "ros2msg");
+        put_string(&mut schema, EVENT_ARRAY_DEFINITION);
+        self.write_record(OP_SCHEMA, &schema)?;
+
+        let mut channel = Vec::new();
+        channel.extend_from_slice(&CHANNEL_ID.to_le_bytes());
+        channel.extend_from_slice(&SCHEMA_ID.to_le_bytes());
+        put_string(&mut channel, &self.options.topic);
+        put_string(&mut channel, "cdr");
+        // Empty metadata map
+        channel.extend_from_slice(&0u32.to_le_bytes());
+        self.write_record(OP_CHANNEL, &channel)?;
+        Ok(())
+    }
+
+    /// Writes a single MCAP record.
+    fn write_record(&mut self, opcode: u8, content: &[u8]) -> Result<(), OutputError> {
+        self.writer.write_all(&[opcode])?;
+        self.writer
+            .write_all(&(content.len() as u64).to_le_bytes())?;
+        self.writer.write_all(content)?;
+        Ok(())
+    }
+
+    /// Writes a batch of CD events, emitting a message for every completed packet.
+    ///
+    /// Events must be in non-decreasing timestamp order.
+    pub fn write_events(&mut self, events: &[CdEvent]) -> Result<(), OutputError> {
+        for event in events {
+            if self.packet.is_empty() {
+                self.packet_start = event.timestamp;
+            } else if event.timestamp >= self.packet_start + self.options.packet_duration_us {
+                self.flush_packet()?;
+                self.packet_start = event.timestamp;
+            }
+            self.packet.push(*event);
+        }
+        Ok(())
+    }
+
+    /// Serializes the buffered packet as an `EventArray` message.
+    fn flush_packet(&mut self) -> Result<(), OutputError> {
+        if self.packet.is_empty() {
+            return Ok(());
+        }
+
+        let mut cdr = CdrBuffer::new();
+        // std_msgs/Header
+        cdr.put_time(self.packet_start);
+        cdr.put_string(&self.options.frame_id);
+        cdr.put_u32(self.height);
+        cdr.put_u32(self.width);
+        // Event[] events
+        cdr.put_u32(self.packet.len() as u32);
+        for event in &self.packet {
+            cdr.put_u16(event.x);
+            cdr.put_u16(event.y);
+            cdr.put_time(event.timestamp);
+            cdr.put_u8(event.polarity & 1);
+        }
+
+        let log_time = self.packet_start * 1_000;
+        let mut message = Vec::with_capacity(22 + cdr.buf.len());
+        message.extend_from_slice(&CHANNEL_ID.to_le_bytes());
+        message.extend_from_slice(&self.sequence.to_le_bytes());
+        message.extend_from_slice(&log_time.to_le_bytes());
+        message.extend_from_slice(&log_time.to_le_bytes());
+        message.extend_from_slice(&cdr.buf);
+        self.write_record(OP_MESSAGE, &message)?;
+
+        self.sequence = self.sequence.wrapping_add(1);
+        self.packet.clear();
+        Ok(())
+    }
+
+    /// Emits the final packet and writes the MCAP footer.
+    pub fn finish(mut self) -> Result<(), OutputError> {
+        self.flush_packet()?;
+
+        // Data section CRC of zero means "not computed"
+        self.write_record(OP_DATA_END, &0u32.to_le_bytes())?;
+
+        // No summary section: summary_start, summary_offset_start, summary_crc
+        let mut footer = Vec::with_capacity(20);
+        footer.extend_from_slice(&0u64.to_le_bytes());
+        footer.extend_from_slice(&0u64.to_le_bytes());
+        footer.extend_from_slice(&0u32.to_le_bytes());
+        self.write_record(OP_FOOTER, &footer)?;
+
+        self.writer.write_all(MCAP_MAGIC)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Appends an MCAP string (u32 length prefix, no terminator).
+fn put_string(buf: &mut Vec<u8>, s: &str) {
+    buf.extend_from_slice(&(s.len() as u32).to_le_bytes());
+    buf.extend_from_slice(s.as_bytes());
+}
+
+/// Little-endian CDR serialization buffer (XCDR1, as used by ROS 2).
+struct CdrBuffer {
+    buf: Vec<u8>,
+}
+
+impl CdrBuffer {
+    /// Length of the encapsulation header, which alignment is relative to.
+    const ENCAPSULATION_LEN: usize = 4;
+
+    fn new() -> Self {
+        // Encapsulation kind CDR_LE, options zero
+        Self {
+            buf: vec![0x00, 0x01, 0x00, 0x00],
+        }
+    }
+
+    fn align(&mut self, alignment: usize) {
+        let offset = self.buf.len() - Self::ENCAPSULATION_LEN;
+        let padding = (alignment - offset % alignment) % alignment;
+        self.buf.resize(self.buf.len() + padding, 0);
+    }
+
+    fn put_u8(&mut self, value: u8) {
+        self.buf.push(value);
+    }
+
+    fn put_u16(&mut self, value: u16) {
+        self.align(2);
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn put_u32(&mut self, value: u32) {
+        self.align(4);
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    /// Writes a `builtin_interfaces/Time` from a microsecond timestamp.
+    fn put_time(&mut self, timestamp_us: u64) {
+        let sec = (timestamp_us / 1_000_000) as i32;
+        let nanosec = ((timestamp_us % 1_000_000) * 1_000) as u32;
+        self.put_u32(sec as u32);
+        self.put_u32(nanosec);
+    }
+
+    /// Writes a CDR string (length including the null terminator).
+    fn put_string(&mut self, s: &str) {
+        self.put_u32(s.len() as u32 + 1);
+        self.buf.extend_from_slice(s.as_bytes());
+        self.buf.push(0);
+    }
+}
+
+/// Writes CD events to a rosbag2 MCAP file.
+pub fn write_rosbag<P: AsRef<Path>>(
+    path: P,
+    events: &[CdEvent],
+    metadata: &SensorMetadata,
+    options: RosbagOptions,
+) -> Result<(), OutputError> {
+    let file = File::create(path)?;
+    let mut writer = RosbagWriter::new(file, metadata, options)?;
+    writer.write_events(events)?;
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Splits an MCAP byte stream into (opcode, content) records.
+    fn parse_records(data: &[u8]) -> Vec<(u8, &[u8])> {
+        assert_eq!(&data[..8], MCAP_MAGIC);
+        assert_eq!(&data[data.len() - 8..], MCAP_MAGIC);
+        let mut records = Vec::new();
+        let mut pos = 8;
+        while pos < data.len() - 8 {
+            let opcode = data[pos];
+            let len = u64::from_le_bytes(data[pos + 1..pos + 9].try_into().unwrap()) as usize;
+            records.push((opcode, &data[pos + 9..pos + 9 + len]));
+            pos += 9 + len;
+        }
+        assert_eq!(pos, data.len() - 8);
+        records
+    }
+
+    #[test]
+    fn test_rosbag_packets() {
+        let mut output = Vec::new();
+        let options = RosbagOptions {
+            packet_duration_us: 1_000,
+            ..Default::default()
+        };
+        let metadata = SensorMetadata {
+            width: 640,
+            height: 480,
+        };
+        let mut writer = RosbagWriter::new(&mut output, &metadata, options).unwrap();
+        writer
+            .write_events(&[
+                CdEvent::new(1, 2, 1, 1_000_000),
+                CdEvent::new(3, 4, 0, 1_000_500),
+                CdEvent::new(5, 6, 1, 1_001_000),
+            ])
+            .unwrap();
+        writer.finish().unwrap();
+
+        let records = parse_records(&output);
+        let opcodes: Vec<u8> = records.iter().map(|r| r.0).collect();
+        assert_eq!(
+            opcodes,
+            vec![
+                OP_HEADER,
+                OP_SCHEMA,
+                OP_CHANNEL,
+                OP_MESSAGE,
+                OP_MESSAGE,
+                OP_DATA_END,
+                OP_FOOTER
+            ]
+        );
+
+        // First message: two events, stamped at the packet start
+        let message = records[3].1;
+        let log_time = u64::from_le_bytes(message[6..14].try_into().unwrap());
+        assert_eq!(log_time, 1_000_000_000);
+        let cdr = &message[22..];
+        assert_eq!(&cdr[..4], &[0x00, 0x01, 0x00, 0x00]);
+        // stamp.sec, stamp.nanosec
+        assert_eq!(u32::from_le_bytes(cdr[4..8].try_into().unwrap()), 1);
+        assert_eq!(u32::from_le_bytes(cdr[8..12].try_into().unwrap()), 0);
+        // frame_id "camera" with terminator, padded to 4 bytes
+        assert_eq!(u32::from_le_bytes(cdr[12..16].try_into().unwrap()), 7);
+        assert_eq!(&cdr[16..23], b"camera\0");
+        // height, width, event count
+        assert_eq!(u32::from_le_bytes(cdr[24..28].try_into().unwrap()), 480);
+        assert_eq!(u32::from_le_bytes(cdr[28..32].try_into().unwrap()), 640);
+        assert_eq!(u32::from_le_bytes(cdr[32..36].try_into().unwrap()), 2);
+        // First event: x, y, ts.sec, ts.nanosec, polarity
+        assert_eq!(&cdr[36..40], &[1, 0, 2, 0]);
+        assert_eq!(u32::from_le_bytes(cdr[40..44].try_into().unwrap()), 1);
+        assert_eq!(cdr[48], 1);
+        // Second event starts 2-byte aligned after the polarity byte
+        assert_eq!(&cdr[50..54], &[3, 0, 4, 0]);
+        assert_eq!(u32::from_le_bytes(cdr[60..64].try_into().unwrap()), 500_000);
+
+        // Second message holds the remaining event
+        let cdr = &records[4].1[22..];
+        assert_eq!(u32::from_le_bytes(cdr[32..36].try_into().unwrap()), 1);
+    }
+
+    #[test]
+    fn test_rosbag_rejects_zero_duration() {
+        let options = RosbagOptions {
+            packet_duration_us: 0,
+            ..Default::default()
+        };
+        assert!(RosbagWriter::new(Vec::new(), &SensorMetadata::default(), options).is_err());
+    }
+}
@@ -0,0 +1,421 @@
+//! Chunk-parallel decoding with rayon.
+//!
+//! EVT 3.0 words depend on the state left by the words before them, so a
+//! chunk of a stream cannot in general be decoded on its own. Once a chunk
+//! has seen a TIME_HIGH, a TIME_LOW, an ADDR_Y and a VECT_BASE_X, however,
+//! its remaining words decode to the same events from any starting state,
+//! up to the number of timestamp wrap-arounds before the chunk.
+//!
+//! The data is therefore split at TIME_HIGH words into chunks that are
+//! decoded in parallel by fresh decoders. The chunks are then stitched in
+//! order: the few words before each chunk's safe point are decoded again
+//! from the true state at the chunk start, and the difference between the
+//! true and the chunk's own time base at the safe point is added to the
+//! rest of the chunk's timestamps. The output is identical to the serial
+//! decoder's.
+
+use crate::buffers::EventBuffers;
+use crate::decoder::{le_words, DecodeError, DecoderState, Evt3Decoder};
+use crate::parser;
+use crate::types::{CdEvent, DecodeResult, RawEventType, SensorMetadata, TriggerEvent};
+use rayon::prelude::*;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::ops::Range;
+use std::path::Path;
+
+/// Target number of words per chunk; each chunk starts at the first
+/// TIME_HIGH at or after its target position.
+const CHUNK_WORDS: usize = 1 << 20;
+
+/// A chunk decoded by a fresh decoder, without the state before it.
+struct DecodedChunk {
+    /// Number of words after which the chunk's decoding no longer depends
+    /// on the state before it, up to the time base
+    safe: usize,
+    /// Decoder state after the first `safe` words
+    safe_state: DecoderState,
+    /// Decoder state at the end of the chunk
+    end_state: DecoderState,
+    /// Events decoded after the first `safe` words
+    cd_events: Vec<CdEvent>,
+    trigger_events: Vec<TriggerEvent>,
+}
+
+impl Evt3Decoder {
+    /// Decodes a buffer of raw little-endian bytes like
+    /// [`decode_bytes`](Self::decode_bytes), with chunks of it decoded in
+    /// parallel on the current rayon thread pool.
+    ///
+    /// The events, their order and the decoder state afterwards are the
+    /// same as with `decode_bytes`. Filters and transforms run over all new
+    /// events once the chunks have been stitched together. Run this inside
+    /// [`rayon::ThreadPool::install`] to control the number of threads.
+    pub fn decode_bytes_parallel(
+        &mut self,
+        bytes: &[u8],
+        cd_events: &mut Vec<CdEvent>,
+        trigger_events: &mut Vec<TriggerEvent>,
+    ) {
+        self.decode_chunks_parallel(bytes, CHUNK_WORDS, cd_events, trigger_events);
+    }
+
+    /// Decodes an EVT 3.0 file from disk like [`decode_file`](Self::decode_file),
+    /// with chunks of it decoded in parallel on the current rayon thread pool.
+    ///
+    /// The whole data section is read into memory first, in reads of the
+    /// [read buffer size](Self::set_read_buffer_size), so this needs the
+    /// file size in memory on top of the decoded events. Progress is only
+    /// reported while reading.
+    pub fn decode_file_parallel<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<DecodeResult, DecodeError> {
+        let mut buffers = EventBuffers::new();
+        let metadata = self.decode_file_parallel_into(path, &mut buffers)?;
+        Ok(buffers.take_result(metadata))
+    }
+
+    /// Decodes an EVT 3.0 file like [`decode_file_parallel`](Self::decode_file_parallel)
+    /// into reusable [`EventBuffers`], returning the output sensor geometry.
+    ///
+    /// The buffers are cleared and refilled; the file is read into their
+    /// raw data buffer, which is reused as well.
+    pub fn decode_file_parallel_into<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        buffers: &mut EventBuffers,
+    ) -> Result<SensorMetadata, DecodeError> {
+        buffers.clear();
+        let file = File::open(path)?;
+        let file_size = file.metadata()?.len();
+        let mut reader = BufReader::new(file);
+        self.parse_header(&mut reader)?;
+        let header_size = reader.stream_position()?;
+
+        let bytes = &mut buffers.bytes;
+        bytes.reserve(file_size.saturating_sub(header_size) as usize);
+        loop {
+            let limit = self.read_buffer_size() as u64;
+            if reader.by_ref().take(limit).read_to_end(bytes)? == 0 {
+                break;
+            }
+            self.report_progress(header_size + bytes.len() as u64);
+        }
+
+        self.decode_bytes_parallel(
+            &buffers.bytes,
+            &mut buffers.cd_events,
+            &mut buffers.trigger_events,
+        );
+        Ok(self.output_metadata())
+    }
+
+    fn decode_chunks_parallel(
+        &mut self,
+        bytes: &[u8],
+        chunk_words: usize,
+        cd_events: &mut Vec<CdEvent>,
+        trigger_events: &mut Vec<TriggerEvent>,
+    ) {
+        let ranges = chunk_ranges(bytes, chunk_words);
+        if ranges.len() == 1 {
+            self.decode_bytes(bytes, cd_events, trigger_events);
+            return;
+        }
+        let initial = self.state();
+        let chunks: Vec<DecodedChunk> = ranges
+            .par_iter()
+            .enumerate()
+            .map(|(i, range)| {
+                let words = &bytes[range.start * 2..range.end * 2];
+                // The first chunk starts from the actual state
+                decode_chunk(words, (i == 0).then_some(initial))
+            })
+            .collect();
+
+        // Stitch the chunks in order, decoding each one's prefix again from
+        // the state the previous one ended in
+        let mut state = initial;
+        let mut prefixes = Vec::with_capacity(chunks.len());
+        let mut offsets = Vec::with_capacity(chunks.len());
+        for (range, chunk) in ranges.iter().zip(&chunks) {
+            let mut decoder = Evt3Decoder::new();
+            decoder.set_state(state);
+            let mut prefix = (Vec::new(), Vec::new());
+            let words = &bytes[range.start * 2..(range.start + chunk.safe) * 2];
+            decoder.decode_words(le_words(words), &mut prefix.0, &mut prefix.1);
+            let safe_state = decoder.state();
+            prefixes.push(prefix);
+
+            if chunk.safe == range.len() {
+                // No safe point; the prefix was the whole chunk
+                state = safe_state;
+                offsets.push(0);
+                continue;
+            }
+            debug_assert_eq!(
+                (safe_state.y, safe_state.base_x, safe_state.polarity),
+                (
+                    chunk.safe_state.y,
+                    chunk.safe_state.base_x,
+                    chunk.safe_state.polarity
+                )
+            );
+            let offset = safe_state.time_base - chunk.safe_state.time_base;
+            let loops = safe_state.n_time_high_loops - chunk.safe_state.n_time_high_loops;
+            state = DecoderState {
+                time_base: chunk.end_state.time_base + offset,
+                current_time: chunk.end_state.current_time + offset,
+                n_time_high_loops: chunk.end_state.n_time_high_loops + loops,
+                ..chunk.end_state
+            };
+            offsets.push(offset);
+        }
+
+        // Copy the chunks into place in parallel, shifting their timestamps
+        let start = cd_events.len();
+        let lens: Vec<usize> = prefixes
+            .iter()
+            .zip(&chunks)
+            .map(|(prefix, chunk)| prefix.0.len() + chunk.cd_events.len())
+            .collect();
+        cd_events.resize(start + lens.iter().sum::<usize>(), CdEvent::new(0, 0, 0, 0));
+        let mut parts = Vec::with_capacity(lens.len());
+        let mut rest = &mut cd_events[start..];
+        for &len in &lens {
+            let (part, tail) = rest.split_at_mut(len);
+            parts.push(part);
+            rest = tail;
+        }
+        parts
+            .into_par_iter()
+            .zip(&prefixes)
+            .zip(&chunks)
+            .zip(&offsets)
+            .for_each(|(((part, prefix), chunk), &offset)| {
+                let (head, tail) = part.split_at_mut(prefix.0.len());
+                head.copy_from_slice(&prefix.0);
+                for (out, event) in tail.iter_mut().zip(&chunk.cd_events) {
+                    *out = CdEvent {
+                        timestamp: event.timestamp + offset,
+                        ..*event
+                    };
+                }
+            });
+        for ((prefix, chunk), &offset) in prefixes.iter().zip(&chunks).zip(&offsets) {
+            trigger_events.extend_from_slice(&prefix.1);
+            trigger_events.extend(chunk.trigger_events.iter().map(|e| TriggerEvent {
+                timestamp: e.timestamp + offset,
+                ..*e
+            }));
+        }
+        self.set_state(state);
+        self.process_tail(cd_events, start);
+    }
+}
+
+/// Returns the word at `index` of `bytes`.
+#[inline]
+fn word_at(bytes: &[u8], index: usize) -> u16 {
+    u16::from_le_bytes([bytes[index * 2], bytes[index * 2 + 1]])
+}
+
+/// Splits the words of `bytes` into ranges of about `chunk_words` words,
+/// each but the first starting at a TIME_HIGH.
+fn chunk_ranges(bytes: &[u8], chunk_words: usize) -> Vec<Range<usize>> {
+    let words = bytes.len() / 2;
+    let is_time_high =
+        |i: &usize| parser::get_event_type(word_at(bytes, *i)) == RawEventType::TimeHigh as u8;
+    let mut starts = vec![0];
+    let mut target = chunk_words.max(1);
+    while target < words {
+        match (target..words).find(is_time_high) {
+            Some(start) => {
+                starts.push(start);
+                target = start.saturating_add(chunk_words.max(1));
+            }
+            None => break,
+        }
+    }
+    let ends = starts[1..].iter().copied().chain([words]);
+    starts
+        .iter()
+        .copied()
+        .zip(ends)
+        .map(|(s, e)| s..e)
+        .collect()
+}
+
+/// Returns the number of words of a chunk after which TIME_HIGH, TIME_LOW,
+/// ADDR_Y and VECT_BASE_X have all been seen, counting only from the first
+/// TIME_HIGH since a fresh decoder skips the words before it. Returns the
+/// chunk length if there is no such point.
+fn safe_point(words: &[u8]) -> usize {
+    let len = words.len() / 2;
+    let (mut time_high, mut time_low, mut addr_y, mut base_x) = (false, false, false, false);
+    for i in 0..len {
+        let event_type = parser::get_event_type(word_at(words, i));
+        if event_type == RawEventType::TimeHigh as u8 {
+            time_high = true;
+        } else if !time_high {
+            continue;
+        } else if event_type == RawEventType::TimeLow as u8 {
+            time_low = true;
+        } else if event_type == RawEventType::AddrY as u8 {
+            addr_y = true;
+        } else if event_type == RawEventType::VectBaseX as u8 {
+            base_x = true;
+        }
+        if time_high && time_low && addr_y && base_x {
+            return i + 1;
+        }
+    }
+    len
+}
+
+/// Decodes a chunk with a fresh decoder, or from `initial` for the first
+/// chunk, which then needs no prefix.
+fn decode_chunk(words: &[u8], initial: Option<DecoderState>) -> DecodedChunk {
+    let mut decoder = Evt3Decoder::new();
+    let safe = match initial {
+        Some(state) => {
+            decoder.set_state(state);
+            0
+        }
+        None => safe_point(words),
+    };
+    let (mut cd_events, mut trigger_events) = (Vec::new(), Vec::new());
+    decoder.decode_words(
+        le_words(&words[..safe * 2]),
+        &mut cd_events,
+        &mut trigger_events,
+    );
+    let safe_state = decoder.state();
+    cd_events.clear();
+    trigger_events.clear();
+    decoder.decode_words(
+        le_words(&words[safe * 2..]),
+        &mut cd_events,
+        &mut trigger_events,
+    );
+    DecodedChunk {
+        safe,
+        safe_state,
+        end_state: decoder.state(),
+        cd_events,
+        trigger_events,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filters::PolarityFilter;
+
+    fn to_bytes(words: &[u16]) -> Vec<u8> {
+        words.iter().flat_map(|w| w.to_le_bytes()).collect()
+    }
+
+    /// Rows of events over several TIME_HIGH wrap-arounds.
+    fn wrapping_words() -> Vec<u16> {
+        let mut words = Vec::new();
+        for i in 0..3000u16 {
+            let high = (i as u32 * 7 % 4096) as u16;
+            words.push(0x8000 | high); // TIME_HIGH
+            words.push(i % 480); // ADDR_Y
+            words.push(0x6000 | (i % 2048)); // TIME_LOW
+            words.push(0x3000 | (i % 600)); // VECT_BASE_X, OFF
+            words.push(0x4000 | (i % 4096)); // VECT_12
+            words.push(0x2000 | (i % 2) << 11 | (i % 640)); // ADDR_X, ON or OFF
+        }
+        words
+    }
+
+    /// Arbitrary words of all types.
+    fn random_words(n: usize) -> Vec<u16> {
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        (0..n)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u16
+            })
+            .collect()
+    }
+
+    fn assert_same_as_serial(bytes: &[u8], chunk_words: usize) {
+        let mut serial = Evt3Decoder::new();
+        let (mut cd, mut triggers) = (Vec::new(), Vec::new());
+        serial.decode_bytes(bytes, &mut cd, &mut triggers);
+
+        let mut parallel = Evt3Decoder::new();
+        let (mut parallel_cd, mut parallel_triggers) = (Vec::new(), Vec::new());
+        parallel.decode_chunks_parallel(
+            bytes,
+            chunk_words,
+            &mut parallel_cd,
+            &mut parallel_triggers,
+        );
+        assert_eq!(parallel_cd, cd, "chunk_words = {}", chunk_words);
+        assert_eq!(parallel_triggers, triggers);
+        assert_eq!(parallel.state(), serial.state());
+    }
+
+    #[test]
+    fn test_parallel_matches_serial() {
+        let bytes = to_bytes(&wrapping_words());
+        for chunk_words in [1, 6, 100, 5000, usize::MAX] {
+            assert_same_as_serial(&bytes, chunk_words);
+        }
+        let bytes = to_bytes(&random_words(20_000));
+        for chunk_words in [1, 37, 1000] {
+            assert_same_as_serial(&bytes, chunk_words);
+        }
+        assert_same_as_serial(&[], 10);
+    }
+
+    #[test]
+    fn test_chunk_ranges() {
+        // TIME_HIGH at 0, 3 and 4
+        let bytes = to_bytes(&[0x8000, 0x0001, 0x6000, 0x8001, 0x8002, 0x0002]);
+        assert_eq!(chunk_ranges(&bytes, 2), [0..3, 3..6]);
+        assert_eq!(chunk_ranges(&bytes, 1), [0..3, 3..4, 4..6]);
+        assert_eq!(chunk_ranges(&bytes, 10), vec![0..6]);
+    }
+
+    #[test]
+    fn test_decode_file_parallel() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, b"% format EVT3;width=640;height=480\n% end\n")
+            .unwrap();
+        std::io::Write::write_all(&mut file, &to_bytes(&wrapping_words())).unwrap();
+
+        let mut serial = Evt3Decoder::new();
+        serial.add_filter(PolarityFilter::new(1));
+        let expected = serial.decode_file(file.path()).unwrap();
+        let mut parallel = Evt3Decoder::new();
+        parallel.add_filter(PolarityFilter::new(1));
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+        let result = pool
+            .install(|| parallel.decode_file_parallel(file.path()))
+            .unwrap();
+        assert_eq!(result.metadata.width, 640);
+        assert!(!result.cd_events.is_empty());
+        assert_eq!(result.cd_events, expected.cd_events);
+        assert_eq!(result.trigger_events, expected.trigger_events);
+
+        let mut buffers = EventBuffers::new();
+        buffers.recycle(result);
+        let mut parallel = Evt3Decoder::new();
+        parallel.add_filter(PolarityFilter::new(1));
+        parallel
+            .decode_file_parallel_into(file.path(), &mut buffers)
+            .unwrap();
+        assert_eq!(buffers.cd_events, expected.cd_events);
+    }
+}
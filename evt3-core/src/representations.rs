@@ -0,0 +1,219 @@
+//! Dense event representations for feature extraction and learning.
+//!
+//! - [`TimeSurface`]: exponentially decaying time of the latest event
+//! - [`voxel_grid`]: events binned in time with bilinear weighting, the
+//!   standard input of most event-based deep learning models
+//!
+//! Like [`frames`](crate::frames), all images are flat, row-major
+//! `width * height` arrays indexed by `y * width + x`.
+
+use crate::types::CdEvent;
+
+/// Per-pixel, per-polarity time of the latest event.
+///
+/// Feed events with [`update`](Self::update) and sample an exponentially
+/// decaying time surface (as used by HOTS/HATS) with
+/// [`render`](Self::render).
+#[derive(Debug, Clone)]
+pub struct TimeSurface {
+    width: u32,
+    height: u32,
+    /// Latest timestamp + 1 per pixel for OFF and ON events; 0 means never
+    last: [Vec<u64>; 2],
+}
+
+impl TimeSurface {
+    /// Creates an empty time surface for the given geometry.
+    pub fn new(width: u32, height: u32) -> Self {
+        let len = width as usize * height as usize;
+        Self {
+            width,
+            height,
+            last: [vec![0; len], vec![0; len]],
+        }
+    }
+
+    /// Records the events, keeping the latest timestamp per pixel and polarity.
+    ///
+    /// Events outside the geometry are ignored.
+    pub fn update(&mut self, events: &[CdEvent]) {
+        for event in events {
+            self.record(event);
+        }
+    }
+
+    #[inline]
+    fn record(&mut self, event: &CdEvent) {
+        if event.x as u32 >= self.width || event.y as u32 >= self.height {
+            return;
+        }
+        let index = event.y as usize * self.width as usize + event.x as usize;
+        let last = &mut self.last[(event.polarity != 0) as usize][index];
+        *last = (*last).max(event.timestamp + 1);
+    }
+
+    /// Samples the surface at time `t` with decay constant `tau` (microseconds).
+    ///
+    /// Returns the `[OFF, ON]` images with values `exp(-(t - t_last) / tau)`
+    /// in `(0, 1]`. Pixels that have not fired, or only fired after `t`, are 0.
+    pub fn render(&self, t: u64, tau: f64) -> [Vec<f32>; 2] {
+        let decay = |&last: &u64| {
+            if last == 0 || last - 1 > t {
+                0.0
+            } else {
+                (-((t - (last - 1)) as f64) / tau).exp() as f32
+            }
+        };
+        [
+            self.last[0].iter().map(decay).collect(),
+            self.last[1].iter().map(decay).collect(),
+        ]
+    }
+
+    /// Forgets all recorded events.
+    pub fn clear(&mut self) {
+        for last in &mut self.last {
+            last.fill(0);
+        }
+    }
+}
+
+/// Builds the time surface of `events` at time `t`.
+///
+/// Only events with timestamps up to `t` contribute. Returns the
+/// `[OFF, ON]` images; see [`TimeSurface::render`].
+pub fn time_surface(
+    events: &[CdEvent],
+    width: u32,
+    height: u32,
+    t: u64,
+    tau: f64,
+) -> [Vec<f32>; 2] {
+    let mut surface = TimeSurface::new(width, height);
+    for event in events.iter().filter(|e| e.timestamp <= t) {
+        surface.record(event);
+    }
+    surface.render(t, tau)
+}
+
+/// Builds a voxel grid of `num_bins` temporal bins spanning the events.
+///
+/// The time range runs from the first to the last event timestamp; see
+/// [`voxel_grid_in_range`].
+pub fn voxel_grid(events: &[CdEvent], width: u32, height: u32, num_bins: usize) -> Vec<f32> {
+    let t0 = events.iter().map(|e| e.timestamp).min().unwrap_or(0);
+    let t1 = events.iter().map(|e| e.timestamp).max().unwrap_or(0);
+    voxel_grid_in_range(events, width, height, num_bins, t0, t1)
+}
+
+/// Builds a voxel grid of `num_bins` temporal bins over `t0..=t1`.
+///
+/// Timestamps are scaled so that `t0` falls on bin 0 and `t1` on the last
+/// bin. Each event adds its polarity (+1 ON, -1 OFF) to the two nearest
+/// bins, weighted by temporal distance. The result is a flat
+/// `num_bins * height * width` array indexed by
+/// `bin * height * width + y * width + x`. Events outside the time range or
+/// geometry are ignored.
+pub fn voxel_grid_in_range(
+    events: &[CdEvent],
+    width: u32,
+    height: u32,
+    num_bins: usize,
+    t0: u64,
+    t1: u64,
+) -> Vec<f32> {
+    let plane = width as usize * height as usize;
+    let mut grid = vec![0f32; num_bins * plane];
+    if num_bins == 0 || t1 < t0 {
+        return grid;
+    }
+
+    let scale = if t1 > t0 {
+        (num_bins - 1) as f64 / (t1 - t0) as f64
+    } else {
+        0.0
+    };
+    for event in events {
+        if event.timestamp < t0
+            || event.timestamp > t1
+            || event.x as u32 >= width
+            || event.y as u32 >= height
+        {
+            continue;
+        }
+        let value = if event.polarity != 0 { 1.0 } else { -1.0 };
+        let index = event.y as usize * width as usize + event.x as usize;
+
+        let position = (event.timestamp - t0) as f64 * scale;
+        let bin = (position as usize).min(num_bins - 1);
+        let fraction = (position - bin as f64) as f32;
+        grid[bin * plane + index] += value * (1.0 - fraction);
+        if fraction > 0.0 && bin + 1 < num_bins {
+            grid[(bin + 1) * plane + index] += value * fraction;
+        }
+    }
+    grid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_surface_decay() {
+        let events = [
+            CdEvent::new(0, 0, 1, 0),
+            CdEvent::new(0, 0, 1, 1_000),
+            CdEvent::new(1, 0, 0, 500),
+            CdEvent::new(1, 1, 1, 3_000), // after the query time
+        ];
+        let [off, on] = time_surface(&events, 2, 2, 2_000, 1_000.0);
+
+        assert_eq!(on.len(), 4);
+        assert!((on[0] - (-1.0f32).exp()).abs() < 1e-6);
+        assert!((off[1] - (-1.5f32).exp()).abs() < 1e-6);
+        assert_eq!(on[1], 0.0);
+        assert_eq!(off[0], 0.0);
+        assert_eq!(on[3], 0.0);
+    }
+
+    #[test]
+    fn test_voxel_grid_bilinear_weights() {
+        let events = [
+            CdEvent::new(0, 0, 1, 100), // bin 0
+            CdEvent::new(1, 0, 0, 150), // halfway between bins 0 and 1
+            CdEvent::new(0, 0, 1, 300), // last bin
+            CdEvent::new(1, 0, 1, 400), // outside the range
+        ];
+        let grid = voxel_grid_in_range(&events, 2, 1, 3, 100, 300);
+        assert_eq!(grid.len(), 6);
+        assert_eq!(&grid[0..2], &[1.0, -0.5]);
+        assert_eq!(&grid[2..4], &[0.0, -0.5]);
+        assert_eq!(&grid[4..6], &[1.0, 0.0]);
+
+        // Total weight per event is preserved
+        let total: f32 = voxel_grid(&events, 2, 1, 5).iter().map(|v| v.abs()).sum();
+        assert!((total - 4.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_voxel_grid_degenerate_range() {
+        let events = [CdEvent::new(0, 0, 1, 7), CdEvent::new(0, 0, 1, 7)];
+        assert_eq!(voxel_grid(&events, 1, 1, 3), vec![2.0, 0.0, 0.0]);
+        assert!(voxel_grid(&events, 1, 1, 0).is_empty());
+    }
+
+    #[test]
+    fn test_time_surface_incremental() {
+        let mut surface = TimeSurface::new(4, 1);
+        surface.update(&[CdEvent::new(3, 0, 0, 100)]);
+        assert_eq!(surface.render(100, 10.0)[0][3], 1.0);
+
+        // An older event does not overwrite a newer one
+        surface.update(&[CdEvent::new(3, 0, 0, 50), CdEvent::new(9, 0, 0, 100)]);
+        assert_eq!(surface.render(100, 10.0)[0][3], 1.0);
+
+        surface.clear();
+        assert_eq!(surface.render(100, 10.0)[0][3], 0.0);
+    }
+}
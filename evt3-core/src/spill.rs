@@ -0,0 +1,231 @@
+//! Decoding under a memory budget.
+//!
+//! [`Evt3Decoder::decode_file_spilled`](crate::decoder::Evt3Decoder::decode_file_spilled)
+//! keeps decoded CD events in memory until they exceed a budget, then
+//! writes them to a temporary file as a columnar segment and starts over.
+//! [`SpilledEvents`] reads the segments back in order, followed by the
+//! events still in memory, so recordings larger than the machine's memory
+//! can be processed end to end.
+
+use crate::types::{CdEvent, ColumnarEvents, SensorMetadata, TriggerEvent};
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Bytes one CD event takes in [`ColumnarEvents`] and in a spill file.
+pub const BYTES_PER_EVENT: usize = 2 + 2 + 1 + 8;
+
+/// CD events of a decode, partly spilled to a temporary file.
+///
+/// The file is deleted when this is dropped. Segments are read back one at
+/// a time, so iterating needs no more memory than the decode did.
+#[derive(Debug, Default)]
+pub struct SpilledEvents {
+    file: Option<File>,
+    // Number of events in each segment of the file, in file order
+    segments: Vec<usize>,
+    memory: ColumnarEvents,
+}
+
+impl SpilledEvents {
+    /// Returns the total number of events, spilled or not.
+    pub fn len(&self) -> usize {
+        self.spilled_len() + self.memory.len()
+    }
+
+    /// Returns `true` if there are no events.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of events written to the spill file.
+    pub fn spilled_len(&self) -> usize {
+        self.segments.iter().sum()
+    }
+
+    /// Iterates over the events in decode order, one segment at a time.
+    ///
+    /// Spilled segments are read from the file; the events still in memory
+    /// come last and are borrowed. Iteration stops after a read error.
+    pub fn chunks(&self) -> SpilledChunks<'_> {
+        SpilledChunks {
+            events: self,
+            segment: 0,
+            offset: 0,
+            buffer: Vec::new(),
+            done: false,
+        }
+    }
+
+    /// Iterates over the events in decode order.
+    ///
+    /// Iteration stops after a read error, which is the last item.
+    pub fn iter(&self) -> impl Iterator<Item = io::Result<CdEvent>> + '_ {
+        self.chunks().flat_map(|chunk| {
+            let (events, error) = match chunk {
+                Ok(events) => (Some(events), None),
+                Err(e) => (None, Some(Err(e))),
+            };
+            events
+                .into_iter()
+                .flat_map(|events| {
+                    let len = events.len();
+                    (0..len).map(move |i| Ok(events.get(i).expect("index is in bounds")))
+                })
+                .chain(error)
+        })
+    }
+
+    /// Appends `events` to the spill file, creating it in `dir` first if
+    /// needed, and clears them.
+    pub(crate) fn spill(&mut self, events: &mut ColumnarEvents, dir: &Path) -> io::Result<()> {
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => self.file.insert(tempfile::tempfile_in(dir)?),
+        };
+        file.seek(SeekFrom::End(0))?;
+        let mut writer = BufWriter::new(file);
+        for x in &events.x {
+            writer.write_all(&x.to_le_bytes())?;
+        }
+        for y in &events.y {
+            writer.write_all(&y.to_le_bytes())?;
+        }
+        writer.write_all(&events.polarity)?;
+        for timestamp in &events.timestamp {
+            writer.write_all(&timestamp.to_le_bytes())?;
+        }
+        writer.flush()?;
+        self.segments.push(events.len());
+        events.clear();
+        Ok(())
+    }
+
+    /// Keeps `events` in memory as the last segment.
+    pub(crate) fn finish(&mut self, events: ColumnarEvents) {
+        self.memory = events;
+    }
+}
+
+/// Iterator over the segments of [`SpilledEvents`], see [`SpilledEvents::chunks`].
+#[derive(Debug)]
+pub struct SpilledChunks<'a> {
+    events: &'a SpilledEvents,
+    segment: usize,
+    offset: u64,
+    buffer: Vec<u8>,
+    done: bool,
+}
+
+impl<'a> SpilledChunks<'a> {
+    /// Reads the `len` events of the segment at the current offset.
+    fn read_segment(&mut self, mut file: &File, len: usize) -> io::Result<ColumnarEvents> {
+        self.buffer.resize(len * BYTES_PER_EVENT, 0);
+        // Other iterators over the same file move its cursor, so always seek
+        file.seek(SeekFrom::Start(self.offset))?;
+        file.read_exact(&mut self.buffer)?;
+        self.offset += self.buffer.len() as u64;
+
+        let (x, rest) = self.buffer.split_at(len * 2);
+        let (y, rest) = rest.split_at(len * 2);
+        let (polarity, timestamp) = rest.split_at(len);
+        let u16s = |bytes: &[u8]| -> Vec<u16> {
+            bytes
+                .chunks_exact(2)
+                .map(|b| u16::from_le_bytes([b[0], b[1]]))
+                .collect()
+        };
+        Ok(ColumnarEvents {
+            x: u16s(x),
+            y: u16s(y),
+            polarity: polarity.to_vec(),
+            timestamp: timestamp
+                .chunks_exact(8)
+                .map(|b| u64::from_le_bytes(b.try_into().expect("chunk is 8 bytes")))
+                .collect(),
+        })
+    }
+}
+
+impl<'a> Iterator for SpilledChunks<'a> {
+    type Item = io::Result<Cow<'a, ColumnarEvents>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let events = self.events;
+        match (&events.file, events.segments.get(self.segment)) {
+            (Some(file), Some(&len)) => {
+                self.segment += 1;
+                let result = self.read_segment(file, len);
+                self.done = result.is_err();
+                Some(result.map(Cow::Owned))
+            }
+            _ => {
+                self.done = true;
+                Some(Ok(Cow::Borrowed(&events.memory))).filter(|_| !events.memory.is_empty())
+            }
+        }
+    }
+}
+
+/// Result of decoding an EVT 3.0 file under a memory budget.
+///
+/// See [`Evt3Decoder::decode_file_spilled`](crate::decoder::Evt3Decoder::decode_file_spilled).
+#[derive(Debug)]
+pub struct SpilledDecodeResult {
+    /// Decoded CD events, spilled to disk beyond the budget
+    pub cd_events: SpilledEvents,
+    /// Decoded trigger events, always kept in memory
+    pub trigger_events: Vec<TriggerEvent>,
+    /// Sensor metadata
+    pub metadata: SensorMetadata,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(n: u64) -> ColumnarEvents {
+        (0..n)
+            .map(|i| CdEvent::new((i % 640) as u16, (i % 480) as u16, (i % 2) as u8, i * 20))
+            .collect()
+    }
+
+    #[test]
+    fn test_spill_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut spilled = SpilledEvents::default();
+        let mut first = events(100);
+        spilled.spill(&mut first, dir.path()).unwrap();
+        assert!(first.is_empty());
+        let mut second = events(50);
+        spilled.spill(&mut second, dir.path()).unwrap();
+        spilled.finish(events(10));
+        assert_eq!(spilled.len(), 160);
+        assert_eq!(spilled.spilled_len(), 150);
+
+        let chunks: Vec<_> = spilled.chunks().map(Result::unwrap).collect();
+        let lens: Vec<usize> = chunks.iter().map(|c| c.len()).collect();
+        assert_eq!(lens, [100, 50, 10]);
+        assert_eq!(*chunks[0], events(100));
+        assert!(matches!(chunks[2], Cow::Borrowed(_)));
+
+        let all: Vec<CdEvent> = spilled.iter().map(Result::unwrap).collect();
+        let expected: Vec<CdEvent> = [events(100), events(50), events(10)]
+            .iter()
+            .flat_map(|c| c.iter())
+            .collect();
+        assert_eq!(all, expected);
+    }
+
+    #[test]
+    fn test_spill_empty() {
+        let spilled = SpilledEvents::default();
+        assert!(spilled.is_empty());
+        assert_eq!(spilled.chunks().count(), 0);
+        assert_eq!(spilled.iter().count(), 0);
+    }
+}
@@ -0,0 +1,318 @@
+//! Summary statistics of decoded event streams.
+//!
+//! [`SummaryBuilder`] accumulates statistics chunk by chunk, so a summary can
+//! be computed while decoding without keeping the events in memory (see
+//! [`Evt3Decoder::summarize_file`](crate::decoder::Evt3Decoder::summarize_file)).
+
+use crate::triggers::{TimingAccumulator, TriggerTiming};
+use crate::types::{CdEvent, DecodeResult, SensorMetadata, TriggerEvent};
+
+/// Width of the time bins used to measure the peak event rate, in microseconds.
+pub const PEAK_RATE_WINDOW_US: u64 = 1_000;
+
+/// Statistics of a decoded recording.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DecodeSummary {
+    /// Sensor metadata
+    pub metadata: SensorMetadata,
+    /// Number of CD events
+    pub event_count: u64,
+    /// Number of trigger events
+    pub trigger_count: u64,
+    /// Frequency and jitter of the rising edges per trigger channel
+    /// (channels with at least two rising edges)
+    pub trigger_timing: Vec<TriggerTiming>,
+    /// Statistics of the OFF events (polarity 0)
+    pub off: PolarityStats,
+    /// Statistics of the ON events (polarity 1 and above)
+    pub on: PolarityStats,
+    /// Smallest and largest x coordinate (inclusive), if any events
+    pub x_range: Option<(u16, u16)>,
+    /// Smallest and largest y coordinate (inclusive), if any events
+    pub y_range: Option<(u16, u16)>,
+    /// Earliest CD event timestamp in microseconds
+    pub first_timestamp: Option<u64>,
+    /// Latest CD event timestamp in microseconds
+    pub last_timestamp: Option<u64>,
+    /// Highest event rate in events/s, measured over
+    /// [`PEAK_RATE_WINDOW_US`] bins
+    pub peak_rate: f64,
+}
+
+impl DecodeSummary {
+    /// Computes the summary of a set of events.
+    pub fn from_events(
+        cd_events: &[CdEvent],
+        trigger_events: &[TriggerEvent],
+        metadata: &SensorMetadata,
+    ) -> Self {
+        let mut builder = SummaryBuilder::new();
+        builder.add_events(cd_events);
+        builder.add_triggers(trigger_events);
+        builder.finish(metadata)
+    }
+
+    /// Time span between the first and last CD event in microseconds.
+    pub fn duration(&self) -> u64 {
+        match (self.first_timestamp, self.last_timestamp) {
+            (Some(first), Some(last)) => last - first,
+            _ => 0,
+        }
+    }
+
+    /// Mean event rate in events/s, or 0 if the recording has no duration.
+    pub fn mean_rate(&self) -> f64 {
+        match self.duration() {
+            0 => 0.0,
+            duration => self.event_count as f64 * 1e6 / duration as f64,
+        }
+    }
+
+    /// Ratio of ON to OFF events, or `None` if there are no OFF events.
+    ///
+    /// A ratio far from 1 usually points at misconfigured sensor biases.
+    pub fn on_off_ratio(&self) -> Option<f64> {
+        match self.off.count {
+            0 => None,
+            off => Some(self.on.count as f64 / off as f64),
+        }
+    }
+}
+
+/// Statistics of the events of one polarity.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PolarityStats {
+    /// Number of events
+    pub count: u64,
+    /// Mean event rate in events/s over the whole recording duration
+    pub mean_rate: f64,
+    /// Smallest and largest x coordinate (inclusive), if any events
+    pub x_range: Option<(u16, u16)>,
+    /// Smallest and largest y coordinate (inclusive), if any events
+    pub y_range: Option<(u16, u16)>,
+    /// Mean event position `(x, y)`, if any events
+    pub centroid: Option<(f64, f64)>,
+    /// Standard deviation of the event positions `(x, y)`, if any events
+    pub spread: Option<(f64, f64)>,
+}
+
+/// Running sums behind a [`PolarityStats`].
+#[derive(Debug, Clone, Default)]
+struct PolarityAccumulator {
+    count: u64,
+    x_range: Option<(u16, u16)>,
+    y_range: Option<(u16, u16)>,
+    sum: (f64, f64),
+    sum_sq: (f64, f64),
+}
+
+impl PolarityAccumulator {
+    fn add(&mut self, event: &CdEvent) {
+        let (x, y) = (event.x as f64, event.y as f64);
+        self.count += 1;
+        self.x_range = Some(extend(self.x_range, event.x));
+        self.y_range = Some(extend(self.y_range, event.y));
+        self.sum.0 += x;
+        self.sum.1 += y;
+        self.sum_sq.0 += x * x;
+        self.sum_sq.1 += y * y;
+    }
+
+    fn finish(&self, duration: u64) -> PolarityStats {
+        let n = self.count as f64;
+        let mean = (self.count > 0).then(|| (self.sum.0 / n, self.sum.1 / n));
+        let spread = mean.map(|(mx, my)| {
+            let var_x = (self.sum_sq.0 / n - mx * mx).max(0.0);
+            let var_y = (self.sum_sq.1 / n - my * my).max(0.0);
+            (var_x.sqrt(), var_y.sqrt())
+        });
+        PolarityStats {
+            count: self.count,
+            mean_rate: match duration {
+                0 => 0.0,
+                duration => n * 1e6 / duration as f64,
+            },
+            x_range: self.x_range,
+            y_range: self.y_range,
+            centroid: mean,
+            spread,
+        }
+    }
+}
+
+impl DecodeResult {
+    /// Computes summary statistics of the decoded events.
+    pub fn summary(&self) -> DecodeSummary {
+        DecodeSummary::from_events(&self.cd_events, &self.trigger_events, &self.metadata)
+    }
+}
+
+/// Incremental builder for [`DecodeSummary`].
+#[derive(Debug, Clone, Default)]
+pub struct SummaryBuilder {
+    summary: DecodeSummary,
+    polarities: [PolarityAccumulator; 2],
+    trigger_timing: TimingAccumulator,
+    current_bin: Option<u64>,
+    current_bin_count: u64,
+    peak_bin_count: u64,
+}
+
+impl SummaryBuilder {
+    /// Creates an empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a chunk of CD events.
+    ///
+    /// Events are expected in time order; the peak rate only counts
+    /// consecutive events that fall into the same bin.
+    pub fn add_events(&mut self, events: &[CdEvent]) {
+        let s = &mut self.summary;
+        for event in events {
+            s.event_count += 1;
+            self.polarities[(event.polarity != 0) as usize].add(event);
+            s.x_range = Some(extend(s.x_range, event.x));
+            s.y_range = Some(extend(s.y_range, event.y));
+            s.first_timestamp = Some(
+                s.first_timestamp
+                    .map_or(event.timestamp, |t| t.min(event.timestamp)),
+            );
+            s.last_timestamp = Some(
+                s.last_timestamp
+                    .map_or(event.timestamp, |t| t.max(event.timestamp)),
+            );
+
+            let bin = event.timestamp / PEAK_RATE_WINDOW_US;
+            if self.current_bin == Some(bin) {
+                self.current_bin_count += 1;
+            } else {
+                self.current_bin = Some(bin);
+                self.current_bin_count = 1;
+            }
+            self.peak_bin_count = self.peak_bin_count.max(self.current_bin_count);
+        }
+    }
+
+    /// Adds a chunk of trigger events.
+    pub fn add_triggers(&mut self, events: &[TriggerEvent]) {
+        self.summary.trigger_count += events.len() as u64;
+        self.trigger_timing.add(events);
+    }
+
+    /// Returns the summary of all events added so far.
+    pub fn finish(&self, metadata: &SensorMetadata) -> DecodeSummary {
+        let duration = self.summary.duration();
+        DecodeSummary {
+            metadata: metadata.clone(),
+            off: self.polarities[0].finish(duration),
+            on: self.polarities[1].finish(duration),
+            trigger_timing: self.trigger_timing.finish(),
+            peak_rate: self.peak_bin_count as f64 * 1e6 / PEAK_RATE_WINDOW_US as f64,
+            ..self.summary.clone()
+        }
+    }
+}
+
+fn extend(range: Option<(u16, u16)>, value: u16) -> (u16, u16) {
+    match range {
+        Some((min, max)) => (min.min(value), max.max(value)),
+        None => (value, value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_from_events() {
+        let events = [
+            CdEvent::new(10, 20, 1, 1_000),
+            CdEvent::new(5, 30, 0, 1_500),
+            CdEvent::new(15, 25, 1, 1_900),
+            CdEvent::new(12, 22, 1, 3_000),
+        ];
+        let triggers = [TriggerEvent::new(1, 0, 1_200)];
+        let summary = DecodeSummary::from_events(&events, &triggers, &SensorMetadata::default());
+
+        assert_eq!(summary.event_count, 4);
+        assert_eq!(summary.trigger_count, 1);
+        assert!(summary.trigger_timing.is_empty());
+        assert_eq!(summary.on.count, 3);
+        assert_eq!(summary.off.count, 1);
+        assert_eq!(summary.x_range, Some((5, 15)));
+        assert_eq!(summary.y_range, Some((20, 30)));
+        assert_eq!(summary.first_timestamp, Some(1_000));
+        assert_eq!(summary.last_timestamp, Some(3_000));
+        assert_eq!(summary.duration(), 2_000);
+        assert_eq!(summary.mean_rate(), 2_000.0);
+        // Three events in the 1 ms bin starting at 1000 us
+        assert_eq!(summary.peak_rate, 3_000.0);
+    }
+
+    #[test]
+    fn test_summary_empty() {
+        let summary = DecodeSummary::from_events(&[], &[], &SensorMetadata::default());
+        assert_eq!(summary.event_count, 0);
+        assert_eq!(summary.x_range, None);
+        assert_eq!(summary.first_timestamp, None);
+        assert_eq!(summary.mean_rate(), 0.0);
+        assert_eq!(summary.peak_rate, 0.0);
+        assert_eq!(summary.on_off_ratio(), None);
+        assert_eq!(summary.on, PolarityStats::default());
+    }
+
+    #[test]
+    fn test_polarity_stats() {
+        let events = [
+            CdEvent::new(10, 10, 1, 0),
+            CdEvent::new(20, 30, 1, 500_000),
+            CdEvent::new(5, 7, 0, 1_000_000),
+        ];
+        let summary = DecodeSummary::from_events(&events, &[], &SensorMetadata::default());
+
+        assert_eq!(summary.on_off_ratio(), Some(2.0));
+        assert_eq!(summary.on.mean_rate, 2.0);
+        assert_eq!(summary.off.mean_rate, 1.0);
+        assert_eq!(summary.on.x_range, Some((10, 20)));
+        assert_eq!(summary.on.centroid, Some((15.0, 20.0)));
+        assert_eq!(summary.on.spread, Some((5.0, 10.0)));
+        assert_eq!(summary.off.centroid, Some((5.0, 7.0)));
+        assert_eq!(summary.off.spread, Some((0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_builder_across_chunks() {
+        let events: Vec<CdEvent> = (0..10)
+            .map(|i| CdEvent::new(i, 0, 1, 500 + i as u64))
+            .collect();
+        let mut builder = SummaryBuilder::new();
+        builder.add_events(&events[..4]);
+        builder.add_events(&events[4..]);
+        let summary = builder.finish(&SensorMetadata::default());
+
+        assert_eq!(
+            summary,
+            DecodeSummary::from_events(&events, &[], &SensorMetadata::default())
+        );
+        assert_eq!(summary.peak_rate, 10_000.0);
+    }
+
+    #[test]
+    fn test_summary_trigger_timing() {
+        let triggers: Vec<TriggerEvent> = (0..10)
+            .map(|i| TriggerEvent::new(1, 2, i * 1_000))
+            .collect();
+        let mut builder = SummaryBuilder::new();
+        builder.add_triggers(&triggers[..3]);
+        builder.add_triggers(&triggers[3..]);
+        let summary = builder.finish(&SensorMetadata::default());
+
+        assert_eq!(summary.trigger_timing.len(), 1);
+        assert_eq!(summary.trigger_timing[0].id, 2);
+        assert_eq!(summary.trigger_timing[0].edge_count, 10);
+        assert_eq!(summary.trigger_timing[0].frequency(), 1_000.0);
+    }
+}
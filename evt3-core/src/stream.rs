@@ -0,0 +1,167 @@
+//! Asynchronous decoding for tokio services (feature `async`).
+//!
+//! [`Evt3Decoder::decode_file_async`] reads a file with tokio's async file
+//! I/O and yields its events as a [`Stream`] of [`EventChunk`]s, e.g. to
+//! broadcast them over a WebSocket while the file is still being decoded.
+
+use crate::decoder::{DecodeError, Evt3Decoder, DEFAULT_READ_BUFFER_SIZE};
+use crate::types::EventChunk;
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::Stream;
+
+/// Chunks decoded ahead of the stream's consumer.
+const QUEUE_CHUNKS: usize = 4;
+
+type ChunkSender = mpsc::Sender<Result<EventChunk, DecodeError>>;
+
+impl Evt3Decoder {
+    /// Decodes an EVT 3.0 file with tokio's async file I/O, yielding the
+    /// events of each read as an [`EventChunk`].
+    ///
+    /// Reading and decoding run on a spawned task, a few chunks ahead of
+    /// the consumer, so this must be called from within a tokio runtime.
+    /// A decode error is the last item of the stream; dropping the stream
+    /// stops decoding. The decoder, with its filters and transforms, moves
+    /// to the task.
+    ///
+    /// ```no_run
+    /// # async fn run() -> Result<(), evt3_core::DecodeError> {
+    /// use evt3_core::Evt3Decoder;
+    /// use tokio_stream::StreamExt;
+    ///
+    /// let mut chunks = Evt3Decoder::new().decode_file_async("recording.raw");
+    /// while let Some(chunk) = chunks.next().await {
+    ///     println!("{} events", chunk?.cd_events.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn decode_file_async<P: AsRef<Path>>(
+        mut self,
+        path: P,
+    ) -> impl Stream<Item = Result<EventChunk, DecodeError>> {
+        let path = path.as_ref().to_path_buf();
+        let (sender, receiver) = mpsc::channel(QUEUE_CHUNKS);
+        tokio::spawn(async move {
+            if let Err(e) = self.send_chunks(&path, &sender).await {
+                // The stream may be gone already
+                let _ = sender.send(Err(e)).await;
+            }
+        });
+        ReceiverStream::new(receiver)
+    }
+
+    /// Reads and decodes the file at `path`, sending one chunk per read
+    /// until the end of the file or the receiver hanging up.
+    async fn send_chunks(&mut self, path: &Path, sender: &ChunkSender) -> Result<(), DecodeError> {
+        let mut file = tokio::fs::File::open(path).await?;
+        // Fill the first buffer and parse the header from it; it is at
+        // least the default size so that the header fits
+        let mut buffer = vec![0u8; self.read_buffer_size().max(DEFAULT_READ_BUFFER_SIZE)];
+        let mut filled = 0;
+        while filled < buffer.len() {
+            match file.read(&mut buffer[filled..]).await? {
+                0 => break,
+                n => filled += n,
+            }
+        }
+        let mut bytes_read = filled as u64;
+        let mut data = &buffer[..filled];
+        self.parse_header(&mut data)?;
+        let mut start = filled - data.len();
+        let metadata = self.output_metadata();
+
+        loop {
+            let mut cd_events = Vec::new();
+            let mut trigger_events = Vec::new();
+            self.decode_bytes(&buffer[start..filled], &mut cd_events, &mut trigger_events);
+            self.report_progress(bytes_read);
+
+            // Reads can end mid-word; keep the odd byte for the next one
+            let odd = (filled - start) % 2;
+            buffer.copy_within(filled - odd..filled, 0);
+            (start, filled) = (0, odd);
+            buffer.truncate(self.read_buffer_size());
+
+            if !cd_events.is_empty() || !trigger_events.is_empty() {
+                let chunk = EventChunk {
+                    cd_events,
+                    trigger_events,
+                    metadata: metadata.clone(),
+                };
+                if sender.send(Ok(chunk)).await.is_err() {
+                    return Ok(());
+                }
+            }
+
+            match file.read(&mut buffer[filled..]).await? {
+                0 => return Ok(()),
+                n => {
+                    filled += n;
+                    bytes_read += n as u64;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CdEvent;
+    use tokio_stream::StreamExt;
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_decode_file_async() {
+        let words: [u16; 7] = [0x8000, 0x60C8, 0x0064, 0x3000, 0x4E38, 0x8001, 0x4E38];
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, b"% format EVT3;width=640;height=480\n% end\n")
+            .unwrap();
+        for word in words {
+            std::io::Write::write_all(&mut file, &word.to_le_bytes()).unwrap();
+        }
+        let expected = Evt3Decoder::new().decode_file(file.path()).unwrap();
+
+        let chunks: Vec<EventChunk> = runtime().block_on(async {
+            let stream = Evt3Decoder::new().decode_file_async(file.path());
+            stream.map(Result::unwrap).collect().await
+        });
+        assert_eq!(chunks[0].metadata.width, 640);
+        let events: Vec<CdEvent> = chunks.into_iter().flat_map(|c| c.cd_events).collect();
+        assert_eq!(events, expected.cd_events);
+
+        // Odd-sized reads after the header split words
+        let chunks: Vec<EventChunk> = runtime().block_on(async {
+            let mut decoder = Evt3Decoder::new();
+            decoder.set_read_buffer_size(3);
+            decoder
+                .decode_file_async(file.path())
+                .map(Result::unwrap)
+                .collect()
+                .await
+        });
+        let events: Vec<CdEvent> = chunks.into_iter().flat_map(|c| c.cd_events).collect();
+        assert_eq!(events, expected.cd_events);
+    }
+
+    #[test]
+    fn test_decode_file_async_error() {
+        let items: Vec<_> = runtime().block_on(async {
+            Evt3Decoder::new()
+                .decode_file_async("/nonexistent/recording.raw")
+                .collect()
+                .await
+        });
+        assert!(matches!(items[..], [Err(DecodeError::Io(_))]));
+    }
+}
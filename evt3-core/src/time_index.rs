@@ -0,0 +1,373 @@
+//! Time index for random access into raw files.
+//!
+//! Decoding a raw file once records the decoder state before every block of
+//! [`INDEX_BLOCK_SIZE`] bytes. [`TimeIndex::read_range`] then resumes at the
+//! block before a window with [`Evt3Decoder::set_state`] and decodes only up
+//! to its end, so reading windows anywhere in a long recording costs about
+//! as much as decoding the windows themselves.
+//!
+//! An index can be saved next to the recording and loaded again, which
+//! skips the initial decode; its file size tells whether it is stale.
+
+use crate::decoder::{DecodeError, DecoderState, Evt3Decoder};
+use crate::types::CdEvent;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Bytes of data between two entries of a time index.
+pub const INDEX_BLOCK_SIZE: u64 = 256 * 1024;
+
+/// Magic number at the start of a saved time index.
+const TIME_INDEX_MAGIC: &[u8; 8] = b"EVT3TIX\0";
+
+/// Entry of a time index: the file offset of a block of data and the
+/// decoder state needed to decode from there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexEntry {
+    /// Byte offset of the block in the file
+    pub offset: u64,
+    /// Decoder state after all data before the block
+    pub state: DecoderState,
+}
+
+/// Decoder states at regular offsets of a raw file, plus what building the
+/// index learns about the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeIndex {
+    /// File size when the index was built, to detect stale saved indices
+    pub file_size: u64,
+    /// Entries in file order
+    pub entries: Vec<IndexEntry>,
+    /// Number of CD events in the file
+    pub event_count: u64,
+    /// Timestamp of the first CD event
+    pub first_timestamp: Option<u64>,
+    /// Timestamp of the last CD event
+    pub last_timestamp: Option<u64>,
+}
+
+impl TimeIndex {
+    /// Decodes the data of `path` from `data_offset` (the end of the
+    /// header) on, recording an entry before every block of
+    /// [`INDEX_BLOCK_SIZE`] bytes.
+    pub fn build<P: AsRef<Path>>(path: P, data_offset: u64) -> io::Result<Self> {
+        Self::build_with_block_size(path.as_ref(), data_offset, INDEX_BLOCK_SIZE)
+    }
+
+    fn build_with_block_size(path: &Path, data_offset: u64, block_size: u64) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let file_size = file.metadata()?.len();
+        file.seek(SeekFrom::Start(data_offset))?;
+        let mut index = Self {
+            file_size,
+            entries: Vec::new(),
+            event_count: 0,
+            first_timestamp: None,
+            last_timestamp: None,
+        };
+        let mut decoder = Evt3Decoder::new();
+        let mut offset = data_offset;
+        let mut buffer = Vec::new();
+        let mut cd_events = Vec::new();
+        loop {
+            buffer.clear();
+            (&mut file).take(block_size).read_to_end(&mut buffer)?;
+            if buffer.is_empty() {
+                break;
+            }
+            index.entries.push(IndexEntry {
+                offset,
+                state: decoder.state(),
+            });
+            cd_events.clear();
+            decoder.decode_bytes(&buffer, &mut cd_events, &mut Vec::new());
+            index.event_count += cd_events.len() as u64;
+            if let (Some(first), Some(last)) = (cd_events.first(), cd_events.last()) {
+                index.first_timestamp.get_or_insert(first.timestamp);
+                index.last_timestamp = Some(last.timestamp);
+            }
+            offset += buffer.len() as u64;
+        }
+        Ok(index)
+    }
+
+    /// Returns the entry to start decoding from for events at or after
+    /// `timestamp`, or `None` for an empty index.
+    ///
+    /// Blocks before the last entry earlier than `timestamp` end before it.
+    pub fn seek(&self, timestamp: u64) -> Option<&IndexEntry> {
+        let index = self
+            .entries
+            .partition_point(|entry| entry.state.current_time < timestamp)
+            .saturating_sub(1);
+        self.entries.get(index)
+    }
+
+    /// Decodes the CD events of `path` with `start <= timestamp < end`,
+    /// starting at the entry found by [`seek`](Self::seek).
+    ///
+    /// `path` must be the file the index was built for.
+    pub fn read_range<P: AsRef<Path>>(
+        &self,
+        path: P,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<CdEvent>, DecodeError> {
+        let mut cd_events = Vec::new();
+        let Some(entry) = self.seek(start).filter(|_| start < end) else {
+            return Ok(cd_events);
+        };
+        let mut file = File::open(path)?;
+        file.seek(SeekFrom::Start(entry.offset))?;
+        let mut decoder = Evt3Decoder::new();
+        decoder.set_state(entry.state);
+        let mut buffer = Vec::new();
+        let mut chunk = Vec::new();
+        loop {
+            buffer.clear();
+            (&mut file)
+                .take(INDEX_BLOCK_SIZE)
+                .read_to_end(&mut buffer)?;
+            if buffer.is_empty() {
+                break;
+            }
+            chunk.clear();
+            decoder.decode_bytes(&buffer, &mut chunk, &mut Vec::new());
+            let past_end = chunk.last().is_some_and(|e| e.timestamp >= end);
+            cd_events.extend(
+                chunk
+                    .iter()
+                    .filter(|e| e.timestamp >= start && e.timestamp < end),
+            );
+            if past_end {
+                break;
+            }
+        }
+        Ok(cd_events)
+    }
+
+    /// Writes the index to `path` (little-endian, 46 bytes per entry).
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Writes the index to any writer, in the format of [`save`](Self::save).
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(TIME_INDEX_MAGIC)?;
+        let first = self.first_timestamp.unwrap_or(0);
+        let last = self.last_timestamp.unwrap_or(0);
+        let entry_count = self.entries.len() as u64;
+        for value in [self.file_size, self.event_count, first, last, entry_count] {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        for entry in &self.entries {
+            let state = &entry.state;
+            let values = [
+                entry.offset,
+                state.time_base,
+                state.time_low,
+                state.current_time,
+                state.n_time_high_loops,
+            ];
+            for value in values {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+            writer.write_all(&state.y.to_le_bytes())?;
+            writer.write_all(&state.base_x.to_le_bytes())?;
+            writer.write_all(&[state.polarity, state.time_base_set as u8])?;
+        }
+        Ok(())
+    }
+
+    /// Reads an index written by [`save`](Self::save).
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, DecodeError> {
+        let path = path.as_ref();
+        let data = std::fs::read(path)?;
+        Self::from_bytes(&data).map_err(|e| match e {
+            DecodeError::InvalidFormat(_) => {
+                DecodeError::InvalidFormat(format!("Not an evt3 time index: {:?}", path))
+            }
+            e => e,
+        })
+    }
+
+    /// Parses the contents of a saved index.
+    ///
+    /// Fails with [`DecodeError::InvalidFormat`] without the magic number
+    /// and with [`DecodeError::UnexpectedEof`] if the data ends early.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, DecodeError> {
+        let Some(mut data) = data.strip_prefix(TIME_INDEX_MAGIC) else {
+            return Err(DecodeError::InvalidFormat(
+                "Not an evt3 time index".to_string(),
+            ));
+        };
+        Self::parse(&mut data).map_err(|_| DecodeError::UnexpectedEof)
+    }
+
+    /// Parses the contents of a saved index after the magic number.
+    fn parse(data: &mut &[u8]) -> io::Result<Self> {
+        fn read_u64(data: &mut &[u8]) -> io::Result<u64> {
+            let mut bytes = [0u8; 8];
+            data.read_exact(&mut bytes)?;
+            Ok(u64::from_le_bytes(bytes))
+        }
+        let file_size = read_u64(data)?;
+        let event_count = read_u64(data)?;
+        let (first, last) = (read_u64(data)?, read_u64(data)?);
+        let entry_count = read_u64(data)?;
+        let mut entries = Vec::new();
+        for _ in 0..entry_count {
+            let offset = read_u64(data)?;
+            let (time_base, time_low) = (read_u64(data)?, read_u64(data)?);
+            let (current_time, n_time_high_loops) = (read_u64(data)?, read_u64(data)?);
+            let mut rest = [0u8; 6];
+            data.read_exact(&mut rest)?;
+            let state = DecoderState {
+                time_base,
+                time_low,
+                current_time,
+                n_time_high_loops,
+                time_base_set: rest[5] != 0,
+                y: u16::from_le_bytes([rest[0], rest[1]]),
+                base_x: u16::from_le_bytes([rest[2], rest[3]]),
+                polarity: rest[4],
+            };
+            entries.push(IndexEntry { offset, state });
+        }
+        let has_events = event_count > 0;
+        Ok(Self {
+            file_size,
+            entries,
+            event_count,
+            first_timestamp: has_events.then_some(first),
+            last_timestamp: has_events.then_some(last),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &[u8] = b"% evt 3.0\n% geometry 640x480\n% end\n";
+
+    /// A raw file with rows of events over several TIME_HIGH wrap-arounds.
+    fn raw_file(dir: &Path) -> (std::path::PathBuf, u64) {
+        let mut bytes = HEADER.to_vec();
+        for i in 0..3000u16 {
+            let words = [
+                0x8000 | (i as u32 * 7 % 4096) as u16, // TIME_HIGH
+                i % 480,                               // ADDR_Y
+                0x6000 | (i % 2048),                   // TIME_LOW
+                0x3000 | (i % 600),                    // VECT_BASE_X
+                0x4000 | (i % 4096),                   // VECT_12
+                0x2000 | (i % 2) << 11 | (i % 640),    // ADDR_X
+            ];
+            bytes.extend(words.iter().flat_map(|w| w.to_le_bytes()));
+        }
+        let path = dir.join("recording.raw");
+        std::fs::write(&path, bytes).unwrap();
+        (path, HEADER.len() as u64)
+    }
+
+    fn all_events(path: &Path) -> Vec<CdEvent> {
+        Evt3Decoder::new().decode_file(path).unwrap().cd_events
+    }
+
+    #[test]
+    fn test_build() {
+        let dir = tempfile::tempdir().unwrap();
+        let (path, data_offset) = raw_file(dir.path());
+        let events = all_events(&path);
+
+        let index = TimeIndex::build_with_block_size(&path, data_offset, 1000).unwrap();
+        assert_eq!(index.file_size, std::fs::metadata(&path).unwrap().len());
+        assert_eq!(index.event_count, events.len() as u64);
+        assert_eq!(index.first_timestamp, Some(events[0].timestamp));
+        assert_eq!(index.last_timestamp, events.last().map(|e| e.timestamp));
+        assert_eq!(index.entries.len(), 36);
+        assert_eq!(index.entries[0].offset, data_offset);
+        assert_eq!(index.entries[1].offset, data_offset + 1000);
+        assert_eq!(index.entries[0].state, DecoderState::default());
+    }
+
+    #[test]
+    fn test_seek_and_read_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let (path, data_offset) = raw_file(dir.path());
+        let events = all_events(&path);
+        let index = TimeIndex::build_with_block_size(&path, data_offset, 1000).unwrap();
+
+        assert_eq!(index.seek(0), index.entries.first());
+        let last = events.last().unwrap().timestamp;
+        assert_eq!(index.seek(last + 1), index.entries.last());
+        let middle = events[events.len() / 2].timestamp;
+        let entry = index.seek(middle).unwrap();
+        assert!(entry.state.current_time < middle);
+        assert!(entry.offset > data_offset);
+
+        for (start, end) in [(0, u64::MAX), (middle, middle + 50_000), (last, last + 1)] {
+            let expected: Vec<CdEvent> = events
+                .iter()
+                .filter(|e| e.timestamp >= start && e.timestamp < end)
+                .copied()
+                .collect();
+            assert!(!expected.is_empty());
+            assert_eq!(index.read_range(&path, start, end).unwrap(), expected);
+        }
+        assert!(index.read_range(&path, middle, middle).unwrap().is_empty());
+        assert!(index
+            .read_range(&path, last + 1, u64::MAX)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let (path, data_offset) = raw_file(dir.path());
+        let index = TimeIndex::build(&path, data_offset).unwrap();
+
+        let index_path = dir.path().join("recording.tidx");
+        index.save(&index_path).unwrap();
+        let saved = std::fs::read(&index_path).unwrap();
+        assert_eq!(saved.len(), 8 + 5 * 8 + 46 * index.entries.len());
+        assert_eq!(TimeIndex::load(&index_path).unwrap(), index);
+
+        let empty = TimeIndex {
+            file_size: 10,
+            entries: Vec::new(),
+            event_count: 0,
+            first_timestamp: None,
+            last_timestamp: None,
+        };
+        let mut bytes = Vec::new();
+        empty.write_to(&mut bytes).unwrap();
+        assert_eq!(TimeIndex::from_bytes(&bytes).unwrap(), empty);
+    }
+
+    #[test]
+    fn test_load_invalid() {
+        let dir = tempfile::tempdir().unwrap();
+        let (path, data_offset) = raw_file(dir.path());
+        let index = TimeIndex::build(&path, data_offset).unwrap();
+        let mut bytes = Vec::new();
+        index.write_to(&mut bytes).unwrap();
+
+        assert!(matches!(
+            TimeIndex::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(DecodeError::UnexpectedEof)
+        ));
+        assert!(matches!(
+            TimeIndex::load(&path),
+            Err(DecodeError::InvalidFormat(message)) if message.contains("recording.raw")
+        ));
+        assert!(matches!(
+            TimeIndex::load(dir.path().join("missing.tidx")),
+            Err(DecodeError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,391 @@
+//! Event transforms.
+//!
+//! Transforms rewrite events (coordinates, polarity) and the sensor geometry
+//! they belong to. Like [filters](crate::filters), they can be applied as a
+//! post-processing pass with [`apply_transform`] or registered on the
+//! decoder with [`Evt3Decoder::add_transform`], where they run after all
+//! filters.
+//!
+//! [`Evt3Decoder::add_transform`]: crate::decoder::Evt3Decoder::add_transform
+
+use crate::types::{CdEvent, Roi, SensorMetadata};
+
+/// A per-event transform.
+pub trait EventTransform: Send {
+    /// Transforms an event in place.
+    ///
+    /// Returns `false` if the event does not exist in the transformed
+    /// output, e.g. because it lies outside a crop region.
+    fn apply(&mut self, event: &mut CdEvent) -> bool;
+
+    /// Returns the sensor geometry of the transformed events.
+    fn transform_metadata(&self, metadata: &SensorMetadata) -> SensorMetadata {
+        metadata.clone()
+    }
+
+    /// Adapts the transform to the geometry of its input events.
+    ///
+    /// Called by the decoder when a transform is added and again once the
+    /// file header has been parsed.
+    fn set_geometry(&mut self, _metadata: &SensorMetadata) {}
+}
+
+/// Applies a transform to a list of events and their metadata in place.
+pub fn apply_transform<T: EventTransform + ?Sized>(
+    transform: &mut T,
+    events: &mut Vec<CdEvent>,
+    metadata: &mut SensorMetadata,
+) {
+    transform.set_geometry(metadata);
+    events.retain_mut(|event| transform.apply(event));
+    *metadata = transform.transform_metadata(metadata);
+}
+
+/// Runs a transform chain over `events[start..]`, compacting the surviving events.
+pub(crate) fn apply_tail(
+    transforms: &mut [Box<dyn EventTransform>],
+    events: &mut Vec<CdEvent>,
+    start: usize,
+) {
+    if transforms.is_empty() {
+        return;
+    }
+    let mut write = start;
+    for read in start..events.len() {
+        let mut event = events[read];
+        if transforms.iter_mut().all(|t| t.apply(&mut event)) {
+            events[write] = event;
+            write += 1;
+        }
+    }
+    events.truncate(write);
+}
+
+/// Crops events to a region of interest.
+///
+/// Events outside the region are dropped and the remaining coordinates are
+/// translated so the region's top-left corner becomes the origin. The sensor
+/// geometry shrinks to the size of the region.
+#[derive(Debug, Clone, Copy)]
+pub struct Crop {
+    roi: Roi,
+}
+
+impl Crop {
+    /// Creates a crop to the given region.
+    pub fn new(roi: Roi) -> Self {
+        Self { roi }
+    }
+
+    /// Returns the crop region.
+    pub fn roi(&self) -> Roi {
+        self.roi
+    }
+}
+
+impl EventTransform for Crop {
+    #[inline]
+    fn apply(&mut self, event: &mut CdEvent) -> bool {
+        if !self.roi.contains(event.x, event.y) {
+            return false;
+        }
+        event.x -= self.roi.x0;
+        event.y -= self.roi.y0;
+        true
+    }
+
+    fn transform_metadata(&self, metadata: &SensorMetadata) -> SensorMetadata {
+        SensorMetadata {
+            width: (self.roi.width() as u32).min(metadata.width.saturating_sub(self.roi.x0 as u32)),
+            height: (self.roi.height() as u32)
+                .min(metadata.height.saturating_sub(self.roi.y0 as u32)),
+        }
+    }
+}
+
+/// Spatially downsamples events by an integer factor.
+///
+/// Each block of `factor` x `factor` pixels becomes one output pixel, so
+/// coordinates are divided by the factor and the geometry shrinks
+/// accordingly (rounding up, so edge pixels are kept). Events of one block
+/// are not merged; several events with the same coordinates and timestamp
+/// can result.
+#[derive(Debug, Clone, Copy)]
+pub struct Downsample {
+    factor: u16,
+}
+
+impl Downsample {
+    /// Creates a transform with the given factor; 0 is treated as 1.
+    pub fn new(factor: u16) -> Self {
+        Self {
+            factor: factor.max(1),
+        }
+    }
+
+    /// Returns the downsampling factor.
+    pub fn factor(&self) -> u16 {
+        self.factor
+    }
+}
+
+impl EventTransform for Downsample {
+    #[inline]
+    fn apply(&mut self, event: &mut CdEvent) -> bool {
+        event.x /= self.factor;
+        event.y /= self.factor;
+        true
+    }
+
+    fn transform_metadata(&self, metadata: &SensorMetadata) -> SensorMetadata {
+        let scale = |size: u32| (size as f64 / self.factor as f64).ceil() as u32;
+        SensorMetadata {
+            width: scale(metadata.width),
+            height: scale(metadata.height),
+        }
+    }
+}
+
+/// A flip, rotation or transposition of the pixel grid.
+///
+/// Rotations are clockwise as seen on screen, with the origin at the
+/// top-left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    /// Mirror horizontally
+    FlipX,
+    /// Mirror vertically
+    FlipY,
+    /// Rotate by 90 degrees clockwise
+    Rotate90,
+    /// Rotate by 180 degrees (e.g. for an upside-down camera)
+    Rotate180,
+    /// Rotate by 270 degrees clockwise
+    Rotate270,
+    /// Swap x and y
+    Transpose,
+}
+
+impl Orientation {
+    /// Returns `true` if the transform swaps width and height.
+    pub fn swaps_axes(self) -> bool {
+        matches!(self, Self::Rotate90 | Self::Rotate270 | Self::Transpose)
+    }
+}
+
+/// Reorients events by flipping, rotating or transposing the pixel grid.
+///
+/// Width and height are swapped for 90/270 degree rotations and
+/// transposition. Events outside the input geometry are dropped.
+#[derive(Debug, Clone, Copy)]
+pub struct Reorient {
+    orientation: Orientation,
+    width: u16,
+    height: u16,
+}
+
+impl Reorient {
+    /// Creates a transform for events of the given geometry.
+    pub fn new(orientation: Orientation, metadata: &SensorMetadata) -> Self {
+        Self {
+            orientation,
+            width: metadata.width.min(u16::MAX as u32) as u16,
+            height: metadata.height.min(u16::MAX as u32) as u16,
+        }
+    }
+
+    /// Returns the orientation applied.
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+}
+
+impl EventTransform for Reorient {
+    #[inline]
+    fn apply(&mut self, event: &mut CdEvent) -> bool {
+        let (x, y) = (event.x, event.y);
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        let (max_x, max_y) = (self.width - 1, self.height - 1);
+        (event.x, event.y) = match self.orientation {
+            Orientation::FlipX => (max_x - x, y),
+            Orientation::FlipY => (x, max_y - y),
+            Orientation::Rotate90 => (max_y - y, x),
+            Orientation::Rotate180 => (max_x - x, max_y - y),
+            Orientation::Rotate270 => (y, max_x - x),
+            Orientation::Transpose => (y, x),
+        };
+        true
+    }
+
+    fn transform_metadata(&self, metadata: &SensorMetadata) -> SensorMetadata {
+        if self.orientation.swaps_axes() {
+            SensorMetadata {
+                width: metadata.height,
+                height: metadata.width,
+            }
+        } else {
+            metadata.clone()
+        }
+    }
+
+    fn set_geometry(&mut self, metadata: &SensorMetadata) {
+        *self = Self::new(self.orientation, metadata);
+    }
+}
+
+/// Remaps event polarities through a lookup table.
+///
+/// Some sensors and firmware versions use the opposite polarity convention;
+/// remapping at decode time keeps every output consistent. Polarities other
+/// than 0 and 1 are left unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PolarityMap {
+    table: [u8; 2],
+}
+
+impl PolarityMap {
+    /// Creates a map sending polarity 0 to `table[0]` and 1 to `table[1]`.
+    pub fn new(table: [u8; 2]) -> Self {
+        Self { table }
+    }
+
+    /// Swaps ON and OFF events.
+    pub fn invert() -> Self {
+        Self::new([1, 0])
+    }
+}
+
+impl EventTransform for PolarityMap {
+    #[inline]
+    fn apply(&mut self, event: &mut CdEvent) -> bool {
+        if let Some(&polarity) = self.table.get(event.polarity as usize) {
+            event.polarity = polarity;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crop() {
+        let mut events = vec![
+            CdEvent::new(10, 20, 1, 0),
+            CdEvent::new(9, 20, 1, 1),
+            CdEvent::new(109, 69, 0, 2),
+            CdEvent::new(110, 30, 0, 3),
+        ];
+        let mut metadata = SensorMetadata::default();
+        apply_transform(
+            &mut Crop::new(Roi::new(10, 20, 110, 70)),
+            &mut events,
+            &mut metadata,
+        );
+
+        assert_eq!(
+            events,
+            vec![CdEvent::new(0, 0, 1, 0), CdEvent::new(99, 49, 0, 2)]
+        );
+        assert_eq!((metadata.width, metadata.height), (100, 50));
+    }
+
+    #[test]
+    fn test_downsample() {
+        let mut events = vec![
+            CdEvent::new(0, 0, 1, 0),
+            CdEvent::new(3, 5, 0, 1),
+            CdEvent::new(639, 479, 1, 2),
+        ];
+        let mut metadata = SensorMetadata {
+            width: 640,
+            height: 479,
+        };
+        apply_transform(&mut Downsample::new(4), &mut events, &mut metadata);
+        let coordinates: Vec<(u16, u16)> = events.iter().map(|e| (e.x, e.y)).collect();
+        assert_eq!(coordinates, vec![(0, 0), (0, 1), (159, 119)]);
+        assert_eq!((metadata.width, metadata.height), (160, 120));
+    }
+
+    #[test]
+    fn test_reorient() {
+        let metadata = SensorMetadata {
+            width: 4,
+            height: 3,
+        };
+        let cases = [
+            (Orientation::FlipX, (3, 0), (4, 3)),
+            (Orientation::FlipY, (0, 2), (4, 3)),
+            (Orientation::Rotate90, (2, 0), (3, 4)),
+            (Orientation::Rotate180, (3, 2), (4, 3)),
+            (Orientation::Rotate270, (0, 3), (3, 4)),
+            (Orientation::Transpose, (0, 0), (3, 4)),
+        ];
+        for (orientation, origin, size) in cases {
+            let mut events = vec![CdEvent::new(0, 0, 1, 0), CdEvent::new(4, 0, 1, 1)];
+            let mut out = metadata.clone();
+            apply_transform(
+                &mut Reorient::new(orientation, &metadata),
+                &mut events,
+                &mut out,
+            );
+            assert_eq!(events.len(), 1, "{:?}", orientation);
+            assert_eq!((events[0].x, events[0].y), origin, "{:?}", orientation);
+            assert_eq!((out.width, out.height), size, "{:?}", orientation);
+        }
+    }
+
+    #[test]
+    fn test_rotations_compose() {
+        let metadata = SensorMetadata {
+            width: 640,
+            height: 480,
+        };
+        let original = CdEvent::new(123, 45, 0, 7);
+        let mut event = original;
+        let mut geometry = metadata.clone();
+        for _ in 0..4 {
+            let mut rotate = Reorient::new(Orientation::Rotate90, &geometry);
+            assert!(rotate.apply(&mut event));
+            assert!((event.x as u32) < rotate.transform_metadata(&geometry).width);
+            geometry = rotate.transform_metadata(&geometry);
+        }
+        assert_eq!(event, original);
+
+        let mut twice = Reorient::new(Orientation::Rotate180, &metadata);
+        let mut event = original;
+        twice.apply(&mut event);
+        assert_eq!((event.x, event.y), (516, 434));
+    }
+
+    #[test]
+    fn test_polarity_map() {
+        let mut events = vec![
+            CdEvent::new(0, 0, 0, 0),
+            CdEvent::new(0, 0, 1, 1),
+            CdEvent::new(0, 0, 7, 2),
+        ];
+        let mut metadata = SensorMetadata::default();
+        apply_transform(&mut PolarityMap::invert(), &mut events, &mut metadata);
+        let polarities: Vec<u8> = events.iter().map(|e| e.polarity).collect();
+        assert_eq!(polarities, vec![1, 0, 7]);
+
+        apply_transform(&mut PolarityMap::new([1, 1]), &mut events, &mut metadata);
+        let polarities: Vec<u8> = events.iter().map(|e| e.polarity).collect();
+        assert_eq!(polarities, vec![1, 1, 7]);
+    }
+
+    #[test]
+    fn test_crop_clamped_to_sensor() {
+        let metadata = SensorMetadata {
+            width: 640,
+            height: 480,
+        };
+        let crop = Crop::new(Roi::new(600, 400, 700, 500));
+        let cropped = crop.transform_metadata(&metadata);
+        assert_eq!((cropped.width, cropped.height), (40, 80));
+    }
+}
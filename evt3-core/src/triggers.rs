@@ -0,0 +1,665 @@
+//! Analysis of external trigger events.
+//!
+//! Trigger edges are how event recordings are aligned with frame cameras,
+//! stimuli and other sensors. The helpers here work on the trigger events
+//! of one channel at a time and expect events sorted by timestamp, as
+//! produced by the decoder.
+
+use crate::types::{CdEvent, TriggerEvent};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+
+/// Trigger edge direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    /// Low-to-high transition (trigger value 1)
+    Rising,
+    /// High-to-low transition (trigger value 0)
+    Falling,
+}
+
+impl Edge {
+    /// Returns true if a trigger event with this value is an edge of this kind.
+    pub fn matches(self, value: u8) -> bool {
+        match self {
+            Edge::Rising => value != 0,
+            Edge::Falling => value == 0,
+        }
+    }
+}
+
+/// A segment of CD events between two consecutive trigger edges.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TriggerSegment {
+    /// Timestamp of the edge opening the segment (inclusive)
+    pub start_time: u64,
+    /// Timestamp of the edge closing the segment (exclusive)
+    pub end_time: u64,
+    /// Indices of the CD events with timestamps in `start_time..end_time`
+    pub events: Range<usize>,
+}
+
+/// Returns the timestamps of the `edge` edges on trigger `channel`.
+pub fn edge_times(triggers: &[TriggerEvent], channel: u8, edge: Edge) -> Vec<u64> {
+    triggers
+        .iter()
+        .filter(|t| t.id == channel && edge.matches(t.value))
+        .map(|t| t.timestamp)
+        .collect()
+}
+
+/// Splits CD events into segments delimited by consecutive `edge` edges on
+/// trigger `channel`.
+///
+/// With `n` matching edges there are `n - 1` segments, e.g. one per frame
+/// period when the trigger is a camera's frame clock. Events before the first
+/// and from the last edge onwards are not part of any segment. `cd_events`
+/// must be sorted by timestamp; segments only hold index ranges into it.
+pub fn slice_by_triggers(
+    cd_events: &[CdEvent],
+    triggers: &[TriggerEvent],
+    channel: u8,
+    edge: Edge,
+) -> Vec<TriggerSegment> {
+    let times = edge_times(triggers, channel, edge);
+    let index_at = |t: u64| cd_events.partition_point(|e| e.timestamp < t);
+    times
+        .windows(2)
+        .map(|pair| TriggerSegment {
+            start_time: pair[0],
+            end_time: pair[1],
+            events: index_at(pair[0])..index_at(pair[1]),
+        })
+        .collect()
+}
+
+/// Shifts all timestamps so that `origin` becomes t=0.
+///
+/// CD and trigger events before `origin` would get negative timestamps and
+/// are dropped.
+pub fn rebase_timestamps(
+    cd_events: &mut Vec<CdEvent>,
+    triggers: &mut Vec<TriggerEvent>,
+    origin: u64,
+) {
+    cd_events.retain_mut(|e| match e.timestamp.checked_sub(origin) {
+        Some(t) => {
+            e.timestamp = t;
+            true
+        }
+        None => false,
+    });
+    triggers.retain_mut(|e| match e.timestamp.checked_sub(origin) {
+        Some(t) => {
+            e.timestamp = t;
+            true
+        }
+        None => false,
+    });
+}
+
+/// Shifts all timestamps so that the earliest CD or trigger event is at t=0.
+///
+/// Returns the original timestamp of that event, or `None` if there are no
+/// events.
+pub fn rebase_to_first_event(
+    cd_events: &mut Vec<CdEvent>,
+    triggers: &mut Vec<TriggerEvent>,
+) -> Option<u64> {
+    let origin = cd_events
+        .iter()
+        .map(|e| e.timestamp)
+        .chain(triggers.iter().map(|e| e.timestamp))
+        .min()?;
+    rebase_timestamps(cd_events, triggers, origin);
+    Some(origin)
+}
+
+/// Shifts all timestamps so that t=0 is the first rising edge on trigger
+/// `channel`, e.g. the stimulus onset.
+///
+/// Events before that edge are dropped. Returns the original timestamp of
+/// the edge, or `None` (leaving the events untouched) if the channel has
+/// no rising edge.
+pub fn rebase_to_trigger(
+    cd_events: &mut Vec<CdEvent>,
+    triggers: &mut Vec<TriggerEvent>,
+    channel: u8,
+) -> Option<u64> {
+    let origin = triggers
+        .iter()
+        .find(|t| t.id == channel && Edge::Rising.matches(t.value))?
+        .timestamp;
+    rebase_timestamps(cd_events, triggers, origin);
+    Some(origin)
+}
+
+/// Number of leading edges tried as anchors by [`estimate_offset`].
+const SYNC_CANDIDATES: usize = 32;
+
+/// Estimated clock offset between two recordings of a shared trigger signal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SyncOffset {
+    /// Time to subtract from the other recording's timestamps to express
+    /// them in the reference recording's time base, in microseconds
+    pub offset: i64,
+    /// Number of edges matched between the recordings
+    pub matched_edges: usize,
+    /// Standard deviation of the matched edge differences in microseconds
+    pub residual: f64,
+}
+
+/// Estimates the offset between two recordings that share a trigger signal,
+/// e.g. the two cameras of a stereo pair.
+///
+/// The `edge` edges on `channel` are matched between both recordings. The
+/// recordings may start at different times: the first edges of each are
+/// tried as anchors and the alignment matching the most edges (within half
+/// the shortest reference period) wins. The offset is then averaged over
+/// all matched edges. Returns `None` if either recording has no such edge.
+pub fn estimate_offset(
+    reference: &[TriggerEvent],
+    other: &[TriggerEvent],
+    channel: u8,
+    edge: Edge,
+) -> Option<SyncOffset> {
+    let reference = edge_times(reference, channel, edge);
+    let other = edge_times(other, channel, edge);
+    if reference.is_empty() || other.is_empty() {
+        return None;
+    }
+
+    let tolerance = reference
+        .windows(2)
+        .map(|pair| pair[1] - pair[0])
+        .min()
+        .map_or(i64::MAX, |period| (period / 2) as i64);
+
+    // Differences `other - reference` of the edges matched under an offset
+    let matches = |offset: i64| -> Vec<i64> {
+        other
+            .iter()
+            .filter_map(|&t| {
+                let target = t as i64 - offset;
+                let index = reference.partition_point(|&r| (r as i64) < target);
+                [index.checked_sub(1), Some(index)]
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|i| reference.get(i))
+                    .map(|&r| t as i64 - r as i64)
+                    .min_by_key(|diff| (diff - offset).abs())
+                    .filter(|diff| (diff - offset).abs() <= tolerance)
+            })
+            .collect()
+    };
+
+    let candidates = other
+        .iter()
+        .take(SYNC_CANDIDATES)
+        .map(|&t| t as i64 - reference[0] as i64)
+        .chain(
+            reference
+                .iter()
+                .take(SYNC_CANDIDATES)
+                .map(|&r| other[0] as i64 - r as i64),
+        );
+    let best = candidates.map(matches).max_by_key(|diffs| diffs.len())?;
+
+    let n = best.len() as f64;
+    let mean = best.iter().map(|&d| d as f64).sum::<f64>() / n;
+    let variance = best.iter().map(|&d| (d as f64 - mean).powi(2)).sum::<f64>() / n;
+    Some(SyncOffset {
+        offset: mean.round() as i64,
+        matched_edges: best.len(),
+        residual: variance.sqrt(),
+    })
+}
+
+/// Moves events into another time base by subtracting `offset`, as returned
+/// by [`estimate_offset`].
+///
+/// Events that would end up before t=0 are dropped.
+pub fn apply_offset(cd_events: &mut Vec<CdEvent>, triggers: &mut Vec<TriggerEvent>, offset: i64) {
+    if offset >= 0 {
+        rebase_timestamps(cd_events, triggers, offset as u64);
+    } else {
+        let shift = offset.unsigned_abs();
+        cd_events.iter_mut().for_each(|e| e.timestamp += shift);
+        triggers.iter_mut().for_each(|e| e.timestamp += shift);
+    }
+}
+
+/// A trigger pulse: a rising edge followed by a falling edge on one channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TriggerPulse {
+    /// Trigger channel ID
+    pub id: u8,
+    /// Timestamp of the rising edge in microseconds
+    pub start: u64,
+    /// Time from the rising to the falling edge in microseconds
+    pub width: u64,
+}
+
+impl TriggerPulse {
+    /// Timestamp of the falling edge in microseconds.
+    pub fn end(&self) -> u64 {
+        self.start + self.width
+    }
+}
+
+/// Pairs rising and falling edges per channel into pulses, ordered by start.
+///
+/// A falling edge without a preceding rising edge (e.g. at the start of a
+/// recording) is ignored, as is a rising edge that is never closed. If two
+/// rising edges occur without a falling edge in between, the pulse starts
+/// at the later one.
+pub fn pair_pulses(triggers: &[TriggerEvent]) -> Vec<TriggerPulse> {
+    let mut open: HashMap<u8, u64> = HashMap::new();
+    let mut pulses = Vec::new();
+    for trigger in triggers {
+        if Edge::Rising.matches(trigger.value) {
+            open.insert(trigger.id, trigger.timestamp);
+        } else if let Some(start) = open.remove(&trigger.id) {
+            pulses.push(TriggerPulse {
+                id: trigger.id,
+                start,
+                width: trigger.timestamp - start,
+            });
+        }
+    }
+    pulses.sort_by_key(|p| p.start);
+    pulses
+}
+
+/// Frequency and jitter of the edges on one trigger channel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriggerTiming {
+    /// Trigger channel ID
+    pub id: u8,
+    /// Number of edges the estimate is based on
+    pub edge_count: u64,
+    /// Mean time between consecutive edges in microseconds
+    pub mean_period: f64,
+    /// Standard deviation of the time between edges in microseconds
+    pub jitter: f64,
+    /// Shortest time between consecutive edges in microseconds
+    pub min_period: u64,
+    /// Longest time between consecutive edges in microseconds
+    pub max_period: u64,
+}
+
+impl TriggerTiming {
+    /// Mean edge frequency in Hz.
+    pub fn frequency(&self) -> f64 {
+        1e6 / self.mean_period
+    }
+}
+
+/// Estimates the frequency and jitter of `edge` edges on every channel.
+///
+/// Returns one entry per channel with at least two such edges, ordered by
+/// channel ID. A long gap (e.g. a missing pulse) shows up in `max_period`
+/// and inflates the jitter.
+pub fn trigger_timing(triggers: &[TriggerEvent], edge: Edge) -> Vec<TriggerTiming> {
+    let mut accumulator = TimingAccumulator::new(edge);
+    accumulator.add(triggers);
+    accumulator.finish()
+}
+
+/// Running period statistics of one channel.
+#[derive(Debug, Clone, Default)]
+struct ChannelTiming {
+    last: Option<u64>,
+    edge_count: u64,
+    sum: f64,
+    sum_sq: f64,
+    min: u64,
+    max: u64,
+}
+
+/// Incremental [`trigger_timing`] over chunks of trigger events.
+#[derive(Debug, Clone)]
+pub(crate) struct TimingAccumulator {
+    edge: Edge,
+    channels: BTreeMap<u8, ChannelTiming>,
+}
+
+impl TimingAccumulator {
+    pub(crate) fn new(edge: Edge) -> Self {
+        Self {
+            edge,
+            channels: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn add(&mut self, triggers: &[TriggerEvent]) {
+        for trigger in triggers.iter().filter(|t| self.edge.matches(t.value)) {
+            let channel = self.channels.entry(trigger.id).or_default();
+            channel.edge_count += 1;
+            if let Some(last) = channel.last {
+                let period = trigger.timestamp - last;
+                if channel.edge_count == 2 {
+                    channel.min = period;
+                    channel.max = period;
+                } else {
+                    channel.min = channel.min.min(period);
+                    channel.max = channel.max.max(period);
+                }
+                channel.sum += period as f64;
+                channel.sum_sq += (period as f64) * (period as f64);
+            }
+            channel.last = Some(trigger.timestamp);
+        }
+    }
+
+    pub(crate) fn finish(&self) -> Vec<TriggerTiming> {
+        self.channels
+            .iter()
+            .filter(|(_, c)| c.edge_count >= 2)
+            .map(|(&id, c)| {
+                let n = (c.edge_count - 1) as f64;
+                let mean = c.sum / n;
+                TriggerTiming {
+                    id,
+                    edge_count: c.edge_count,
+                    mean_period: mean,
+                    jitter: (c.sum_sq / n - mean * mean).max(0.0).sqrt(),
+                    min_period: c.min,
+                    max_period: c.max,
+                }
+            })
+            .collect()
+    }
+}
+
+impl Default for TimingAccumulator {
+    fn default() -> Self {
+        Self::new(Edge::Rising)
+    }
+}
+
+/// Streaming debouncer that collapses trigger edges closer than a minimum gap.
+///
+/// Mechanical trigger sources bounce, producing bursts of edges around each
+/// real transition. An edge is kept only if no edge was kept on the same
+/// channel within the preceding `gap` microseconds, so each burst collapses
+/// to its first edge. Channels are debounced independently.
+#[derive(Debug, Clone)]
+pub struct TriggerDebouncer {
+    gap: u64,
+    last_kept: HashMap<u8, u64>,
+}
+
+impl TriggerDebouncer {
+    /// Creates a debouncer with the given minimum gap in microseconds.
+    pub fn new(gap: u64) -> Self {
+        Self {
+            gap,
+            last_kept: HashMap::new(),
+        }
+    }
+
+    /// Returns the minimum gap between kept edges in microseconds.
+    pub fn gap(&self) -> u64 {
+        self.gap
+    }
+
+    /// Returns true if the trigger event should be kept.
+    pub fn keep(&mut self, event: &TriggerEvent) -> bool {
+        match self.last_kept.get(&event.id) {
+            Some(&last) if event.timestamp.saturating_sub(last) < self.gap => false,
+            _ => {
+                self.last_kept.insert(event.id, event.timestamp);
+                true
+            }
+        }
+    }
+
+    /// Forgets the previously kept edges.
+    pub fn reset(&mut self) {
+        self.last_kept.clear();
+    }
+}
+
+/// Removes bouncing trigger edges in place, see [`TriggerDebouncer`].
+pub fn debounce(triggers: &mut Vec<TriggerEvent>, gap: u64) {
+    let mut debouncer = TriggerDebouncer::new(gap);
+    triggers.retain(|t| debouncer.keep(t));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cd_events(timestamps: &[u64]) -> Vec<CdEvent> {
+        timestamps
+            .iter()
+            .map(|&t| CdEvent::new(0, 0, 1, t))
+            .collect()
+    }
+
+    #[test]
+    fn test_edge_times() {
+        let triggers = [
+            TriggerEvent::new(1, 0, 100),
+            TriggerEvent::new(0, 0, 150),
+            TriggerEvent::new(1, 1, 160),
+            TriggerEvent::new(1, 0, 200),
+        ];
+        assert_eq!(edge_times(&triggers, 0, Edge::Rising), vec![100, 200]);
+        assert_eq!(edge_times(&triggers, 0, Edge::Falling), vec![150]);
+        assert_eq!(edge_times(&triggers, 1, Edge::Rising), vec![160]);
+    }
+
+    #[test]
+    fn test_slice_by_triggers() {
+        let events = cd_events(&[50, 100, 120, 199, 200, 250, 300, 350]);
+        let triggers = [
+            TriggerEvent::new(1, 0, 100),
+            TriggerEvent::new(0, 0, 150),
+            TriggerEvent::new(1, 2, 180),
+            TriggerEvent::new(1, 0, 200),
+            TriggerEvent::new(1, 0, 300),
+        ];
+        let segments = slice_by_triggers(&events, &triggers, 0, Edge::Rising);
+
+        assert_eq!(
+            segments,
+            vec![
+                TriggerSegment {
+                    start_time: 100,
+                    end_time: 200,
+                    events: 1..4,
+                },
+                TriggerSegment {
+                    start_time: 200,
+                    end_time: 300,
+                    events: 4..6,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_slice_without_enough_edges() {
+        let events = cd_events(&[1, 2, 3]);
+        let triggers = [TriggerEvent::new(1, 0, 2)];
+        assert!(slice_by_triggers(&events, &triggers, 0, Edge::Rising).is_empty());
+        assert!(slice_by_triggers(&events, &[], 0, Edge::Rising).is_empty());
+    }
+
+    #[test]
+    fn test_debounce_collapses_bursts() {
+        let mut triggers = vec![
+            TriggerEvent::new(1, 0, 1_000),
+            TriggerEvent::new(0, 0, 1_003),
+            TriggerEvent::new(1, 0, 1_007),
+            TriggerEvent::new(1, 1, 1_008),
+            TriggerEvent::new(0, 0, 5_000),
+            TriggerEvent::new(1, 0, 5_020),
+            TriggerEvent::new(1, 0, 5_100),
+        ];
+        debounce(&mut triggers, 50);
+
+        assert_eq!(
+            triggers,
+            vec![
+                TriggerEvent::new(1, 0, 1_000),
+                TriggerEvent::new(1, 1, 1_008),
+                TriggerEvent::new(0, 0, 5_000),
+                TriggerEvent::new(1, 0, 5_100),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_debouncer_reset() {
+        let mut debouncer = TriggerDebouncer::new(100);
+        assert!(debouncer.keep(&TriggerEvent::new(1, 0, 10)));
+        assert!(!debouncer.keep(&TriggerEvent::new(0, 0, 20)));
+        debouncer.reset();
+        assert!(debouncer.keep(&TriggerEvent::new(0, 0, 20)));
+    }
+
+    #[test]
+    fn test_pair_pulses() {
+        let triggers = [
+            TriggerEvent::new(0, 0, 5),
+            TriggerEvent::new(1, 0, 100),
+            TriggerEvent::new(1, 1, 110),
+            TriggerEvent::new(0, 0, 130),
+            TriggerEvent::new(1, 0, 200),
+            TriggerEvent::new(1, 0, 210),
+            TriggerEvent::new(0, 1, 250),
+            TriggerEvent::new(0, 0, 260),
+            TriggerEvent::new(1, 0, 300),
+        ];
+        let pulses = pair_pulses(&triggers);
+
+        assert_eq!(
+            pulses,
+            vec![
+                TriggerPulse {
+                    id: 0,
+                    start: 100,
+                    width: 30,
+                },
+                TriggerPulse {
+                    id: 1,
+                    start: 110,
+                    width: 140,
+                },
+                TriggerPulse {
+                    id: 0,
+                    start: 210,
+                    width: 50,
+                },
+            ]
+        );
+        assert_eq!(pulses[1].end(), 250);
+    }
+
+    #[test]
+    fn test_trigger_timing() {
+        // 1 kHz on channel 0 with +/-10 us jitter, a single edge on channel 1
+        let mut triggers = Vec::new();
+        for i in 0..100u64 {
+            let offset = if i % 2 == 1 { 10 } else { 0 };
+            triggers.push(TriggerEvent::new(1, 0, 5_000 + i * 1_000 + offset));
+            triggers.push(TriggerEvent::new(0, 0, 5_500 + i * 1_000));
+        }
+        triggers.push(TriggerEvent::new(1, 1, 7_000));
+
+        let timing = trigger_timing(&triggers, Edge::Rising);
+        assert_eq!(timing.len(), 1);
+        let ch0 = &timing[0];
+        assert_eq!(ch0.id, 0);
+        assert_eq!(ch0.edge_count, 100);
+        assert!((ch0.frequency() - 1_000.0).abs() < 0.2);
+        assert_eq!((ch0.min_period, ch0.max_period), (990, 1_010));
+        assert!((ch0.jitter - 10.0).abs() < 0.01);
+
+        let falling = trigger_timing(&triggers, Edge::Falling);
+        assert_eq!(falling[0].jitter, 0.0);
+        assert_eq!(falling[0].mean_period, 1_000.0);
+    }
+
+    #[test]
+    fn test_rebase_to_trigger() {
+        let mut events = cd_events(&[50, 100, 150]);
+        let mut triggers = vec![
+            TriggerEvent::new(0, 0, 20),
+            TriggerEvent::new(1, 1, 60),
+            TriggerEvent::new(1, 0, 100),
+            TriggerEvent::new(0, 0, 130),
+        ];
+        assert_eq!(rebase_to_trigger(&mut events, &mut triggers, 0), Some(100));
+
+        let timestamps: Vec<u64> = events.iter().map(|e| e.timestamp).collect();
+        assert_eq!(timestamps, vec![0, 50]);
+        assert_eq!(
+            triggers,
+            vec![TriggerEvent::new(1, 0, 0), TriggerEvent::new(0, 0, 30)]
+        );
+
+        // No rising edge on channel 3: nothing changes
+        assert_eq!(rebase_to_trigger(&mut events, &mut triggers, 3), None);
+        assert_eq!(events.len(), 2);
+    }
+
+    #[test]
+    fn test_rebase_to_first_event() {
+        let mut events = cd_events(&[1_050, 1_100]);
+        let mut triggers = vec![TriggerEvent::new(1, 0, 1_020)];
+        assert_eq!(
+            rebase_to_first_event(&mut events, &mut triggers),
+            Some(1_020)
+        );
+        let timestamps: Vec<u64> = events.iter().map(|e| e.timestamp).collect();
+        assert_eq!(timestamps, vec![30, 80]);
+        assert_eq!(triggers, vec![TriggerEvent::new(1, 0, 0)]);
+
+        assert_eq!(
+            rebase_to_first_event(&mut Vec::new(), &mut Vec::new()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_estimate_offset() {
+        // The other camera started later (misses 3 edges) and its clock is
+        // 123456 us ahead, with a few microseconds of jitter
+        let reference: Vec<TriggerEvent> = (0..50u64)
+            .map(|i| TriggerEvent::new(1, 0, 10_000 + i * 1_000))
+            .collect();
+        let other: Vec<TriggerEvent> = reference[3..]
+            .iter()
+            .enumerate()
+            .map(|(i, t)| {
+                let jitter = [0, 2, 0, 4][i % 4];
+                TriggerEvent::new(1, 0, t.timestamp + 123_456 + jitter)
+            })
+            .collect();
+
+        let sync = estimate_offset(&reference, &other, 0, Edge::Rising).unwrap();
+        assert_eq!(sync.matched_edges, 47);
+        assert!((sync.offset - 123_457).abs() <= 1, "offset {}", sync.offset);
+        assert!(sync.residual < 2.0);
+
+        assert!(estimate_offset(&reference, &other, 1, Edge::Rising).is_none());
+    }
+
+    #[test]
+    fn test_apply_offset() {
+        let mut events = cd_events(&[100, 200]);
+        let mut triggers = vec![TriggerEvent::new(1, 0, 150)];
+        apply_offset(&mut events, &mut triggers, 120);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].timestamp, 80);
+        assert_eq!(triggers[0].timestamp, 30);
+
+        apply_offset(&mut events, &mut triggers, -20);
+        assert_eq!(events[0].timestamp, 100);
+        assert_eq!(triggers[0].timestamp, 50);
+    }
+}
@@ -34,6 +34,158 @@ impl CdEvent {
     }
 }
 
+/// CD events stored as one vector per field (struct of arrays).
+///
+/// Filled directly by [`Evt3Decoder::decode_file_columnar`](crate::decoder::Evt3Decoder::decode_file_columnar)
+/// and [`decode_bytes_columnar`](crate::decoder::Evt3Decoder::decode_bytes_columnar),
+/// so columnar consumers such as numpy arrays or Arrow buffers can take the
+/// vectors without converting from `Vec<CdEvent>` first. All four vectors
+/// always have the same length.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnarEvents {
+    /// X coordinates
+    pub x: Vec<u16>,
+    /// Y coordinates
+    pub y: Vec<u16>,
+    /// Polarities: 0 = OFF, 1 = ON
+    pub polarity: Vec<u8>,
+    /// Timestamps in microseconds
+    pub timestamp: Vec<u64>,
+}
+
+impl ColumnarEvents {
+    /// Creates empty columns.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates empty columns with room for `capacity` events.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            x: Vec::with_capacity(capacity),
+            y: Vec::with_capacity(capacity),
+            polarity: Vec::with_capacity(capacity),
+            timestamp: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the number of events.
+    pub fn len(&self) -> usize {
+        self.timestamp.len()
+    }
+
+    /// Reserves room for at least `additional` more events in every column.
+    pub fn reserve(&mut self, additional: usize) {
+        self.x.reserve(additional);
+        self.y.reserve(additional);
+        self.polarity.reserve(additional);
+        self.timestamp.reserve(additional);
+    }
+
+    /// Returns `true` if there are no events.
+    pub fn is_empty(&self) -> bool {
+        self.timestamp.is_empty()
+    }
+
+    /// Appends an event.
+    #[inline]
+    pub fn push(&mut self, event: CdEvent) {
+        self.x.push(event.x);
+        self.y.push(event.y);
+        self.polarity.push(event.polarity);
+        self.timestamp.push(event.timestamp);
+    }
+
+    /// Returns the event at `index`, if any.
+    pub fn get(&self, index: usize) -> Option<CdEvent> {
+        Some(CdEvent::new(
+            *self.x.get(index)?,
+            self.y[index],
+            self.polarity[index],
+            self.timestamp[index],
+        ))
+    }
+
+    /// Iterates over the events.
+    pub fn iter(&self) -> impl Iterator<Item = CdEvent> + '_ {
+        (0..self.len())
+            .map(|i| CdEvent::new(self.x[i], self.y[i], self.polarity[i], self.timestamp[i]))
+    }
+
+    /// Removes all events, keeping the allocations.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Keeps the first `len` events.
+    pub fn truncate(&mut self, len: usize) {
+        self.x.truncate(len);
+        self.y.truncate(len);
+        self.polarity.truncate(len);
+        self.timestamp.truncate(len);
+    }
+
+    /// Keeps the events for which `keep` returns `true`, preserving order.
+    ///
+    /// `keep` may modify the events it keeps.
+    pub fn retain_mut<F: FnMut(&mut CdEvent) -> bool>(&mut self, keep: F) {
+        self.retain_tail(0, keep);
+    }
+
+    /// Like [`retain_mut`](Self::retain_mut) for the events from `start` on.
+    pub(crate) fn retain_tail<F: FnMut(&mut CdEvent) -> bool>(
+        &mut self,
+        start: usize,
+        mut keep: F,
+    ) {
+        let mut write = start;
+        for read in start..self.len() {
+            let mut event = CdEvent::new(
+                self.x[read],
+                self.y[read],
+                self.polarity[read],
+                self.timestamp[read],
+            );
+            if keep(&mut event) {
+                self.x[write] = event.x;
+                self.y[write] = event.y;
+                self.polarity[write] = event.polarity;
+                self.timestamp[write] = event.timestamp;
+                write += 1;
+            }
+        }
+        self.truncate(write);
+    }
+
+    /// Converts the columns to a vector of events.
+    pub fn to_events(&self) -> Vec<CdEvent> {
+        self.iter().collect()
+    }
+}
+
+impl Extend<CdEvent> for ColumnarEvents {
+    fn extend<I: IntoIterator<Item = CdEvent>>(&mut self, iter: I) {
+        for event in iter {
+            self.push(event);
+        }
+    }
+}
+
+impl<'a> Extend<&'a CdEvent> for ColumnarEvents {
+    fn extend<I: IntoIterator<Item = &'a CdEvent>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl FromIterator<CdEvent> for ColumnarEvents {
+    fn from_iter<I: IntoIterator<Item = CdEvent>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut columns = Self::with_capacity(iter.size_hint().0);
+        columns.extend(iter);
+        columns
+    }
+}
+
 /// An external trigger event.
 ///
 /// Trigger events indicate that an edge (change of electrical state) was
@@ -61,6 +213,52 @@ impl TriggerEvent {
     }
 }
 
+/// A CD or trigger event, for streams that interleave both kinds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// Change Detection event
+    Cd(CdEvent),
+    /// External trigger event
+    Trigger(TriggerEvent),
+}
+
+impl Event {
+    /// Returns the timestamp in microseconds.
+    #[inline]
+    pub fn timestamp(&self) -> u64 {
+        match self {
+            Event::Cd(e) => e.timestamp,
+            Event::Trigger(e) => e.timestamp,
+        }
+    }
+}
+
+/// Interleaves CD and trigger events in timestamp order.
+///
+/// Both inputs must be sorted by timestamp. On equal timestamps the trigger
+/// event comes first, so it opens the interval it marks.
+pub fn merge_by_time<'a>(
+    cd_events: &'a [CdEvent],
+    trigger_events: &'a [TriggerEvent],
+) -> impl Iterator<Item = Event> + 'a {
+    let (mut i, mut j) = (0, 0);
+    std::iter::from_fn(move || match (cd_events.get(i), trigger_events.get(j)) {
+        (Some(cd), Some(trigger)) if cd.timestamp < trigger.timestamp => {
+            i += 1;
+            Some(Event::Cd(*cd))
+        }
+        (_, Some(trigger)) => {
+            j += 1;
+            Some(Event::Trigger(*trigger))
+        }
+        (Some(cd), None) => {
+            i += 1;
+            Some(Event::Cd(*cd))
+        }
+        (None, None) => None,
+    })
+}
+
 /// EVT 3.0 raw event types.
 ///
 /// Each 16-bit word in the EVT 3.0 format has a 4-bit type field in the MSB
@@ -114,7 +312,7 @@ impl RawEventType {
 }
 
 /// Sensor metadata parsed from file headers.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SensorMetadata {
     /// Sensor width in pixels
     pub width: u32,
@@ -132,6 +330,52 @@ impl Default for SensorMetadata {
     }
 }
 
+/// A rectangular region of interest in pixel coordinates.
+///
+/// The region spans `x0..x1` horizontally and `y0..y1` vertically; the
+/// upper bounds are exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Roi {
+    /// Left edge (inclusive)
+    pub x0: u16,
+    /// Top edge (inclusive)
+    pub y0: u16,
+    /// Right edge (exclusive)
+    pub x1: u16,
+    /// Bottom edge (exclusive)
+    pub y1: u16,
+}
+
+impl Roi {
+    /// Creates a region from its corners, ordering them if needed.
+    pub fn new(x0: u16, y0: u16, x1: u16, y1: u16) -> Self {
+        Self {
+            x0: x0.min(x1),
+            y0: y0.min(y1),
+            x1: x0.max(x1),
+            y1: y0.max(y1),
+        }
+    }
+
+    /// Returns the width of the region in pixels.
+    #[inline]
+    pub fn width(&self) -> u16 {
+        self.x1 - self.x0
+    }
+
+    /// Returns the height of the region in pixels.
+    #[inline]
+    pub fn height(&self) -> u16 {
+        self.y1 - self.y0
+    }
+
+    /// Returns `true` if the pixel lies inside the region.
+    #[inline]
+    pub fn contains(&self, x: u16, y: u16) -> bool {
+        x >= self.x0 && x < self.x1 && y >= self.y0 && y < self.y1
+    }
+}
+
 /// Result of decoding an EVT 3.0 file.
 #[derive(Debug)]
 pub struct DecodeResult {
@@ -143,6 +387,75 @@ pub struct DecodeResult {
     pub metadata: SensorMetadata,
 }
 
+/// A batch of events sent by [`Evt3Decoder::spawn_decode`](crate::decoder::Evt3Decoder::spawn_decode)
+/// or yielded by `Evt3Decoder::decode_file_async`.
+#[derive(Debug, Clone)]
+pub struct EventChunk {
+    /// CD events
+    pub cd_events: Vec<CdEvent>,
+    /// Trigger events decoded since the previous chunk
+    pub trigger_events: Vec<TriggerEvent>,
+    /// Output sensor geometry
+    pub metadata: SensorMetadata,
+}
+
+/// Result of decoding an EVT 3.0 file into [`ColumnarEvents`].
+#[derive(Debug)]
+pub struct ColumnarDecodeResult {
+    /// Decoded CD events, one vector per field
+    pub cd_events: ColumnarEvents,
+    /// Decoded trigger events
+    pub trigger_events: Vec<TriggerEvent>,
+    /// Sensor metadata
+    pub metadata: SensorMetadata,
+}
+
+/// Quick overview of an EVT 3.0 file obtained without a full decode.
+///
+/// See [`Evt3Decoder::probe`](crate::decoder::Evt3Decoder::probe).
+#[derive(Debug, Clone)]
+pub struct ProbeResult {
+    /// Sensor metadata from the file header
+    pub metadata: SensorMetadata,
+    /// Total file size in bytes
+    pub file_size: u64,
+    /// Size of the text header in bytes (offset of the event data)
+    pub header_size: u64,
+    /// Text header lines without the leading `%` and the closing `% end`
+    pub header: Vec<String>,
+    /// Timestamp of the first CD event, if any
+    pub first_timestamp: Option<u64>,
+    /// Timestamp of the last CD event, if any
+    pub last_timestamp: Option<u64>,
+    /// Number of CD events extrapolated from the sampled data
+    pub estimated_event_count: u64,
+    /// `true` if the whole file was decoded, making all values exact
+    pub exact: bool,
+}
+
+impl ProbeResult {
+    /// Returns the time between the first and last CD event in microseconds.
+    pub fn duration(&self) -> u64 {
+        match (self.first_timestamp, self.last_timestamp) {
+            (Some(first), Some(last)) => last.saturating_sub(first),
+            _ => 0,
+        }
+    }
+
+    /// Returns the event format named in the header, e.g. `"EVT3"` from
+    /// `% format EVT3;width=1280;height=720` or `"EVT 3.0"` from `% evt 3.0`.
+    pub fn format_version(&self) -> Option<String> {
+        self.header.iter().find_map(|line| {
+            if let Some(format) = line.strip_prefix("format ") {
+                format.split(';').next().map(|name| name.trim().to_string())
+            } else {
+                line.strip_prefix("evt ")
+                    .map(|version| format!("EVT {}", version.trim()))
+            }
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -164,4 +477,52 @@ mod tests {
         assert_eq!(event.polarity, 1);
         assert_eq!(event.timestamp, 12345);
     }
+
+    #[test]
+    fn test_columnar_events() {
+        let events = vec![
+            CdEvent::new(1, 2, 1, 10),
+            CdEvent::new(3, 4, 0, 20),
+            CdEvent::new(5, 6, 1, 30),
+        ];
+        let mut columns: ColumnarEvents = events.iter().copied().collect();
+        assert_eq!(columns.len(), 3);
+        assert_eq!(columns.x, vec![1, 3, 5]);
+        assert_eq!(columns.timestamp, vec![10, 20, 30]);
+        assert_eq!(columns.get(1), Some(events[1]));
+        assert_eq!(columns.get(3), None);
+        assert_eq!(columns.to_events(), events);
+
+        columns.retain_mut(|e| {
+            e.x += 1;
+            e.polarity == 1
+        });
+        assert_eq!(columns.x, vec![2, 6]);
+        assert_eq!(columns.polarity, vec![1, 1]);
+        columns.clear();
+        assert!(columns.is_empty());
+    }
+
+    #[test]
+    fn test_roi() {
+        let roi = Roi::new(100, 50, 10, 20);
+        assert_eq!((roi.x0, roi.y0, roi.x1, roi.y1), (10, 20, 100, 50));
+        assert_eq!((roi.width(), roi.height()), (90, 30));
+        assert!(roi.contains(10, 20));
+        assert!(!roi.contains(100, 20));
+        assert!(!roi.contains(50, 50));
+    }
+
+    #[test]
+    fn test_merge_by_time() {
+        let cd = [CdEvent::new(0, 0, 1, 10), CdEvent::new(1, 0, 1, 20)];
+        let triggers = [TriggerEvent::new(1, 0, 5), TriggerEvent::new(0, 0, 20)];
+        let timestamps: Vec<(bool, u64)> = merge_by_time(&cd, &triggers)
+            .map(|e| (matches!(e, Event::Cd(_)), e.timestamp()))
+            .collect();
+        assert_eq!(
+            timestamps,
+            vec![(false, 5), (true, 10), (false, 20), (true, 20)]
+        );
+    }
 }
@@ -0,0 +1,311 @@
+//! Integrity checks of EVT 3.0 raw files.
+//!
+//! [`validate_file`] decodes a whole file and counts everything that points
+//! at a damaged or misconfigured recording: an incomplete or inconsistent
+//! header, word types not defined by EVT 3.0, data that does not start at a
+//! time base or ends mid-word, events outside the sensor and timestamps that
+//! run backwards.
+
+use crate::decoder::{DecodeError, Evt3Decoder};
+use crate::parser;
+use crate::types::{RawEventType, SensorMetadata};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::Path;
+
+/// Buffer size for reading raw data (bytes).
+const READ_BUFFER_SIZE: usize = 1 << 20;
+
+/// Findings of [`validate_file`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// Sensor geometry used for the bounds check
+    pub metadata: SensorMetadata,
+    /// Problems with the text header
+    pub header_issues: Vec<String>,
+    /// Number of complete 16-bit data words
+    pub word_count: u64,
+    /// Words with a type not defined by EVT 3.0
+    pub unknown_words: u64,
+    /// Words before the first TIME_HIGH, which cannot be timestamped
+    pub leading_words: u64,
+    /// Bytes after the last complete word (a truncated file)
+    pub trailing_bytes: u64,
+    /// Number of CD events
+    pub cd_events: u64,
+    /// Number of trigger events
+    pub trigger_events: u64,
+    /// CD events outside the sensor geometry
+    pub out_of_bounds: u64,
+    /// CD or trigger events earlier than the previous event of the same kind
+    pub timestamp_regressions: u64,
+    /// Largest backwards step in microseconds
+    pub max_regression: u64,
+}
+
+impl ValidationReport {
+    /// Describes every failed check; empty if the file passed.
+    pub fn issues(&self) -> Vec<String> {
+        let mut issues = self.header_issues.clone();
+        if self.unknown_words > 0 {
+            issues.push(format!("{} words of unknown type", self.unknown_words));
+        }
+        if self.leading_words > 0 {
+            issues.push(format!(
+                "{} words before the first TIME_HIGH",
+                self.leading_words
+            ));
+        }
+        if self.trailing_bytes > 0 {
+            issues.push(format!(
+                "truncated: {} byte(s) after the last complete word",
+                self.trailing_bytes
+            ));
+        }
+        if self.out_of_bounds > 0 {
+            issues.push(format!(
+                "{} events outside the {}x{} sensor",
+                self.out_of_bounds, self.metadata.width, self.metadata.height
+            ));
+        }
+        if self.timestamp_regressions > 0 {
+            issues.push(format!(
+                "{} timestamp regressions (up to {} us)",
+                self.timestamp_regressions, self.max_regression
+            ));
+        }
+        issues
+    }
+
+    /// Returns `true` if all checks passed.
+    pub fn is_valid(&self) -> bool {
+        self.issues().is_empty()
+    }
+}
+
+/// Checks the header and decodes the whole file, collecting a
+/// [`ValidationReport`]. Only I/O errors are returned as errors.
+pub fn validate_file<P: AsRef<Path>>(path: P) -> Result<ValidationReport, DecodeError> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut decoder = Evt3Decoder::new();
+    let header = decoder.parse_header(&mut reader)?;
+    let header_size = reader.stream_position()?;
+
+    let mut report = ValidationReport {
+        metadata: decoder.metadata.clone(),
+        header_issues: check_header(&header, header_size, &mut reader)?,
+        ..Default::default()
+    };
+
+    let mut buffer = vec![0u8; READ_BUFFER_SIZE];
+    let mut carry = None;
+    let mut words = Vec::new();
+    let mut cd_events = Vec::new();
+    let mut trigger_events = Vec::new();
+    let mut seen_time_high = false;
+    let mut last_cd = None;
+    let mut last_trigger = None;
+
+    loop {
+        let bytes_read = reader.read(&mut buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+
+        // Reads may end mid-word; keep the odd byte for the next buffer
+        words.clear();
+        let mut bytes = &buffer[..bytes_read];
+        if let Some(low) = carry.take() {
+            words.push(u16::from_le_bytes([low, bytes[0]]));
+            bytes = &bytes[1..];
+        }
+        let mut chunks = bytes.chunks_exact(2);
+        words.extend(chunks.by_ref().map(|c| u16::from_le_bytes([c[0], c[1]])));
+        carry = chunks.remainder().first().copied();
+
+        for &word in &words {
+            let event_type = parser::get_event_type(word);
+            if RawEventType::from_u8(event_type).is_none() {
+                report.unknown_words += 1;
+            }
+            if event_type == RawEventType::TimeHigh as u8 {
+                seen_time_high = true;
+            } else if !seen_time_high {
+                report.leading_words += 1;
+            }
+        }
+        report.word_count += words.len() as u64;
+
+        decoder.decode_buffer(&words, &mut cd_events, &mut trigger_events);
+        let (width, height) = (report.metadata.width, report.metadata.height);
+        for event in &cd_events {
+            if event.x as u32 >= width || event.y as u32 >= height {
+                report.out_of_bounds += 1;
+            }
+            check_order(&mut report, &mut last_cd, event.timestamp);
+        }
+        for event in &trigger_events {
+            check_order(&mut report, &mut last_trigger, event.timestamp);
+        }
+        report.cd_events += cd_events.len() as u64;
+        report.trigger_events += trigger_events.len() as u64;
+        cd_events.clear();
+        trigger_events.clear();
+    }
+    report.trailing_bytes = carry.is_some() as u64;
+
+    Ok(report)
+}
+
+/// Counts `timestamp` as a regression if it is earlier than `last`.
+fn check_order(report: &mut ValidationReport, last: &mut Option<u64>, timestamp: u64) {
+    if let Some(previous) = *last {
+        if timestamp < previous {
+            report.timestamp_regressions += 1;
+            report.max_regression = report.max_regression.max(previous - timestamp);
+        }
+    }
+    *last = Some(timestamp);
+}
+
+/// Checks the header lines for a format, a geometry and a closing `% end`.
+fn check_header<R: Read + Seek>(
+    header: &[String],
+    header_size: u64,
+    reader: &mut R,
+) -> Result<Vec<String>, DecodeError> {
+    let mut issues = Vec::new();
+    if header_size == 0 {
+        issues.push("no text header".to_string());
+        return Ok(issues);
+    }
+
+    // The parsed lines omit `% end`, so look at the raw header text
+    let position = reader.stream_position()?;
+    reader.rewind()?;
+    let mut text = vec![0u8; header_size as usize];
+    reader.read_exact(&mut text)?;
+    reader.seek(std::io::SeekFrom::Start(position))?;
+    if !text
+        .split(|&b| b == b'\n')
+        .any(|line| line.starts_with(b"% end"))
+    {
+        issues.push("header has no '% end' line".to_string());
+    }
+
+    let mut format_geometry = None;
+    let mut geometry = None;
+    let mut format = None;
+    for line in header {
+        if let Some(spec) = line.strip_prefix("format ") {
+            let mut parts = spec.split(';');
+            format = parts.next().map(|name| name.trim().to_string());
+            let (mut width, mut height) = (None, None);
+            for part in parts {
+                match part.split_once('=') {
+                    Some(("width", value)) => width = value.trim().parse::<u32>().ok(),
+                    Some(("height", value)) => height = value.trim().parse::<u32>().ok(),
+                    _ => {}
+                }
+            }
+            format_geometry = width.zip(height);
+        } else if let Some(spec) = line.strip_prefix("geometry ") {
+            geometry = spec
+                .split_once('x')
+                .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)));
+        } else if let Some(version) = line.strip_prefix("evt ") {
+            if version.trim() != "3.0" {
+                issues.push(format!("header declares EVT {}", version.trim()));
+            }
+            format.get_or_insert_with(|| "EVT3".to_string());
+        }
+    }
+
+    match format.as_deref() {
+        None => issues.push("header names no event format".to_string()),
+        Some("EVT3") => {}
+        Some(other) => issues.push(format!("header declares format {}", other)),
+    }
+    match (format_geometry, geometry) {
+        (None, None) => issues.push("header has no sensor geometry".to_string()),
+        (Some(a), Some(b)) if a != b => issues.push(format!(
+            "format line says {}x{} but geometry line says {}x{}",
+            a.0, a.1, b.0, b.1
+        )),
+        _ => {}
+    }
+    Ok(issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::Evt3Writer;
+    use crate::types::{CdEvent, TriggerEvent};
+
+    fn write_file(data: &[u8]) -> tempfile::NamedTempFile {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, data).unwrap();
+        file
+    }
+
+    fn encode(events: &[CdEvent], triggers: &[TriggerEvent]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut writer = Evt3Writer::new(&mut data);
+        writer
+            .write_header(&SensorMetadata {
+                width: 640,
+                height: 480,
+            })
+            .unwrap();
+        writer.write_merged(events, triggers).unwrap();
+        writer.flush().unwrap();
+        drop(writer);
+        data
+    }
+
+    #[test]
+    fn test_validate_clean_file() {
+        let events = [CdEvent::new(1, 2, 1, 100), CdEvent::new(639, 479, 0, 200)];
+        let file = write_file(&encode(&events, &[TriggerEvent::new(1, 0, 150)]));
+        let report = validate_file(file.path()).unwrap();
+
+        assert!(report.is_valid(), "{:?}", report.issues());
+        assert_eq!(report.cd_events, 2);
+        assert_eq!(report.trigger_events, 1);
+        assert_eq!((report.metadata.width, report.metadata.height), (640, 480));
+    }
+
+    #[test]
+    fn test_validate_damaged_file() {
+        let events = [CdEvent::new(1, 2, 1, 100), CdEvent::new(700, 2, 1, 200)];
+        let mut data = encode(&events, &[]);
+        // The writer refuses to go back in time, so step back to 150 us by hand
+        data.extend_from_slice(&(0x6000u16 | 150).to_le_bytes()); // TIME_LOW
+        data.extend_from_slice(&0x2801u16.to_le_bytes()); // ADDR_X 1, ON
+        data.extend_from_slice(&0x9000u16.to_le_bytes()); // reserved type
+        data.push(0x12); // half a word
+        let file = write_file(&data);
+        let report = validate_file(file.path()).unwrap();
+
+        assert!(!report.is_valid());
+        assert_eq!(report.out_of_bounds, 1);
+        assert_eq!(report.timestamp_regressions, 1);
+        assert_eq!(report.max_regression, 50);
+        assert_eq!(report.unknown_words, 1);
+        assert_eq!(report.trailing_bytes, 1);
+        assert_eq!(report.issues().len(), 4);
+    }
+
+    #[test]
+    fn test_validate_header() {
+        let mut data = b"% format EVT3;width=640;height=480\n% geometry 320x240\n".to_vec();
+        data.extend_from_slice(&0x8000u16.to_le_bytes());
+        let report = validate_file(write_file(&data).path()).unwrap();
+        assert_eq!(report.header_issues.len(), 2, "{:?}", report.header_issues);
+
+        let report = validate_file(write_file(&0x2001u16.to_le_bytes()).path()).unwrap();
+        assert_eq!(report.header_issues, ["no text header"]);
+        assert_eq!(report.leading_words, 1);
+    }
+}
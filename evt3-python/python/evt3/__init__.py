@@ -15,24 +15,87 @@ Example:
     >>> p = events.polarity  # np.ndarray[np.uint8]
     >>> t = events.timestamp  # np.ndarray[np.uint64]
     
-    # Or get as dictionary for DataFrame creation
-    >>> import pandas as pd
-    >>> df = pd.DataFrame(events.to_dict())
+    # Or get as a pandas DataFrame
+    >>> df = events.to_pandas()
 """
 
 from ._evt3 import (
     decode_file,
     decode_file_with_triggers,
+    decode_file_into,
     decode_bytes,
+    decode_bytes_with_triggers,
+    decode_stream,
+    decode_stream_with_triggers,
+    set_default_geometry,
+    get_default_geometry,
+    iter_file,
+    frame_generator,
+    concatenate,
+    write_csv,
+    write_binary,
+    write_mat,
+    write_es,
+    encode_file,
+    voxel_grid,
+    slice_by_triggers,
+    debounce_triggers,
+    trigger_pulses,
+    synchronize,
     Events,
     TriggerEvents,
+    Decoder,
+    EventChunks,
+    FrameGenerator,
+    EventIter,
+    EventDataset,
+    Evt3File,
+    Event,
+    DecodeError,
+    FormatError,
+    TruncatedFileError,
 )
+from ._async import decode_file_async, decode_file_with_triggers_async
+from ._binary import open_binary, BinaryFile
 
 __version__ = "0.1.0"
 __all__ = [
     "decode_file",
     "decode_file_with_triggers", 
+    "decode_file_into",
+    "decode_file_async",
+    "decode_file_with_triggers_async",
     "decode_bytes",
+    "decode_bytes_with_triggers",
+    "decode_stream",
+    "decode_stream_with_triggers",
+    "set_default_geometry",
+    "get_default_geometry",
+    "iter_file",
+    "frame_generator",
+    "concatenate",
+    "write_csv",
+    "write_binary",
+    "write_mat",
+    "write_es",
+    "open_binary",
+    "BinaryFile",
+    "encode_file",
+    "voxel_grid",
+    "slice_by_triggers",
+    "debounce_triggers",
+    "trigger_pulses",
+    "synchronize",
     "Events",
     "TriggerEvents",
+    "Decoder",
+    "EventChunks",
+    "FrameGenerator",
+    "EventIter",
+    "EventDataset",
+    "Evt3File",
+    "Event",
+    "DecodeError",
+    "FormatError",
+    "TruncatedFileError",
 ]
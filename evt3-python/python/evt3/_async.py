@@ -0,0 +1,29 @@
+"""asyncio wrappers that decode on a worker thread."""
+
+import asyncio
+import functools
+
+from ._evt3 import decode_file, decode_file_with_triggers
+
+
+async def decode_file_async(path, **kwargs):
+    """Decodes a file like `decode_file` without blocking the event loop.
+
+    The decode runs in the loop's default executor (a thread pool) with the
+    GIL released, so other tasks keep running meanwhile. Takes the same
+    keyword arguments as `decode_file`; a `progress` callback is called on
+    the worker thread.
+
+    Example:
+        >>> events = await evt3.decode_file_async("recording.raw", t_end=1_000_000)
+    """
+    loop = asyncio.get_running_loop()
+    return await loop.run_in_executor(None, functools.partial(decode_file, path, **kwargs))
+
+
+async def decode_file_with_triggers_async(path, **kwargs):
+    """Like `decode_file_async`, returning (Events, TriggerEvents)."""
+    loop = asyncio.get_running_loop()
+    return await loop.run_in_executor(
+        None, functools.partial(decode_file_with_triggers, path, **kwargs)
+    )
@@ -0,0 +1,157 @@
+"""Memory-mapped access to EVT3BIN files and column directories."""
+
+import json
+import os
+
+import numpy as np
+
+from ._evt3 import FormatError, TruncatedFileError, _restore_events
+
+_HEADER_LEN = 28
+_VERSION_EVENT_KINDS = 2
+_KIND_CD = 0
+_HEADER = np.dtype([
+    ("magic", "S8"),
+    ("version", "<u4"),
+    ("width", "<u4"),
+    ("height", "<u4"),
+    ("event_count", "<u8"),
+])
+# Packed like the writer's records: 14 bytes per event
+_RECORD = np.dtype([
+    ("x", "<u2"),
+    ("y", "<u2"),
+    ("polarity", "u1"),
+    ("kind", "u1"),
+    ("timestamp", "<u8"),
+])
+
+
+def _memmap(path, dtype, count, offset=0):
+    """Maps `count` values of `dtype`; mmap cannot map zero bytes."""
+    if count == 0:
+        return np.zeros(0, dtype=dtype)
+    return np.memmap(path, dtype=dtype, mode="r", offset=offset, shape=(count,))
+
+
+class BinaryFile:
+    """A memory-mapped EVT3BIN file or column directory.
+
+    The columns are numpy views of the mapped file: nothing is read until
+    they are indexed, so files larger than memory can be sliced like arrays.
+    Indexing the file itself with a slice copies that range into `Events`.
+
+    Version 2 EVT3BIN files interleave trigger records, marked by `kind`
+    (0 = CD, 1 = trigger); the columns include them, while slices into
+    `Events` keep the CD records only. `kind` is None for other files.
+    """
+
+    def __init__(self, path):
+        self.path = os.fspath(path)
+        self.kind = None
+        if os.path.isdir(self.path):
+            self._open_columns()
+        else:
+            self._open_binary()
+
+    def _open_binary(self):
+        size = os.path.getsize(self.path)
+        if size < _HEADER_LEN:
+            raise FormatError(f"Not an EVT3BIN file: {self.path}")
+        header = np.fromfile(self.path, dtype=_HEADER, count=1)[0]
+        if header["magic"] != b"EVT3BIN":
+            raise FormatError(f"Missing EVT3BIN magic number: {self.path}")
+        if header["version"] not in (1, _VERSION_EVENT_KINDS):
+            raise FormatError(f"Unsupported EVT3BIN version: {header['version']}")
+        count = int(header["event_count"])
+        if size < _HEADER_LEN + count * _RECORD.itemsize:
+            raise TruncatedFileError(
+                f"{self.path} holds fewer than the {count} events in its header"
+            )
+        self.sensor_width = int(header["width"])
+        self.sensor_height = int(header["height"])
+
+        records = _memmap(self.path, _RECORD, count, offset=_HEADER_LEN)
+        self.x = records["x"]
+        self.y = records["y"]
+        self.polarity = records["polarity"]
+        self.timestamp = records["timestamp"]
+        if header["version"] == _VERSION_EVENT_KINDS:
+            self.kind = records["kind"]
+
+    def _open_columns(self):
+        manifest_path = os.path.join(self.path, "manifest.json")
+        try:
+            with open(manifest_path) as f:
+                manifest = json.load(f)
+        except FileNotFoundError:
+            raise FormatError(f"No manifest.json in column directory: {self.path}") from None
+        if manifest.get("format") != "evt3-columns":
+            raise FormatError(f"Not an evt3 column manifest: {manifest_path}")
+        count = manifest["event_count"]
+        self.sensor_width = manifest["width"]
+        self.sensor_height = manifest["height"]
+
+        for column in manifest["columns"]:
+            column_path = os.path.join(self.path, column["file"])
+            dtype = np.dtype(column["dtype"])
+            if os.path.getsize(column_path) < count * dtype.itemsize:
+                raise TruncatedFileError(
+                    f"{column_path} holds fewer than the {count} events in the manifest"
+                )
+            setattr(self, column["name"], _memmap(column_path, dtype, count))
+
+    # Short aliases, as on Events
+    @property
+    def p(self):
+        return self.polarity
+
+    @property
+    def t(self):
+        return self.timestamp
+
+    @property
+    def sensor_size(self):
+        return (self.sensor_width, self.sensor_height)
+
+    def __len__(self):
+        return len(self.timestamp)
+
+    def __getitem__(self, key):
+        if not isinstance(key, slice):
+            raise TypeError("BinaryFile indices must be slices; index the columns for single events")
+        columns = [self.x[key], self.y[key], self.polarity[key], self.timestamp[key]]
+        if self.kind is not None:
+            cd = self.kind[key] == _KIND_CD
+            columns = [values[cd] for values in columns]
+        x, y, polarity, timestamp = (np.array(values) for values in columns)
+        return _restore_events(x, y, polarity, timestamp,
+                               self.sensor_width, self.sensor_height, False, [])
+
+    def time_slice(self, t_start, t_end):
+        """Returns the events with t_start <= t < t_end as `Events`.
+
+        The range is found by binary search on the mapped timestamps, so
+        only the pages around its ends and inside it are read.
+        """
+        start, end = np.searchsorted(self.timestamp, [t_start, t_end])
+        return self[start:end]
+
+    def __repr__(self):
+        return (f"BinaryFile({self.path!r}, {len(self)} events, "
+                f"{self.sensor_width}x{self.sensor_height})")
+
+
+def open_binary(path):
+    """Memory-maps an EVT3BIN file or a column directory.
+
+    Accepts the outputs of `evt3-decode --format bin` (or `write_binary`)
+    and `--format cols`. Returns a `BinaryFile` whose x, y, polarity and
+    timestamp columns are lazily read numpy views of the file.
+
+    Example:
+        >>> data = evt3.open_binary("recording.bin")
+        >>> data.t[-1]                         # reads one page
+        >>> events = data.time_slice(0, 1_000_000)
+    """
+    return BinaryFile(path)
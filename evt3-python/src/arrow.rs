@@ -0,0 +1,142 @@
+//! Export of event columns to pyarrow through the Arrow C data interface.
+//!
+//! Each numpy column is described by an `ArrowSchema` and an `ArrowArray`
+//! (<https://arrow.apache.org/docs/format/CDataInterface.html>) whose data
+//! buffer points into the numpy array, and handed to
+//! `pyarrow.Array._import_from_c`. The array's private data holds a
+//! reference to the numpy array, so the buffer stays alive until pyarrow
+//! releases it.
+
+use numpy::{Element, IntoPyArray, PyArray1};
+use pyo3::prelude::*;
+use std::ffi::{c_char, c_void};
+use std::ptr;
+
+/// `struct ArrowSchema` of the C data interface.
+#[repr(C)]
+struct ArrowSchema {
+    format: *const c_char,
+    name: *const c_char,
+    metadata: *const c_char,
+    flags: i64,
+    n_children: i64,
+    children: *mut *mut ArrowSchema,
+    dictionary: *mut ArrowSchema,
+    release: Option<unsafe extern "C" fn(*mut ArrowSchema)>,
+    private_data: *mut c_void,
+}
+
+/// `struct ArrowArray` of the C data interface.
+#[repr(C)]
+struct ArrowArray {
+    length: i64,
+    null_count: i64,
+    offset: i64,
+    n_buffers: i64,
+    n_children: i64,
+    buffers: *mut *const c_void,
+    children: *mut *mut ArrowArray,
+    dictionary: *mut ArrowArray,
+    release: Option<unsafe extern "C" fn(*mut ArrowArray)>,
+    private_data: *mut c_void,
+}
+
+/// Private data of an exported array: its buffer list (no validity bitmap,
+/// then the values) and the numpy array owning the values.
+struct ArrayOwner {
+    buffers: [*const c_void; 2],
+    _array: PyObject,
+}
+
+unsafe extern "C" fn release_schema(schema: *mut ArrowSchema) {
+    // The format string is static and nothing else is owned
+    (*schema).release = None;
+}
+
+unsafe extern "C" fn release_array(array: *mut ArrowArray) {
+    let owner = Box::from_raw((*array).private_data as *mut ArrayOwner);
+    // pyarrow may release the buffer from one of its own threads
+    Python::with_gil(|_| drop(owner));
+    (*array).release = None;
+}
+
+/// Arrow format string of a numpy element type.
+pub trait ArrowFormat: Element {
+    /// Null-terminated format string, e.g. `S` for uint16.
+    const FORMAT: &'static [u8];
+}
+
+impl ArrowFormat for u8 {
+    const FORMAT: &'static [u8] = b"C\0";
+}
+
+impl ArrowFormat for u16 {
+    const FORMAT: &'static [u8] = b"S\0";
+}
+
+impl ArrowFormat for u64 {
+    const FORMAT: &'static [u8] = b"L\0";
+}
+
+/// Returns `column` as a pyarrow Array sharing its buffer.
+///
+/// A non-contiguous column (e.g. a strided view) is copied into a new
+/// contiguous array first.
+pub fn import_column<T: ArrowFormat>(
+    py: Python<'_>,
+    pyarrow: &PyModule,
+    column: &Py<PyArray1<T>>,
+) -> PyResult<PyObject> {
+    let mut column = column.as_ref(py);
+    if !column.is_contiguous() {
+        column = column.to_owned_array().into_pyarray(py);
+    }
+
+    let mut schema = ArrowSchema {
+        format: T::FORMAT.as_ptr() as *const c_char,
+        name: ptr::null(),
+        metadata: ptr::null(),
+        flags: 0,
+        n_children: 0,
+        children: ptr::null_mut(),
+        dictionary: ptr::null_mut(),
+        release: Some(release_schema),
+        private_data: ptr::null_mut(),
+    };
+    let owner = Box::into_raw(Box::new(ArrayOwner {
+        buffers: [ptr::null(), column.data() as *const c_void],
+        _array: column.to_object(py),
+    }));
+    let mut array = ArrowArray {
+        length: column.len() as i64,
+        null_count: 0,
+        offset: 0,
+        n_buffers: 2,
+        n_children: 0,
+        // SAFETY: `owner` was just allocated and lives until `release_array`
+        buffers: unsafe { (*owner).buffers.as_mut_ptr() },
+        children: ptr::null_mut(),
+        dictionary: ptr::null_mut(),
+        release: Some(release_array),
+        private_data: owner as *mut c_void,
+    };
+
+    // pyarrow moves both structures out, marking them released
+    let imported = pyarrow.getattr("Array")?.call_method1(
+        "_import_from_c",
+        (
+            &mut array as *mut ArrowArray as usize,
+            &mut schema as *mut ArrowSchema as usize,
+        ),
+    );
+    // SAFETY: the structures are still ours if the import failed early
+    unsafe {
+        if let Some(release) = array.release {
+            release(&mut array);
+        }
+        if let Some(release) = schema.release {
+            release(&mut schema);
+        }
+    }
+    Ok(imported?.into())
+}
@@ -3,89 +3,256 @@
 //! This module provides Python bindings using PyO3 that allow efficient
 //! decoding of EVT 3.0 files with direct numpy array access to the decoded data.
 
-use evt3_core::{CdEvent, Evt3Decoder, TriggerEvent};
-use numpy::{IntoPyArray, PyArray1};
-use pyo3::exceptions::PyIOError;
+// pyo3 0.20 expands `#[new]` constructors into impls inside functions
+#![allow(non_local_definitions)]
+
+mod arrow;
+
+use evt3_core::filters::{
+    self, BackgroundActivityFilter, DuplicateFilter, EventFilter, PixelMaskFilter, PolarityFilter,
+    RoiFilter,
+};
+use evt3_core::output::{self, CsvOptions, OutputError};
+use evt3_core::representations;
+use evt3_core::transforms::{self, Crop, EventTransform, PolarityMap};
+use evt3_core::triggers::{self, Edge};
+use evt3_core::{frames, input};
+use evt3_core::{
+    CdEvent, ColumnarDecodeResult, ColumnarEvents, Evt3Decoder, Roi, SensorMetadata, TimeIndex,
+    TriggerEvent,
+};
+use numpy::{Element, IntoPyArray, PyArray1, PyArray2, PyArray3, PyReadonlyArray1};
+use pyo3::create_exception;
+use pyo3::exceptions::{PyIOError, PyImportError, PyIndexError, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::PyDict;
-use std::path::PathBuf;
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyDict, PySlice};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Moves a decoded column into a numpy array owned by Python.
+fn column<T: Element>(py: Python<'_>, values: Vec<T>) -> Py<PyArray1<T>> {
+    values.into_pyarray(py).into()
+}
+
+/// Imports an optional dependency, with a hint on how to install it.
+fn import_optional<'py>(py: Python<'py>, module: &str) -> PyResult<&'py PyModule> {
+    py.import(module).map_err(|e| {
+        PyImportError::new_err(format!(
+            "{} is required for this method (pip install {}): {}",
+            module, module, e
+        ))
+    })
+}
+
+create_exception!(
+    evt3,
+    DecodeError,
+    PyIOError,
+    "Base class of the errors raised for data that cannot be decoded."
+);
+create_exception!(
+    evt3,
+    FormatError,
+    DecodeError,
+    "The data is not in EVT 3.0 format."
+);
+create_exception!(
+    evt3,
+    TruncatedFileError,
+    DecodeError,
+    "The data ends in the middle of an event word."
+);
+
+/// Converts a decoding error into the matching Python exception: OSError
+/// subclasses such as FileNotFoundError for I/O errors, FormatError and
+/// TruncatedFileError for invalid data.
+fn decode_error(e: evt3_core::DecodeError) -> PyErr {
+    match e {
+        evt3_core::DecodeError::Io(e) => {
+            std::io::Error::new(e.kind(), format!("Failed to decode file: {}", e)).into()
+        }
+        evt3_core::DecodeError::InvalidFormat(message) => FormatError::new_err(message),
+        evt3_core::DecodeError::UnexpectedEof => {
+            TruncatedFileError::new_err("Unexpected end of file")
+        }
+    }
+}
+
+/// Checks that a header declares EVT 3.0 data (or no format at all) and,
+/// if `strict`, that `data_len`, the number of bytes after the header, is
+/// a whole number of 16-bit words.
+///
+/// Otherwise a trailing odd byte, e.g. of a recording cut off by a crash,
+/// is dropped by the decoder like the CLI does.
+fn check_data(header: &[(String, String)], data_len: u64, strict: bool) -> PyResult<()> {
+    for (key, value) in header {
+        let supported = match key.as_str() {
+            "format" => value
+                .split(';')
+                .next()
+                .is_some_and(|name| name.trim().eq_ignore_ascii_case("EVT3")),
+            "evt" => matches!(value.as_str(), "3" | "3.0"),
+            _ => true,
+        };
+        if !supported {
+            return Err(FormatError::new_err(format!(
+                "Not an EVT 3.0 file: the header declares {} {}",
+                key, value
+            )));
+        }
+    }
+    if strict && data_len % 2 == 1 {
+        return Err(TruncatedFileError::new_err(format!(
+            "The data ends in the middle of a 16-bit word after {} bytes; \
+             the recording was probably cut off",
+            data_len
+        )));
+    }
+    Ok(())
+}
+
+/// Named tuple type of single events, `evt3.Event(x, y, p, t)`.
+static EVENT_TYPE: GILOnceCell<PyObject> = GILOnceCell::new();
+
+/// Returns the `evt3.Event` named tuple type, creating it on first use.
+fn event_type(py: Python<'_>) -> PyResult<&PyAny> {
+    let event_type = EVENT_TYPE.get_or_try_init(py, || {
+        let namedtuple = py.import("collections")?.getattr("namedtuple")?;
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("module", "evt3")?;
+        let event_type = namedtuple.call(("Event", ["x", "y", "p", "t"]), Some(kwargs))?;
+        Ok::<_, PyErr>(event_type.into())
+    })?;
+    Ok(event_type.as_ref(py))
+}
 
 /// Container for decoded CD events with zero-copy numpy access.
 ///
-/// The data is stored in columnar format (separate arrays for x, y, p, t)
-/// which is more efficient for numpy access and allows true zero-copy views.
+/// The data is stored in columnar format (separate arrays for x, y, p, t).
+/// Each column is converted into a numpy array once, when the container is
+/// created; the getters return that same array every time without copying.
+/// The arrays support the buffer protocol, so `memoryview(events.x)` and
+/// `torch.from_numpy(events.p)` share their memory.
 #[pyclass]
 pub struct Events {
     /// X coordinates
-    x: Vec<u16>,
+    x: Py<PyArray1<u16>>,
     /// Y coordinates
-    y: Vec<u16>,
+    y: Py<PyArray1<u16>>,
     /// Polarities
-    polarity: Vec<u8>,
+    polarity: Py<PyArray1<u8>>,
     /// Timestamps in microseconds
-    timestamp: Vec<u64>,
+    timestamp: Py<PyArray1<u64>>,
     /// Sensor width
     sensor_width: u32,
     /// Sensor height
     sensor_height: u32,
+    /// Whether decoding stopped at `max_events` before the end of the file
+    truncated: bool,
+    /// Key-value pairs of the file header
+    header: Header,
 }
 
 #[pymethods]
 impl Events {
     /// Returns the number of events.
-    fn __len__(&self) -> usize {
-        self.x.len()
+    fn __len__(&self, py: Python<'_>) -> usize {
+        self.x.as_ref(py).len()
+    }
+
+    /// Returns event `index` as an `evt3.Event(x, y, p, t)` named tuple;
+    /// negative indices count from the end. A slice such as `events[10:20]`
+    /// returns an Events object whose arrays are views, like `time_slice`.
+    fn __getitem__(&self, py: Python<'_>, index: &PyAny) -> PyResult<PyObject> {
+        let len = self.x.as_ref(py).len();
+        if let Ok(slice) = index.downcast::<PySlice>() {
+            let indices = slice.indices(len as std::os::raw::c_long)?;
+            if indices.step != 1 {
+                return Err(PyValueError::new_err(
+                    "Only slices with step 1 are supported",
+                ));
+            }
+            let start = indices.start as usize;
+            let events = self.slice(py, start, start + indices.slicelength as usize)?;
+            return Ok(Py::new(py, events)?.into_py(py));
+        }
+        let index: isize = index.extract()?;
+        let index = if index < 0 {
+            index + len as isize
+        } else {
+            index
+        };
+        if index < 0 {
+            return Err(PyIndexError::new_err("Event index out of range"));
+        }
+        self.event(py, index as usize)
+    }
+
+    /// Iterates over the events as `evt3.Event(x, y, p, t)` named tuples.
+    ///
+    /// Convenient for small results; use the arrays for anything large.
+    fn __iter__(slf: PyRef<'_, Self>) -> EventIter {
+        EventIter {
+            events: slf.into(),
+            index: 0,
+        }
     }
 
     /// Returns a string representation.
-    fn __repr__(&self) -> String {
+    fn __repr__(&self, py: Python<'_>) -> String {
         format!(
-            "Events(count={}, sensor={}x{})",
-            self.x.len(),
+            "Events(count={}, sensor={}x{}{})",
+            self.x.as_ref(py).len(),
             self.sensor_width,
-            self.sensor_height
+            self.sensor_height,
+            if self.truncated { ", truncated" } else { "" }
         )
     }
 
     /// Returns the X coordinates as a numpy array.
     ///
-    /// This creates a view into the Rust-allocated memory without copying.
-    /// The array is valid as long as this Events object is alive.
+    /// Repeated access returns the same array without copying. The array
+    /// is shared with this Events object: modifying it in place also
+    /// changes the events seen by functions such as `voxel_grid`.
     #[getter]
-    fn x<'py>(&self, py: Python<'py>) -> &'py PyArray1<u16> {
-        self.x.clone().into_pyarray(py)
+    fn x(&self, py: Python<'_>) -> Py<PyArray1<u16>> {
+        self.x.clone_ref(py)
     }
 
     /// Returns the Y coordinates as a numpy array.
     #[getter]
-    fn y<'py>(&self, py: Python<'py>) -> &'py PyArray1<u16> {
-        self.y.clone().into_pyarray(py)
+    fn y(&self, py: Python<'_>) -> Py<PyArray1<u16>> {
+        self.y.clone_ref(py)
     }
 
     /// Returns the polarities as a numpy array.
     ///
     /// Values: 0 = OFF (decrease in brightness), 1 = ON (increase)
     #[getter]
-    fn polarity<'py>(&self, py: Python<'py>) -> &'py PyArray1<u8> {
-        self.polarity.clone().into_pyarray(py)
+    fn polarity(&self, py: Python<'_>) -> Py<PyArray1<u8>> {
+        self.polarity.clone_ref(py)
     }
 
     /// Alias for polarity (shorter name).
     #[getter]
-    fn p<'py>(&self, py: Python<'py>) -> &'py PyArray1<u8> {
-        self.polarity.clone().into_pyarray(py)
+    fn p(&self, py: Python<'_>) -> Py<PyArray1<u8>> {
+        self.polarity.clone_ref(py)
     }
 
     /// Returns the timestamps as a numpy array (in microseconds).
     #[getter]
-    fn timestamp<'py>(&self, py: Python<'py>) -> &'py PyArray1<u64> {
-        self.timestamp.clone().into_pyarray(py)
+    fn timestamp(&self, py: Python<'_>) -> Py<PyArray1<u64>> {
+        self.timestamp.clone_ref(py)
     }
 
     /// Alias for timestamp (shorter name).
     #[getter]
-    fn t<'py>(&self, py: Python<'py>) -> &'py PyArray1<u64> {
-        self.timestamp.clone().into_pyarray(py)
+    fn t(&self, py: Python<'_>) -> Py<PyArray1<u64>> {
+        self.timestamp.clone_ref(py)
     }
 
     /// Returns the sensor width in pixels.
@@ -100,91 +267,778 @@ impl Events {
         self.sensor_height
     }
 
+    /// Returns the timestamps as a numpy `datetime64[us]` array, e.g. for
+    /// merging with other time series in pandas.
+    ///
+    /// Args:
+    ///     epoch: Wall-clock time of t=0, as anything `numpy.datetime64`
+    ///         accepts (a `datetime.datetime`, an ISO 8601 string, ...).
+    ///         Defaults to the `date` line of the file header, the local
+    ///         time the recording was started.
+    ///
+    /// Example:
+    ///     >>> df = events.to_pandas()
+    ///     >>> df["time"] = events.t_datetime("2024-05-01T12:00:00")
+    #[pyo3(signature = (epoch=None))]
+    fn t_datetime(&self, py: Python<'_>, epoch: Option<&PyAny>) -> PyResult<PyObject> {
+        let epoch = match epoch {
+            Some(epoch) => epoch.into_py(py),
+            None => {
+                let Some((_, date)) = self.header.iter().find(|(key, _)| key == "date") else {
+                    return Err(PyValueError::new_err(
+                        "The file header has no date; pass epoch explicitly",
+                    ));
+                };
+                // "2023-03-29 16:37:46" as ISO 8601
+                date.replacen(' ', "T", 1).into_py(py)
+            }
+        };
+        let numpy = py.import("numpy")?;
+        let start = numpy.getattr("datetime64")?.call1((epoch, "us"))?;
+        let start: i64 = start.call_method1("astype", ("int64",))?.extract()?;
+        let timestamp = self.timestamp.as_ref(py).readonly();
+        let values: Vec<i64> = timestamp
+            .as_array()
+            .iter()
+            .map(|&t| start + t as i64)
+            .collect();
+        let array = values.into_pyarray(py);
+        Ok(array.call_method1("view", ("datetime64[us]",))?.into())
+    }
+
     /// Returns a tuple of (width, height) for the sensor geometry.
     #[getter]
     fn sensor_size(&self) -> (u32, u32) {
         (self.sensor_width, self.sensor_height)
     }
 
+    /// True if decoding stopped at `max_events` before the end of the
+    /// file, so later events are missing.
+    #[getter]
+    fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Returns the total size of the event arrays in bytes.
+    #[getter]
+    fn nbytes(&self, py: Python<'_>) -> usize {
+        self.columns(py)
+            .iter()
+            .map(|(_, size, len)| size * len)
+            .sum()
+    }
+
+    /// Returns the memory used by the events as a dict of byte counts.
+    ///
+    /// The keys are the columns (x, y, polarity, timestamp), `total`, and
+    /// `bytes_per_event` for estimating the memory of a larger decode.
+    /// Views such as the result of `time_slice` count the part of the
+    /// arrays they cover, although that memory is shared with the events
+    /// they were taken from.
+    ///
+    /// Example:
+    ///     >>> report = events.memory_report()
+    ///     >>> print(f"{report['total'] / 1e6:.1f} MB")
+    fn memory_report<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let dict = PyDict::new(py);
+        let columns = self.columns(py);
+        for (name, size, len) in columns {
+            dict.set_item(name, size * len)?;
+        }
+        dict.set_item("total", self.nbytes(py))?;
+        let per_event: usize = columns.iter().map(|(_, size, _)| size).sum();
+        dict.set_item("bytes_per_event", per_event)?;
+        Ok(dict)
+    }
+
+    /// Supports pickling, e.g. to cache events with joblib or send them
+    /// to multiprocessing workers.
+    fn __reduce__(&self, py: Python<'_>) -> PyResult<(PyObject, PyObject)> {
+        let restore = py.import("evt3._evt3")?.getattr("_restore_events")?;
+        let state = (
+            self.x.clone_ref(py),
+            self.y.clone_ref(py),
+            self.polarity.clone_ref(py),
+            self.timestamp.clone_ref(py),
+            self.sensor_width,
+            self.sensor_height,
+            self.truncated,
+            self.header.clone(),
+        );
+        Ok((restore.into(), state.into_py(py)))
+    }
+
+    /// Returns the file header as a dict.
+    ///
+    /// Every `% key value` line of the header becomes an entry, e.g.
+    /// `serial_number`, `integrator_name`, `date` and `format` (the full
+    /// format string such as `"EVT3;width=1280;height=720"`). `width` and
+    /// `height` always hold the current sensor geometry. Events not read
+    /// from a file only have `width` and `height`.
+    #[getter]
+    fn metadata<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let dict = PyDict::new(py);
+        for (key, value) in &self.header {
+            dict.set_item(key, value)?;
+        }
+        dict.set_item("width", self.sensor_width)?;
+        dict.set_item("height", self.sensor_height)?;
+        Ok(dict)
+    }
+
+    /// Returns the number of events per pixel as a (height, width) uint32 array.
+    ///
+    /// Args:
+    ///     t0: Only count events at or after this time in microseconds
+    ///     t1: Only count events before this time in microseconds
+    #[pyo3(signature = (t0=None, t1=None))]
+    fn heatmap<'py>(
+        &self,
+        py: Python<'py>,
+        t0: Option<u64>,
+        t1: Option<u64>,
+    ) -> PyResult<&'py PyArray2<u32>> {
+        let time_range = match (t0, t1) {
+            (None, None) => None,
+            (t0, t1) => Some((t0.unwrap_or(0), t1.unwrap_or(u64::MAX))),
+        };
+        let counts = frames::heatmap(
+            &self.cd_events(py)?,
+            self.sensor_width,
+            self.sensor_height,
+            time_range,
+        );
+        counts
+            .into_pyarray(py)
+            .reshape([self.sensor_height as usize, self.sensor_width as usize])
+    }
+
+    /// Counts the events in a 2D spatial histogram over the sensor.
+    ///
+    /// A faster replacement for `np.histogram2d(events.x, events.y, ...)`
+    /// on large recordings; the GIL is released while counting.
+    ///
+    /// Args:
+    ///     bins: None for one bin per pixel (default), an int n for n x n
+    ///         bins, or a (bins_x, bins_y) pair
+    ///     polarity: Only count ON (1) or OFF (0) events (default: both)
+    ///
+    /// Returns:
+    ///     np.ndarray: uint32 array of shape (bins_y, bins_x), indexed by
+    ///     row like `heatmap`
+    ///
+    /// Example:
+    ///     >>> coarse = events.hist2d(bins=(64, 36), polarity=1)
+    #[pyo3(signature = (bins=None, polarity=None))]
+    fn hist2d<'py>(
+        &self,
+        py: Python<'py>,
+        bins: Option<&PyAny>,
+        polarity: Option<u8>,
+    ) -> PyResult<&'py PyArray2<u32>> {
+        let bins = match bins {
+            None => (self.sensor_width, self.sensor_height),
+            Some(bins) => match bins.extract::<u32>() {
+                Ok(n) => (n, n),
+                Err(_) => bins.extract::<(u32, u32)>().map_err(|_| {
+                    PyValueError::new_err("bins must be an int or a (bins_x, bins_y) pair")
+                })?,
+            },
+        };
+        if bins.0 == 0 || bins.1 == 0 {
+            return Err(PyValueError::new_err("bins must be positive"));
+        }
+        if polarity.is_some_and(|p| p > 1) {
+            return Err(PyValueError::new_err("polarity must be 0 (OFF) or 1 (ON)"));
+        }
+        let events = self.cd_events(py)?;
+        let (width, height) = (self.sensor_width, self.sensor_height);
+        let counts =
+            py.allow_threads(|| frames::histogram2d(&events, width, height, bins, polarity));
+        counts
+            .into_pyarray(py)
+            .reshape([bins.1 as usize, bins.0 as usize])
+    }
+
+    /// Accumulates the events into a (height, width) frame.
+    ///
+    /// Args:
+    ///     t0: Only use events at or after this time in microseconds
+    ///     t1: Only use events before this time in microseconds
+    ///     signed: Sum polarities (+1 ON, -1 OFF) into an int32 frame
+    ///         instead of counting events into a uint32 frame (default: False)
+    ///
+    /// Returns:
+    ///     np.ndarray: uint32 or int32 array of shape (height, width)
+    ///
+    /// Example:
+    ///     >>> frame = events.to_frame(t0, t0 + 33_000, signed=True)
+    #[pyo3(signature = (t0=None, t1=None, signed=false))]
+    fn to_frame(
+        &self,
+        py: Python<'_>,
+        t0: Option<u64>,
+        t1: Option<u64>,
+        signed: bool,
+    ) -> PyResult<PyObject> {
+        let events = self.cd_events(py)?;
+        let (width, height) = (self.sensor_width, self.sensor_height);
+        let (t0, t1) = (t0.unwrap_or(0), t1.unwrap_or(u64::MAX));
+        let shape = [height as usize, width as usize];
+        if signed {
+            let frame =
+                py.allow_threads(|| frames::accumulate_signed(&events, width, height, t0, t1));
+            Ok(frame.into_pyarray(py).reshape(shape)?.into())
+        } else {
+            let frame = py.allow_threads(|| frames::accumulate(&events, width, height, t0, t1));
+            Ok(frame.into_pyarray(py).reshape(shape)?.into())
+        }
+    }
+
+    /// Builds a voxel grid representation of the events.
+    ///
+    /// Same as `evt3.voxel_grid(events, ...)`; the GIL is released while
+    /// the grid is computed.
+    ///
+    /// Args:
+    ///     num_bins: Number of temporal bins
+    ///     t0: Start of the time range in microseconds (default: first event)
+    ///     t1: End of the time range in microseconds, inclusive (default: last event)
+    ///
+    /// Returns:
+    ///     np.ndarray: float32 array of shape (num_bins, height, width)
+    ///
+    /// Example:
+    ///     >>> grid = events.to_voxel_grid(5)
+    #[pyo3(signature = (num_bins, t0=None, t1=None))]
+    fn to_voxel_grid<'py>(
+        &self,
+        py: Python<'py>,
+        num_bins: usize,
+        t0: Option<u64>,
+        t1: Option<u64>,
+    ) -> PyResult<&'py PyArray3<f32>> {
+        let events = self.cd_events(py)?;
+        let (width, height) = (self.sensor_width, self.sensor_height);
+        let grid = py.allow_threads(|| {
+            let timestamps = events.iter().map(|e| e.timestamp);
+            let t0 = t0.unwrap_or_else(|| timestamps.clone().min().unwrap_or(0));
+            let t1 = t1.unwrap_or_else(|| timestamps.max().unwrap_or(0));
+            representations::voxel_grid_in_range(&events, width, height, num_bins, t0, t1)
+        });
+        grid.into_pyarray(py)
+            .reshape([num_bins, height as usize, width as usize])
+    }
+
+    /// Returns the events with `t0 <= timestamp < t1`.
+    ///
+    /// The window is found by binary search, so the timestamps must be
+    /// sorted, as returned by the decoder (see `sort_by_time`). The arrays
+    /// of the result are views into this object's arrays, not copies.
+    ///
+    /// Args:
+    ///     t0: Start of the window in microseconds (default: first event)
+    ///     t1: End of the window in microseconds, exclusive (default: after
+    ///         the last event)
+    ///
+    /// Returns:
+    ///     Events: The events in the window
+    ///
+    /// Example:
+    ///     >>> first_second = events.time_slice(0, 1_000_000)
+    #[pyo3(signature = (t0=None, t1=None))]
+    fn time_slice(&self, py: Python<'_>, t0: Option<u64>, t1: Option<u64>) -> PyResult<Events> {
+        let timestamp = self.timestamp.as_ref(py).readonly();
+        let timestamp = timestamp.as_slice()?;
+        let start = t0.map_or(0, |t0| timestamp.partition_point(|&t| t < t0));
+        let end = t1.map_or(timestamp.len(), |t1| timestamp.partition_point(|&t| t < t1));
+        self.slice(py, start, end.max(start))
+    }
+
+    /// Returns the events inside a rectangular region of interest.
+    ///
+    /// Args:
+    ///     x0, y0: Top-left corner of the region (inclusive)
+    ///     x1, y1: Bottom-right corner of the region (exclusive)
+    ///     crop: Also move the region to the origin and shrink the sensor
+    ///         size to the region (default: False)
+    ///
+    /// Returns:
+    ///     Events: The events inside the region
+    ///
+    /// Example:
+    ///     >>> center = events.filter_roi(320, 180, 960, 540, crop=True)
+    #[pyo3(signature = (x0, y0, x1, y1, crop=false))]
+    fn filter_roi(
+        &self,
+        py: Python<'_>,
+        x0: u16,
+        y0: u16,
+        x1: u16,
+        y1: u16,
+        crop: bool,
+    ) -> PyResult<Events> {
+        let roi = Roi::new(x0, y0, x1, y1);
+        if crop {
+            self.transformed(py, Crop::new(roi))
+        } else {
+            self.filtered(py, RoiFilter::new(roi))
+        }
+    }
+
+    /// Returns the events of one polarity.
+    ///
+    /// Args:
+    ///     polarity: 1 to keep ON events, 0 to keep OFF events
+    ///
+    /// Returns:
+    ///     Events: The events with that polarity
+    fn filter_polarity(&self, py: Python<'_>, polarity: u8) -> PyResult<Events> {
+        if polarity > 1 {
+            return Err(PyValueError::new_err("polarity must be 0 (OFF) or 1 (ON)"));
+        }
+        self.filtered(py, PolarityFilter::new(polarity))
+    }
+
+    /// Returns the events without those of hot pixels.
+    ///
+    /// Hot pixels are detected from the per-pixel event counts: a pixel is
+    /// hot if its count exceeds the median by more than `sigma` robust
+    /// standard deviations. Alternatively, pass the pixels to remove.
+    ///
+    /// Args:
+    ///     sigma: Detection threshold (default: 5.0)
+    ///     pixels: Pixels to remove instead of detecting them, as a list of
+    ///         (x, y) pairs or a path to a mask file (as `pixel_mask`)
+    ///
+    /// Returns:
+    ///     Events: The events of all other pixels
+    #[pyo3(signature = (sigma=5.0, pixels=None))]
+    fn remove_hot_pixels(
+        &self,
+        py: Python<'_>,
+        sigma: f64,
+        pixels: Option<&PyAny>,
+    ) -> PyResult<Events> {
+        let metadata = SensorMetadata {
+            width: self.sensor_width,
+            height: self.sensor_height,
+        };
+        let filter = match pixels {
+            Some(pixels) => PixelMaskFilter::new(extract_pixel_mask(pixels)?, &metadata),
+            None => {
+                let events = self.cd_events(py)?;
+                py.allow_threads(|| PixelMaskFilter::detect(&events, &metadata, sigma))
+            }
+        };
+        self.filtered(py, filter)
+    }
+
+    /// Whether the timestamps are in non-decreasing order.
+    ///
+    /// Decoded events are sorted; merging recordings or rewriting
+    /// timestamps can break the order that `time_slice` relies on.
+    #[getter]
+    fn is_sorted(&self, py: Python<'_>) -> PyResult<bool> {
+        let timestamp = self.timestamp.as_ref(py).readonly();
+        Ok(timestamp.as_slice()?.windows(2).all(|t| t[0] <= t[1]))
+    }
+
+    /// Returns the events sorted by timestamp.
+    ///
+    /// The sort is stable, so events with equal timestamps keep their
+    /// order, and all four columns are reordered together. Already sorted
+    /// events are returned as views without copying.
+    ///
+    /// Returns:
+    ///     Events: The events in time order
+    ///
+    /// Example:
+    ///     >>> merged = (left + right).sort_by_time()
+    fn sort_by_time(&self, py: Python<'_>) -> PyResult<Events> {
+        if self.is_sorted(py)? {
+            return self.slice(py, 0, self.__len__(py));
+        }
+        let mut events = self.cd_events(py)?;
+        py.allow_threads(|| events.sort_by_key(|e| e.timestamp));
+        let (width, height) = (self.sensor_width, self.sensor_height);
+        Ok(Events {
+            truncated: self.truncated,
+            header: self.header.clone(),
+            ..Events::from_cd_events(py, events, width, height)
+        })
+    }
+
+    /// Concatenates two Events objects, see `evt3.concatenate`.
+    fn __add__(&self, py: Python<'_>, other: &Events) -> PyResult<Events> {
+        Events::concatenate(py, &[(self, 0), (other, 0)])
+    }
+
     /// Returns all arrays as a dictionary.
     ///
     /// This is useful for creating a pandas DataFrame or structured array.
+    /// The values are the same arrays as the attributes, not copies.
     fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<PyObject> {
         let dict = PyDict::new(py);
-        dict.set_item("x", self.x.clone().into_pyarray(py))?;
-        dict.set_item("y", self.y.clone().into_pyarray(py))?;
-        dict.set_item("polarity", self.polarity.clone().into_pyarray(py))?;
-        dict.set_item("timestamp", self.timestamp.clone().into_pyarray(py))?;
+        dict.set_item("x", &self.x)?;
+        dict.set_item("y", &self.y)?;
+        dict.set_item("polarity", &self.polarity)?;
+        dict.set_item("timestamp", &self.timestamp)?;
         Ok(dict.into())
     }
+
+    /// Returns the events as a numpy structured array with fields x, y,
+    /// polarity and timestamp, so that `np.asarray(events)` works.
+    ///
+    /// The columns are interleaved into a new array (15 bytes per event);
+    /// use the column attributes to share memory instead.
+    #[pyo3(signature = (dtype=None, copy=None))]
+    fn __array__(
+        &self,
+        py: Python<'_>,
+        dtype: Option<&PyAny>,
+        copy: Option<bool>,
+    ) -> PyResult<PyObject> {
+        if copy == Some(false) {
+            return Err(PyValueError::new_err(
+                "Events cannot be converted to a structured array without copying",
+            ));
+        }
+        let numpy = py.import("numpy")?;
+        let fields = vec![
+            ("x", "<u2"),
+            ("y", "<u2"),
+            ("polarity", "u1"),
+            ("timestamp", "<u8"),
+        ];
+        let record = numpy.call_method1("dtype", (fields,))?;
+        let array = numpy.call_method1("empty", (self.__len__(py), record))?;
+        array.set_item("x", &self.x)?;
+        array.set_item("y", &self.y)?;
+        array.set_item("polarity", &self.polarity)?;
+        array.set_item("timestamp", &self.timestamp)?;
+        match dtype {
+            Some(dtype) => Ok(array.call_method1("astype", (dtype,))?.into()),
+            None => Ok(array.into()),
+        }
+    }
+
+    /// Returns the events as a pandas DataFrame with columns x, y,
+    /// polarity and timestamp.
+    ///
+    /// The arrays are passed with `copy=False`, so pandas 2 uses them as
+    /// the columns instead of copying them. Requires pandas.
+    fn to_pandas(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let pandas = import_optional(py, "pandas")?;
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("copy", false)?;
+        let frame = pandas
+            .getattr("DataFrame")?
+            .call((self.to_dict(py)?,), Some(kwargs))?;
+        Ok(frame.into())
+    }
+
+    /// Returns the events as a polars DataFrame with columns x, y,
+    /// polarity and timestamp.
+    ///
+    /// polars reuses the numpy buffers where its version supports it.
+    /// Requires polars.
+    fn to_polars(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let polars = import_optional(py, "polars")?;
+        let frame = polars.getattr("DataFrame")?.call1((self.to_dict(py)?,))?;
+        Ok(frame.into())
+    }
+
+    /// Returns the events as a pyarrow Table with columns x, y, polarity
+    /// and timestamp.
+    ///
+    /// The columns are handed to pyarrow through the Arrow C data
+    /// interface, so the table shares the numpy buffers without copying
+    /// them (a column that is a strided view is copied first). The sensor
+    /// geometry is stored in the schema metadata (`sensor_width`,
+    /// `sensor_height`), so it is kept when the table is written to
+    /// Parquet or Feather. Requires pyarrow.
+    ///
+    /// Example:
+    ///     >>> import pyarrow.parquet as pq
+    ///     >>> pq.write_table(events.to_arrow(), "events.parquet")
+    fn to_arrow(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let pyarrow = import_optional(py, "pyarrow")?;
+        let metadata = PyDict::new(py);
+        metadata.set_item("sensor_width", self.sensor_width.to_string())?;
+        metadata.set_item("sensor_height", self.sensor_height.to_string())?;
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("names", ["x", "y", "polarity", "timestamp"])?;
+        kwargs.set_item("metadata", metadata)?;
+        let columns = vec![
+            arrow::import_column(py, pyarrow, &self.x)?,
+            arrow::import_column(py, pyarrow, &self.y)?,
+            arrow::import_column(py, pyarrow, &self.polarity)?,
+            arrow::import_column(py, pyarrow, &self.timestamp)?,
+        ];
+        let table =
+            pyarrow
+                .getattr("Table")?
+                .call_method("from_arrays", (columns,), Some(kwargs))?;
+        Ok(table.into())
+    }
 }
 
 impl Events {
-    /// Creates an Events container from a vector of CdEvent structs.
-    fn from_cd_events(events: Vec<CdEvent>, width: u32, height: u32) -> Self {
-        let len = events.len();
+    /// Returns the events `start..end` as views into this object's arrays.
+    fn slice(&self, py: Python<'_>, start: usize, end: usize) -> PyResult<Events> {
+        fn view<T: Element>(
+            py: Python<'_>,
+            array: &Py<PyArray1<T>>,
+            slice: &PySlice,
+        ) -> PyResult<Py<PyArray1<T>>> {
+            let view = array.as_ref(py).get_item(slice)?;
+            Ok(view.downcast::<PyArray1<T>>()?.into())
+        }
+        let slice = PySlice::new(py, start as isize, end as isize, 1);
+        Ok(Events {
+            x: view(py, &self.x, slice)?,
+            y: view(py, &self.y, slice)?,
+            polarity: view(py, &self.polarity, slice)?,
+            timestamp: view(py, &self.timestamp, slice)?,
+            sensor_width: self.sensor_width,
+            sensor_height: self.sensor_height,
+            truncated: self.truncated,
+            header: self.header.clone(),
+        })
+    }
+
+    /// Concatenates the events of `parts`, adding the given offset to the
+    /// timestamps of each part.
+    fn concatenate(py: Python<'_>, parts: &[(&Events, u64)]) -> PyResult<Events> {
+        let Some((first, _)) = parts.first() else {
+            return Err(PyValueError::new_err("Need at least one Events object"));
+        };
+        let (width, height) = (first.sensor_width, first.sensor_height);
+        if let Some((other, _)) = parts
+            .iter()
+            .find(|(e, _)| (e.sensor_width, e.sensor_height) != (width, height))
+        {
+            return Err(PyValueError::new_err(format!(
+                "Cannot concatenate events from a {}x{} and a {}x{} sensor",
+                width, height, other.sensor_width, other.sensor_height
+            )));
+        }
+
+        let len = parts.iter().map(|(e, _)| e.x.as_ref(py).len()).sum();
         let mut x = Vec::with_capacity(len);
         let mut y = Vec::with_capacity(len);
         let mut polarity = Vec::with_capacity(len);
         let mut timestamp = Vec::with_capacity(len);
+        for (events, offset) in parts {
+            x.extend_from_slice(events.x.as_ref(py).readonly().as_slice()?);
+            y.extend_from_slice(events.y.as_ref(py).readonly().as_slice()?);
+            polarity.extend_from_slice(events.polarity.as_ref(py).readonly().as_slice()?);
+            let t = events.timestamp.as_ref(py).readonly();
+            timestamp.extend(t.as_slice()?.iter().map(|t| t + offset));
+        }
 
-        for event in events {
-            x.push(event.x);
-            y.push(event.y);
-            polarity.push(event.polarity);
-            timestamp.push(event.timestamp);
+        Ok(Self {
+            x: column(py, x),
+            y: column(py, y),
+            polarity: column(py, polarity),
+            timestamp: column(py, timestamp),
+            sensor_width: width,
+            sensor_height: height,
+            truncated: parts.iter().any(|(e, _)| e.truncated),
+            header: first.header.clone(),
+        })
+    }
+
+    /// Returns the events kept by `filter`, computed without the GIL.
+    fn filtered(&self, py: Python<'_>, mut filter: impl EventFilter) -> PyResult<Events> {
+        let mut events = self.cd_events(py)?;
+        py.allow_threads(|| filters::filter_events(&mut filter, &mut events));
+        let (width, height) = (self.sensor_width, self.sensor_height);
+        Ok(Events {
+            truncated: self.truncated,
+            header: self.header.clone(),
+            ..Events::from_cd_events(py, events, width, height)
+        })
+    }
+
+    /// Returns the events rewritten by `transform`, with its output geometry.
+    fn transformed(&self, py: Python<'_>, mut transform: impl EventTransform) -> PyResult<Events> {
+        let mut events = self.cd_events(py)?;
+        let mut metadata = SensorMetadata {
+            width: self.sensor_width,
+            height: self.sensor_height,
+        };
+        py.allow_threads(|| {
+            transforms::apply_transform(&mut transform, &mut events, &mut metadata)
+        });
+        Ok(Events {
+            truncated: self.truncated,
+            header: self.header.clone(),
+            ..Events::from_cd_events(py, events, metadata.width, metadata.height)
+        })
+    }
+
+    /// Returns the sensor geometry.
+    fn sensor_metadata(&self) -> SensorMetadata {
+        SensorMetadata {
+            width: self.sensor_width,
+            height: self.sensor_height,
         }
+    }
+
+    /// Returns event `index` as an `evt3.Event` named tuple.
+    fn event(&self, py: Python<'_>, index: usize) -> PyResult<PyObject> {
+        let event = (
+            self.x.as_ref(py).get_owned(index),
+            self.y.as_ref(py).get_owned(index),
+            self.polarity.as_ref(py).get_owned(index),
+            self.timestamp.as_ref(py).get_owned(index),
+        );
+        let (Some(x), Some(y), Some(p), Some(t)) = event else {
+            return Err(PyIndexError::new_err("Event index out of range"));
+        };
+        Ok(event_type(py)?.call1((x, y, p, t))?.into())
+    }
+
+    /// Returns the name, element size in bytes and length of each column.
+    fn columns(&self, py: Python<'_>) -> [(&'static str, usize, usize); 4] {
+        use std::mem::size_of;
+        [
+            ("x", size_of::<u16>(), self.x.as_ref(py).len()),
+            ("y", size_of::<u16>(), self.y.as_ref(py).len()),
+            ("polarity", size_of::<u8>(), self.polarity.as_ref(py).len()),
+            (
+                "timestamp",
+                size_of::<u64>(),
+                self.timestamp.as_ref(py).len(),
+            ),
+        ]
+    }
+
+    /// Reassembles the columns into CdEvent structs.
+    fn cd_events(&self, py: Python<'_>) -> PyResult<Vec<CdEvent>> {
+        let x = self.x.as_ref(py).readonly();
+        let y = self.y.as_ref(py).readonly();
+        let polarity = self.polarity.as_ref(py).readonly();
+        let timestamp = self.timestamp.as_ref(py).readonly();
+        let (x, y) = (x.as_slice()?, y.as_slice()?);
+        let (polarity, timestamp) = (polarity.as_slice()?, timestamp.as_slice()?);
+        Ok((0..x.len())
+            .map(|i| CdEvent::new(x[i], y[i], polarity[i], timestamp[i]))
+            .collect())
+    }
+
+    /// Creates an Events container from a vector of CdEvent structs.
+    fn from_cd_events(py: Python<'_>, events: Vec<CdEvent>, width: u32, height: u32) -> Self {
+        Self::from_columnar(py, events.into_iter().collect(), width, height)
+    }
 
+    /// Wraps decoded columns; the vectors become the numpy arrays without a copy.
+    fn from_columnar(py: Python<'_>, events: ColumnarEvents, width: u32, height: u32) -> Self {
         Self {
-            x,
-            y,
-            polarity,
-            timestamp,
+            x: column(py, events.x),
+            y: column(py, events.y),
+            polarity: column(py, events.polarity),
+            timestamp: column(py, events.timestamp),
             sensor_width: width,
             sensor_height: height,
+            truncated: false,
+            header: Vec::new(),
+        }
+    }
+}
+
+/// Iterator over the events of an Events object, returned by `iter(events)`.
+#[pyclass]
+pub struct EventIter {
+    events: Py<Events>,
+    index: usize,
+}
+
+#[pymethods]
+impl EventIter {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        let events = self.events.borrow(py);
+        if self.index >= events.__len__(py) {
+            return Ok(None);
         }
+        let event = events.event(py, self.index)?;
+        self.index += 1;
+        Ok(Some(event))
     }
 }
 
 /// Container for decoded trigger events.
+///
+/// Like [`Events`], the columns are numpy arrays created once and returned
+/// by the getters without copying.
 #[pyclass]
 pub struct TriggerEvents {
     /// Trigger values (edge polarity): 0=falling, 1=rising
-    value: Vec<u8>,
+    value: Py<PyArray1<u8>>,
     /// Trigger channel IDs
-    id: Vec<u8>,
+    id: Py<PyArray1<u8>>,
     /// Timestamps in microseconds
-    timestamp: Vec<u64>,
+    timestamp: Py<PyArray1<u64>>,
 }
 
 #[pymethods]
 impl TriggerEvents {
     /// Returns the number of trigger events.
-    fn __len__(&self) -> usize {
-        self.value.len()
+    fn __len__(&self, py: Python<'_>) -> usize {
+        self.value.as_ref(py).len()
     }
 
     /// Returns the trigger values as a numpy array.
     #[getter]
-    fn value<'py>(&self, py: Python<'py>) -> &'py PyArray1<u8> {
-        self.value.clone().into_pyarray(py)
+    fn value(&self, py: Python<'_>) -> Py<PyArray1<u8>> {
+        self.value.clone_ref(py)
     }
 
     /// Returns the trigger channel IDs as a numpy array.
     #[getter]
-    fn id<'py>(&self, py: Python<'py>) -> &'py PyArray1<u8> {
-        self.id.clone().into_pyarray(py)
+    fn id(&self, py: Python<'_>) -> Py<PyArray1<u8>> {
+        self.id.clone_ref(py)
     }
 
     /// Returns the timestamps as a numpy array.
     #[getter]
-    fn timestamp<'py>(&self, py: Python<'py>) -> &'py PyArray1<u64> {
-        self.timestamp.clone().into_pyarray(py)
+    fn timestamp(&self, py: Python<'_>) -> Py<PyArray1<u64>> {
+        self.timestamp.clone_ref(py)
+    }
+
+    /// Supports pickling, as for Events.
+    fn __reduce__(&self, py: Python<'_>) -> PyResult<(PyObject, PyObject)> {
+        let restore = py
+            .import("evt3._evt3")?
+            .getattr("_restore_trigger_events")?;
+        let state = (
+            self.value.clone_ref(py),
+            self.id.clone_ref(py),
+            self.timestamp.clone_ref(py),
+        );
+        Ok((restore.into(), state.into_py(py)))
     }
 }
 
 impl TriggerEvents {
-    fn from_trigger_events(events: Vec<TriggerEvent>) -> Self {
+    /// Reassembles the columns into TriggerEvent structs.
+    fn trigger_events(&self, py: Python<'_>) -> PyResult<Vec<TriggerEvent>> {
+        let value = self.value.as_ref(py).readonly();
+        let id = self.id.as_ref(py).readonly();
+        let timestamp = self.timestamp.as_ref(py).readonly();
+        let (value, id, timestamp) = (value.as_slice()?, id.as_slice()?, timestamp.as_slice()?);
+        Ok((0..value.len())
+            .map(|i| TriggerEvent::new(value[i], id[i], timestamp[i]))
+            .collect())
+    }
+
+    fn from_trigger_events(py: Python<'_>, events: Vec<TriggerEvent>) -> Self {
         let len = events.len();
         let mut value = Vec::with_capacity(len);
         let mut id = Vec::with_capacity(len);
@@ -197,13 +1051,60 @@ impl TriggerEvents {
         }
 
         Self {
-            value,
-            id,
-            timestamp,
+            value: column(py, value),
+            id: column(py, id),
+            timestamp: column(py, timestamp),
         }
     }
 }
 
+/// Rebuilds pickled Events from the state returned by `Events.__reduce__`.
+#[pyfunction(name = "_restore_events")]
+#[allow(clippy::too_many_arguments)]
+fn restore_events(
+    x: &PyArray1<u16>,
+    y: &PyArray1<u16>,
+    polarity: &PyArray1<u8>,
+    timestamp: &PyArray1<u64>,
+    sensor_width: u32,
+    sensor_height: u32,
+    truncated: bool,
+    header: Header,
+) -> PyResult<Events> {
+    let len = x.len();
+    if y.len() != len || polarity.len() != len || timestamp.len() != len {
+        return Err(PyValueError::new_err("Columns must have the same length"));
+    }
+    Ok(Events {
+        x: x.into(),
+        y: y.into(),
+        polarity: polarity.into(),
+        timestamp: timestamp.into(),
+        sensor_width,
+        sensor_height,
+        truncated,
+        header,
+    })
+}
+
+/// Rebuilds pickled TriggerEvents from the state returned by
+/// `TriggerEvents.__reduce__`.
+#[pyfunction(name = "_restore_trigger_events")]
+fn restore_trigger_events(
+    value: &PyArray1<u8>,
+    id: &PyArray1<u8>,
+    timestamp: &PyArray1<u64>,
+) -> PyResult<TriggerEvents> {
+    if id.len() != value.len() || timestamp.len() != value.len() {
+        return Err(PyValueError::new_err("Columns must have the same length"));
+    }
+    Ok(TriggerEvents {
+        value: value.into(),
+        id: id.into(),
+        timestamp: timestamp.into(),
+    })
+}
+
 /// Result of decoding an EVT3 file.
 #[pyclass]
 pub struct DecodeResult {
@@ -213,10 +1114,233 @@ pub struct DecodeResult {
     triggers: Py<TriggerEvents>,
 }
 
+/// Reads a `pixel_mask` argument: a path to a mask file or a list of (x, y) pairs.
+fn extract_pixel_mask(mask: &PyAny) -> PyResult<Vec<(u16, u16)>> {
+    if let Ok(path) = mask.extract::<PathBuf>() {
+        return input::read_pixel_mask(&path)
+            .map_err(|e| PyIOError::new_err(format!("Failed to read pixel mask: {}", e)));
+    }
+    mask.extract::<Vec<(u16, u16)>>().map_err(|_| {
+        PyValueError::new_err("pixel_mask must be a path or a sequence of (x, y) pairs")
+    })
+}
+
+/// Adds the filters and transforms requested through keyword arguments to a decoder.
+fn configure_filters(
+    decoder: &mut Evt3Decoder,
+    denoise_dt: Option<u64>,
+    dedup: bool,
+    invert_polarity: bool,
+    pixel_mask: Option<&PyAny>,
+) -> PyResult<()> {
+    if let Some(mask) = pixel_mask {
+        let filter = PixelMaskFilter::new(extract_pixel_mask(mask)?, &decoder.metadata);
+        decoder.add_filter(filter);
+    }
+    if dedup {
+        decoder.add_filter(DuplicateFilter::new());
+    }
+    if let Some(dt) = denoise_dt {
+        let filter = BackgroundActivityFilter::new(&decoder.metadata, dt);
+        decoder.add_filter(filter);
+    }
+    if invert_polarity {
+        decoder.add_transform(PolarityMap::invert());
+    }
+    Ok(())
+}
+
+/// Error of a `decode_file_chunked` callback that can end decoding early.
+enum ChunkError {
+    Decode(evt3_core::DecodeError),
+    /// Exception raised by the `progress` callback
+    Callback(PyErr),
+    /// Everything needed has been decoded
+    Stop,
+}
+
+impl From<evt3_core::DecodeError> for ChunkError {
+    fn from(e: evt3_core::DecodeError) -> Self {
+        Self::Decode(e)
+    }
+}
+
+/// Decodes a file, applying the `t_start`, `t_end`, `roi`, `max_events`
+/// and `progress` keyword arguments while decoding.
+///
+/// Returns the result and whether it was cut off at `max_events`. The GIL
+/// is released while decoding, except to call `progress`.
+#[allow(clippy::too_many_arguments)]
+fn decode_path(
+    py: Python<'_>,
+    decoder: &mut Evt3Decoder,
+    path: &Path,
+    t_start: Option<u64>,
+    t_end: Option<u64>,
+    roi: Option<(u16, u16, u16, u16)>,
+    max_events: Option<usize>,
+    progress: Option<&PyAny>,
+) -> PyResult<(ColumnarDecodeResult, bool)> {
+    if let Some((x0, y0, x1, y1)) = roi {
+        decoder.add_filter(RoiFilter::new(Roi::new(x0, y0, x1, y1)));
+    }
+    if let (Some(start), Some(end)) = (t_start, t_end) {
+        if end <= start {
+            return Err(PyValueError::new_err("t_end must be after t_start"));
+        }
+    }
+    if max_events.is_none() && progress.is_none() && t_start.is_none() && t_end.is_none() {
+        let result = py.allow_threads(|| decoder.decode_file_columnar(path));
+        return result.map(|result| (result, false)).map_err(decode_error);
+    }
+
+    let total_bytes = match progress {
+        Some(_) => std::fs::metadata(path)
+            .map_err(|e| decode_error(e.into()))?
+            .len(),
+        None => 0,
+    };
+    let bytes_read = Arc::new(AtomicU64::new(0));
+    let counter = Arc::clone(&bytes_read);
+    decoder.set_progress_callback(move |bytes| counter.store(bytes, Ordering::Relaxed));
+
+    let progress: Option<PyObject> = progress.map(Into::into);
+    let max_events = max_events.unwrap_or(usize::MAX);
+    let (start, end) = (t_start.unwrap_or(0), t_end.unwrap_or(u64::MAX));
+    let mut cd_events = ColumnarEvents::new();
+    let mut trigger_events = Vec::new();
+    let mut truncated = false;
+    let result = py.allow_threads(|| {
+        decoder.decode_file_chunked(path, |_, cd, triggers| {
+            let past_end = cd.last().is_some_and(|e| e.timestamp >= end)
+                || triggers.last().is_some_and(|e| e.timestamp >= end);
+            let in_range = |t: u64| t >= start && t < end;
+            let mut cd = cd.iter().filter(|e| in_range(e.timestamp));
+            cd_events.extend(cd.by_ref().take(max_events - cd_events.len()));
+            // Triggers are kept up to the first CD event that did not fit
+            let cut = cd.next().map_or(u64::MAX, |e| e.timestamp);
+            trigger_events.extend(
+                triggers
+                    .iter()
+                    .filter(|e| in_range(e.timestamp) && e.timestamp < cut),
+            );
+            if let Some(progress) = &progress {
+                let bytes = bytes_read.load(Ordering::Relaxed);
+                Python::with_gil(|py| progress.call1(py, (bytes, total_bytes, cd_events.len())))
+                    .map_err(ChunkError::Callback)?;
+            }
+            truncated = cut != u64::MAX;
+            if truncated || past_end {
+                Err(ChunkError::Stop)
+            } else {
+                Ok(())
+            }
+        })
+    });
+    decoder.clear_progress_callback();
+    match result {
+        Ok(()) | Err(ChunkError::Stop) => {}
+        Err(ChunkError::Decode(e)) => return Err(decode_error(e)),
+        Err(ChunkError::Callback(e)) => return Err(e),
+    }
+    let result = ColumnarDecodeResult {
+        cd_events,
+        trigger_events,
+        metadata: decoder.output_metadata(),
+    };
+    Ok((result, truncated))
+}
+
+/// Key-value pairs of a file header, in file order.
+type Header = Vec<(String, String)>;
+
+/// Splits header lines such as `serial_number 00050423` into key and value.
+fn header_fields(lines: Vec<String>) -> Header {
+    lines
+        .into_iter()
+        .map(|line| match line.split_once(char::is_whitespace) {
+            Some((key, value)) => (key.to_string(), value.trim().to_string()),
+            None => (line, String::new()),
+        })
+        .collect()
+}
+
+/// Opens the file at `path` and parses its header with `decoder`, checking
+/// that the file holds EVT 3.0 data (see [`check_data`]).
+///
+/// Returns the reader, positioned at the first data byte, and the
+/// key-value pairs of the header.
+fn open_raw(
+    path: &Path,
+    decoder: &mut Evt3Decoder,
+    strict: bool,
+) -> PyResult<(BufReader<File>, Header)> {
+    let io_error = |e: std::io::Error| decode_error(e.into());
+    let file = File::open(path).map_err(io_error)?;
+    let file_size = file.metadata().map_err(io_error)?.len();
+    let mut reader = BufReader::new(file);
+    let header = header_fields(decoder.parse_header(&mut reader).map_err(decode_error)?);
+    let header_size = reader.stream_position().map_err(io_error)?;
+    check_data(&header, file_size - header_size, strict)?;
+    Ok((reader, header))
+}
+
+/// Reads the key-value pairs of the header of the file at `path`, see
+/// [`open_raw`].
+fn read_header(path: &Path, strict: bool) -> PyResult<Header> {
+    open_raw(path, &mut default_decoder(), strict).map(|(_, header)| header)
+}
+
+/// Applies the `rebase_trigger` keyword argument to a decode result.
+fn rebase_result(result: &mut ColumnarDecodeResult, rebase_trigger: Option<u8>) -> PyResult<()> {
+    if let Some(channel) = rebase_trigger {
+        // Rebase the triggers to find the edge, then the columns likewise
+        let origin =
+            triggers::rebase_to_trigger(&mut Vec::new(), &mut result.trigger_events, channel)
+                .ok_or_else(|| {
+                    PyValueError::new_err(format!("No rising edge on trigger channel {}", channel))
+                })?;
+        result
+            .cd_events
+            .retain_mut(|e| match e.timestamp.checked_sub(origin) {
+                Some(t) => {
+                    e.timestamp = t;
+                    true
+                }
+                None => false,
+            });
+    }
+    Ok(())
+}
+
 /// Decodes an EVT 3.0 raw file and returns the events.
 ///
 /// Args:
-///     path: Path to the .raw file
+///     path: Path to the .raw file (str or os.PathLike)
+///     denoise_dt: If set, drop events without a neighboring event within
+///         this many microseconds (background activity filter)
+///     dedup: Drop exact duplicate events (default: False)
+///     invert_polarity: Swap ON and OFF polarities (default: False)
+///     pixel_mask: Pixels whose events are dropped, as a list of (x, y)
+///         pairs or a path to a mask file (x,y text list or PNG where
+///         non-black pixels are masked)
+///     rebase_trigger: If set, shift timestamps so that t=0 is the first
+///         rising edge on this trigger channel; earlier events are dropped
+///     t_start: Drop events before this time in microseconds (before
+///         rebasing)
+///     t_end: Drop events at or after this time in microseconds; decoding
+///         stops once it is reached
+///     roi: Keep only events inside (x0, y0, x1, y1), with x1 and y1
+///         exclusive
+///     max_events: Stop decoding after this many CD events, e.g. for a
+///         quick preview; `truncated` on the result tells whether the file
+///         had more
+///     progress: Called with (bytes_processed, total_bytes, events_so_far)
+///         after every read of about 2 MB, e.g. to update a tqdm bar;
+///         an exception raised by it stops decoding
+///     strict: Raise TruncatedFileError if the data ends in the middle of
+///         a 16-bit word instead of dropping the incomplete last word, as
+///         is done by default so recordings cut off by a crash still open
 ///
 /// Returns:
 ///     Events: Container with x, y, polarity, and timestamp arrays
@@ -228,19 +1352,48 @@ pub struct DecodeResult {
 ///     >>> x = events.x  # numpy array of x coordinates
 ///     >>> y = events.y  # numpy array of y coordinates
 #[pyfunction]
-fn decode_file(py: Python<'_>, path: &str) -> PyResult<Py<Events>> {
-    let path = PathBuf::from(path);
-
-    let mut decoder = Evt3Decoder::new();
-    let result = decoder
-        .decode_file(&path)
-        .map_err(|e| PyIOError::new_err(format!("Failed to decode file: {}", e)))?;
+#[allow(clippy::too_many_arguments)]
+#[pyo3(signature = (path, denoise_dt=None, dedup=false, invert_polarity=false, pixel_mask=None, rebase_trigger=None, t_start=None, t_end=None, roi=None, max_events=None, progress=None, strict=false))]
+fn decode_file(
+    py: Python<'_>,
+    path: PathBuf,
+    denoise_dt: Option<u64>,
+    dedup: bool,
+    invert_polarity: bool,
+    pixel_mask: Option<&PyAny>,
+    rebase_trigger: Option<u8>,
+    t_start: Option<u64>,
+    t_end: Option<u64>,
+    roi: Option<(u16, u16, u16, u16)>,
+    max_events: Option<usize>,
+    progress: Option<&PyAny>,
+    strict: bool,
+) -> PyResult<Py<Events>> {
+    let header = read_header(&path, strict)?;
+    let mut decoder = default_decoder();
+    configure_filters(&mut decoder, denoise_dt, dedup, invert_polarity, pixel_mask)?;
+    let (mut result, truncated) = decode_path(
+        py,
+        &mut decoder,
+        &path,
+        t_start,
+        t_end,
+        roi,
+        max_events,
+        progress,
+    )?;
+    rebase_result(&mut result, rebase_trigger)?;
 
-    let events = Events::from_cd_events(
-        result.cd_events,
-        result.metadata.width,
-        result.metadata.height,
-    );
+    let events = Events {
+        truncated,
+        header,
+        ..Events::from_columnar(
+            py,
+            result.cd_events,
+            result.metadata.width,
+            result.metadata.height,
+        )
+    };
 
     Py::new(py, events)
 }
@@ -248,7 +1401,31 @@ fn decode_file(py: Python<'_>, path: &str) -> PyResult<Py<Events>> {
 /// Decodes an EVT 3.0 raw file and returns both CD and trigger events.
 ///
 /// Args:
-///     path: Path to the .raw file
+///     path: Path to the .raw file (str or os.PathLike)
+///     denoise_dt: If set, drop events without a neighboring event within
+///         this many microseconds (background activity filter)
+///     dedup: Drop exact duplicate events (default: False)
+///     invert_polarity: Swap ON and OFF polarities (default: False)
+///     pixel_mask: Pixels whose events are dropped, as a list of (x, y)
+///         pairs or a path to a mask file (x,y text list or PNG where
+///         non-black pixels are masked)
+///     rebase_trigger: If set, shift timestamps so that t=0 is the first
+///         rising edge on this trigger channel; earlier events are dropped
+///     t_start: Drop events before this time in microseconds (before
+///         rebasing)
+///     t_end: Drop events at or after this time in microseconds; decoding
+///         stops once it is reached
+///     roi: Keep only events inside (x0, y0, x1, y1), with x1 and y1
+///         exclusive
+///     max_events: Stop decoding after this many CD events, e.g. for a
+///         quick preview; `truncated` on the result tells whether the file
+///         had more
+///     progress: Called with (bytes_processed, total_bytes, events_so_far)
+///         after every read of about 2 MB, e.g. to update a tqdm bar;
+///         an exception raised by it stops decoding
+///     strict: Raise TruncatedFileError if the data ends in the middle of
+///         a 16-bit word instead of dropping the incomplete last word, as
+///         is done by default so recordings cut off by a crash still open
 ///
 /// Returns:
 ///     tuple: (Events, TriggerEvents)
@@ -258,72 +1435,1714 @@ fn decode_file(py: Python<'_>, path: &str) -> PyResult<Py<Events>> {
 ///     >>> events, triggers = evt3.decode_file_with_triggers("recording.raw")
 ///     >>> print(f"CD events: {len(events)}, Triggers: {len(triggers)}")
 #[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[pyo3(signature = (path, denoise_dt=None, dedup=false, invert_polarity=false, pixel_mask=None, rebase_trigger=None, t_start=None, t_end=None, roi=None, max_events=None, progress=None, strict=false))]
 fn decode_file_with_triggers(
     py: Python<'_>,
-    path: &str,
+    path: PathBuf,
+    denoise_dt: Option<u64>,
+    dedup: bool,
+    invert_polarity: bool,
+    pixel_mask: Option<&PyAny>,
+    rebase_trigger: Option<u8>,
+    t_start: Option<u64>,
+    t_end: Option<u64>,
+    roi: Option<(u16, u16, u16, u16)>,
+    max_events: Option<usize>,
+    progress: Option<&PyAny>,
+    strict: bool,
 ) -> PyResult<(Py<Events>, Py<TriggerEvents>)> {
-    let path = PathBuf::from(path);
+    let header = read_header(&path, strict)?;
+    let mut decoder = default_decoder();
+    configure_filters(&mut decoder, denoise_dt, dedup, invert_polarity, pixel_mask)?;
+    let (mut result, truncated) = decode_path(
+        py,
+        &mut decoder,
+        &path,
+        t_start,
+        t_end,
+        roi,
+        max_events,
+        progress,
+    )?;
+    rebase_result(&mut result, rebase_trigger)?;
 
-    let mut decoder = Evt3Decoder::new();
-    let result = decoder
-        .decode_file(&path)
-        .map_err(|e| PyIOError::new_err(format!("Failed to decode file: {}", e)))?;
-
-    let events = Events::from_cd_events(
-        result.cd_events,
-        result.metadata.width,
-        result.metadata.height,
-    );
+    let events = Events {
+        truncated,
+        header,
+        ..Events::from_columnar(
+            py,
+            result.cd_events,
+            result.metadata.width,
+            result.metadata.height,
+        )
+    };
 
-    let triggers = TriggerEvents::from_trigger_events(result.trigger_events);
+    let triggers = TriggerEvents::from_trigger_events(py, result.trigger_events);
 
     Ok((Py::new(py, events)?, Py::new(py, triggers)?))
 }
 
-/// Decodes raw EVT 3.0 bytes and returns events.
+/// Decodes an EVT 3.0 raw file into preallocated numpy arrays.
 ///
-/// This is useful for streaming decoding or when the data is already in memory.
+/// Fills x, y, p and t from the start and returns the number of events
+/// written. Decoding stops once the shortest array is full, so arrays from
+/// a buffer pool (or pinned memory for GPU transfers) can be reused across
+/// calls; continue a longer recording with `t_start` past the last
+/// timestamp written.
 ///
 /// Args:
-///     data: Raw bytes containing EVT 3.0 encoded data
-///     sensor_width: Sensor width in pixels (default: 1280)
-///     sensor_height: Sensor height in pixels (default: 720)
+///     path: Path to the .raw file (str or os.PathLike)
+///     x, y: Writable contiguous uint16 arrays for the pixel coordinates
+///     p: Writable contiguous uint8 array for the polarities
+///     t: Writable contiguous uint64 array for the timestamps
+///     denoise_dt, dedup, invert_polarity, pixel_mask, t_start, t_end,
+///     roi: As for `decode_file`
 ///
 /// Returns:
-///     Events: Container with decoded events
+///     int: Number of events written to the front of each array
+///
+/// Example:
+///     >>> x = np.empty(1_000_000, np.uint16); y = np.empty_like(x)
+///     >>> p = np.empty(1_000_000, np.uint8); t = np.empty(1_000_000, np.uint64)
+///     >>> n = evt3.decode_file_into("recording.raw", x, y, p, t)
+///     >>> recent = t[:n]
 #[pyfunction]
-#[pyo3(signature = (data, sensor_width=1280, sensor_height=720))]
-fn decode_bytes(
+#[allow(clippy::too_many_arguments)]
+#[pyo3(signature = (path, x, y, p, t, denoise_dt=None, dedup=false, invert_polarity=false, pixel_mask=None, t_start=None, t_end=None, roi=None))]
+fn decode_file_into(
     py: Python<'_>,
-    data: &[u8],
-    sensor_width: u32,
-    sensor_height: u32,
-) -> PyResult<Py<Events>> {
-    // Convert bytes to u16 words (little-endian)
-    let words: Vec<u16> = data
-        .chunks_exact(2)
-        .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
-        .collect();
+    path: PathBuf,
+    x: &PyArray1<u16>,
+    y: &PyArray1<u16>,
+    p: &PyArray1<u8>,
+    t: &PyArray1<u64>,
+    denoise_dt: Option<u64>,
+    dedup: bool,
+    invert_polarity: bool,
+    pixel_mask: Option<&PyAny>,
+    t_start: Option<u64>,
+    t_end: Option<u64>,
+    roi: Option<(u16, u16, u16, u16)>,
+) -> PyResult<usize> {
+    let (start, end) = (t_start.unwrap_or(0), t_end.unwrap_or(u64::MAX));
+    if end <= start {
+        return Err(PyValueError::new_err("t_end must be after t_start"));
+    }
+    read_header(&path, false)?;
+    let mut decoder = default_decoder();
+    configure_filters(&mut decoder, denoise_dt, dedup, invert_polarity, pixel_mask)?;
+    if let Some((x0, y0, x1, y1)) = roi {
+        decoder.add_filter(RoiFilter::new(Roi::new(x0, y0, x1, y1)));
+    }
 
-    let mut decoder = Evt3Decoder::new();
-    decoder.metadata.width = sensor_width;
-    decoder.metadata.height = sensor_height;
+    // Borrowing every array mutably rejects the same array passed twice
+    let (mut x, mut y, mut p, mut t) = (
+        x.try_readwrite()?,
+        y.try_readwrite()?,
+        p.try_readwrite()?,
+        t.try_readwrite()?,
+    );
+    let (x, y, p, t) = (
+        x.as_slice_mut()?,
+        y.as_slice_mut()?,
+        p.as_slice_mut()?,
+        t.as_slice_mut()?,
+    );
+    let capacity = x.len().min(y.len()).min(p.len()).min(t.len());
+
+    let mut count = 0;
+    let result = py.allow_threads(|| {
+        decoder.decode_file_chunked(&path, |_, cd, _| {
+            let past_end = cd.last().is_some_and(|e| e.timestamp >= end);
+            for event in cd
+                .iter()
+                .filter(|e| e.timestamp >= start && e.timestamp < end)
+            {
+                if count == capacity {
+                    return Err(ChunkError::Stop);
+                }
+                x[count] = event.x;
+                y[count] = event.y;
+                p[count] = event.polarity;
+                t[count] = event.timestamp;
+                count += 1;
+            }
+            if past_end {
+                Err(ChunkError::Stop)
+            } else {
+                Ok(())
+            }
+        })
+    });
+    match result {
+        Ok(()) | Err(ChunkError::Stop) => Ok(count),
+        Err(ChunkError::Decode(e)) => Err(decode_error(e)),
+        Err(ChunkError::Callback(e)) => Err(e),
+    }
+}
+
+/// Sensor geometry assumed for data whose header does not give one, see
+/// `set_default_geometry`.
+static DEFAULT_GEOMETRY: Mutex<(u32, u32)> = Mutex::new((1280, 720));
+
+/// Creates a decoder that assumes the default geometry until a header
+/// says otherwise.
+fn default_decoder() -> Evt3Decoder {
+    let (width, height) = *DEFAULT_GEOMETRY.lock().unwrap();
+    let mut decoder = Evt3Decoder::new();
+    decoder.metadata.width = width;
+    decoder.metadata.height = height;
+    decoder
+}
+
+/// Sets the sensor geometry assumed for data without one in its header.
+///
+/// Applies to `decode_bytes`, `decode_stream`, `Decoder` and files whose
+/// header has no geometry, unless `sensor_width`/`sensor_height` are given
+/// in the call. The initial default is 1280x720 (Gen4 sensors).
+///
+/// Example:
+///     >>> evt3.set_default_geometry(640, 480)  # e.g. GenX320 cropped data
+#[pyfunction]
+fn set_default_geometry(width: u32, height: u32) -> PyResult<()> {
+    if width == 0 || height == 0 {
+        return Err(PyValueError::new_err("width and height must be positive"));
+    }
+    *DEFAULT_GEOMETRY.lock().unwrap() = (width, height);
+    Ok(())
+}
+
+/// Returns the default sensor geometry as (width, height), see
+/// `set_default_geometry`.
+#[pyfunction]
+fn get_default_geometry() -> (u32, u32) {
+    *DEFAULT_GEOMETRY.lock().unwrap()
+}
+
+/// Creates a decoder for headerless data with the filters requested through
+/// keyword arguments; missing sizes fall back to the default geometry.
+fn new_decoder(
+    sensor_width: Option<u32>,
+    sensor_height: Option<u32>,
+    denoise_dt: Option<u64>,
+    dedup: bool,
+    invert_polarity: bool,
+    pixel_mask: Option<&PyAny>,
+) -> PyResult<Evt3Decoder> {
+    let mut decoder = default_decoder();
+    if let Some(width) = sensor_width {
+        decoder.metadata.width = width;
+    }
+    if let Some(height) = sensor_height {
+        decoder.metadata.height = height;
+    }
+    configure_filters(&mut decoder, denoise_dt, dedup, invert_polarity, pixel_mask)?;
+    Ok(decoder)
+}
+
+/// Decodes in-memory EVT 3.0 data with the filters requested through keyword arguments.
+#[allow(clippy::too_many_arguments)]
+fn decode_data(
+    data: &[u8],
+    sensor_width: Option<u32>,
+    sensor_height: Option<u32>,
+    denoise_dt: Option<u64>,
+    dedup: bool,
+    invert_polarity: bool,
+    pixel_mask: Option<&PyAny>,
+) -> PyResult<evt3_core::DecodeResult> {
+    let mut decoder = new_decoder(
+        sensor_width,
+        sensor_height,
+        denoise_dt,
+        dedup,
+        invert_polarity,
+        pixel_mask,
+    )?;
 
     let mut cd_events = Vec::new();
     let mut trigger_events = Vec::new();
-    decoder.decode_buffer(&words, &mut cd_events, &mut trigger_events);
+    decoder.decode_bytes(data, &mut cd_events, &mut trigger_events);
+
+    Ok(evt3_core::DecodeResult {
+        cd_events,
+        trigger_events,
+        metadata: decoder.output_metadata(),
+    })
+}
+
+/// Decodes raw EVT 3.0 bytes and returns events.
+///
+/// This is useful for streaming decoding or when the data is already in memory.
+/// Trigger events are discarded; use `decode_bytes_with_triggers` to keep them.
+///
+/// Args:
+///     data: Raw bytes containing EVT 3.0 encoded data
+///     sensor_width: Sensor width in pixels (default: the width set with
+///         `set_default_geometry`, initially 1280)
+///     sensor_height: Sensor height in pixels (default: the height set
+///         with `set_default_geometry`, initially 720)
+///     denoise_dt: If set, drop events without a neighboring event within
+///         this many microseconds (background activity filter)
+///     dedup: Drop exact duplicate events (default: False)
+///     invert_polarity: Swap ON and OFF polarities (default: False)
+///     pixel_mask: Pixels whose events are dropped, as a list of (x, y)
+///         pairs or a path to a mask file (x,y text list or PNG where
+///         non-black pixels are masked)
+///
+/// Returns:
+///     Events: Container with decoded events
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[pyo3(signature = (data, sensor_width=None, sensor_height=None, denoise_dt=None, dedup=false, invert_polarity=false, pixel_mask=None))]
+fn decode_bytes(
+    py: Python<'_>,
+    data: &[u8],
+    sensor_width: Option<u32>,
+    sensor_height: Option<u32>,
+    denoise_dt: Option<u64>,
+    dedup: bool,
+    invert_polarity: bool,
+    pixel_mask: Option<&PyAny>,
+) -> PyResult<Py<Events>> {
+    let result = decode_data(
+        data,
+        sensor_width,
+        sensor_height,
+        denoise_dt,
+        dedup,
+        invert_polarity,
+        pixel_mask,
+    )?;
+
+    let events = Events::from_cd_events(
+        py,
+        result.cd_events,
+        result.metadata.width,
+        result.metadata.height,
+    );
+    Py::new(py, events)
+}
+
+/// Decodes raw EVT 3.0 bytes and returns both CD and trigger events.
+///
+/// Takes the same arguments as `decode_bytes`.
+///
+/// Returns:
+///     tuple: (Events, TriggerEvents)
+///
+/// Example:
+///     >>> import evt3
+///     >>> events, triggers = evt3.decode_bytes_with_triggers(chunk, 640, 480)
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[pyo3(signature = (data, sensor_width=None, sensor_height=None, denoise_dt=None, dedup=false, invert_polarity=false, pixel_mask=None))]
+fn decode_bytes_with_triggers(
+    py: Python<'_>,
+    data: &[u8],
+    sensor_width: Option<u32>,
+    sensor_height: Option<u32>,
+    denoise_dt: Option<u64>,
+    dedup: bool,
+    invert_polarity: bool,
+    pixel_mask: Option<&PyAny>,
+) -> PyResult<(Py<Events>, Py<TriggerEvents>)> {
+    let result = decode_data(
+        data,
+        sensor_width,
+        sensor_height,
+        denoise_dt,
+        dedup,
+        invert_polarity,
+        pixel_mask,
+    )?;
+
+    let events = Events::from_cd_events(
+        py,
+        result.cd_events,
+        result.metadata.width,
+        result.metadata.height,
+    );
+    let triggers = TriggerEvents::from_trigger_events(py, result.trigger_events);
 
-    let events = Events::from_cd_events(cd_events, sensor_width, sensor_height);
+    Ok((Py::new(py, events)?, Py::new(py, triggers)?))
+}
+
+/// Reader over a Python binary file-like object, calling its `read` method
+/// with the GIL held.
+struct PyFileReader {
+    file: PyObject,
+    /// Bytes read so far
+    count: u64,
+    /// Exception raised by `read`, re-raised once decoding has stopped
+    error: Option<PyErr>,
+}
+
+impl Read for PyFileReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        Python::with_gil(|py| {
+            let result = self
+                .file
+                .call_method1(py, "read", (buf.len(),))
+                .and_then(|data| {
+                    let data: &[u8] = data.as_ref(py).extract().map_err(|_| {
+                        PyValueError::new_err(
+                            "read() must return bytes; open the file in binary mode",
+                        )
+                    })?;
+                    if data.len() > buf.len() {
+                        return Err(PyValueError::new_err(
+                            "read() returned more bytes than requested",
+                        ));
+                    }
+                    buf[..data.len()].copy_from_slice(data);
+                    self.count += data.len() as u64;
+                    Ok(data.len())
+                });
+            result.map_err(|e| {
+                let message = e.to_string();
+                self.error = Some(e);
+                std::io::Error::other(message)
+            })
+        })
+    }
+}
+
+/// Decodes a file-like object with the filters requested through keyword
+/// arguments, releasing the GIL between reads.
+///
+/// Returns the result and the key-value pairs of the header, if any.
+#[allow(clippy::too_many_arguments)]
+fn decode_fileobj(
+    py: Python<'_>,
+    fileobj: &PyAny,
+    sensor_width: Option<u32>,
+    sensor_height: Option<u32>,
+    denoise_dt: Option<u64>,
+    dedup: bool,
+    invert_polarity: bool,
+    pixel_mask: Option<&PyAny>,
+    strict: bool,
+) -> PyResult<(evt3_core::DecodeResult, Header)> {
+    let mut decoder = new_decoder(
+        sensor_width,
+        sensor_height,
+        denoise_dt,
+        dedup,
+        invert_polarity,
+        pixel_mask,
+    )?;
+    let mut reader = BufReader::with_capacity(
+        ITER_READ_SIZE,
+        PyFileReader {
+            file: fileobj.into(),
+            count: 0,
+            error: None,
+        },
+    );
+    let result = py.allow_threads(|| {
+        let header = decoder.parse_header(&mut reader)?;
+        let header_size = reader.get_ref().count - reader.buffer().len() as u64;
+        decoder
+            .decode_reader(&mut reader)
+            .map(|result| (result, header, header_size))
+    });
+    let reader = reader.into_inner();
+    if let Some(error) = reader.error {
+        return Err(error);
+    }
+    let (result, header, header_size) = result.map_err(decode_error)?;
+    let header = header_fields(header);
+    check_data(&header, reader.count - header_size, strict)?;
+    Ok((result, header))
+}
+
+/// Decodes EVT 3.0 data read from a binary file-like object.
+///
+/// Anything with a `read(n)` method returning bytes works, e.g. the result
+/// of `open(path, "rb")`, `gzip.open`, `io.BytesIO` or an fsspec file. The
+/// data may start with a file header; the sensor size arguments are used
+/// when it has none. Trigger events are discarded; use
+/// `decode_stream_with_triggers` to keep them.
+///
+/// Args:
+///     fileobj: Binary file-like object
+///     sensor_width, sensor_height: Geometry of headerless data
+///         (default: see `set_default_geometry`, initially 1280x720)
+///     denoise_dt, dedup, invert_polarity, pixel_mask, strict: As for
+///         `decode_file`
+///
+/// Returns:
+///     Events: Container with decoded events
+///
+/// Example:
+///     >>> import gzip
+///     >>> with gzip.open("recording.raw.gz") as f:
+///     ...     events = evt3.decode_stream(f)
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[pyo3(signature = (fileobj, sensor_width=None, sensor_height=None, denoise_dt=None, dedup=false, invert_polarity=false, pixel_mask=None, strict=false))]
+fn decode_stream(
+    py: Python<'_>,
+    fileobj: &PyAny,
+    sensor_width: Option<u32>,
+    sensor_height: Option<u32>,
+    denoise_dt: Option<u64>,
+    dedup: bool,
+    invert_polarity: bool,
+    pixel_mask: Option<&PyAny>,
+    strict: bool,
+) -> PyResult<Py<Events>> {
+    let (result, header) = decode_fileobj(
+        py,
+        fileobj,
+        sensor_width,
+        sensor_height,
+        denoise_dt,
+        dedup,
+        invert_polarity,
+        pixel_mask,
+        strict,
+    )?;
+
+    let events = Events {
+        header,
+        ..Events::from_cd_events(
+            py,
+            result.cd_events,
+            result.metadata.width,
+            result.metadata.height,
+        )
+    };
     Py::new(py, events)
 }
 
+/// Decodes EVT 3.0 data read from a binary file-like object and returns
+/// both CD and trigger events.
+///
+/// Takes the same arguments as `decode_stream`.
+///
+/// Returns:
+///     tuple: (Events, TriggerEvents)
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[pyo3(signature = (fileobj, sensor_width=None, sensor_height=None, denoise_dt=None, dedup=false, invert_polarity=false, pixel_mask=None, strict=false))]
+fn decode_stream_with_triggers(
+    py: Python<'_>,
+    fileobj: &PyAny,
+    sensor_width: Option<u32>,
+    sensor_height: Option<u32>,
+    denoise_dt: Option<u64>,
+    dedup: bool,
+    invert_polarity: bool,
+    pixel_mask: Option<&PyAny>,
+    strict: bool,
+) -> PyResult<(Py<Events>, Py<TriggerEvents>)> {
+    let (result, header) = decode_fileobj(
+        py,
+        fileobj,
+        sensor_width,
+        sensor_height,
+        denoise_dt,
+        dedup,
+        invert_polarity,
+        pixel_mask,
+        strict,
+    )?;
+
+    let events = Events {
+        header,
+        ..Events::from_cd_events(
+            py,
+            result.cd_events,
+            result.metadata.width,
+            result.metadata.height,
+        )
+    };
+    let triggers = TriggerEvents::from_trigger_events(py, result.trigger_events);
+
+    Ok((Py::new(py, events)?, Py::new(py, triggers)?))
+}
+
+/// Decodes a chunk straight from its bytes, completing the odd byte left
+/// over from the previous chunk in `pending` and keeping this chunk's.
+fn decode_chunk(
+    decoder: &mut Evt3Decoder,
+    pending: &mut Option<u8>,
+    data: &[u8],
+    cd_events: &mut Vec<CdEvent>,
+    trigger_events: &mut Vec<TriggerEvent>,
+) {
+    let mut data = data;
+    if let (Some(low), Some((&high, rest))) = (*pending, data.split_first()) {
+        decoder.decode_bytes(&[low, high], cd_events, trigger_events);
+        *pending = None;
+        data = rest;
+    }
+    decoder.decode_bytes(data, cd_events, trigger_events);
+    if data.len() % 2 == 1 {
+        *pending = data.last().copied();
+    }
+}
+
+/// Stateful decoder for EVT 3.0 data that arrives in chunks, e.g. from a
+/// socket or a camera SDK callback.
+///
+/// Unlike `decode_bytes`, the timestamp, row and vector base state is kept
+/// between calls, so events that straddle two chunks decode correctly and
+/// timestamps keep counting across TIME_HIGH wrap-arounds. Chunks may have
+/// an odd length; the last byte is kept until the next chunk. The data must
+/// not contain a file header.
+///
+/// Args:
+///     sensor_width: Sensor width in pixels (default: the width set with
+///         `set_default_geometry`, initially 1280)
+///     sensor_height: Sensor height in pixels (default: the height set
+///         with `set_default_geometry`, initially 720)
+///     denoise_dt, dedup, invert_polarity, pixel_mask: As for `decode_bytes`;
+///         filter state is kept between chunks too
+///
+/// Example:
+///     >>> decoder = evt3.Decoder(640, 480)
+///     >>> while chunk := sock.recv(65536):
+///     ...     events = decoder.feed(chunk)
+#[pyclass]
+pub struct Decoder {
+    decoder: Evt3Decoder,
+    /// Odd trailing byte of the last chunk, completed by the next one
+    pending: Option<u8>,
+}
+
+#[pymethods]
+impl Decoder {
+    #[new]
+    #[pyo3(signature = (sensor_width=None, sensor_height=None, denoise_dt=None, dedup=false, invert_polarity=false, pixel_mask=None))]
+    fn new(
+        sensor_width: Option<u32>,
+        sensor_height: Option<u32>,
+        denoise_dt: Option<u64>,
+        dedup: bool,
+        invert_polarity: bool,
+        pixel_mask: Option<&PyAny>,
+    ) -> PyResult<Self> {
+        let decoder = new_decoder(
+            sensor_width,
+            sensor_height,
+            denoise_dt,
+            dedup,
+            invert_polarity,
+            pixel_mask,
+        )?;
+        Ok(Self {
+            decoder,
+            pending: None,
+        })
+    }
+
+    /// Returns a string representation.
+    fn __repr__(&self) -> String {
+        let metadata = self.decoder.output_metadata();
+        format!("Decoder(sensor={}x{})", metadata.width, metadata.height)
+    }
+
+    /// Decodes the next chunk and returns its CD events.
+    ///
+    /// Trigger events are discarded; use `feed_with_triggers` to keep them.
+    fn feed(&mut self, py: Python<'_>, data: &[u8]) -> PyResult<Py<Events>> {
+        let (events, _) = self.feed_with_triggers(py, data)?;
+        Ok(events)
+    }
+
+    /// Decodes the next chunk and returns its CD and trigger events.
+    ///
+    /// Returns:
+    ///     tuple: (Events, TriggerEvents)
+    fn feed_with_triggers(
+        &mut self,
+        py: Python<'_>,
+        data: &[u8],
+    ) -> PyResult<(Py<Events>, Py<TriggerEvents>)> {
+        let mut cd_events = Vec::new();
+        let mut trigger_events = Vec::new();
+        py.allow_threads(|| {
+            decode_chunk(
+                &mut self.decoder,
+                &mut self.pending,
+                data,
+                &mut cd_events,
+                &mut trigger_events,
+            );
+        });
+
+        let metadata = self.decoder.output_metadata();
+        let events = Events::from_cd_events(py, cd_events, metadata.width, metadata.height);
+        let triggers = TriggerEvents::from_trigger_events(py, trigger_events);
+        Ok((Py::new(py, events)?, Py::new(py, triggers)?))
+    }
+
+    /// Forgets all decoding and filter state, e.g. before feeding a new stream.
+    fn reset(&mut self) {
+        self.decoder.reset();
+        self.pending = None;
+    }
+
+    /// Returns the sensor width in pixels.
+    #[getter]
+    fn sensor_width(&self) -> u32 {
+        self.decoder.output_metadata().width
+    }
+
+    /// Returns the sensor height in pixels.
+    #[getter]
+    fn sensor_height(&self) -> u32 {
+        self.decoder.output_metadata().height
+    }
+}
+
+/// Bytes read from the file per decode step of `iter_file`.
+const ITER_READ_SIZE: usize = 1 << 20;
+
+/// A raw file decoded one read at a time, for `iter_file` and
+/// `frame_generator`.
+struct RawStream {
+    decoder: Evt3Decoder,
+    reader: BufReader<File>,
+    buffer: Vec<u8>,
+    /// Odd trailing byte of the last read
+    pending: Option<u8>,
+    /// Discarded trigger events, kept to reuse the allocation
+    trigger_events: Vec<TriggerEvent>,
+    done: bool,
+}
+
+impl RawStream {
+    /// Opens the file at `path` with the filters requested through keyword
+    /// arguments; returns the stream and the header.
+    fn open(
+        path: &Path,
+        denoise_dt: Option<u64>,
+        dedup: bool,
+        invert_polarity: bool,
+        pixel_mask: Option<&PyAny>,
+    ) -> PyResult<(Self, Header)> {
+        let mut decoder = default_decoder();
+        let (reader, header) = open_raw(path, &mut decoder, false)?;
+        configure_filters(&mut decoder, denoise_dt, dedup, invert_polarity, pixel_mask)?;
+        let stream = Self {
+            decoder,
+            reader,
+            buffer: vec![0; ITER_READ_SIZE],
+            pending: None,
+            trigger_events: Vec::new(),
+            done: false,
+        };
+        Ok((stream, header))
+    }
+
+    /// Decodes the next read of the file, appending its CD events to
+    /// `cd_events` and discarding trigger events; sets `done` at the end.
+    fn decode_next(&mut self, cd_events: &mut Vec<CdEvent>) -> std::io::Result<()> {
+        let n = loop {
+            match self.reader.read(&mut self.buffer) {
+                Ok(n) => break n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        };
+        if n == 0 {
+            self.done = true;
+            return Ok(());
+        }
+        decode_chunk(
+            &mut self.decoder,
+            &mut self.pending,
+            &self.buffer[..n],
+            cd_events,
+            &mut self.trigger_events,
+        );
+        self.trigger_events.clear();
+        Ok(())
+    }
+}
+
+/// Iterator over the CD events of a file in chunks, returned by `iter_file`.
+#[pyclass]
+pub struct EventChunks {
+    stream: RawStream,
+    chunk_events: usize,
+    /// Decoded events not yet returned
+    decoded: Vec<CdEvent>,
+    /// Key-value pairs of the file header
+    header: Header,
+}
+
+#[pymethods]
+impl EventChunks {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<Py<Events>>> {
+        let chunk = py
+            .allow_threads(|| self.next_chunk())
+            .map_err(|e| decode_error(e.into()))?;
+        if chunk.is_empty() {
+            return Ok(None);
+        }
+        let metadata = self.stream.decoder.output_metadata();
+        let events = Events {
+            header: self.header.clone(),
+            ..Events::from_cd_events(py, chunk, metadata.width, metadata.height)
+        };
+        Ok(Some(Py::new(py, events)?))
+    }
+}
+
+impl EventChunks {
+    /// Decodes until `chunk_events` events are available or the file ends
+    /// and returns up to `chunk_events` of them.
+    fn next_chunk(&mut self) -> std::io::Result<Vec<CdEvent>> {
+        while !self.stream.done && self.decoded.len() < self.chunk_events {
+            self.stream.decode_next(&mut self.decoded)?;
+        }
+        let rest = self
+            .decoded
+            .split_off(self.decoded.len().min(self.chunk_events));
+        Ok(std::mem::replace(&mut self.decoded, rest))
+    }
+}
+
+/// Iterates over the CD events of an EVT 3.0 raw file in chunks.
+///
+/// Only one chunk is held in memory at a time, so files larger than the
+/// available memory can be processed. The GIL is released while each chunk
+/// is decoded. Trigger events are discarded.
+///
+/// Args:
+///     path: Path to the .raw file (str or os.PathLike)
+///     chunk_events: Number of events per chunk; the last chunk may be
+///         smaller (default: 1000000)
+///     denoise_dt, dedup, invert_polarity, pixel_mask: As for `decode_file`
+///
+/// Returns:
+///     Iterator of Events
+///
+/// Example:
+///     >>> counts = np.zeros((720, 1280), dtype=np.uint64)
+///     >>> for chunk in evt3.iter_file("recording.raw"):
+///     ...     np.add.at(counts, (chunk.y, chunk.x), 1)
+#[pyfunction]
+#[pyo3(signature = (path, chunk_events=1_000_000, denoise_dt=None, dedup=false, invert_polarity=false, pixel_mask=None))]
+fn iter_file(
+    path: PathBuf,
+    chunk_events: usize,
+    denoise_dt: Option<u64>,
+    dedup: bool,
+    invert_polarity: bool,
+    pixel_mask: Option<&PyAny>,
+) -> PyResult<EventChunks> {
+    if chunk_events == 0 {
+        return Err(PyValueError::new_err("chunk_events must be positive"));
+    }
+    let (stream, header) = RawStream::open(&path, denoise_dt, dedup, invert_polarity, pixel_mask)?;
+    Ok(EventChunks {
+        stream,
+        chunk_events,
+        decoded: Vec::new(),
+        header,
+    })
+}
+
+/// A frame accumulated by `frame_generator`.
+enum Frame {
+    Count(Vec<u32>),
+    Signed(Vec<i32>),
+}
+
+/// Iterator over fixed-rate event frames of a file, returned by
+/// `frame_generator`.
+#[pyclass]
+pub struct FrameGenerator {
+    stream: RawStream,
+    fps: f64,
+    signed: bool,
+    /// Decoded events not yet in a returned frame
+    decoded: Vec<CdEvent>,
+    /// Timestamp of the first event, where the first frame starts
+    origin: Option<u64>,
+    frame_index: u64,
+}
+
+#[pymethods]
+impl FrameGenerator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<(u64, PyObject)>> {
+        let frame = py
+            .allow_threads(|| self.next_frame())
+            .map_err(|e| decode_error(e.into()))?;
+        let Some((t0, frame)) = frame else {
+            return Ok(None);
+        };
+        let metadata = self.stream.decoder.output_metadata();
+        let shape = [metadata.height as usize, metadata.width as usize];
+        let frame = match frame {
+            Frame::Count(frame) => frame.into_pyarray(py).reshape(shape)?.into(),
+            Frame::Signed(frame) => frame.into_pyarray(py).reshape(shape)?.into(),
+        };
+        Ok(Some((t0, frame)))
+    }
+}
+
+impl FrameGenerator {
+    /// Returns the start time of frame `index`.
+    fn frame_start(&self, origin: u64, index: u64) -> u64 {
+        origin + (index as f64 * 1e6 / self.fps).round() as u64
+    }
+
+    /// Decodes until the next frame is complete and returns its start time
+    /// and pixels, or None once all events have been returned.
+    fn next_frame(&mut self) -> std::io::Result<Option<(u64, Frame)>> {
+        loop {
+            if self.origin.is_none() {
+                self.origin = self.decoded.first().map(|e| e.timestamp);
+            }
+            if let Some(origin) = self.origin {
+                if self.stream.done && self.decoded.is_empty() {
+                    return Ok(None);
+                }
+                let t0 = self.frame_start(origin, self.frame_index);
+                let t1 = self.frame_start(origin, self.frame_index + 1);
+                let complete = self.decoded.last().is_some_and(|e| e.timestamp >= t1);
+                if complete || self.stream.done {
+                    let n = self.decoded.partition_point(|e| e.timestamp < t1);
+                    let events = &self.decoded[..n];
+                    let metadata = self.stream.decoder.output_metadata();
+                    let (width, height) = (metadata.width, metadata.height);
+                    let frame = if self.signed {
+                        Frame::Signed(frames::accumulate_signed(events, width, height, t0, t1))
+                    } else {
+                        Frame::Count(frames::accumulate(events, width, height, t0, t1))
+                    };
+                    self.decoded.drain(..n);
+                    self.frame_index += 1;
+                    return Ok(Some((t0, frame)));
+                }
+            } else if self.stream.done {
+                return Ok(None);
+            }
+            self.stream.decode_next(&mut self.decoded)?;
+        }
+    }
+}
+
+/// Streams a raw file as event frames at a fixed frame rate.
+///
+/// Frame i accumulates the events from `t0 = t_first + i / fps` seconds
+/// for one frame period, where `t_first` is the timestamp of the first
+/// event; frames without events are yielded as well. Only the events of
+/// the current frame are held in memory, and the GIL is released while
+/// decoding and accumulating.
+///
+/// Args:
+///     path: Path to the .raw file (str or os.PathLike)
+///     fps: Frames per second of recording time (default: 60)
+///     mode: "count" for uint32 event counts per pixel, "signed" for int32
+///         polarity sums (+1 ON, -1 OFF) (default: "count")
+///     denoise_dt, dedup, invert_polarity, pixel_mask: As for `decode_file`
+///
+/// Returns:
+///     Iterator of (t0, frame) tuples, frame of shape (height, width)
+///
+/// Example:
+///     >>> for t0, frame in evt3.frame_generator("recording.raw", fps=30):
+///     ...     cv2.imshow("events", (frame > 0).astype(np.uint8) * 255)
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[pyo3(signature = (path, fps=60.0, mode="count", denoise_dt=None, dedup=false, invert_polarity=false, pixel_mask=None))]
+fn frame_generator(
+    path: PathBuf,
+    fps: f64,
+    mode: &str,
+    denoise_dt: Option<u64>,
+    dedup: bool,
+    invert_polarity: bool,
+    pixel_mask: Option<&PyAny>,
+) -> PyResult<FrameGenerator> {
+    if !(fps > 0.0 && fps.is_finite()) {
+        return Err(PyValueError::new_err("fps must be positive"));
+    }
+    let signed = match mode {
+        "count" => false,
+        "signed" => true,
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "Unknown mode {:?}; use \"count\" or \"signed\"",
+                mode
+            )))
+        }
+    };
+    let (stream, _) = RawStream::open(&path, denoise_dt, dedup, invert_polarity, pixel_mask)?;
+    Ok(FrameGenerator {
+        stream,
+        fps,
+        signed,
+        decoded: Vec::new(),
+        origin: None,
+        frame_index: 0,
+    })
+}
+
+/// One recording of an EventDataset.
+struct DatasetFile {
+    /// The recording with its time index
+    file: Evt3File,
+    /// Timestamps of the first and last CD event
+    first_timestamp: u64,
+    last_timestamp: u64,
+    /// Number of items before this file
+    offset: usize,
+}
+
+/// Map-style dataset over a directory of EVT 3.0 raw files, e.g. for a
+/// PyTorch `Dataset`.
+///
+/// Opening the dataset decodes each file once to build a time index, as
+/// `Evt3File` does. Each item is decoded when it is requested, starting at
+/// the indexed block before it, so the recordings are never loaded all at
+/// once and reading any item costs about as much as decoding that item.
+/// Without `window`, item i is the whole of file i; with
+/// `window`, each file is split into consecutive windows of that many
+/// microseconds, starting at its first event, and each window is an item.
+///
+/// Args:
+///     root: Directory containing the .raw files (searched non-recursively,
+///         in name order)
+///     window: Length of each item in microseconds, or None for whole files
+///
+/// Example:
+///     >>> dataset = evt3.EventDataset("recordings/", window=50_000)
+///     >>> events = dataset[0]  # first 50ms of the first file
+///     >>> path, t0, t1 = dataset.locate(0)
+#[pyclass(module = "evt3")]
+pub struct EventDataset {
+    root: PathBuf,
+    window: Option<u64>,
+    files: Vec<DatasetFile>,
+    len: usize,
+}
+
+#[pymethods]
+impl EventDataset {
+    #[new]
+    #[pyo3(signature = (root, window=None))]
+    fn new(py: Python<'_>, root: PathBuf, window: Option<u64>) -> PyResult<Self> {
+        if window == Some(0) {
+            return Err(PyValueError::new_err("window must be positive"));
+        }
+        let io_error = |e: std::io::Error| decode_error(e.into());
+        let mut paths = std::fs::read_dir(&root)
+            .map_err(io_error)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<std::io::Result<Vec<_>>>()
+            .map_err(io_error)?;
+        paths.retain(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "raw"));
+        paths.sort();
+
+        let mut files = Vec::with_capacity(paths.len());
+        let mut len = 0;
+        for path in paths {
+            let file = Evt3File::new(py, path, None)?;
+            let (Some(first), Some(last)) = (file.index.first_timestamp, file.index.last_timestamp)
+            else {
+                // Files without events have no items
+                continue;
+            };
+            let items = match window {
+                Some(window) => ((last - first) / window + 1) as usize,
+                None => 1,
+            };
+            files.push(DatasetFile {
+                file,
+                first_timestamp: first,
+                last_timestamp: last,
+                offset: len,
+            });
+            len += items;
+        }
+
+        Ok(Self {
+            root,
+            window,
+            files,
+            len,
+        })
+    }
+
+    /// Returns the number of items.
+    fn __len__(&self) -> usize {
+        self.len
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "EventDataset(root={:?}, files={}, items={})",
+            self.root,
+            self.files.len(),
+            self.len
+        )
+    }
+
+    /// Decodes item `index` (negative indices count from the end).
+    fn __getitem__(&self, py: Python<'_>, index: isize) -> PyResult<Py<Events>> {
+        let (file, t_start, t_end) = self.item(index)?;
+        file.read(py, Some(t_start), Some(t_end))
+    }
+
+    /// Returns the file and time range of item `index` as
+    /// (path, t_start, t_end), with t_end exclusive.
+    fn locate(&self, index: isize) -> PyResult<(PathBuf, u64, u64)> {
+        let (file, t_start, t_end) = self.item(index)?;
+        Ok((file.path.clone(), t_start, t_end))
+    }
+
+    /// Returns the indexed files in item order.
+    #[getter]
+    fn files(&self) -> Vec<PathBuf> {
+        self.files
+            .iter()
+            .map(|file| file.file.path.clone())
+            .collect()
+    }
+
+    /// Supports pickling, e.g. for DataLoader workers; the files are
+    /// indexed again when unpickled.
+    fn __reduce__(slf: PyRef<'_, Self>, py: Python<'_>) -> (PyObject, PyObject) {
+        let state = (slf.root.clone(), slf.window);
+        (
+            slf.into_py(py).as_ref(py).get_type().into(),
+            state.into_py(py),
+        )
+    }
+}
+
+impl EventDataset {
+    /// Returns the file and time range of item `index`.
+    fn item(&self, index: isize) -> PyResult<(&Evt3File, u64, u64)> {
+        let index = if index < 0 {
+            index + self.len as isize
+        } else {
+            index
+        };
+        if index < 0 || index as usize >= self.len {
+            return Err(PyIndexError::new_err("Dataset index out of range"));
+        }
+        let index = index as usize;
+        let file = &self.files[self.files.partition_point(|file| file.offset <= index) - 1];
+        Ok(match self.window {
+            Some(window) => {
+                let t_start = file.first_timestamp + (index - file.offset) as u64 * window;
+                (&file.file, t_start, t_start + window)
+            }
+            None => (&file.file, file.first_timestamp, file.last_timestamp + 1),
+        })
+    }
+}
+
+/// An EVT 3.0 raw file with a time index, for repeated random access.
+///
+/// Opening the file decodes it once to record the decoder state every
+/// 256 KB of data. `read(t0, t1)` then seeks to the block before `t0`
+/// and decodes only up to `t1`, so reading windows anywhere in a long
+/// recording costs about as much as decoding the windows themselves.
+///
+/// With `index_path`, the index is loaded from that file if it was built
+/// for the current file size, and built and saved there otherwise.
+///
+/// Args:
+///     path: Path to the .raw file (str or os.PathLike)
+///     index_path: Optional file to load the index from or save it to
+///
+/// Example:
+///     >>> f = evt3.Evt3File("recording.raw", index_path="recording.tidx")
+///     >>> events = f.read(60_000_000, 60_050_000)
+#[pyclass(module = "evt3")]
+pub struct Evt3File {
+    path: PathBuf,
+    header: Header,
+    metadata: SensorMetadata,
+    index: TimeIndex,
+}
+
+#[pymethods]
+impl Evt3File {
+    #[new]
+    #[pyo3(signature = (path, index_path=None))]
+    fn new(py: Python<'_>, path: PathBuf, index_path: Option<PathBuf>) -> PyResult<Self> {
+        let io_error = |e: std::io::Error| decode_error(e.into());
+        let mut decoder = default_decoder();
+        let (mut reader, header) = open_raw(&path, &mut decoder, false)?;
+        let data_offset = reader.stream_position().map_err(io_error)?;
+        let file_size = reader.get_ref().metadata().map_err(io_error)?.len();
+        drop(reader);
+
+        let saved = match &index_path {
+            Some(index_path) if index_path.exists() => {
+                Some(TimeIndex::load(index_path).map_err(decode_error)?)
+            }
+            _ => None,
+        };
+        let index = match saved {
+            Some(index) if index.file_size == file_size => index,
+            _ => {
+                let index = py
+                    .allow_threads(|| TimeIndex::build(&path, data_offset))
+                    .map_err(io_error)?;
+                if let Some(index_path) = &index_path {
+                    index.save(index_path).map_err(io_error)?;
+                }
+                index
+            }
+        };
+
+        Ok(Self {
+            path,
+            header,
+            metadata: decoder.metadata,
+            index,
+        })
+    }
+
+    /// Returns the number of CD events in the file.
+    fn __len__(&self) -> usize {
+        self.index.event_count as usize
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Evt3File({:?}, events={}, sensor={}x{})",
+            self.path, self.index.event_count, self.metadata.width, self.metadata.height
+        )
+    }
+
+    /// Returns the path of the raw file.
+    #[getter]
+    fn path(&self) -> PathBuf {
+        self.path.clone()
+    }
+
+    /// Returns the sensor geometry as (width, height).
+    #[getter]
+    fn sensor_size(&self) -> (u32, u32) {
+        (self.metadata.width, self.metadata.height)
+    }
+
+    /// Returns the timestamp of the first CD event, or None without events.
+    #[getter]
+    fn first_timestamp(&self) -> Option<u64> {
+        self.index.first_timestamp
+    }
+
+    /// Returns the timestamp of the last CD event, or None without events.
+    #[getter]
+    fn last_timestamp(&self) -> Option<u64> {
+        self.index.last_timestamp
+    }
+
+    /// Decodes the events with `t0 <= timestamp < t1`.
+    ///
+    /// Args:
+    ///     t0: Start of the window in microseconds (default: start of file)
+    ///     t1: End of the window in microseconds, exclusive (default: end
+    ///         of file)
+    ///
+    /// Returns:
+    ///     Events: The events in the window
+    #[pyo3(signature = (t0=None, t1=None))]
+    fn read(&self, py: Python<'_>, t0: Option<u64>, t1: Option<u64>) -> PyResult<Py<Events>> {
+        let (start, end) = (t0.unwrap_or(0), t1.unwrap_or(u64::MAX));
+        if end <= start {
+            return Err(PyValueError::new_err("t1 must be after t0"));
+        }
+        let cd_events = py
+            .allow_threads(|| self.index.read_range(&self.path, start, end))
+            .map_err(decode_error)?;
+
+        let events = Events {
+            header: self.header.clone(),
+            ..Events::from_cd_events(py, cd_events, self.metadata.width, self.metadata.height)
+        };
+        Py::new(py, events)
+    }
+}
+
+/// Concatenates Events objects, e.g. the files of a split recording.
+///
+/// `a + b` is the same as `evt3.concatenate([a, b])`. All objects must
+/// come from sensors of the same size.
+///
+/// Args:
+///     events: Sequence of Events objects
+///     offsets: Optional microseconds added to the timestamps of each
+///         object, one per object, e.g. to place recordings that each start
+///         at t=0 one after another
+///
+/// Returns:
+///     Events: All events, in the order given
+///
+/// Example:
+///     >>> parts = [evt3.decode_file(p) for p in ["a.raw", "b.raw"]]
+///     >>> events = evt3.concatenate(parts, offsets=[0, int(parts[0].t[-1]) + 1])
+#[pyfunction]
+#[pyo3(signature = (events, offsets=None))]
+fn concatenate(
+    py: Python<'_>,
+    events: Vec<PyRef<'_, Events>>,
+    offsets: Option<Vec<u64>>,
+) -> PyResult<Events> {
+    let offsets = offsets.unwrap_or_else(|| vec![0; events.len()]);
+    if offsets.len() != events.len() {
+        return Err(PyValueError::new_err(format!(
+            "Got {} offsets for {} Events objects",
+            offsets.len(),
+            events.len()
+        )));
+    }
+    let parts: Vec<(&Events, u64)> = events.iter().map(|e| &**e).zip(offsets).collect();
+    Events::concatenate(py, &parts)
+}
+
+/// Converts an output error into a Python exception.
+fn output_error(e: OutputError) -> PyErr {
+    match e {
+        OutputError::InvalidFormat(message) => PyValueError::new_err(message),
+        OutputError::Io(e) => PyIOError::new_err(format!("Failed to write file: {}", e)),
+    }
+}
+
+/// Writes events to a CSV (or other delimited text) file.
+///
+/// Much faster than `numpy.savetxt`; the GIL is released while writing.
+///
+/// Args:
+///     events: Events to write
+///     path: Output file path
+///     order: Column order (default: "x,y,p,t")
+///     time_unit: Timestamp unit: "us", "ns", "ms" or "s" (default: "us")
+///     delimiter: Column delimiter: a single character or "comma", "tab",
+///         "semicolon", "space" (default: ",")
+///     header: Write the `%geometry:W,H` line first (default: True)
+///     column_names: Write a row of column names, e.g. for
+///         `pandas.read_csv` (default: False)
+///
+/// Example:
+///     >>> evt3.write_csv(events, "events.csv", order="t,x,y,p")
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[pyo3(signature = (events, path, order="x,y,p,t", time_unit="us", delimiter=",", header=true, column_names=false))]
+fn write_csv(
+    py: Python<'_>,
+    events: &Events,
+    path: PathBuf,
+    order: &str,
+    time_unit: &str,
+    delimiter: &str,
+    header: bool,
+    column_names: bool,
+) -> PyResult<()> {
+    let options = CsvOptions {
+        field_order: order.parse().map_err(output_error)?,
+        timestamp_unit: time_unit.parse().map_err(output_error)?,
+        delimiter: output::parse_delimiter(delimiter).map_err(output_error)?,
+        geometry_header: header,
+        column_names,
+        ..CsvOptions::default()
+    };
+    let metadata = events.sensor_metadata();
+    let cd_events = events.cd_events(py)?;
+    py.allow_threads(|| output::write_csv(&path, &cd_events, Some(&metadata), &options))
+        .map_err(output_error)
+}
+
+/// Writes events to an EVT3BIN binary file (14 bytes per event), as
+/// written by `evt3-decode --format bin`.
+#[pyfunction]
+fn write_binary(py: Python<'_>, events: &Events, path: PathBuf) -> PyResult<()> {
+    let metadata = events.sensor_metadata();
+    let cd_events = events.cd_events(py)?;
+    py.allow_threads(|| output::write_binary(&path, &cd_events, &metadata))
+        .map_err(output_error)
+}
+
+/// Writes events to a MATLAB MAT-file with variables x, y, p, t, width
+/// and height.
+///
+/// The file is a v7.3 MAT-file (`save -v7.3`), which is HDF5-based and
+/// can also be read with `h5py`.
+#[pyfunction]
+fn write_mat(py: Python<'_>, events: &Events, path: PathBuf) -> PyResult<()> {
+    let metadata = events.sensor_metadata();
+    let cd_events = events.cd_events(py)?;
+    py.allow_threads(|| output::write_mat(&path, &cd_events, &metadata))
+        .map_err(output_error)
+}
+
+/// Writes events to an Event Stream (.es) file.
+#[pyfunction]
+fn write_es(py: Python<'_>, events: &Events, path: PathBuf) -> PyResult<()> {
+    let metadata = events.sensor_metadata();
+    let cd_events = events.cd_events(py)?;
+    py.allow_threads(|| output::write_es(&path, &cd_events, &metadata))
+        .map_err(output_error)
+}
+
+/// Converts an array-like of integers to a contiguous int64 numpy array.
+fn int64_array(values: &PyAny) -> PyResult<PyReadonlyArray1<'_, i64>> {
+    let numpy = values.py().import("numpy")?;
+    let array = numpy.call_method1("ascontiguousarray", (values, "int64"))?;
+    Ok(array.downcast::<PyArray1<i64>>()?.readonly())
+}
+
+/// Encodes events given as arrays into an EVT 3.0 raw file.
+///
+/// The file can be read back with `decode_file`, the CLI and the Metavision
+/// SDK, e.g. to store synthetic event data in the native camera format.
+///
+/// Args:
+///     path: Output .raw file path
+///     x, y: Pixel coordinates (array-likes of integers)
+///     p: Polarities, 0 (OFF) or 1 (ON)
+///     t: Timestamps in microseconds, sorted in ascending order
+///     width, height: Sensor size written to the header; all events must
+///         lie inside it
+///     triggers: Optional trigger events to interleave with the CD events
+///
+/// Example:
+///     >>> t = np.arange(1000) * 10
+///     >>> x = np.random.randint(0, 640, 1000)
+///     >>> y = np.random.randint(0, 480, 1000)
+///     >>> evt3.encode_file("synthetic.raw", x, y, np.ones(1000), t, 640, 480)
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[pyo3(signature = (path, x, y, p, t, width, height, triggers=None))]
+fn encode_file(
+    py: Python<'_>,
+    path: PathBuf,
+    x: &PyAny,
+    y: &PyAny,
+    p: &PyAny,
+    t: &PyAny,
+    width: u32,
+    height: u32,
+    triggers: Option<&TriggerEvents>,
+) -> PyResult<()> {
+    let (x, y, p, t) = (
+        int64_array(x)?,
+        int64_array(y)?,
+        int64_array(p)?,
+        int64_array(t)?,
+    );
+    let (x, y, p, t) = (x.as_slice()?, y.as_slice()?, p.as_slice()?, t.as_slice()?);
+    if [y.len(), p.len(), t.len()]
+        .iter()
+        .any(|&len| len != x.len())
+    {
+        return Err(PyValueError::new_err(
+            "x, y, p and t must have the same length",
+        ));
+    }
+
+    let mut cd_events = Vec::with_capacity(x.len());
+    let mut last = 0;
+    for i in 0..x.len() {
+        if !(0..width as i64).contains(&x[i]) || !(0..height as i64).contains(&y[i]) {
+            return Err(PyValueError::new_err(format!(
+                "Event {} at ({}, {}) is outside the {}x{} sensor",
+                i, x[i], y[i], width, height
+            )));
+        }
+        if !(0..=1).contains(&p[i]) {
+            return Err(PyValueError::new_err(format!(
+                "Event {} has polarity {}; use 0 or 1",
+                i, p[i]
+            )));
+        }
+        if t[i] < last {
+            return Err(PyValueError::new_err(format!(
+                "Event {} at t={} is out of timestamp order",
+                i, t[i]
+            )));
+        }
+        last = t[i];
+        cd_events.push(CdEvent::new(
+            x[i] as u16,
+            y[i] as u16,
+            p[i] as u8,
+            t[i] as u64,
+        ));
+    }
+    let trigger_events = match triggers {
+        Some(triggers) => triggers.trigger_events(py)?,
+        None => Vec::new(),
+    };
+
+    let metadata = SensorMetadata { width, height };
+    py.allow_threads(|| output::write_raw(&path, &cd_events, &trigger_events, &metadata))
+        .map_err(output_error)
+}
+
+/// Builds a voxel grid representation of the events.
+///
+/// Each event adds its polarity (+1 ON, -1 OFF) to the two temporal bins
+/// nearest to its timestamp, weighted by distance (bilinear weighting).
+///
+/// Args:
+///     events: Events to bin
+///     num_bins: Number of temporal bins
+///     t0: Start of the time range in microseconds (default: first event)
+///     t1: End of the time range in microseconds, inclusive (default: last event)
+///
+/// Returns:
+///     np.ndarray: float32 array of shape (num_bins, height, width)
+///
+/// Example:
+///     >>> events = evt3.decode_file("recording.raw")
+///     >>> grid = evt3.voxel_grid(events, 5)
+#[pyfunction]
+#[pyo3(signature = (events, num_bins, t0=None, t1=None))]
+fn voxel_grid<'py>(
+    py: Python<'py>,
+    events: &Events,
+    num_bins: usize,
+    t0: Option<u64>,
+    t1: Option<u64>,
+) -> PyResult<&'py PyArray3<f32>> {
+    events.to_voxel_grid(py, num_bins, t0, t1)
+}
+
+/// Parses an `edge` argument ("rising" or "falling").
+fn parse_edge(edge: &str) -> PyResult<Edge> {
+    match edge {
+        "rising" => Ok(Edge::Rising),
+        "falling" => Ok(Edge::Falling),
+        _ => Err(PyValueError::new_err(format!(
+            "edge must be 'rising' or 'falling', got '{}'",
+            edge
+        ))),
+    }
+}
+
+/// Splits events into segments between consecutive trigger edges.
+///
+/// With n matching edges on the channel there are n - 1 segments; events
+/// before the first and from the last edge onwards are not included.
+/// Events must be sorted by timestamp, as returned by the decoder.
+///
+/// Args:
+///     events: Decoded CD events
+///     triggers: Decoded trigger events
+///     channel: Trigger channel ID (default: 0)
+///     edge: "rising" or "falling" (default: "rising")
+///
+/// Returns:
+///     list[tuple[int, int, int, int]]: (start_time, end_time, start_index,
+///     end_index) per segment; times and indices are half-open
+///
+/// Example:
+///     >>> events, triggers = evt3.decode_file_with_triggers("recording.raw")
+///     >>> for t0, t1, i0, i1 in evt3.slice_by_triggers(events, triggers):
+///     ...     frame_x = events.x[i0:i1]
+#[pyfunction]
+#[pyo3(signature = (events, triggers, channel=0, edge="rising"))]
+fn slice_by_triggers(
+    py: Python<'_>,
+    events: &Events,
+    triggers: &TriggerEvents,
+    channel: u8,
+    edge: &str,
+) -> PyResult<Vec<(u64, u64, usize, usize)>> {
+    let edge = parse_edge(edge)?;
+    let segments = triggers::slice_by_triggers(
+        &events.cd_events(py)?,
+        &triggers.trigger_events(py)?,
+        channel,
+        edge,
+    );
+    Ok(segments
+        .into_iter()
+        .map(|s| (s.start_time, s.end_time, s.events.start, s.events.end))
+        .collect())
+}
+
+/// Removes bouncing trigger edges.
+///
+/// An edge is kept only if no edge was kept on the same channel within the
+/// preceding `gap` microseconds, so each burst of contact bounce collapses
+/// to its first edge.
+///
+/// Args:
+///     triggers: Decoded trigger events
+///     gap: Minimum gap between kept edges in microseconds
+///
+/// Returns:
+///     TriggerEvents: The debounced trigger events
+///
+/// Example:
+///     >>> events, triggers = evt3.decode_file_with_triggers("recording.raw")
+///     >>> triggers = evt3.debounce_triggers(triggers, 500)
+#[pyfunction]
+fn debounce_triggers(
+    py: Python<'_>,
+    triggers: &TriggerEvents,
+    gap: u64,
+) -> PyResult<TriggerEvents> {
+    let mut events = triggers.trigger_events(py)?;
+    triggers::debounce(&mut events, gap);
+    Ok(TriggerEvents::from_trigger_events(py, events))
+}
+
+/// Pairs rising and falling trigger edges per channel into pulses.
+///
+/// Unmatched edges are ignored; if two rising edges occur without a falling
+/// edge in between, the pulse starts at the later one.
+///
+/// Args:
+///     triggers: Decoded trigger events
+///
+/// Returns:
+///     dict: numpy arrays "id" (uint8), "start" and "width" (uint64,
+///     microseconds), ordered by start time
+///
+/// Example:
+///     >>> events, triggers = evt3.decode_file_with_triggers("recording.raw")
+///     >>> pulses = evt3.trigger_pulses(triggers)
+///     >>> frame_ids = pulses["width"] // 100
+#[pyfunction]
+fn trigger_pulses<'py>(py: Python<'py>, triggers: &TriggerEvents) -> PyResult<PyObject> {
+    let pulses = triggers::pair_pulses(&triggers.trigger_events(py)?);
+    let dict = PyDict::new(py);
+    dict.set_item(
+        "id",
+        pulses
+            .iter()
+            .map(|p| p.id)
+            .collect::<Vec<_>>()
+            .into_pyarray(py),
+    )?;
+    dict.set_item(
+        "start",
+        pulses
+            .iter()
+            .map(|p| p.start)
+            .collect::<Vec<_>>()
+            .into_pyarray(py),
+    )?;
+    dict.set_item(
+        "width",
+        pulses
+            .iter()
+            .map(|p| p.width)
+            .collect::<Vec<_>>()
+            .into_pyarray(py),
+    )?;
+    Ok(dict.into())
+}
+
+/// Moves a recording into the time base of a reference recording that shares
+/// a trigger signal, e.g. the second camera of a stereo pair.
+///
+/// The offset is estimated by matching the trigger edges of both recordings
+/// on `channel`; the recordings may start at different times. Events that
+/// would end up before t=0 are dropped.
+///
+/// Args:
+///     reference_triggers: Trigger events of the reference recording
+///     events: CD events of the recording to align
+///     triggers: Trigger events of the recording to align
+///     channel: Shared trigger channel ID (default: 0)
+///     edge: "rising" or "falling" (default: "rising")
+///
+/// Returns:
+///     tuple: (Events, TriggerEvents, offset) with the aligned events and the
+///     offset in microseconds that was subtracted from their timestamps
+///
+/// Example:
+///     >>> left, left_trig = evt3.decode_file_with_triggers("left.raw")
+///     >>> right, right_trig = evt3.decode_file_with_triggers("right.raw")
+///     >>> right, right_trig, offset = evt3.synchronize(left_trig, right, right_trig)
+#[pyfunction]
+#[pyo3(signature = (reference_triggers, events, triggers, channel=0, edge="rising"))]
+fn synchronize(
+    py: Python<'_>,
+    reference_triggers: &TriggerEvents,
+    events: &Events,
+    triggers: &TriggerEvents,
+    channel: u8,
+    edge: &str,
+) -> PyResult<(Py<Events>, Py<TriggerEvents>, i64)> {
+    let edge = parse_edge(edge)?;
+    let mut trigger_events = triggers.trigger_events(py)?;
+    let sync = triggers::estimate_offset(
+        &reference_triggers.trigger_events(py)?,
+        &trigger_events,
+        channel,
+        edge,
+    )
+    .ok_or_else(|| {
+        PyValueError::new_err(format!(
+            "Both recordings need edges on trigger channel {}",
+            channel
+        ))
+    })?;
+
+    let mut cd_events = events.cd_events(py)?;
+    triggers::apply_offset(&mut cd_events, &mut trigger_events, sync.offset);
+    let events = Events::from_cd_events(py, cd_events, events.sensor_width, events.sensor_height);
+    let triggers = TriggerEvents::from_trigger_events(py, trigger_events);
+    Ok((Py::new(py, events)?, Py::new(py, triggers)?, sync.offset))
+}
+
 /// EVT 3.0 decoder module for Python.
 #[pymodule]
-fn _evt3(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
+fn _evt3(py: Python<'_>, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(decode_file, m)?)?;
     m.add_function(wrap_pyfunction!(decode_file_with_triggers, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_file_into, m)?)?;
     m.add_function(wrap_pyfunction!(decode_bytes, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_bytes_with_triggers, m)?)?;
+    m.add_function(wrap_pyfunction!(set_default_geometry, m)?)?;
+    m.add_function(wrap_pyfunction!(get_default_geometry, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_stream, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_stream_with_triggers, m)?)?;
+    m.add_function(wrap_pyfunction!(iter_file, m)?)?;
+    m.add_function(wrap_pyfunction!(frame_generator, m)?)?;
+    m.add_function(wrap_pyfunction!(concatenate, m)?)?;
+    m.add_function(wrap_pyfunction!(write_csv, m)?)?;
+    m.add_function(wrap_pyfunction!(write_binary, m)?)?;
+    m.add_function(wrap_pyfunction!(write_mat, m)?)?;
+    m.add_function(wrap_pyfunction!(write_es, m)?)?;
+    m.add_function(wrap_pyfunction!(encode_file, m)?)?;
+    m.add_function(wrap_pyfunction!(voxel_grid, m)?)?;
+    m.add_function(wrap_pyfunction!(slice_by_triggers, m)?)?;
+    m.add_function(wrap_pyfunction!(debounce_triggers, m)?)?;
+    m.add_function(wrap_pyfunction!(trigger_pulses, m)?)?;
+    m.add_function(wrap_pyfunction!(synchronize, m)?)?;
+    m.add_function(wrap_pyfunction!(restore_events, m)?)?;
+    m.add_function(wrap_pyfunction!(restore_trigger_events, m)?)?;
     m.add_class::<Events>()?;
     m.add_class::<TriggerEvents>()?;
+    m.add_class::<Decoder>()?;
+    m.add_class::<EventChunks>()?;
+    m.add_class::<FrameGenerator>()?;
+    m.add_class::<EventIter>()?;
+    m.add_class::<EventDataset>()?;
+    m.add_class::<Evt3File>()?;
+    m.add("Event", event_type(py)?)?;
+    m.add("DecodeError", py.get_type::<DecodeError>())?;
+    m.add("FormatError", py.get_type::<FormatError>())?;
+    m.add("TruncatedFileError", py.get_type::<TruncatedFileError>())?;
     Ok(())
 }
@@ -52,3 +52,35 @@ def synthetic_evt3_bytes():
     data.extend(struct.pack('<H', 0x4038))
     
     return bytes(data)
+
+
+@pytest.fixture
+def synthetic_trigger_file(tmp_path):
+    """Write a small EVT3 file with CD events and external triggers.
+
+    CD events at t=60, 100, 130, 210. Channel 0 has rising edges at
+    t=50, 120, 200 and falling edges at t=80, 140; channel 1 has a rising
+    edge at t=140.
+    """
+    import struct
+
+    words = [
+        0x8000,          # TIME_HIGH: time=0
+        0x6032, 0xA001,  # t=50: trigger ch0 rising
+        0x603C, 0x000A,  # t=60: ADDR_Y y=10
+        0x2801,          # ADDR_X pol=1 x=1
+        0x6050, 0xA000,  # t=80: trigger ch0 falling
+        0x6064, 0x2002,  # t=100: ADDR_X pol=0 x=2
+        0x6078, 0xA001,  # t=120: trigger ch0 rising
+        0x6082, 0x2803,  # t=130: ADDR_X pol=1 x=3
+        0x608C, 0xA000,  # t=140: trigger ch0 falling
+        0xA101,          # trigger ch1 rising
+        0x60C8, 0xA001,  # t=200: trigger ch0 rising
+        0x60D2, 0x2804,  # t=210: ADDR_X pol=1 x=4
+    ]
+    path = tmp_path / "triggers.raw"
+    path.write_bytes(
+        b"% format EVT3;width=640;height=480\n% end\n"
+        + b"".join(struct.pack("<H", w) for w in words)
+    )
+    return path
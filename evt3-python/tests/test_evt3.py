@@ -33,6 +33,28 @@ class TestDecodeBytes:
         assert events.sensor_height == 720
         assert events.sensor_size == (1280, 720)
 
+    def test_default_geometry(self, synthetic_evt3_bytes, tmp_path):
+        """Test that headerless data uses the module default geometry."""
+        import evt3
+
+        assert evt3.get_default_geometry() == (1280, 720)
+        evt3.set_default_geometry(640, 480)
+        try:
+            assert evt3.decode_bytes(synthetic_evt3_bytes).sensor_size == (640, 480)
+            assert evt3.Decoder().sensor_width == 640
+            path = tmp_path / "headerless.raw"
+            path.write_bytes(synthetic_evt3_bytes)
+            assert evt3.decode_file(path).sensor_size == (640, 480)
+
+            # Per-call sizes override the default
+            events = evt3.decode_bytes(synthetic_evt3_bytes, sensor_width=1024)
+            assert events.sensor_size == (1024, 480)
+        finally:
+            evt3.set_default_geometry(1280, 720)
+
+        with pytest.raises(ValueError):
+            evt3.set_default_geometry(0, 480)
+
     def test_numpy_array_types(self, synthetic_evt3_bytes):
         """Test that returned arrays have correct numpy dtypes."""
         import evt3
@@ -46,6 +68,17 @@ class TestDecodeBytes:
         assert events.timestamp.dtype == np.uint64
         assert events.t.dtype == np.uint64  # Alias
 
+    def test_arrays_not_copied(self, synthetic_evt3_bytes):
+        """Test that repeated access returns the same array without copying."""
+        import evt3
+
+        events = evt3.decode_bytes(synthetic_evt3_bytes)
+
+        assert events.x is events.x
+        assert events.t is events.timestamp
+        assert events.p is events.polarity
+        assert np.shares_memory(events.to_dict()['x'], events.x)
+
     def test_to_dict(self, synthetic_evt3_bytes):
         """Test to_dict() returns proper dictionary."""
         import evt3
@@ -62,6 +95,40 @@ class TestDecodeBytes:
         # All arrays should have same length
         assert len(d['x']) == len(d['y']) == len(d['polarity']) == len(d['timestamp'])
 
+    def test_array_protocols(self, synthetic_evt3_bytes):
+        """Test np.asarray on Events and the buffer protocol of the columns."""
+        import evt3
+
+        events = evt3.decode_bytes(synthetic_evt3_bytes)
+        records = np.asarray(events)
+
+        assert records.dtype.names == ('x', 'y', 'polarity', 'timestamp')
+        assert records.shape == (len(events),)
+        assert list(records['x']) == list(events.x)
+        assert records[0]['timestamp'] == 100
+
+        view = memoryview(events.x)
+        assert view.format == 'H'
+        assert view[0] == 300
+
+        torch = pytest.importorskip("torch")
+        tensor = torch.from_numpy(events.p)
+        assert tensor.tolist() == list(events.p)
+
+    def test_decode_bytes_with_triggers(self, synthetic_trigger_file):
+        """Trigger events are returned alongside the CD events."""
+        import evt3
+
+        data = synthetic_trigger_file.read_bytes()
+        data = data[data.index(b"% end\n") + len(b"% end\n"):]
+        events, triggers = evt3.decode_bytes_with_triggers(data, 640, 480)
+
+        assert len(events) == 4
+        assert events.sensor_size == (640, 480)
+        assert list(triggers.timestamp) == [50, 80, 120, 140, 140, 200]
+        assert list(triggers.id) == [0, 0, 0, 0, 1, 0]
+        assert list(triggers.value) == [1, 0, 1, 0, 1, 1]
+
     def test_repr(self, synthetic_evt3_bytes):
         """Test string representation."""
         import evt3
@@ -73,6 +140,689 @@ class TestDecodeBytes:
         assert '1280x720' in repr_str
 
 
+    def test_time_slice(self, synthetic_evt3_bytes):
+        """Test that time_slice returns a half-open window as views."""
+        import evt3
+
+        events = evt3.decode_bytes(synthetic_evt3_bytes)
+
+        assert list(events.time_slice(t1=150).timestamp) == [100]
+        window = events.time_slice(120, 151)
+        assert list(window.x) == [400, 503, 504, 505]
+        assert window.sensor_size == events.sensor_size
+        assert np.shares_memory(window.x, events.x)
+        assert len(events.time_slice(200, 100)) == 0
+
+    def test_concatenate(self, synthetic_evt3_bytes):
+        """Test concatenation with and without timestamp offsets."""
+        import evt3
+
+        events = evt3.decode_bytes(synthetic_evt3_bytes)
+        joined = evt3.concatenate([events, events], offsets=[0, 1000])
+
+        assert len(joined) == 2 * len(events)
+        assert list(joined.t[len(events):]) == list(events.t + 1000)
+        assert list((events + events).x) == list(joined.x)
+
+        with pytest.raises(ValueError):
+            evt3.concatenate([events, evt3.decode_bytes(synthetic_evt3_bytes, 640, 480)])
+        with pytest.raises(ValueError):
+            evt3.concatenate([events], offsets=[0, 1])
+
+    def test_sort_by_time(self, synthetic_evt3_bytes):
+        """Test a stable sort that keeps the columns together."""
+        import evt3
+
+        events = evt3.decode_bytes(synthetic_evt3_bytes)
+        assert events.is_sorted
+        assert np.shares_memory(events.sort_by_time().t, events.t)
+
+        merged = events + events
+        assert not merged.is_sorted
+        ordered = merged.sort_by_time()
+        assert ordered.is_sorted
+        assert list(ordered.t) == [100, 100] + [150] * 8
+        assert list(ordered.x) == [300, 300, 400, 503, 504, 505, 400, 503, 504, 505]
+        assert list(ordered.p) == [1, 1, 0, 1, 1, 1, 0, 1, 1, 1]
+
+    def test_pickle(self, synthetic_trigger_file):
+        """Test that Events and TriggerEvents survive a pickle round trip."""
+        import pickle
+        import evt3
+
+        events, triggers = evt3.decode_file_with_triggers(str(synthetic_trigger_file))
+        restored = pickle.loads(pickle.dumps(events))
+        restored_triggers = pickle.loads(pickle.dumps(triggers))
+
+        np.testing.assert_array_equal(restored.x, events.x)
+        np.testing.assert_array_equal(restored.t, events.t)
+        assert restored.sensor_size == (640, 480)
+        assert restored.metadata == events.metadata
+        np.testing.assert_array_equal(restored_triggers.id, triggers.id)
+        np.testing.assert_array_equal(restored_triggers.timestamp, triggers.timestamp)
+        assert len(pickle.loads(pickle.dumps(events.time_slice(100, 131)))) == 2
+
+    def test_memory_report(self, synthetic_evt3_bytes):
+        """Test that nbytes and memory_report match the numpy arrays."""
+        import evt3
+
+        events = evt3.decode_bytes(synthetic_evt3_bytes)
+        report = events.memory_report()
+
+        assert events.nbytes == sum(a.nbytes for a in events.to_dict().values())
+        assert report["x"] == events.x.nbytes
+        assert report["timestamp"] == events.t.nbytes
+        assert report["total"] == events.nbytes
+        assert report["bytes_per_event"] == 13
+
+    def test_iteration(self, synthetic_evt3_bytes):
+        """Test indexing and iterating over single events."""
+        import evt3
+
+        events = evt3.decode_bytes(synthetic_evt3_bytes)
+
+        assert events[0] == evt3.Event(x=300, y=200, p=1, t=100)
+        assert events[-1].x == 505
+        x, y, p, t = events[1]
+        assert (x, p, t) == (400, 0, 150)
+        assert [e.x for e in events] == list(events.x)
+        assert list(events[1:3].x) == [400, 503]
+        with pytest.raises(IndexError):
+            events[len(events)]
+
+class TestDecoder:
+    """Tests for the stateful streaming decoder."""
+
+    def test_feed_in_chunks(self, synthetic_evt3_bytes):
+        """Test that chunks split anywhere decode like the whole buffer."""
+        import evt3
+
+        expected = evt3.decode_bytes(synthetic_evt3_bytes)
+        decoder = evt3.Decoder()
+        chunks = [decoder.feed(synthetic_evt3_bytes[i:i + 3])
+                  for i in range(0, len(synthetic_evt3_bytes), 3)]
+
+        assert sum(len(c) for c in chunks) == len(expected)
+        np.testing.assert_array_equal(np.concatenate([c.x for c in chunks]), expected.x)
+        np.testing.assert_array_equal(np.concatenate([c.t for c in chunks]), expected.t)
+
+    def test_reset(self, synthetic_evt3_bytes):
+        """Test that reset starts over as with a new decoder."""
+        import evt3
+
+        decoder = evt3.Decoder(640, 480)
+        first = decoder.feed(synthetic_evt3_bytes)
+        decoder.reset()
+        second = decoder.feed(synthetic_evt3_bytes)
+
+        assert decoder.sensor_width == 640
+        np.testing.assert_array_equal(first.t, second.t)
+
+    def test_iter_file(self, synthetic_trigger_file):
+        """Test that chunks of iter_file add up to decode_file."""
+        import evt3
+
+        expected = evt3.decode_file(str(synthetic_trigger_file))
+        chunks = list(evt3.iter_file(str(synthetic_trigger_file), chunk_events=2))
+
+        assert [len(c) for c in chunks[:-1]] == [2] * (len(chunks) - 1)
+        assert 0 < len(chunks[-1]) <= 2
+        np.testing.assert_array_equal(np.concatenate([c.t for c in chunks]), expected.t)
+        assert chunks[0].sensor_size == expected.sensor_size
+
+        with pytest.raises(ValueError):
+            evt3.iter_file(str(synthetic_trigger_file), chunk_events=0)
+
+    def test_path_like(self, synthetic_trigger_file):
+        """Test that pathlib.Path works wherever a path string does."""
+        import evt3
+
+        expected = evt3.decode_file(str(synthetic_trigger_file))
+        events = evt3.decode_file(synthetic_trigger_file)
+
+        np.testing.assert_array_equal(events.t, expected.t)
+        assert len(next(evt3.iter_file(synthetic_trigger_file))) == len(expected)
+
+    def test_decode_stream(self, synthetic_trigger_file):
+        """Test decoding from binary file-like objects."""
+        import gzip
+        import io
+        import evt3
+
+        expected, expected_triggers = evt3.decode_file_with_triggers(synthetic_trigger_file)
+        data = synthetic_trigger_file.read_bytes()
+
+        events, triggers = evt3.decode_stream_with_triggers(io.BytesIO(data))
+        np.testing.assert_array_equal(events.t, expected.t)
+        np.testing.assert_array_equal(triggers.timestamp, expected_triggers.timestamp)
+        assert events.sensor_size == (640, 480)
+
+        with gzip.open(io.BytesIO(gzip.compress(data))) as f:
+            np.testing.assert_array_equal(evt3.decode_stream(f).x, expected.x)
+
+        with pytest.raises(ValueError):
+            evt3.decode_stream(io.StringIO("text"))
+
+    def test_decode_file_async(self, synthetic_trigger_file):
+        """Test that the async decoders match the blocking ones."""
+        import asyncio
+        import evt3
+
+        async def decode():
+            return await asyncio.gather(
+                evt3.decode_file_async(synthetic_trigger_file, t_end=200),
+                evt3.decode_file_with_triggers_async(synthetic_trigger_file),
+            )
+        events, (all_events, triggers) = asyncio.run(decode())
+
+        assert list(events.t) == [60, 100, 130]
+        assert len(all_events) == 4
+        assert len(triggers) == 6
+
+    def test_event_dataset(self, synthetic_trigger_file):
+        """Test that a dataset splits each file into time windows."""
+        import pickle
+        import evt3
+
+        second = synthetic_trigger_file.with_name("z.raw")
+        second.write_bytes(synthetic_trigger_file.read_bytes())
+        dataset = evt3.EventDataset(synthetic_trigger_file.parent, window=100)
+
+        assert len(dataset) == 4
+        assert list(dataset[0].t) == [60, 100, 130]
+        assert list(dataset[1].t) == [210]
+        assert dataset.locate(-1) == (str(second), 160, 260)
+        assert len(evt3.EventDataset(synthetic_trigger_file.parent)[1]) == 4
+        assert len(pickle.loads(pickle.dumps(dataset))) == 4
+        with pytest.raises(IndexError):
+            dataset[4]
+
+
+class TestFilters:
+    """Tests for decode-time filters."""
+
+    def test_denoise_dt(self, synthetic_evt3_bytes):
+        """Test that isolated events are dropped by the background activity filter."""
+        import evt3
+
+        events = evt3.decode_bytes(synthetic_evt3_bytes, denoise_dt=1000)
+
+        # Only the vector events at x=504 and x=505 have a neighbor that fired before them
+        assert list(events.x) == [504, 505]
+        assert np.all(events.y == 200)
+
+    def test_denoise_disabled_by_default(self, synthetic_evt3_bytes):
+        """Test that no events are dropped without denoise_dt."""
+        import evt3
+
+        events = evt3.decode_bytes(synthetic_evt3_bytes)
+        assert len(events) == 5
+
+    def test_dedup(self, synthetic_evt3_bytes):
+        """Test that a repeated vector word does not produce duplicate events."""
+        import evt3
+
+        # Repeat the VECT_BASE_X + VECT_12 pair at the end of the data
+        data = synthetic_evt3_bytes + synthetic_evt3_bytes[-4:]
+
+        assert len(evt3.decode_bytes(data)) == 8
+        events = evt3.decode_bytes(data, dedup=True)
+        assert list(events.x) == [300, 400, 503, 504, 505]
+
+    def test_invert_polarity(self, synthetic_evt3_bytes):
+        """Test that polarities are swapped while decoding."""
+        import evt3
+
+        events = evt3.decode_bytes(synthetic_evt3_bytes)
+        inverted = evt3.decode_bytes(synthetic_evt3_bytes, invert_polarity=True)
+        assert np.array_equal(inverted.polarity, 1 - events.polarity)
+
+    def test_pixel_mask_list(self, synthetic_evt3_bytes):
+        """Test dropping events from a list of masked pixels."""
+        import evt3
+
+        events = evt3.decode_bytes(synthetic_evt3_bytes, pixel_mask=[(300, 200), (504, 200)])
+        assert list(events.x) == [400, 503, 505]
+
+    def test_pixel_mask_file(self, synthetic_evt3_bytes, tmp_path):
+        """Test dropping events from pixels listed in a mask file."""
+        import evt3
+
+        mask = tmp_path / "mask.csv"
+        mask.write_text("x,y\n400,200\n")
+        events = evt3.decode_bytes(synthetic_evt3_bytes, pixel_mask=str(mask))
+        assert list(events.x) == [300, 503, 504, 505]
+
+        with pytest.raises(ValueError):
+            evt3.decode_bytes(synthetic_evt3_bytes, pixel_mask=42)
+
+
+    def test_event_filter_methods(self, synthetic_evt3_bytes):
+        """Test ROI, polarity and hot pixel filtering of decoded events."""
+        import evt3
+
+        events = evt3.decode_bytes(synthetic_evt3_bytes)
+
+        assert list(events.filter_roi(400, 0, 504, 720).x) == [400, 503]
+        cropped = events.filter_roi(400, 0, 504, 720, crop=True)
+        assert list(cropped.x) == [0, 103]
+        assert cropped.sensor_size == (104, 720)
+        assert list(events.filter_polarity(0).x) == [400]
+        assert list(events.remove_hot_pixels(pixels=[(300, 200)]).x) == [400, 503, 504, 505]
+        # Five pixels firing once each: nothing stands out
+        assert len(events.remove_hot_pixels()) == len(events)
+
+    def test_decode_file_range_and_roi(self, synthetic_trigger_file):
+        """Test that t_start/t_end and roi are applied while decoding."""
+        import evt3
+
+        path = str(synthetic_trigger_file)
+        assert list(evt3.decode_file(path, t_start=100, t_end=210).t) == [100, 130]
+        assert list(evt3.decode_file(path, t_start=100).t) == [100, 130, 210]
+        assert list(evt3.decode_file(path, roi=(2, 0, 4, 480)).x) == [2, 3]
+
+        events, triggers = evt3.decode_file_with_triggers(path, t_end=100)
+        assert list(events.t) == [60]
+        assert list(triggers.timestamp) == [50, 80]
+
+        with pytest.raises(ValueError):
+            evt3.decode_file(path, t_start=100, t_end=100)
+
+    def test_decode_file_max_events(self, synthetic_trigger_file):
+        """Test that max_events stops decoding and marks the result."""
+        import evt3
+
+        path = str(synthetic_trigger_file)
+        events, triggers = evt3.decode_file_with_triggers(path, max_events=2)
+        assert list(events.t) == [60, 100]
+        assert events.truncated
+        assert 'truncated' in repr(events)
+        # Triggers up to the first event that was left out (t=130)
+        assert list(triggers.timestamp) == [50, 80, 120]
+
+        assert list(evt3.decode_file(path, t_start=100, max_events=1).t) == [100]
+        assert not evt3.decode_file(path, max_events=4).truncated
+        assert not evt3.decode_file(path).truncated
+    def test_metadata(self, synthetic_evt3_bytes, tmp_path):
+        """Test that the whole file header is exposed as a dict."""
+        import evt3
+
+        path = tmp_path / "header.raw"
+        path.write_bytes(
+            b"% camera_integrator_name Prophesee\n"
+            b"% date 2023-03-29 16:37:46\n"
+            b"% format EVT3;width=1280;height=720\n"
+            b"% serial_number 00050423\n"
+            b"% end\n" + synthetic_evt3_bytes
+        )
+        events = evt3.decode_file(str(path))
+
+        assert events.metadata["serial_number"] == "00050423"
+        assert events.metadata["camera_integrator_name"] == "Prophesee"
+        assert events.metadata["date"] == "2023-03-29 16:37:46"
+        assert events.metadata["format"] == "EVT3;width=1280;height=720"
+        assert events.filter_roi(0, 0, 400, 400, crop=True).metadata["width"] == 400
+        assert next(evt3.iter_file(str(path))).metadata == events.metadata
+        assert evt3.decode_bytes(synthetic_evt3_bytes).metadata == {
+            "width": 1280, "height": 720}
+
+    def test_t_datetime(self, synthetic_evt3_bytes, tmp_path):
+        """Test datetime64 timestamps from an explicit or header epoch."""
+        import datetime
+        import evt3
+
+        path = tmp_path / "dated.raw"
+        path.write_bytes(b"% date 2023-03-29 16:37:46\n% end\n" + synthetic_evt3_bytes)
+        events = evt3.decode_file(path)
+
+        t = events.t_datetime()
+        assert t.dtype == np.dtype("datetime64[us]")
+        assert t[0] == np.datetime64("2023-03-29T16:37:46.000100")
+        t = events.t_datetime(datetime.datetime(2024, 1, 1))
+        assert t[1] - t[0] == np.timedelta64(50, "us")
+        with pytest.raises(ValueError):
+            evt3.decode_bytes(synthetic_evt3_bytes).t_datetime()
+
+    def test_progress_callback(self, synthetic_trigger_file):
+        """Test that progress reports bytes and events and can abort."""
+        import evt3
+
+        calls = []
+        events = evt3.decode_file(synthetic_trigger_file, progress=lambda *a: calls.append(a))
+
+        size = synthetic_trigger_file.stat().st_size
+        assert calls[-1] == (size, size, len(events))
+
+        def cancel(done, total, count):
+            raise KeyboardInterrupt
+        with pytest.raises(KeyboardInterrupt):
+            evt3.decode_file(synthetic_trigger_file, progress=cancel)
+
+    def test_decode_file_into(self, synthetic_trigger_file):
+        """Test that events fill preallocated arrays up to their length."""
+        import evt3
+
+        x, y = np.zeros(10, np.uint16), np.zeros(10, np.uint16)
+        p, t = np.zeros(10, np.uint8), np.zeros(10, np.uint64)
+        n = evt3.decode_file_into(synthetic_trigger_file, x, y, p, t)
+        assert n == 4
+        assert list(t[:n]) == [60, 100, 130, 210]
+        assert list(x[:n]) == [1, 2, 3, 4]
+        assert list(p[:n]) == [1, 0, 1, 1]
+
+        # Decoding stops when the arrays are full
+        n = evt3.decode_file_into(synthetic_trigger_file, x[:2], y[:2], p[:2], t[:2],
+                                  t_start=100)
+        assert n == 2
+        assert list(t[:2]) == [100, 130]
+
+        with pytest.raises(TypeError):
+            evt3.decode_file_into(synthetic_trigger_file, x, y, p, t.astype(np.int64))
+
+    def test_evt3_file(self, tmp_path):
+        """Test that indexed reads match decoding from the start."""
+        import evt3
+
+        # Large enough for several index blocks
+        n = 300_000
+        rng = np.random.default_rng(0)
+        path = tmp_path / "long.raw"
+        evt3.encode_file(path, rng.integers(0, 640, n), rng.integers(0, 480, n),
+                         rng.integers(0, 2, n), np.arange(n) * 7, 640, 480)
+
+        index_path = tmp_path / "long.tidx"
+        f = evt3.Evt3File(path, index_path=index_path)
+        assert len(f) == n
+        assert f.sensor_size == (640, 480)
+        assert (f.first_timestamp, f.last_timestamp) == (0, (n - 1) * 7)
+        assert index_path.exists()
+
+        for t0, t1 in [(0, 1000), (1_500_000, 1_600_000), ((n - 10) * 7, None)]:
+            expected = evt3.decode_file(path, t_start=t0, t_end=t1)
+            events = f.read(t0, t1)
+            np.testing.assert_array_equal(events.x, expected.x)
+            np.testing.assert_array_equal(events.t, expected.t)
+
+        # The saved index is loaded instead of decoding the file again
+        reopened = evt3.Evt3File(path, index_path=index_path)
+        assert len(reopened) == n
+        assert len(reopened.read(1_500_000, 1_600_000)) == len(f.read(1_500_000, 1_600_000))
+
+        with pytest.raises(ValueError):
+            f.read(100, 100)
+
+
+class TestRepresentations:
+    """Tests for dense event representations."""
+
+    def test_voxel_grid(self, synthetic_evt3_bytes):
+        """Test voxel grid shape and temporal weighting."""
+        import evt3
+
+        events = evt3.decode_bytes(synthetic_evt3_bytes, sensor_width=640, sensor_height=480)
+        grid = evt3.voxel_grid(events, 3)
+
+        assert grid.shape == (3, 480, 640)
+        assert grid.dtype == np.float32
+        # First event (t=100) is in bin 0, the others (t=150) in the last bin
+        assert grid[0, 200, 300] == 1.0
+        assert grid[2, 200, 400] == -1.0
+        assert np.isclose(np.abs(grid).sum(), len(events))
+
+    def test_to_voxel_grid(self, synthetic_evt3_bytes):
+        """Test that the method matches evt3.voxel_grid."""
+        import evt3
+
+        events = evt3.decode_bytes(synthetic_evt3_bytes, sensor_width=640, sensor_height=480)
+
+        np.testing.assert_array_equal(events.to_voxel_grid(3), evt3.voxel_grid(events, 3))
+        grid = events.to_voxel_grid(2, t0=100, t1=200)
+        assert grid.shape == (2, 480, 640)
+        assert grid[0, 200, 300] == 1.0
+        assert np.isclose(grid[:, 200, 504].sum(), 1.0)
+
+
+    def test_heatmap(self, synthetic_evt3_bytes):
+        """Test per-pixel event counts."""
+        import evt3
+
+        events = evt3.decode_bytes(synthetic_evt3_bytes, sensor_width=640, sensor_height=480)
+        heatmap = events.heatmap()
+
+        assert heatmap.shape == (480, 640)
+        assert heatmap.dtype == np.uint32
+        assert heatmap.sum() == len(events)
+        assert heatmap[200, 300] == 1
+
+        # Only the events at t=150
+        assert events.heatmap(t0=120).sum() == 4
+
+    def test_hist2d(self, synthetic_evt3_bytes):
+        """Test that hist2d matches heatmap and np.histogram2d."""
+        import evt3
+
+        events = evt3.decode_bytes(synthetic_evt3_bytes, sensor_width=640, sensor_height=480)
+        np.testing.assert_array_equal(events.hist2d(), events.heatmap())
+
+        assert events.hist2d(bins=2).tolist() == [[1, 4], [0, 0]]
+        assert events.hist2d(bins=2, polarity=0).tolist() == [[0, 1], [0, 0]]
+
+        coarse = events.hist2d(bins=(64, 48))
+        assert coarse.shape == (48, 64)
+        expected, _, _ = np.histogram2d(events.y, events.x, bins=(48, 64),
+                                        range=((0, 480), (0, 640)))
+        np.testing.assert_array_equal(coarse, expected)
+
+        with pytest.raises(ValueError):
+            events.hist2d(bins=0)
+
+    def test_to_frame(self, synthetic_evt3_bytes):
+        """Test count and signed frames over a time window."""
+        import evt3
+
+        events = evt3.decode_bytes(synthetic_evt3_bytes, sensor_width=640, sensor_height=480)
+        frame = events.to_frame(t1=151)
+        signed = events.to_frame(120, 151, signed=True)
+
+        assert frame.shape == (480, 640)
+        assert frame.dtype == np.uint32
+        assert frame.sum() == len(events)
+        assert signed.dtype == np.int32
+        assert signed[200, 300] == 0  # t=100 is outside the window
+        assert signed[200, 400] == -1
+        assert signed[200, 504] == 1
+
+    def test_frame_generator(self, synthetic_trigger_file):
+        """Test fixed-rate frames streamed from a file."""
+        import evt3
+
+        frames = list(evt3.frame_generator(synthetic_trigger_file, fps=10_000))
+
+        assert [t0 for t0, _ in frames] == [60, 160]
+        assert frames[0][1].shape == (480, 640)
+        assert frames[0][1].sum() == 3
+        assert frames[1][1][10, 4] == 1
+        signed = [f for _, f in evt3.frame_generator(synthetic_trigger_file, 10_000, "signed")]
+        assert signed[0][10, 2] == -1
+        with pytest.raises(ValueError):
+            evt3.frame_generator(synthetic_trigger_file, mode="rgb")
+
+
+class TestWriters:
+    """Tests for the file writers."""
+
+    def test_write_csv(self, synthetic_evt3_bytes, tmp_path):
+        """Test CSV output with a custom column order and delimiter."""
+        import evt3
+
+        events = evt3.decode_bytes(synthetic_evt3_bytes, 640, 480)
+        path = tmp_path / "events.csv"
+        evt3.write_csv(events, path, order="t,x,y,p", delimiter="tab", column_names=True)
+
+        lines = path.read_text().splitlines()
+        assert lines[0] == "%geometry:640,480"
+        assert lines[1] == "t\tx\ty\tp"
+        assert lines[2] == "100\t300\t200\t1"
+        assert len(lines) == 2 + len(events)
+
+        with pytest.raises(ValueError):
+            evt3.write_csv(events, path, order="x,y")
+
+    def test_write_binary(self, synthetic_evt3_bytes, tmp_path):
+        """Test that binary output has the header and one record per event."""
+        import evt3
+
+        events = evt3.decode_bytes(synthetic_evt3_bytes)
+        path = tmp_path / "events.bin"
+        evt3.write_binary(events, str(path))
+
+        data = path.read_bytes()
+        assert data[:8] == b"EVT3BIN\0"
+        assert len(data) == 28 + 14 * len(events)
+
+    def test_open_binary(self, synthetic_evt3_bytes, tmp_path):
+        """Test that EVT3BIN files and column directories are memory-mapped."""
+        import json
+        import evt3
+
+        events = evt3.decode_bytes(synthetic_evt3_bytes, 640, 480)
+        path = tmp_path / "events.bin"
+        evt3.write_binary(events, path)
+
+        data = evt3.open_binary(path)
+        assert len(data) == len(events)
+        assert data.sensor_size == (640, 480)
+        assert isinstance(data.t, np.memmap)
+        np.testing.assert_array_equal(data.x, events.x)
+        np.testing.assert_array_equal(data.t, events.t)
+
+        window = data.time_slice(150, 200)
+        assert isinstance(window, evt3.Events)
+        assert list(window.x) == [400, 503, 504, 505]
+        assert list(data[:2].p) == [1, 0]
+
+        columns = tmp_path / "columns"
+        columns.mkdir()
+        names = [("x", "x.u16", "<u2"), ("y", "y.u16", "<u2"),
+                 ("polarity", "p.u8", "u1"), ("timestamp", "t.u64", "<u8")]
+        for name, file, dtype in names:
+            getattr(events, name).astype(dtype).tofile(columns / file)
+        (columns / "manifest.json").write_text(json.dumps({
+            "format": "evt3-columns", "version": 1, "width": 640, "height": 480,
+            "event_count": len(events), "timestamp_unit": "us",
+            "columns": [{"name": n, "file": f, "dtype": d} for n, f, d in names],
+        }))
+        np.testing.assert_array_equal(evt3.open_binary(columns).y, events.y)
+
+        raw = tmp_path / "not_binary.bin"
+        raw.write_bytes(b"\0" * 64)
+        with pytest.raises(evt3.FormatError):
+            evt3.open_binary(raw)
+
+    def test_encode_file_round_trip(self, synthetic_trigger_file, tmp_path):
+        """Test that encoded arrays decode back to the same events."""
+        import evt3
+
+        events, triggers = evt3.decode_file_with_triggers(str(synthetic_trigger_file))
+        path = tmp_path / "encoded.raw"
+        evt3.encode_file(path, events.x, events.y, events.p, events.t, 640, 480,
+                         triggers=triggers)
+
+        decoded, decoded_triggers = evt3.decode_file_with_triggers(str(path))
+        assert decoded.sensor_size == (640, 480)
+        for name in ['x', 'y', 'p', 't']:
+            np.testing.assert_array_equal(getattr(decoded, name), getattr(events, name))
+        np.testing.assert_array_equal(decoded_triggers.timestamp, triggers.timestamp)
+
+        # Plain lists work too; events must lie inside the sensor
+        evt3.encode_file(path, [1, 2], [3, 4], [0, 1], [10, 20], 640, 480)
+        assert list(evt3.decode_file(str(path)).t) == [10, 20]
+        with pytest.raises(ValueError):
+            evt3.encode_file(path, [640], [0], [1], [0], 640, 480)
+        with pytest.raises(ValueError):
+            evt3.encode_file(path, [1, 2], [0, 0], [1, 1], [20, 10], 640, 480)
+
+class TestTriggers:
+    """Tests for trigger analysis helpers."""
+
+    def test_slice_by_triggers(self, synthetic_trigger_file):
+        """Segments span consecutive rising edges on the channel."""
+        import evt3
+
+        events, triggers = evt3.decode_file_with_triggers(str(synthetic_trigger_file))
+        segments = evt3.slice_by_triggers(events, triggers, channel=0)
+
+        assert segments == [(50, 120, 0, 2), (120, 200, 2, 3)]
+        t0, t1, i0, i1 = segments[0]
+        assert np.all((events.timestamp[i0:i1] >= t0) & (events.timestamp[i0:i1] < t1))
+
+    def test_slice_by_falling_edges(self, synthetic_trigger_file):
+        """Falling edges can delimit segments too."""
+        import evt3
+
+        events, triggers = evt3.decode_file_with_triggers(str(synthetic_trigger_file))
+        assert evt3.slice_by_triggers(events, triggers, edge="falling") == [(80, 140, 1, 3)]
+
+        with pytest.raises(ValueError):
+            evt3.slice_by_triggers(events, triggers, edge="up")
+
+    def test_debounce_triggers(self, synthetic_trigger_file):
+        """Edges within the gap of a kept edge on the same channel are dropped."""
+        import evt3
+
+        _, triggers = evt3.decode_file_with_triggers(str(synthetic_trigger_file))
+        debounced = evt3.debounce_triggers(triggers, 40)
+
+        # ch0: 50 kept, 80 dropped, 120 kept, 140 dropped, 200 kept; ch1: 140 kept
+        assert list(debounced.timestamp) == [50, 120, 140, 200]
+        assert list(debounced.id) == [0, 0, 1, 0]
+        assert len(evt3.debounce_triggers(triggers, 0)) == len(triggers)
+
+    def test_trigger_pulses(self, synthetic_trigger_file):
+        """Rising/falling pairs become pulses; the unclosed ones are dropped."""
+        import evt3
+
+        _, triggers = evt3.decode_file_with_triggers(str(synthetic_trigger_file))
+        pulses = evt3.trigger_pulses(triggers)
+
+        assert list(pulses["id"]) == [0, 0]
+        assert list(pulses["start"]) == [50, 120]
+        assert list(pulses["width"]) == [30, 20]
+        assert pulses["width"].dtype == np.uint64
+
+    def test_rebase_trigger(self, synthetic_trigger_file):
+        """t=0 moves to the first rising edge; earlier events are dropped."""
+        import evt3
+
+        events, triggers = evt3.decode_file_with_triggers(
+            str(synthetic_trigger_file), rebase_trigger=1
+        )
+        assert list(events.timestamp) == [70]
+        assert list(triggers.timestamp) == [0, 0, 60]
+
+        events = evt3.decode_file(str(synthetic_trigger_file), rebase_trigger=0)
+        assert list(events.timestamp) == [10, 50, 80, 160]
+
+        with pytest.raises(ValueError):
+            evt3.decode_file(str(synthetic_trigger_file), rebase_trigger=5)
+
+    def test_synchronize(self, synthetic_trigger_file):
+        """A recording aligned to its own rebased copy gets the rebase offset back."""
+        import evt3
+
+        events, triggers = evt3.decode_file_with_triggers(str(synthetic_trigger_file))
+        _, shifted_triggers = evt3.decode_file_with_triggers(
+            str(synthetic_trigger_file), rebase_trigger=0
+        )
+        aligned, aligned_triggers, offset = evt3.synchronize(
+            shifted_triggers, events, triggers
+        )
+
+        assert offset == 50
+        assert list(aligned.timestamp) == [10, 50, 80, 160]
+        assert aligned_triggers.timestamp[0] == 0
+
+
 class TestDecodeFile:
     """Tests for decode_file function (requires real test data)."""
 
@@ -129,6 +879,38 @@ class TestErrorHandling:
         with pytest.raises(IOError):
             evt3.decode_file("/nonexistent/path/to/file.raw")
 
+    def test_exception_types(self, synthetic_trigger_file, tmp_path):
+        """Test that missing, foreign and truncated files raise distinct errors."""
+        import evt3
+
+        with pytest.raises(FileNotFoundError):
+            evt3.decode_file(tmp_path / "missing.raw")
+
+        evt2 = tmp_path / "evt2.raw"
+        evt2.write_bytes(b"% format EVT2;width=640;height=480\n% end\n\x00\x00\x00\x00")
+        with pytest.raises(evt3.FormatError):
+            evt3.decode_file(evt2)
+
+        # A stray last byte is dropped unless strict=True
+        cut = tmp_path / "cut.raw"
+        cut.write_bytes(synthetic_trigger_file.read_bytes()[:-1])
+        complete = evt3.decode_file(synthetic_trigger_file)
+        events = evt3.decode_file(cut)
+        assert len(events) <= len(complete)
+        np.testing.assert_array_equal(events.t, complete.t[: len(events)])
+        assert len(evt3.decode_stream(open(cut, "rb"))) == len(events)
+        with pytest.raises(evt3.TruncatedFileError):
+            evt3.decode_file(cut, strict=True)
+        with pytest.raises(evt3.TruncatedFileError):
+            evt3.decode_stream(open(cut, "rb"), strict=True)
+        with pytest.raises(evt3.TruncatedFileError):
+            evt3.decode_file_with_triggers(cut, strict=True)
+
+        # Both derive from DecodeError, which remains an IOError
+        assert issubclass(evt3.TruncatedFileError, evt3.DecodeError)
+        assert issubclass(evt3.FormatError, evt3.DecodeError)
+        assert issubclass(evt3.DecodeError, IOError)
+
     def test_empty_bytes(self):
         """Test decoding empty bytes."""
         import evt3
@@ -152,3 +934,47 @@ class TestPandasIntegration:
         assert isinstance(df, pd.DataFrame)
         assert list(df.columns) == ['x', 'y', 'polarity', 'timestamp']
         assert len(df) == len(events)
+
+    def test_to_pandas(self, synthetic_evt3_bytes):
+        """Test that to_pandas returns the events as a DataFrame."""
+        pytest.importorskip("pandas")
+        import evt3
+
+        events = evt3.decode_bytes(synthetic_evt3_bytes)
+        df = events.to_pandas()
+
+        assert list(df.columns) == ['x', 'y', 'polarity', 'timestamp']
+        assert df['x'].dtype == np.uint16
+        assert list(df['timestamp']) == list(events.timestamp)
+
+    def test_to_polars(self, synthetic_evt3_bytes):
+        """Test that to_polars returns a polars DataFrame."""
+        pl = pytest.importorskip("polars")
+        import evt3
+
+        events = evt3.decode_bytes(synthetic_evt3_bytes)
+        df = events.to_polars()
+
+        assert isinstance(df, pl.DataFrame)
+        assert df.columns == ['x', 'y', 'polarity', 'timestamp']
+        assert df['x'].to_list() == list(events.x)
+
+    def test_to_arrow(self, synthetic_evt3_bytes):
+        """Test that to_arrow shares the event buffers and keeps the geometry."""
+        pa = pytest.importorskip("pyarrow")
+        import evt3
+
+        events = evt3.decode_bytes(synthetic_evt3_bytes, 640, 480)
+        table = events.to_arrow()
+
+        assert isinstance(table, pa.Table)
+        assert table.column_names == ['x', 'y', 'polarity', 'timestamp']
+        assert table.schema.field('timestamp').type == pa.uint64()
+        assert table.schema.metadata[b'sensor_width'] == b'640'
+        column = table.column('x').chunk(0).to_numpy(zero_copy_only=True)
+        assert np.shares_memory(column, events.x)
+
+        # The table keeps the buffers alive on its own
+        expected = list(events.timestamp)
+        del events, column
+        assert table.column('timestamp').to_pylist() == expected